@@ -1,55 +1,485 @@
 //! Information for a single account.
 
+use super::archive::{archived_files, is_archive};
 use super::parse::{
-    parse_account_directory, parse_account_name, parse_first_statement_date,
-    parse_institution_name, parse_statement_format, parse_statement_period,
+    interpolate_props, parse_account_directory, parse_account_directory_unchecked,
+    parse_account_name, parse_checksums, parse_currency, parse_enabled, parse_exclude,
+    parse_fetch_cmd, parse_first_statement_date, parse_follow_symlinks, parse_grace_days,
+    parse_group, parse_institution_name, parse_kind, parse_last_statement_date,
+    parse_ledger_account, parse_open_cmd, parse_paused_until, parse_period_source, parse_schedules,
+    parse_search_archives, parse_statement_format, parse_statement_period, parse_transactions,
+    validate_statement_format, PeriodSource,
 };
-use super::AccountCreationError;
-use chrono::prelude::*;
-use kronos::Shim;
+use super::storage::{is_case_insensitive, is_remote, resolve_links, storage_for};
+use super::{AccountCreationError, AccountKind, DateCache, ExcludePatterns, StatementPeriod};
+use chrono::{prelude::*, Duration};
 use quill_statement::{
-    expected_statement_dates, next_date_from_given, next_date_from_today, pair_dates_statements,
-    prev_date_from_given, prev_date_from_today, IgnoredStatements, ObservedStatement, Statement,
+    find_gaps, next_date_from_given, next_date_from_today, pair_dates_statements_with_grace,
+    prev_date_from_given, prev_date_from_today, upcoming_dates, Gap, IgnoredStatements,
+    ObservedStatement, Statement,
 };
 use regex::Regex;
+use serde::{ser::SerializeMap, Serialize, Serializer};
+use std::collections::{BTreeMap, HashSet};
 use std::convert::TryFrom;
 use std::ffi::OsStr;
 use std::fmt::{Debug, Display};
 use std::path::{Path, PathBuf};
 use toml::Value;
+use tracing::{debug, debug_span, trace, warn};
 use walkdir::WalkDir;
 
+/// Name of the schedule that tracks an account's transaction files, set by its `transaction_fmt`
+pub const TRANSACTIONS_SCHEDULE: &str = "transactions";
+
 #[derive(Clone)]
 /// Information related to an account, its billing period, and where to find the bills
-pub struct Account<'a> {
+pub struct Account {
     name: String,
     institution: String,
     statement_first: NaiveDate,
-    statement_period: Shim<'a>,
+    statement_last: Option<NaiveDate>,
+    statement_period: StatementPeriod,
+    period_source: Option<PeriodSource>,
     statement_fmt: String,
     dir: PathBuf,
+    archive_dir: Option<PathBuf>,
     ignored: IgnoredStatements,
+    search_archives: bool,
+    follow_symlinks: bool,
+    exclude: ExcludePatterns,
+    checksums: bool,
+    grace_days: u32,
+    group: Option<String>,
+    enabled: bool,
+    paused_until: Option<NaiveDate>,
+    fetch_cmd: Option<String>,
+    open_cmd: Option<String>,
+    ledger_account: Option<String>,
+    kind: Option<AccountKind>,
+    currency: Option<String>,
+    transaction_fmt: Option<String>,
+    schedule: Option<String>,
+    schedules: Vec<Account>,
+}
+
+/// Builds an [`Account`] one property at a time.
+/// The properties are checked together when the account is built.
+#[derive(Clone, Debug, Default)]
+pub struct AccountBuilder {
+    name: Option<String>,
+    institution: Option<String>,
+    first: Option<NaiveDate>,
+    last: Option<NaiveDate>,
+    period: Option<StatementPeriod>,
+    fmt: Option<String>,
+    dir: Option<PathBuf>,
+    unchecked_dir: bool,
 }
 
-impl<'a> Account<'a> {
-    /// Declare a new Account
-    pub fn new(
-        name: &str,
-        institution: &str,
-        first: NaiveDate,
-        period: Shim<'a>,
-        fmt: &str,
-        dir: &Path,
-    ) -> Account<'a> {
-        Account {
-            name: String::from(name),
-            institution: String::from(institution),
+impl AccountBuilder {
+    /// Set the name of the account
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    /// Set the name of the institution the account is with
+    pub fn institution(mut self, institution: &str) -> Self {
+        self.institution = Some(institution.to_string());
+        self
+    }
+
+    /// Set the date of the account's first statement
+    pub fn first(mut self, first: NaiveDate) -> Self {
+        self.first = Some(first);
+        self
+    }
+
+    /// Set the date of the account's last statement, after which no more are expected
+    pub fn last(mut self, last: Option<NaiveDate>) -> Self {
+        self.last = last;
+        self
+    }
+
+    /// Set how often the account's statements are issued
+    pub fn period(mut self, period: StatementPeriod) -> Self {
+        self.period = Some(period);
+        self
+    }
+
+    /// Set the format of the statements' file names, like `%Y-%m-%d.pdf`
+    pub fn format(mut self, fmt: &str) -> Self {
+        self.fmt = Some(fmt.to_string());
+        self
+    }
+
+    /// Set the directory containing the account's statements
+    pub fn directory(mut self, dir: &Path) -> Self {
+        self.dir = Some(dir.to_path_buf());
+        self
+    }
+
+    /// Don't check that the statement directory exists when the account is built.
+    /// This is useful when the statements come from somewhere other than the file system.
+    pub fn unchecked_directory(mut self) -> Self {
+        self.unchecked_dir = true;
+        self
+    }
+
+    /// Create the account, if every required property is set and they're all valid
+    pub fn build(self) -> Result<Account, AccountCreationError> {
+        let name = self.name.ok_or(AccountCreationError::MissingAccountName)?;
+        let institution = self
+            .institution
+            .ok_or(AccountCreationError::MissingInstitutionName)?;
+        let fmt = self
+            .fmt
+            .ok_or(AccountCreationError::MissingStatementFormat)?;
+        validate_statement_format(&fmt)?;
+        let dir = self
+            .dir
+            .ok_or(AccountCreationError::MissingStatementDirectory)?;
+        if !self.unchecked_dir {
+            check_directory(&dir)?;
+        }
+        let first = self.first.ok_or(AccountCreationError::MissingFirstDate)?;
+        if let Some(last) = self.last.filter(|last| *last < first) {
+            return Err(AccountCreationError::LastBeforeFirst(first, last));
+        }
+        let period = self.period.ok_or(AccountCreationError::MissingPeriod)?;
+
+        Ok(Account {
+            name,
+            institution,
             statement_first: first,
+            statement_last: self.last,
             statement_period: period,
-            statement_fmt: String::from(fmt),
-            dir: dir.to_path_buf(),
-            ignored: IgnoredStatements::from(dir),
+            period_source: None,
+            statement_fmt: fmt,
+            ignored: IgnoredStatements::from(dir.as_path()),
+            dir,
+            archive_dir: None,
+            search_archives: false,
+            follow_symlinks: true,
+            exclude: ExcludePatterns::default(),
+            checksums: false,
+            grace_days: 0,
+            group: None,
+            enabled: true,
+            paused_until: None,
+            fetch_cmd: None,
+            open_cmd: None,
+            ledger_account: None,
+            kind: None,
+            currency: None,
+            transaction_fmt: None,
+            schedule: None,
+            schedules: vec![],
+        })
+    }
+}
+
+/// Check that a statement directory exists, or that a remote one can be listed
+fn check_directory(dir: &Path) -> Result<(), AccountCreationError> {
+    if is_remote(dir) {
+        return storage_for(dir)
+            .map(|_| ())
+            .map_err(|e| AccountCreationError::InvalidRemoteDirectory(e.to_string()));
+    }
+
+    match dir.exists() {
+        true => Ok(()),
+        false => Err(AccountCreationError::StatementDirectoryNotFound(
+            dir.to_path_buf(),
+        )),
+    }
+}
+
+impl Account {
+    /// Start building a new account
+    pub fn builder() -> AccountBuilder {
+        AccountBuilder::default()
+    }
+
+    /// Set the group the account is shown under, instead of its institution
+    pub fn with_group(mut self, group: Option<&str>) -> Self {
+        self.group = group.map(String::from);
+        self
+    }
+
+    /// Set whether the account is enabled.
+    /// Disabled accounts stay in the configuration, but their statements aren't checked.
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Pause checking the account's statements until a given date
+    pub fn with_paused_until(mut self, until: Option<NaiveDate>) -> Self {
+        self.paused_until = until;
+        self
+    }
+
+    /// Set the command that downloads the account's statements into its directory
+    pub fn with_fetch_cmd(mut self, cmd: Option<&str>) -> Self {
+        self.fetch_cmd = cmd.map(String::from);
+        self
+    }
+
+    /// Set the command that opens the account's statements
+    pub fn with_open_cmd(mut self, cmd: Option<&str>) -> Self {
+        self.open_cmd = cmd.map(String::from);
+        self
+    }
+
+    /// Set the name of the account in a plain-text accounting journal
+    pub fn with_ledger_account(mut self, name: Option<&str>) -> Self {
+        self.ledger_account = name.map(String::from);
+        self
+    }
+
+    /// Set the kind of account, like a credit card
+    pub fn with_kind(mut self, kind: Option<AccountKind>) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Set the currency of the account, as a three letter code like `CAD`
+    pub fn with_currency(mut self, currency: Option<&str>) -> Self {
+        self.currency = currency.map(String::from);
+        self
+    }
+
+    /// Set the directory that `quill archive` moves the account's statements into,
+    /// under a directory for each year. The account's named schedules share it.
+    pub fn with_archive_dir(mut self, dir: Option<&Path>) -> Self {
+        self.archive_dir = dir.map(Path::to_path_buf);
+        self.schedules = self
+            .schedules
+            .into_iter()
+            .map(|sched| sched.with_archive_dir(dir))
+            .collect();
+        self
+    }
+
+    /// Set whether `.zip` archives in the statement directory are searched for statements
+    pub fn with_search_archives(mut self, search_archives: bool) -> Self {
+        self.search_archives = search_archives;
+        self
+    }
+
+    /// Set whether symbolic links in the statement directory are followed to the statements they point to
+    pub fn with_follow_symlinks(mut self, follow_symlinks: bool) -> Self {
+        self.follow_symlinks = follow_symlinks;
+        self
+    }
+
+    /// Set the patterns for files in the statement directory that are never statements
+    pub fn with_exclude(mut self, exclude: ExcludePatterns) -> Self {
+        self.exclude = exclude;
+        self
+    }
+
+    /// Set whether the checksums of the account's statements are recorded
+    pub fn with_checksums(mut self, checksums: bool) -> Self {
+        self.checksums = checksums;
+        self
+    }
+
+    /// Set how many days after its date a statement may take to be published,
+    /// during which it's pending instead of missing
+    pub fn with_grace_days(mut self, days: u32) -> Self {
+        self.grace_days = days;
+        self
+    }
+
+    /// Add the account's named schedules, each tracking its own statements
+    /// in the account's directory.
+    fn with_schedules(
+        mut self,
+        schedules: &[(&str, &Value)],
+    ) -> Result<Self, AccountCreationError> {
+        self.schedules = schedules
+            .iter()
+            .map(|(name, props)| self.schedule_account(name, props))
+            .collect::<Result<_, _>>()?;
+
+        Ok(self)
+    }
+
+    /// Track the account's transaction files with their own schedule, named
+    /// `transactions`, given the properties built by `parse_transactions`
+    fn with_transactions(mut self, props: Option<&Value>) -> Result<Self, AccountCreationError> {
+        if let Some(props) = props {
+            let sched = self.schedule_account(TRANSACTIONS_SCHEDULE, props)?;
+            self.transaction_fmt = Some(sched.statement_fmt.clone());
+            self.schedules.push(sched);
+        }
+
+        Ok(self)
+    }
+
+    /// Create the account for one of this account's named schedules.
+    /// Everything but the statement format, period, and first and last dates is shared.
+    fn schedule_account(&self, name: &str, props: &Value) -> Result<Account, AccountCreationError> {
+        let in_schedule = |e| AccountCreationError::InSchedule(name.to_string(), Box::new(e));
+
+        let fmt = parse_statement_format(props).map_err(in_schedule)?;
+        let period = parse_statement_period(props).map_err(in_schedule)?;
+        let first = match props.get("first_date") {
+            Some(_) => parse_first_statement_date(props).map_err(in_schedule)?,
+            None => self.statement_first,
+        };
+        let last = match props.get("last_date") {
+            Some(_) => parse_last_statement_date(props).map_err(in_schedule)?,
+            None => self.statement_last,
+        };
+
+        Ok(Account {
+            name: format!("{} ({})", self.name, name),
+            statement_first: first,
+            statement_last: last,
+            statement_period: period,
+            period_source: parse_period_source(props),
+            statement_fmt: fmt.to_string(),
+            schedule: Some(name.to_string()),
+            schedules: vec![],
+            ..self.clone()
+        })
+    }
+
+    /// Check the properties of an account for every problem that would
+    /// prevent it from being created, instead of stopping at the first one.
+    pub fn validate(props: &Value) -> Vec<AccountCreationError> {
+        let props = match interpolate_props(props) {
+            Ok(props) => props,
+            // the other properties can't be checked without their variables
+            Err(e) => return vec![e],
+        };
+        let props = &props;
+        let mut errors: Vec<AccountCreationError> = [
+            parse_account_name(props).err(),
+            parse_institution_name(props).err(),
+            parse_statement_format(props).err(),
+            parse_account_directory(props).err(),
+            parse_first_statement_date(props).err(),
+            parse_last_statement_date(props).err(),
+            parse_statement_period(props).err(),
+            parse_group(props).err(),
+            parse_search_archives(props).err(),
+            parse_follow_symlinks(props).err(),
+            parse_exclude(props).err(),
+            parse_checksums(props).err(),
+            parse_grace_days(props).err(),
+            parse_enabled(props).err(),
+            parse_paused_until(props).err(),
+            parse_fetch_cmd(props).err(),
+            parse_open_cmd(props).err(),
+            parse_ledger_account(props).err(),
+            parse_kind(props).err(),
+            parse_currency(props).err(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        match parse_schedules(props) {
+            Ok(schedules) => {
+                for (name, sched) in schedules {
+                    let sched_errors = [
+                        parse_statement_format(sched).err(),
+                        parse_statement_period(sched).err(),
+                        sched
+                            .get("first_date")
+                            .and_then(|_| parse_first_statement_date(sched).err()),
+                        parse_last_statement_date(sched).err(),
+                    ];
+                    errors.extend(
+                        sched_errors.into_iter().flatten().map(|e| {
+                            AccountCreationError::InSchedule(name.to_string(), Box::new(e))
+                        }),
+                    );
+                }
+            }
+            Err(e) => errors.push(e),
         }
+
+        match parse_transactions(props) {
+            Ok(Some(sched)) => errors.extend(
+                [
+                    parse_statement_format(&sched).err(),
+                    parse_statement_period(&sched).err(),
+                ]
+                .into_iter()
+                .flatten()
+                .map(|e| {
+                    AccountCreationError::InSchedule(TRANSACTIONS_SCHEDULE.to_string(), Box::new(e))
+                }),
+            ),
+            Ok(None) => {}
+            Err(e) => errors.push(e),
+        }
+
+        errors
+    }
+
+    /// Create an account from its properties without checking that its
+    /// statement directory exists.
+    /// This is useful when the statements come from somewhere other than the
+    /// file system, such as a recorded session from another computer.
+    pub fn try_from_unchecked(props: &Value) -> Result<Self, AccountCreationError> {
+        let props = &interpolate_props(props)?;
+        let name = parse_account_name(props)?;
+        let institution = parse_institution_name(props)?;
+        let fmt = parse_statement_format(props)?;
+        let dir = parse_account_directory_unchecked(props)?;
+        let first = parse_first_statement_date(props)?;
+        let last = parse_last_statement_date(props)?;
+        let period = parse_statement_period(props)?;
+        let group = parse_group(props)?;
+        let search_archives = parse_search_archives(props)?;
+        let follow_symlinks = parse_follow_symlinks(props)?;
+        let exclude = parse_exclude(props)?;
+        let checksums = parse_checksums(props)?;
+        let grace_days = parse_grace_days(props)?;
+        let enabled = parse_enabled(props)?;
+        let paused_until = parse_paused_until(props)?;
+        let fetch_cmd = parse_fetch_cmd(props)?;
+        let open_cmd = parse_open_cmd(props)?;
+        let ledger_account = parse_ledger_account(props)?;
+        let kind = parse_kind(props)?;
+        let currency = parse_currency(props)?;
+        let schedules = parse_schedules(props)?;
+        let transactions = parse_transactions(props)?;
+
+        let mut acct = Account::builder()
+            .name(name)
+            .institution(institution)
+            .first(first)
+            .last(last)
+            .period(period)
+            .format(fmt)
+            .directory(&dir)
+            .unchecked_directory()
+            .build()?;
+        acct.period_source = parse_period_source(props);
+
+        acct.with_group(group)
+            .with_search_archives(search_archives)
+            .with_follow_symlinks(follow_symlinks)
+            .with_exclude(exclude)
+            .with_checksums(checksums)
+            .with_grace_days(grace_days)
+            .with_enabled(enabled)
+            .with_paused_until(paused_until)
+            .with_fetch_cmd(fetch_cmd)
+            .with_open_cmd(open_cmd)
+            .with_ledger_account(ledger_account)
+            .with_kind(kind)
+            .with_currency(currency.as_deref())
+            .with_schedules(&schedules)?
+            .with_transactions(transactions.as_ref())
     }
 
     /// Return the name of the account
@@ -57,21 +487,83 @@ impl<'a> Account<'a> {
         &self.name
     }
 
+    /// Return the name of the schedule this account tracks, if it is one of
+    /// another account's named schedules
+    pub fn schedule(&self) -> Option<&str> {
+        self.schedule.as_deref()
+    }
+
+    /// Return the accounts for each of this account's named schedules
+    pub fn schedules(&self) -> &[Account] {
+        &self.schedules
+    }
+
+    /// Return the format of the account's transaction files, if they're tracked.
+    /// They're tracked by the named schedule called `transactions`.
+    pub fn transaction_fmt(&self) -> Option<&str> {
+        self.transaction_fmt.as_deref()
+    }
+
     /// Return the account's first statement date
     pub fn first(&self) -> &NaiveDate {
         &self.statement_first
     }
 
+    /// Return the account's last statement date, if it has been closed
+    pub fn last(&self) -> Option<&NaiveDate> {
+        self.statement_last.as_ref()
+    }
+
+    /// Return whether the account has been closed, with its last statement in the past
+    pub fn is_closed(&self) -> bool {
+        let today = Local::now().naive_local().date();
+        self.statement_last.map_or(false, |last| last < today)
+    }
+
+    /// Return whether the account is enabled
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Return the date the account is paused until, if any
+    pub fn paused_until(&self) -> Option<&NaiveDate> {
+        self.paused_until.as_ref()
+    }
+
+    /// Return whether the account's statements shouldn't be checked right now,
+    /// either because it's disabled or because it's paused until a later date
+    pub fn is_paused(&self) -> bool {
+        let today = Local::now().naive_local().date();
+        !self.enabled || self.paused_until.map_or(false, |until| until > today)
+    }
+
+    /// Describe the statement period in words, like "1st day of every month".
+    /// Accounts that weren't created from their properties can't describe their period.
+    pub fn period_description(&self) -> Option<String> {
+        self.period_source.as_ref().and_then(PeriodSource::describe)
+    }
+
     /// Return the name of the related institution
     pub fn institution(&self) -> &str {
         &self.institution
     }
 
+    /// Return the name of the group the account is shown under.
+    /// This is the institution, unless the account sets its own group.
+    pub fn group(&self) -> &str {
+        self.group.as_deref().unwrap_or(&self.institution)
+    }
+
     /// Return the directory containing statements for this account
     pub fn directory(&self) -> &Path {
         self.dir.as_path()
     }
 
+    /// Return whether the account's statements are in a remote directory, rather than a local one
+    pub fn is_remote(&self) -> bool {
+        is_remote(&self.dir)
+    }
+
     /// Return the directory containing statements for this account
     pub fn format_string(&self) -> &str {
         &self.statement_fmt
@@ -82,111 +574,532 @@ impl<'a> Account<'a> {
         &self.ignored
     }
 
+    /// Return the directory that the account's statements are archived in, if any
+    pub fn archive_dir(&self) -> Option<&Path> {
+        self.archive_dir.as_deref()
+    }
+
+    /// Return whether archives in the statement directory are searched for statements
+    pub fn search_archives(&self) -> bool {
+        self.search_archives
+    }
+
+    /// Return whether symbolic links in the statement directory are followed
+    pub fn follow_symlinks(&self) -> bool {
+        self.follow_symlinks
+    }
+
+    /// Return the patterns for files in the statement directory that are never statements
+    pub fn exclude(&self) -> &ExcludePatterns {
+        &self.exclude
+    }
+
+    /// Check if a file is left out by the account's `exclude` patterns, which match its path
+    /// relative to the statement directory or the archive directory
+    pub fn excludes_file(&self, path: &Path) -> bool {
+        let mut dirs = vec![self.directory()];
+        dirs.extend(self.archive_dir.as_deref());
+        self.exclude.excludes(path, &dirs)
+    }
+
+    /// Return whether the checksums of the account's statements are recorded
+    pub fn checksums(&self) -> bool {
+        self.checksums
+    }
+
+    /// Return how many days after its date a statement may take to be published
+    pub fn grace_days(&self) -> u32 {
+        self.grace_days
+    }
+
+    /// Return the command that downloads the account's statements, if it has one
+    pub fn fetch_cmd(&self) -> Option<&str> {
+        self.fetch_cmd.as_deref()
+    }
+
+    /// Return the command that opens the account's statements, if it has one
+    pub fn open_cmd(&self) -> Option<&str> {
+        self.open_cmd.as_deref()
+    }
+
+    /// Return the name of the account in a plain-text accounting journal, if it has one
+    pub fn ledger_account(&self) -> Option<&str> {
+        self.ledger_account.as_deref()
+    }
+
+    /// Return the kind of account, if it's given
+    pub fn kind(&self) -> Option<AccountKind> {
+        self.kind
+    }
+
+    /// Return the currency of the account, if it's given
+    pub fn currency(&self) -> Option<&str> {
+        self.currency.as_deref()
+    }
+
     /// Calculate the most recent statement before a given date for the account
     pub fn prev_statement_date(&self, date: NaiveDate) -> NaiveDate {
-        prev_date_from_given(&date, &self.statement_period)
+        prev_date_from_given(&date, &self.statement_period.sequence())
     }
 
     /// Print the most recent statement before today for the account
     pub fn prev_statement(&self) -> NaiveDate {
-        prev_date_from_today(&self.statement_period)
+        prev_date_from_today(&self.statement_period.sequence())
     }
 
     /// Calculate the next statement for the account from a given date
     pub fn next_statement_date(&self, date: NaiveDate) -> NaiveDate {
-        next_date_from_given(&date, &self.statement_period)
+        next_date_from_given(&date, &self.statement_period.sequence())
     }
 
     /// Print the next statement for the account from today
     pub fn next_statement(&self) -> NaiveDate {
-        next_date_from_today(&self.statement_period)
+        next_date_from_today(&self.statement_period.sequence())
     }
 
-    /// List all statement dates for the account
+    /// Return the dates of the next `n` statements that are expected after today,
+    /// skipping any that are ignored.
+    /// If the first statement hasn't been issued yet, it is the first of these.
+    pub fn upcoming_statements(&self, n: usize) -> Vec<NaiveDate> {
+        let today = Local::now().naive_local().date();
+        self.upcoming_statements_from(&today, n)
+    }
+
+    /// Return the dates of the next `n` statements that are expected after `from`,
+    /// skipping any that are ignored.
+    fn upcoming_statements_from(&self, from: &NaiveDate, n: usize) -> Vec<NaiveDate> {
+        // start from just before the first statement if it hasn't been issued yet
+        let start = match self.statement_first.pred_opt() {
+            Some(before_first) if before_first > *from => before_first,
+            _ => *from,
+        };
+
+        upcoming_dates(&start, &self.statement_period.sequence())
+            .take_while(|d| self.statement_last.map_or(true, |last| *d <= last))
+            .filter(|d| !self.ignored.contains(d))
+            .take(n)
+            .collect()
+    }
+
+    /// List all statement dates for the account, up to its last statement if it's closed.
     /// This list is guaranteed to be sorted, earliest first
     pub fn statement_dates(&self) -> Vec<NaiveDate> {
-        expected_statement_dates(&self.statement_first, &self.statement_period)
+        let today = Local::now().naive_local().date();
+        let mut dates = self
+            .statement_period
+            .dates_until(&self.statement_first, &today);
+        if let Some(last) = self.statement_last {
+            dates.retain(|d| *d <= last);
+        }
+
+        dates
+    }
+
+    /// List the statement dates for the account between `from` and `to`, inclusive,
+    /// up to its last statement if it's closed.
+    /// Unlike `statement_dates`, dates after today are included.
+    /// This list is guaranteed to be sorted, earliest first
+    pub fn statement_dates_between(&self, from: &NaiveDate, to: &NaiveDate) -> Vec<NaiveDate> {
+        std::iter::once(self.statement_first)
+            .chain(upcoming_dates(
+                &self.statement_first,
+                &self.statement_period.sequence(),
+            ))
+            .take_while(|d| d <= to && self.statement_last.map_or(true, |last| *d <= last))
+            .filter(|d| d >= from)
+            .collect()
     }
 
-    /// Check the account's directory for all downloaded statements
+    /// Check the account's directory for all downloaded statements.
+    /// If archives are searched, statements inside them are found too,
+    /// unless the directory is remote.
+    /// Statements that have been moved to the account's archive directory are found there.
+    /// Symbolic links are followed unless the account turns it off, and on file systems that
+    /// ignore the case of file names, so does the statement format.
     /// This list is guaranteed to be sorted, earliest first
     pub fn downloaded_statements(&self) -> Vec<Statement> {
-        // all files in the directory that match the statement format string
-        let matching_files: Vec<PathBuf> = WalkDir::new(self.directory())
-            .max_depth(1)
+        let fmt = self.format_string();
+
+        self.find_statements(|p, fold_case| parse_statement(p, fmt, fold_case))
+    }
+
+    /// Check the account's directory for all downloaded statements, like `downloaded_statements`,
+    /// reusing the dates in `dates` for any files that haven't changed since they were parsed.
+    /// `dates` is updated with the files that were parsed, and forgets the ones that are gone.
+    /// Remote directories don't use the cache.
+    /// This list is guaranteed to be sorted, earliest first
+    pub fn downloaded_statements_cached(&self, dates: &mut DateCache) -> Vec<Statement> {
+        if self.is_remote() {
+            return self.downloaded_statements();
+        }
+
+        let fmt = self.format_string();
+        let stmts = self.find_statements(|p, fold_case| dates.statement(p, fmt, fold_case));
+        let found: HashSet<&Path> = stmts.iter().map(|s| s.path()).collect();
+        dates.retain(|p| found.contains(p));
+
+        stmts
+    }
+
+    /// Find the downloaded statements, getting the statement for each file with a matching name
+    /// from `parse`, along with whether the case of the name's letters should be ignored
+    fn find_statements(
+        &self,
+        mut parse: impl FnMut(&Path, bool) -> Option<Statement>,
+    ) -> Vec<Statement> {
+        let _span = debug_span!("scan_directory", dir = %self.directory().display()).entered();
+
+        // a directory that can't be listed has no statements, like an empty one
+        let mut files = match storage_for(self.directory()).and_then(|s| s.list_files()) {
+            Ok(files) => files,
+            Err(e) => {
+                warn!("couldn't list the statements in the directory: {}", e);
+                vec![]
+            }
+        };
+
+        // a file reached through several links is still only one statement
+        let mut fold_case = false;
+        if !self.is_remote() {
+            files = resolve_links(files, self.follow_symlinks);
+            fold_case = files
+                .iter()
+                .find_map(|p| is_case_insensitive(p))
+                .unwrap_or(false);
+            if fold_case {
+                debug!("file names are matched without regard to case");
+            }
+        }
+
+        // statements inside archives come after the loose files, so that a loose
+        // file is preferred when both exist for the same date
+        if self.search_archives && !self.is_remote() {
+            let archived: Vec<PathBuf> = files
+                .iter()
+                .filter(|p| is_archive(p) && !self.excludes_file(p))
+                .flat_map(|p| {
+                    trace!(archive = %p.display(), "searching archive");
+                    archived_files(p)
+                })
+                .collect();
+            files.extend(archived);
+        }
+
+        // archived statements are kept in a directory for each year
+        if let Some(archive_dir) = &self.archive_dir {
+            files.extend(
+                WalkDir::new(archive_dir)
+                    .follow_links(self.follow_symlinks)
+                    .min_depth(2)
+                    .max_depth(2)
+                    .into_iter()
+                    .filter_map(|e| e.ok())
+                    .filter(|e| e.file_type().is_file())
+                    .map(|e| e.into_path()),
+            );
+        }
+
+        // all files that match the statement format string, and aren't excluded or ignored
+        let matching_files: Vec<PathBuf> = files
             .into_iter()
-            .filter_map(|p| p.ok())
-            .map(|p| p.into_path())
-            .filter(|p| p.is_file())
-            .filter(|p| file_name_matches(p, self.format_string()))
+            .filter(|p| {
+                let excluded = self.excludes_file(p);
+                if excluded {
+                    debug!(file = %p.display(), "file is excluded");
+                }
+                !excluded
+            })
+            .filter(|p| {
+                let ignored = self.ignored.ignores_file(p);
+                if ignored {
+                    debug!(file = %p.display(), "file is ignored");
+                }
+                !ignored
+            })
+            .filter(|p| {
+                let matches = file_name_matches(p, self.format_string(), fold_case);
+                if !matches {
+                    debug!(
+                        file = %p.display(),
+                        fmt = self.format_string(),
+                        "file doesn't match the statement format"
+                    );
+                }
+                matches
+            })
             .collect();
 
         // a vec of the statements
         let mut stmts: Vec<Statement> = matching_files
             .iter()
-            .filter_map(|p| Statement::try_from((p.as_path(), self.format_string())).ok())
+            .map(PathBuf::as_path)
+            .filter_map(|p| parse(p, fold_case))
             .collect();
         stmts.sort_by(|a, b| a.date().partial_cmp(b.date()).unwrap());
 
         stmts
     }
 
+    /// Check if a file's name follows the account's statement format, ignoring the case of
+    /// its letters if the file is on a file system that does
+    pub fn matches_file_name(&self, path: &Path) -> bool {
+        let fold_case = !self.is_remote() && is_case_insensitive(path).unwrap_or(false);
+        file_name_matches(path, self.format_string(), fold_case)
+    }
+
     /// Match expected and downloaded statements
     pub fn match_statements(&self) -> Vec<ObservedStatement> {
-        // get expected statements
-        let required = self.statement_dates();
-
         // get downloaded statements
         let available = self.downloaded_statements();
 
-        match pair_dates_statements(&required, &available, self.ignored()) {
-            Ok(v) => v,
-            Err(_) => vec![],
-        }
+        self.match_downloaded(&available)
+    }
+
+    /// Find every run of consecutive missing statements, oldest first
+    pub fn gaps(&self) -> Vec<Gap> {
+        find_gaps(&self.match_statements())
+    }
+
+    /// Match expected statements against a list of downloaded statements,
+    /// such as one from a previous call to `downloaded_statements`.
+    /// The downloaded statements must be sorted, earliest first.
+    pub fn match_downloaded(&self, available: &[Statement]) -> Vec<ObservedStatement> {
+        // get expected statements
+        let required = self.statement_dates();
+        // statements that may not have been published yet aren't missing
+        let pending_after =
+            Local::now().naive_local().date() - Duration::days(self.grace_days.into());
+
+        pair_dates_statements_with_grace(&required, available, self.ignored(), &pending_after)
+            .unwrap_or_default()
     }
 }
 
-impl<'a> Debug for Account<'a> {
+impl Debug for Account {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{} ({})", self.name, self.institution)
     }
 }
 
-impl<'a> Display for Account<'a> {
+impl Display for Account {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{} ({})", self.name, self.institution)
     }
 }
 
-impl<'a> PartialEq<Account<'_>> for Account<'_> {
-    fn eq(&self, other: &Account<'_>) -> bool {
-        // TODO: Figure out what to do about the `statement_period` for equality
+impl Account {
+    /// Write the properties that describe when statements are expected, and what they're called
+    fn serialize_statement_props<M: SerializeMap>(&self, map: &mut M) -> Result<(), M::Error> {
+        map.serialize_entry("statement_fmt", &self.statement_fmt)?;
+        map.serialize_entry("first_date", &self.statement_first)?;
+        if let Some(last) = &self.statement_last {
+            map.serialize_entry("last_date", last)?;
+        }
+        match &self.period_source {
+            Some(PeriodSource::Period(arr)) => map.serialize_entry("statement_period", arr)?,
+            Some(PeriodSource::Schedule(s)) => map.serialize_entry("statement_schedule", s)?,
+            None => {}
+        }
+
+        Ok(())
+    }
+}
+
+/// Accounts are written with the same properties as in the configuration file,
+/// so they can be read back in.
+/// The statement period is only written for accounts created from their properties,
+/// since it can't be recovered otherwise.
+impl Serialize for Account {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("name", &self.name)?;
+        map.serialize_entry("institution", &self.institution)?;
+        map.serialize_entry("dir", &self.dir)?;
+        self.serialize_statement_props(&mut map)?;
+        if let Some(group) = &self.group {
+            map.serialize_entry("group", group)?;
+        }
+        map.serialize_entry("search_archives", &self.search_archives)?;
+        map.serialize_entry("follow_symlinks", &self.follow_symlinks)?;
+        if !self.exclude.is_empty() {
+            map.serialize_entry("exclude", self.exclude.patterns())?;
+        }
+        map.serialize_entry("checksums", &self.checksums)?;
+        map.serialize_entry("grace_days", &self.grace_days)?;
+        map.serialize_entry("enabled", &self.enabled)?;
+        if let Some(until) = &self.paused_until {
+            map.serialize_entry("paused_until", until)?;
+        }
+        if let Some(cmd) = &self.fetch_cmd {
+            map.serialize_entry("fetch_cmd", cmd)?;
+        }
+        if let Some(cmd) = &self.open_cmd {
+            map.serialize_entry("open_cmd", cmd)?;
+        }
+        if let Some(name) = &self.ledger_account {
+            map.serialize_entry("ledger_account", name)?;
+        }
+        if let Some(kind) = &self.kind {
+            map.serialize_entry("kind", kind)?;
+        }
+        if let Some(currency) = &self.currency {
+            map.serialize_entry("currency", currency)?;
+        }
+        // the transactions schedule is written as the properties it was created from
+        let (transactions, schedules): (Vec<&Account>, Vec<&Account>) =
+            self.schedules.iter().partition(|sched| {
+                self.transaction_fmt.is_some() && sched.schedule() == Some(TRANSACTIONS_SCHEDULE)
+            });
+        if let Some(sched) = transactions.first() {
+            map.serialize_entry("transaction_fmt", &sched.statement_fmt)?;
+            match &sched.period_source {
+                Some(PeriodSource::Period(arr)) => {
+                    map.serialize_entry("transaction_period", arr)?
+                }
+                Some(PeriodSource::Schedule(s)) => {
+                    map.serialize_entry("transaction_schedule", s)?
+                }
+                None => {}
+            }
+        }
+        if !schedules.is_empty() {
+            let schedules: BTreeMap<&str, ScheduleProps> = schedules
+                .into_iter()
+                .map(|sched| (sched.schedule().unwrap_or_default(), ScheduleProps(sched)))
+                .collect();
+            map.serialize_entry("schedules", &schedules)?;
+        }
+
+        map.end()
+    }
+}
+
+/// The properties of a named schedule, without those it shares with its account
+struct ScheduleProps<'a>(&'a Account);
+
+impl Serialize for ScheduleProps<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        self.0.serialize_statement_props(&mut map)?;
+
+        map.end()
+    }
+}
+
+impl PartialEq<Account> for Account {
+    fn eq(&self, other: &Account) -> bool {
         (self.name() == other.name())
             && (self.first() == other.first())
             && (self.institution() == other.institution())
             && (self.directory() == other.directory())
             && (self.format_string() == other.format_string())
+            && (self.statement_period == other.statement_period)
     }
 }
 
-impl<'a> TryFrom<&Value> for Account<'a> {
+impl TryFrom<&Value> for Account {
     type Error = AccountCreationError;
 
     fn try_from(props: &Value) -> Result<Self, Self::Error> {
+        let props = &interpolate_props(props)?;
         let name = parse_account_name(props)?;
         let institution = parse_institution_name(props)?;
         let fmt = parse_statement_format(props)?;
         let dir_buf = parse_account_directory(props)?;
         let dir = dir_buf.as_path();
         let first = parse_first_statement_date(props)?;
+        let last = parse_last_statement_date(props)?;
         let period = parse_statement_period(props)?;
+        let group = parse_group(props)?;
+        let search_archives = parse_search_archives(props)?;
+        let follow_symlinks = parse_follow_symlinks(props)?;
+        let exclude = parse_exclude(props)?;
+        let checksums = parse_checksums(props)?;
+        let grace_days = parse_grace_days(props)?;
+        let enabled = parse_enabled(props)?;
+        let paused_until = parse_paused_until(props)?;
+        let fetch_cmd = parse_fetch_cmd(props)?;
+        let open_cmd = parse_open_cmd(props)?;
+        let ledger_account = parse_ledger_account(props)?;
+        let kind = parse_kind(props)?;
+        let currency = parse_currency(props)?;
+        let schedules = parse_schedules(props)?;
+        let transactions = parse_transactions(props)?;
+
+        let mut acct = Account::builder()
+            .name(name)
+            .institution(institution)
+            .first(first)
+            .last(last)
+            .period(period)
+            .format(fmt)
+            .directory(dir)
+            .build()?;
+        acct.period_source = parse_period_source(props);
 
-        Ok(Account::new(name, institution, first, period, fmt, dir))
+        acct.with_group(group)
+            .with_search_archives(search_archives)
+            .with_follow_symlinks(follow_symlinks)
+            .with_exclude(exclude)
+            .with_checksums(checksums)
+            .with_grace_days(grace_days)
+            .with_enabled(enabled)
+            .with_paused_until(paused_until)
+            .with_fetch_cmd(fetch_cmd)
+            .with_open_cmd(open_cmd)
+            .with_ledger_account(ledger_account)
+            .with_kind(kind)
+            .with_currency(currency.as_deref())
+            .with_schedules(&schedules)?
+            .with_transactions(transactions.as_ref())
     }
 }
 
-/// Check if the path's filename matches a given regex
-fn file_name_matches(path: &Path, fmt: &str) -> bool {
+/// Lowercase the letters of a statement format, except those of its specifiers, so that it
+/// matches lowercased file names
+fn fold_format(fmt: &str) -> String {
+    let mut folded = String::with_capacity(fmt.len());
+    let mut chars = fmt.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            folded.extend(c.to_lowercase());
+            continue;
+        }
+
+        // keep the specifier as it is, along with any padding or width before it
+        folded.push(c);
+        for c in chars.by_ref() {
+            folded.push(c);
+            if c.is_ascii_alphabetic() || c == '%' {
+                break;
+            }
+        }
+    }
+
+    folded
+}
+
+/// Parse the date from a file name with a statement format.
+/// If `fold_case`, the letters of the name and the format don't need to have the same case.
+fn parse_file_date(fname: &str, fmt: &str, fold_case: bool) -> chrono::ParseResult<NaiveDate> {
+    match fold_case {
+        true => NaiveDate::parse_from_str(&fname.to_lowercase(), &fold_format(fmt)),
+        false => NaiveDate::parse_from_str(fname, fmt),
+    }
+}
+
+/// Get the statement for a file from its name, like `Statement::try_from`.
+/// If `fold_case`, the letters of the name and the format don't need to have the same case.
+pub(crate) fn parse_statement(path: &Path, fmt: &str, fold_case: bool) -> Option<Statement> {
+    let fname = path.file_name()?.to_str()?;
+    let date = parse_file_date(fname, fmt, fold_case).ok()?;
+
+    Some(Statement::new(path, &date))
+}
+
+/// Check if the path's filename matches a given regex.
+/// If `fold_case`, the letters of the name and the format don't need to have the same case.
+fn file_name_matches(path: &Path, fmt: &str, fold_case: bool) -> bool {
     let fname = path
         .file_name()
         .unwrap_or_else(|| OsStr::new(""))
@@ -195,13 +1108,16 @@ fn file_name_matches(path: &Path, fmt: &str) -> bool {
 
     // extract the date, if possible, from the file name with the statement's
     // format string
-    let fname_date = match NaiveDate::parse_from_str(fname, fmt) {
+    let fname_date = match parse_file_date(fname, fmt, fold_case) {
         Ok(d) => d,
         Err(_) => return false,
     };
 
     // reconstruct what the filename for this date should be
-    let re_str = format!("^{}$", fname_date.format(fmt));
+    let re_str = match fold_case {
+        true => format!("(?i)^{}$", fname_date.format(fmt)),
+        false => format!("^{}$", fname_date.format(fmt)),
+    };
     let re = Regex::new(&re_str).unwrap();
 
     // check for the match
@@ -211,41 +1127,731 @@ fn file_name_matches(path: &Path, fmt: &str) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use kronos::{Grain, Grains, NthOf};
+    use kronos::Grain;
+    use quill_statement::StatementStatus;
+
+    /// A builder for a monthly account with statements in `dir`
+    fn monthly(dir: &str) -> AccountBuilder {
+        Account::builder()
+            .name("test name")
+            .institution("institution name")
+            .first(NaiveDate::from_ymd_opt(2011, 1, 1).unwrap())
+            .period(StatementPeriod::nth_of(1, Grain::Day, 1, Grain::Month))
+            .format("%Y-%m-%d.pdf")
+            .directory(Path::new(dir))
+    }
 
     #[track_caller]
-    fn check_new(input: (&str, &str, NaiveDate, Shim<'static>, &str, &Path), expected: Account) {
-        let observed = Account::new(input.0, input.1, input.2, input.3, input.4, input.5);
+    fn check_build(input: AccountBuilder, expected: Result<Account, AccountCreationError>) {
+        let observed = input.build();
 
         assert_eq!(expected, observed);
     }
 
     #[test]
-    fn new() {
-        let input = (
-            "test name",
-            "institution name",
-            NaiveDate::from_ymd_opt(2011, 1, 1).unwrap(),
-            Shim::new(NthOf(1, Grains(Grain::Day), Grains(Grain::Month))),
-            "%Y-%m-%d.pdf",
-            Path::new("test-dir"),
-        );
+    fn build() {
+        let input = monthly("test-dir").unchecked_directory();
         let expected = Account {
             name: "test name".to_string(),
             institution: "institution name".to_string(),
             statement_first: NaiveDate::from_ymd_opt(2011, 1, 1).unwrap(),
-            statement_period: Shim::new(NthOf(1, Grains(Grain::Day), Grains(Grain::Month))),
+            statement_last: None,
+            statement_period: StatementPeriod::nth_of(1, Grain::Day, 1, Grain::Month),
+            period_source: None,
             statement_fmt: "%Y-%m-%d.pdf".to_string(),
             dir: PathBuf::from("test-dir"),
+            archive_dir: None,
             ignored: IgnoredStatements::empty(),
+            search_archives: false,
+            follow_symlinks: true,
+            exclude: ExcludePatterns::default(),
+            checksums: false,
+            grace_days: 0,
+            group: None,
+            enabled: true,
+            paused_until: None,
+            fetch_cmd: None,
+            open_cmd: None,
+            ledger_account: None,
+            kind: None,
+            currency: None,
+            transaction_fmt: None,
+            schedule: None,
+            schedules: vec![],
         };
 
-        check_new(input, expected);
+        check_build(input, Ok(expected));
+    }
+
+    #[test]
+    fn build_missing_property() {
+        let input = Account::builder()
+            .name("test name")
+            .institution("institution name")
+            .format("%Y-%m-%d.pdf")
+            .directory(Path::new("tests/no-statements"));
+
+        check_build(input, Err(AccountCreationError::MissingFirstDate));
+    }
+
+    #[test]
+    fn build_incomplete_format() {
+        let input = monthly("tests/no-statements").format("%Y-%m.pdf");
+        let expected = AccountCreationError::IncompleteStatementFormat("%Y-%m.pdf".to_string());
+
+        check_build(input, Err(expected));
+    }
+
+    #[test]
+    fn build_missing_directory() {
+        let input = monthly("tests/does-not-exist");
+        let expected =
+            AccountCreationError::StatementDirectoryNotFound(PathBuf::from("tests/does-not-exist"));
+
+        check_build(input, Err(expected));
+    }
+
+    #[test]
+    fn build_last_before_first() {
+        let first = NaiveDate::from_ymd_opt(2011, 1, 1).unwrap();
+        let last = NaiveDate::from_ymd_opt(2010, 12, 1).unwrap();
+        let input = monthly("tests/no-statements").last(Some(last));
+
+        check_build(
+            input,
+            Err(AccountCreationError::LastBeforeFirst(first, last)),
+        );
+    }
+
+    #[test]
+    fn validate_valid() {
+        let props: Value = toml::from_str(
+            r#"
+            name = "Name"
+            institution = "Institution"
+            statement_fmt = "%Y-%m-%d.pdf"
+            dir = "tests/no-statements"
+            first_date = 2021-01-01
+            statement_period = [1, "Day", 1, "Month"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            Vec::<AccountCreationError>::new(),
+            Account::validate(&props)
+        );
+    }
+
+    #[test]
+    fn exclude_patterns() {
+        let props: Value = toml::from_str(
+            r#"
+            name = "Name"
+            institution = "Institution"
+            statement_fmt = "%Y-%m-%d.pdf"
+            dir = "tests/no-statements"
+            first_date = 2021-01-01
+            statement_period = [1, "Day", 1, "Month"]
+            exclude = ["*.tmp", "drafts/**"]
+            "#,
+        )
+        .unwrap();
+
+        let acct = Account::try_from_unchecked(&props).unwrap();
+        assert_eq!(&["*.tmp", "drafts/**"], acct.exclude().patterns());
+        assert!(acct.excludes_file(Path::new("tests/no-statements/drafts/2021-01-01.pdf")));
+
+        let mut props = props;
+        props["exclude"] = Value::from("*.tmp");
+        assert!(matches!(
+            Account::validate(&props).as_slice(),
+            [AccountCreationError::InvalidExclude(_)]
+        ));
+        props["exclude"] = Value::from(vec!["drafts/[a"]);
+        assert!(matches!(
+            Account::validate(&props).as_slice(),
+            [AccountCreationError::InvalidExcludePattern(..)]
+        ));
+    }
+
+    #[test]
+    fn schedule_instead_of_period() {
+        let props: Value = toml::from_str(
+            r#"
+            name = "Name"
+            institution = "Institution"
+            statement_fmt = "%Y-%m-%d.pdf"
+            dir = "tests/does-not-exist"
+            first_date = 2021-02-26
+            statement_schedule = "LW FEB,APR *"
+            "#,
+        )
+        .unwrap();
+
+        let acct = Account::try_from_unchecked(&props).unwrap();
+        assert_eq!(
+            NaiveDate::from_ymd_opt(2021, 4, 30).unwrap(),
+            acct.next_statement_date(NaiveDate::from_ymd_opt(2021, 2, 26).unwrap())
+        );
+    }
+
+    #[test]
+    fn describe_period() {
+        let props: Value = toml::from_str(
+            r#"
+            name = "Name"
+            institution = "Institution"
+            statement_fmt = "%Y-%m-%d.pdf"
+            dir = "tests/does-not-exist"
+            first_date = 2021-01-01
+            statement_period = [[1, -1], "Day", 3, "Month"]
+            "#,
+        )
+        .unwrap();
+
+        let acct = Account::try_from_unchecked(&props).unwrap();
+        assert_eq!(
+            Some("1st and last day of every 3rd month".to_string()),
+            acct.period_description()
+        );
+    }
+
+    #[test]
+    fn gaps_without_statements() {
+        let props: Value = toml::from_str(
+            r#"
+            name = "Name"
+            institution = "Institution"
+            statement_fmt = "%Y-%m-%d.pdf"
+            dir = "tests/does-not-exist"
+            first_date = 2021-01-01
+            last_date = 2021-06-01
+            statement_period = [1, "Day", 1, "Month"]
+            "#,
+        )
+        .unwrap();
+
+        let acct = Account::try_from_unchecked(&props).unwrap();
+        let gaps = acct.gaps();
+        assert_eq!(1, gaps.len());
+        assert_eq!("Jan\u{2013}Jun 2021: 6 missing", gaps[0].to_string());
+    }
+
+    #[test]
+    fn period_and_schedule_conflict() {
+        let props: Value = toml::from_str(
+            r#"
+            name = "Name"
+            institution = "Institution"
+            statement_fmt = "%Y-%m-%d.pdf"
+            dir = "tests/no-statements"
+            first_date = 2021-01-01
+            statement_period = [1, "Day", 1, "Month"]
+            statement_schedule = "1 * *"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec![AccountCreationError::PeriodAndSchedule],
+            Account::validate(&props)
+        );
+    }
+
+    #[test]
+    fn named_schedules() {
+        let props: Value = toml::from_str(
+            r#"
+            name = "Name"
+            institution = "Institution"
+            statement_fmt = "%Y-%m-%d.pdf"
+            dir = "tests/does-not-exist"
+            first_date = 2021-01-15
+            statement_period = [15, "Day", 1, "Month"]
+
+            [schedules.tax]
+            statement_fmt = "tax-%Y-%m-%d.pdf"
+            statement_schedule = "28 FEB *"
+            first_date = 2022-02-28
+            "#,
+        )
+        .unwrap();
+
+        let acct = Account::try_from_unchecked(&props).unwrap();
+        assert_eq!(None, acct.schedule());
+        assert_eq!(1, acct.schedules().len());
+
+        let tax = &acct.schedules()[0];
+        assert_eq!(Some("tax"), tax.schedule());
+        assert_eq!("Name (tax)", tax.name());
+        assert_eq!("tax-%Y-%m-%d.pdf", tax.format_string());
+        assert_eq!(acct.directory(), tax.directory());
+        assert_eq!(
+            NaiveDate::from_ymd_opt(2022, 2, 28).unwrap(),
+            tax.statement_dates()[0]
+        );
+    }
+
+    #[test]
+    fn named_schedule_errors() {
+        let props: Value = toml::from_str(
+            r#"
+            name = "Name"
+            institution = "Institution"
+            statement_fmt = "%Y-%m-%d.pdf"
+            dir = "tests/no-statements"
+            first_date = 2021-01-15
+            statement_period = [15, "Day", 1, "Month"]
+
+            [schedules.tax]
+            statement_fmt = "tax-%Y-%m-%d.pdf"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec![AccountCreationError::InSchedule(
+                "tax".to_string(),
+                Box::new(AccountCreationError::MissingPeriod)
+            )],
+            Account::validate(&props)
+        );
+    }
+
+    #[test]
+    fn transaction_files() {
+        let props: Value = toml::from_str(
+            r#"
+            name = "Name"
+            institution = "Institution"
+            statement_fmt = "%Y-%m-%d.pdf"
+            dir = "tests/does-not-exist"
+            first_date = 2021-01-15
+            last_date = 2021-06-15
+            statement_period = [15, "Day", 1, "Month"]
+            transaction_fmt = "%Y-%m-%d.qfx"
+            "#,
+        )
+        .unwrap();
+
+        let acct = Account::try_from_unchecked(&props).unwrap();
+        assert_eq!(Some("%Y-%m-%d.qfx"), acct.transaction_fmt());
+        assert_eq!(1, acct.schedules().len());
+
+        let transactions = &acct.schedules()[0];
+        assert_eq!(Some(TRANSACTIONS_SCHEDULE), transactions.schedule());
+        assert_eq!("%Y-%m-%d.qfx", transactions.format_string());
+        assert_eq!(acct.statement_dates(), transactions.statement_dates());
+
+        // transaction files can be downloaded on their own schedule
+        let mut props = props;
+        let table = props.as_table_mut().unwrap();
+        table.insert(
+            "transaction_schedule".to_string(),
+            Value::String("28 * *".to_string()),
+        );
+        let acct = Account::try_from_unchecked(&props).unwrap();
+        assert!(acct.schedules()[0]
+            .statement_dates()
+            .contains(&NaiveDate::from_ymd_opt(2021, 1, 28).unwrap()));
+
+        let json = serde_json::to_string(&acct).unwrap();
+        let read_back: Value = serde_json::from_str(&json).unwrap();
+        let acct_read_back = Account::try_from_unchecked(&read_back).unwrap();
+        assert_eq!(1, acct_read_back.schedules().len());
+        assert_eq!(
+            acct.schedules()[0].statement_dates(),
+            acct_read_back.schedules()[0].statement_dates()
+        );
+    }
+
+    #[test]
+    fn transaction_file_errors() {
+        let props: Value = toml::from_str(
+            r#"
+            name = "Name"
+            institution = "Institution"
+            statement_fmt = "%Y-%m-%d.pdf"
+            dir = "tests/no-statements"
+            first_date = 2021-01-15
+            statement_period = [15, "Day", 1, "Month"]
+            transaction_fmt = "%Y-%m-%d.qfx"
+
+            [schedules.transactions]
+            statement_fmt = "%Y-%m-%d.csv"
+            statement_period = [15, "Day", 1, "Month"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec![AccountCreationError::DuplicateTransactionsSchedule],
+            Account::validate(&props)
+        );
+    }
+
+    #[test]
+    fn serialize_round_trip() {
+        let props: Value = toml::from_str(
+            r#"
+            name = "Name"
+            institution = "Institution"
+            statement_fmt = "%Y-%m-%d.pdf"
+            dir = "tests/does-not-exist"
+            first_date = 2021-01-15
+            last_date = 2022-06-15
+            statement_period = [15, "Day", 1, "Month"]
+            group = "Group"
+            fetch_cmd = "./download.sh"
+            open_cmd = "zathura {path}"
+            ledger_account = "Assets:Bank:Chequing"
+            kind = "chequing"
+            currency = "CAD"
+            grace_days = 5
+
+            [schedules.tax]
+            statement_fmt = "tax-%Y-%m-%d.pdf"
+            statement_schedule = "28 FEB *"
+            first_date = 2022-02-28
+            "#,
+        )
+        .unwrap();
+        let acct = Account::try_from_unchecked(&props).unwrap();
+
+        let json = serde_json::to_string(&acct).unwrap();
+        let read_back: Value = serde_json::from_str(&json).unwrap();
+        let acct_read_back = Account::try_from_unchecked(&read_back).unwrap();
+
+        assert_eq!(acct, acct_read_back);
+        assert_eq!(acct.statement_dates(), acct_read_back.statement_dates());
+        assert_eq!(acct.last(), acct_read_back.last());
+        assert_eq!(acct.group(), acct_read_back.group());
+        assert_eq!(Some("./download.sh"), acct_read_back.fetch_cmd());
+        assert_eq!(Some("zathura {path}"), acct_read_back.open_cmd());
+        assert_eq!(
+            Some("Assets:Bank:Chequing"),
+            acct_read_back.ledger_account()
+        );
+        assert_eq!(Some(AccountKind::Chequing), acct_read_back.kind());
+        assert_eq!(Some("CAD"), acct_read_back.currency());
+        assert_eq!(5, acct_read_back.grace_days());
+        assert_eq!(
+            acct.schedules()[0].statement_dates(),
+            acct_read_back.schedules()[0].statement_dates()
+        );
+    }
+
+    #[test]
+    fn grace_period() {
+        let props = |grace: &str| -> Value {
+            toml::from_str(&format!(
+                r#"
+                name = "Name"
+                institution = "Institution"
+                statement_fmt = "%Y-%m-%d.pdf"
+                dir = "tests/no-statements"
+                first_date = 2021-01-01
+                statement_period = [1, "Day", 1, "Month"]
+                {}
+                "#,
+                grace
+            ))
+            .unwrap()
+        };
+        let statuses = |acct: &Account| -> Vec<StatementStatus> {
+            acct.match_statements()
+                .iter()
+                .map(|obs| obs.status())
+                .collect()
+        };
+
+        // without a grace period, every statement that isn't found is missing
+        let acct = Account::try_from_unchecked(&props("")).unwrap();
+        assert_eq!(0, acct.grace_days());
+        assert!(statuses(&acct)
+            .iter()
+            .all(|s| *s == StatementStatus::Missing));
+
+        // only statements within the grace period are pending
+        let acct = Account::try_from_unchecked(&props("grace_days = 45")).unwrap();
+        let observed = statuses(&acct);
+        assert_eq!(Some(&StatementStatus::Missing), observed.first());
+        assert_eq!(Some(&StatementStatus::Pending), observed.last());
+
+        for invalid in ["grace_days = -1", "grace_days = \"7\""] {
+            assert_eq!(
+                Some("grace_days"),
+                Account::try_from_unchecked(&props(invalid))
+                    .unwrap_err()
+                    .property()
+            );
+        }
+    }
+
+    #[test]
+    fn closed_account() {
+        let acct = Account::builder()
+            .name("test name")
+            .institution("institution name")
+            .first(NaiveDate::from_ymd_opt(2021, 1, 1).unwrap())
+            .period(StatementPeriod::nth_of(1, Grain::Day, 1, Grain::Month))
+            .format("%Y-%m-%d.pdf")
+            .directory(Path::new("test-dir"))
+            .last(NaiveDate::from_ymd_opt(2021, 3, 1))
+            .unchecked_directory()
+            .build()
+            .unwrap();
+
+        assert!(acct.is_closed());
+        assert_eq!(
+            vec![
+                NaiveDate::from_ymd_opt(2021, 1, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2021, 2, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2021, 3, 1).unwrap(),
+            ],
+            acct.statement_dates()
+        );
+        assert_eq!(
+            vec![NaiveDate::from_ymd_opt(2021, 3, 1).unwrap()],
+            acct.upcoming_statements_from(&NaiveDate::from_ymd_opt(2021, 2, 15).unwrap(), 3)
+        );
+    }
+
+    #[test]
+    fn paused_accounts() {
+        let acct = Account::builder()
+            .name("test name")
+            .institution("institution name")
+            .first(NaiveDate::from_ymd_opt(2021, 1, 1).unwrap())
+            .period(StatementPeriod::nth_of(1, Grain::Day, 1, Grain::Month))
+            .format("%Y-%m-%d.pdf")
+            .directory(Path::new("test-dir"))
+            .unchecked_directory()
+            .build()
+            .unwrap();
+        assert!(!acct.is_paused());
+
+        let disabled = acct.clone().with_enabled(false);
+        assert!(disabled.is_paused());
+
+        let paused = acct
+            .clone()
+            .with_paused_until(NaiveDate::from_ymd_opt(9999, 1, 1));
+        assert!(paused.is_paused());
+
+        let resumed = acct.with_paused_until(NaiveDate::from_ymd_opt(2021, 6, 1));
+        assert!(!resumed.is_paused());
+    }
+
+    #[test]
+    fn last_before_first() {
+        let props: Value = toml::from_str(
+            r#"
+            name = "Name"
+            institution = "Institution"
+            statement_fmt = "%Y-%m-%d.pdf"
+            dir = "tests/no-statements"
+            first_date = 2021-01-01
+            last_date = 2020-12-01
+            statement_period = [1, "Day", 1, "Month"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec![AccountCreationError::LastBeforeFirst(
+                NaiveDate::from_ymd_opt(2021, 1, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2020, 12, 1).unwrap()
+            )],
+            Account::validate(&props)
+        );
+    }
+
+    #[test]
+    fn validate_reports_all_errors() {
+        let props: Value = toml::from_str(
+            r#"
+            name = "Name"
+            statement_fmt = "%Y-%m.pdf"
+            dir = "tests/no-statements"
+            first_date = 2021-01-01
+            statement_period = [1, "Day", "Month"]
+            "#,
+        )
+        .unwrap();
+
+        let expected = vec![
+            AccountCreationError::MissingInstitutionName,
+            AccountCreationError::IncompleteStatementFormat("%Y-%m.pdf".to_string()),
+            AccountCreationError::InvalidPeriodIncorrectLength(3),
+        ];
+
+        assert_eq!(expected, Account::validate(&props));
+    }
+
+    #[test]
+    fn upcoming_before_first_statement() {
+        let acct = Account::builder()
+            .name("test name")
+            .institution("institution name")
+            .first(NaiveDate::from_ymd_opt(2100, 1, 15).unwrap())
+            .period(StatementPeriod::nth_of(15, Grain::Day, 1, Grain::Month))
+            .format("%Y-%m-%d.pdf")
+            .directory(Path::new("test-dir"))
+            .unchecked_directory()
+            .build()
+            .unwrap();
+
+        let expected = vec![
+            NaiveDate::from_ymd_opt(2100, 1, 15).unwrap(),
+            NaiveDate::from_ymd_opt(2100, 2, 15).unwrap(),
+        ];
+
+        assert_eq!(
+            expected,
+            acct.upcoming_statements_from(&NaiveDate::from_ymd_opt(2021, 1, 1).unwrap(), 2)
+        );
+    }
+
+    #[test]
+    fn upcoming_after_first_statement() {
+        let acct = Account::builder()
+            .name("test name")
+            .institution("institution name")
+            .first(NaiveDate::from_ymd_opt(2021, 1, 15).unwrap())
+            .period(StatementPeriod::nth_of(15, Grain::Day, 1, Grain::Month))
+            .format("%Y-%m-%d.pdf")
+            .directory(Path::new("test-dir"))
+            .unchecked_directory()
+            .build()
+            .unwrap();
+
+        let expected = vec![
+            NaiveDate::from_ymd_opt(2100, 2, 15).unwrap(),
+            NaiveDate::from_ymd_opt(2100, 3, 15).unwrap(),
+            NaiveDate::from_ymd_opt(2100, 4, 15).unwrap(),
+        ];
+
+        assert_eq!(
+            expected,
+            acct.upcoming_statements_from(&NaiveDate::from_ymd_opt(2100, 1, 20).unwrap(), 3)
+        );
+    }
+
+    #[test]
+    fn statement_dates_between() {
+        let acct = Account::builder()
+            .name("test name")
+            .institution("institution name")
+            .first(NaiveDate::from_ymd_opt(2021, 1, 15).unwrap())
+            .period(StatementPeriod::nth_of(15, Grain::Day, 1, Grain::Month))
+            .format("%Y-%m-%d.pdf")
+            .directory(Path::new("test-dir"))
+            .last(Some(NaiveDate::from_ymd_opt(2021, 3, 15).unwrap()))
+            .unchecked_directory()
+            .build()
+            .unwrap();
+
+        let expected = vec![
+            NaiveDate::from_ymd_opt(2021, 1, 15).unwrap(),
+            NaiveDate::from_ymd_opt(2021, 2, 15).unwrap(),
+            NaiveDate::from_ymd_opt(2021, 3, 15).unwrap(),
+        ];
+
+        assert_eq!(
+            expected,
+            acct.statement_dates_between(
+                &NaiveDate::from_ymd_opt(2020, 1, 1).unwrap(),
+                &NaiveDate::from_ymd_opt(2021, 12, 31).unwrap()
+            )
+        );
+        assert_eq!(
+            vec![NaiveDate::from_ymd_opt(2021, 2, 15).unwrap()],
+            acct.statement_dates_between(
+                &NaiveDate::from_ymd_opt(2021, 2, 1).unwrap(),
+                &NaiveDate::from_ymd_opt(2021, 3, 1).unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn unchecked_missing_directory() {
+        let props: Value = toml::from_str(
+            r#"
+            name = "Name"
+            institution = "Institution"
+            statement_fmt = "%Y-%m-%d.pdf"
+            dir = "tests/does-not-exist"
+            first_date = 2021-01-01
+            statement_period = [1, "Day", 1, "Month"]
+            "#,
+        )
+        .unwrap();
+
+        assert!(Account::try_from(&props).is_err());
+
+        let acct = Account::try_from_unchecked(&props).unwrap();
+        assert_eq!(Path::new("tests/does-not-exist"), acct.directory());
+    }
+
+    #[test]
+    fn expanded_directory() {
+        std::env::set_var("QUILL_ACCOUNT_TEST_ROOT", "tests");
+        let props: Value = toml::from_str(
+            r#"
+            name = "Name"
+            institution = "Institution"
+            statement_fmt = "%Y-%m-%d.pdf"
+            dir = "${QUILL_ACCOUNT_TEST_ROOT}\\does-not-exist"
+            first_date = 2021-01-01
+            statement_period = [1, "Day", 1, "Month"]
+            "#,
+        )
+        .unwrap();
+
+        let acct = Account::try_from_unchecked(&props).unwrap();
+        assert_eq!(Path::new("tests").join("does-not-exist"), acct.directory());
+
+        let unset = props
+            .as_table()
+            .cloned()
+            .map(|mut t| {
+                t.insert(
+                    "dir".to_string(),
+                    Value::from("$QUILL_ACCOUNT_TEST_UNSET/a"),
+                );
+                Value::Table(t)
+            })
+            .unwrap();
+        assert_eq!(
+            Some("dir"),
+            Account::try_from_unchecked(&unset).unwrap_err().property()
+        );
+    }
+
+    #[test]
+    fn group_defaults_to_institution() {
+        let props: Value = toml::from_str(
+            r#"
+            name = "Name"
+            institution = "Institution"
+            statement_fmt = "%Y-%m-%d.pdf"
+            dir = "tests/does-not-exist"
+            first_date = 2021-01-01
+            statement_period = [1, "Day", 1, "Month"]
+            "#,
+        )
+        .unwrap();
+
+        let acct = Account::try_from_unchecked(&props).unwrap();
+        assert_eq!("Institution", acct.group());
+        assert_eq!("Family", acct.with_group(Some("Family")).group());
     }
 
     #[track_caller]
     fn check_file_name_matches(input: (&Path, &str), expected: bool) {
-        let observed = file_name_matches(input.0, input.1);
+        let observed = file_name_matches(input.0, input.1, false);
 
         assert_eq!(expected, observed)
     }
@@ -267,15 +1873,38 @@ mod tests {
     }
 
     #[test]
-    fn downloaded_none() {
-        let acct = Account::new(
-            "Name",
-            "Institution",
-            NaiveDate::from_ymd_opt(2021, 1, 1).unwrap(),
-            Shim::new(NthOf(1, Grains(Grain::Day), Grains(Grain::Month))),
-            "%Y-%m-%d.pdf",
-            Path::new("tests/no-statements"),
+    fn folded_formats_keep_specifiers() {
+        assert_eq!(
+            "statement_%Y-%m-%d.pdf",
+            fold_format("Statement_%Y-%m-%d.PDF")
         );
+        assert_eq!("%B %-d, %Y%%.pdf", fold_format("%B %-d, %Y%%.pdf"));
+    }
+
+    #[test]
+    fn case_insensitive_format() {
+        let path = Path::new("STATEMENT_JAN_2021-01-01.pdf");
+        let s = "Statement_%b_%Y-%m-%d.PDF";
+
+        assert!(!file_name_matches(path, s, false));
+        assert!(file_name_matches(path, s, true));
+        assert_eq!(
+            Some(NaiveDate::from_ymd_opt(2021, 1, 1).unwrap()),
+            parse_statement(path, s, true).map(|stmt| *stmt.date())
+        );
+    }
+
+    #[test]
+    fn downloaded_none() {
+        let acct = Account::builder()
+            .name("Name")
+            .institution("Institution")
+            .first(NaiveDate::from_ymd_opt(2021, 1, 1).unwrap())
+            .period(StatementPeriod::nth_of(1, Grain::Day, 1, Grain::Month))
+            .format("%Y-%m-%d.pdf")
+            .directory(Path::new("tests/no-statements"))
+            .build()
+            .unwrap();
         let expected: Vec<Statement> = vec![];
 
         assert_eq!(expected, acct.downloaded_statements());
@@ -283,14 +1912,15 @@ mod tests {
 
     #[test]
     fn downloaded_some() {
-        let acct = Account::new(
-            "Name",
-            "Institution",
-            NaiveDate::from_ymd_opt(2021, 1, 1).unwrap(),
-            Shim::new(NthOf(1, Grains(Grain::Day), Grains(Grain::Month))),
-            "%Y-%m-%d.pdf",
-            Path::new("tests/exact-matching-statements"),
-        );
+        let acct = Account::builder()
+            .name("Name")
+            .institution("Institution")
+            .first(NaiveDate::from_ymd_opt(2021, 1, 1).unwrap())
+            .period(StatementPeriod::nth_of(1, Grain::Day, 1, Grain::Month))
+            .format("%Y-%m-%d.pdf")
+            .directory(Path::new("tests/exact-matching-statements"))
+            .build()
+            .unwrap();
 
         let expected = vec![
             Statement::new(
@@ -308,14 +1938,15 @@ mod tests {
 
     #[test]
     fn downloaded_some_with_others() {
-        let acct = Account::new(
-            "Name",
-            "Institution",
-            NaiveDate::from_ymd_opt(2021, 1, 1).unwrap(),
-            Shim::new(NthOf(1, Grains(Grain::Day), Grains(Grain::Month))),
-            "%Y-%m-%d.pdf",
-            Path::new("tests/matching-with-others"),
-        );
+        let acct = Account::builder()
+            .name("Name")
+            .institution("Institution")
+            .first(NaiveDate::from_ymd_opt(2021, 1, 1).unwrap())
+            .period(StatementPeriod::nth_of(1, Grain::Day, 1, Grain::Month))
+            .format("%Y-%m-%d.pdf")
+            .directory(Path::new("tests/matching-with-others"))
+            .build()
+            .unwrap();
 
         let expected = vec![
             Statement::new(
@@ -330,4 +1961,145 @@ mod tests {
 
         assert_eq!(expected, acct.downloaded_statements());
     }
+
+    #[test]
+    fn downloaded_skips_ignored_files() {
+        let acct = Account::builder()
+            .name("Name")
+            .institution("Institution")
+            .first(NaiveDate::from_ymd_opt(2021, 1, 1).unwrap())
+            .period(StatementPeriod::nth_of(1, Grain::Day, 1, Grain::Month))
+            .format("%Y-%m-%d.pdf")
+            .directory(Path::new("tests/ignored-files"))
+            .build()
+            .unwrap();
+
+        let expected = vec![Statement::new(
+            Path::new("tests/ignored-files/2021-01-01.pdf"),
+            &NaiveDate::from_ymd_opt(2021, 1, 1).unwrap(),
+        )];
+
+        assert_eq!(expected, acct.downloaded_statements());
+    }
+
+    #[test]
+    fn downloaded_in_archive() {
+        use std::{fs, io::Write};
+        use zip::{write::SimpleFileOptions, ZipWriter};
+
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        fs::write(dir.join("2021-02-01.pdf"), "February").unwrap();
+
+        let archive = dir.join("2021.zip");
+        let mut zip = ZipWriter::new(fs::File::create(&archive).unwrap());
+        for name in ["2021-01-01.pdf", "2021-02-01.pdf", "notes.txt"] {
+            zip.start_file(name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(name.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+
+        let acct = Account::builder()
+            .name("Name")
+            .institution("Institution")
+            .first(NaiveDate::from_ymd_opt(2021, 1, 1).unwrap())
+            .period(StatementPeriod::nth_of(1, Grain::Day, 1, Grain::Month))
+            .format("%Y-%m-%d.pdf")
+            .directory(dir)
+            .build()
+            .unwrap();
+        let loose = vec![Statement::new(
+            &dir.join("2021-02-01.pdf"),
+            &NaiveDate::from_ymd_opt(2021, 2, 1).unwrap(),
+        )];
+        assert_eq!(loose, acct.downloaded_statements());
+
+        // the loose file comes before the archived one for the same date
+        let acct = acct.with_search_archives(true);
+        let expected = vec![
+            Statement::new(
+                &archive.join("2021-01-01.pdf"),
+                &NaiveDate::from_ymd_opt(2021, 1, 1).unwrap(),
+            ),
+            Statement::new(
+                &dir.join("2021-02-01.pdf"),
+                &NaiveDate::from_ymd_opt(2021, 2, 1).unwrap(),
+            ),
+            Statement::new(
+                &archive.join("2021-02-01.pdf"),
+                &NaiveDate::from_ymd_opt(2021, 2, 1).unwrap(),
+            ),
+        ];
+        assert_eq!(expected, acct.downloaded_statements());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn downloaded_through_symlinks() {
+        use std::{fs, os::unix::fs::symlink};
+
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        let outside_tmp = tempfile::tempdir().unwrap();
+        let outside = outside_tmp.path();
+        fs::write(dir.join("2021-01-01.pdf"), "statement").unwrap();
+        fs::write(outside.join("2021-02-01.pdf"), "statement").unwrap();
+        symlink(outside.join("2021-02-01.pdf"), dir.join("2021-02-01.pdf")).unwrap();
+
+        let acct = monthly(dir.to_str().unwrap()).build().unwrap();
+        let real = Statement::new(
+            &dir.join("2021-01-01.pdf"),
+            &NaiveDate::from_ymd_opt(2021, 1, 1).unwrap(),
+        );
+        let linked = Statement::new(
+            &dir.join("2021-02-01.pdf"),
+            &NaiveDate::from_ymd_opt(2021, 2, 1).unwrap(),
+        );
+        assert_eq!(vec![real.clone(), linked], acct.downloaded_statements());
+
+        let acct = acct.with_follow_symlinks(false);
+        assert_eq!(vec![real], acct.downloaded_statements());
+    }
+
+    #[test]
+    fn downloaded_with_exclusions() {
+        use std::{fs, io::Write};
+        use zip::{write::SimpleFileOptions, ZipWriter};
+
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        fs::write(dir.join("2021-01-01.pdf"), "January").unwrap();
+        fs::write(dir.join("2021-02-01.pdf"), "half of February").unwrap();
+
+        let archive = dir.join("2021.zip");
+        let mut zip = ZipWriter::new(fs::File::create(&archive).unwrap());
+        for name in ["2021-03-01.pdf", "drafts/2021-04-01.pdf"] {
+            zip.start_file(name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(name.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+
+        let exclude = ExcludePatterns::new(&["2021-02-*", "drafts/**"]).unwrap();
+        let acct = monthly(dir.to_str().unwrap())
+            .build()
+            .unwrap()
+            .with_search_archives(true)
+            .with_exclude(exclude);
+        let expected = vec![
+            Statement::new(
+                &dir.join("2021-01-01.pdf"),
+                &NaiveDate::from_ymd_opt(2021, 1, 1).unwrap(),
+            ),
+            Statement::new(
+                &archive.join("2021-03-01.pdf"),
+                &NaiveDate::from_ymd_opt(2021, 3, 1).unwrap(),
+            ),
+        ];
+        assert_eq!(expected, acct.downloaded_statements());
+
+        // excluding the archive leaves out everything in it
+        let exclude = ExcludePatterns::new(&["*.zip"]).unwrap();
+        let acct = acct.with_exclude(exclude);
+        assert_eq!(2, acct.downloaded_statements().len());
+    }
 }
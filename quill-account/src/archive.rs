@@ -0,0 +1,131 @@
+//! Statements stored inside archives, such as a year of statements downloaded as one `.zip` file.
+//!
+//! A statement inside an archive is identified by a path through the archive itself,
+//! e.g. `statements/2021.zip/2021-01-01.pdf`.
+
+use crate::error::ArchiveError;
+use std::{
+    env,
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+};
+use zip::ZipArchive;
+
+/// File extension of the archives that can contain statements
+const ARCHIVE_EXTENSION: &str = "zip";
+
+/// Check if a path names an archive that can contain statements
+pub fn is_archive(path: &Path) -> bool {
+    path.extension()
+        .map_or(false, |ext| ext.eq_ignore_ascii_case(ARCHIVE_EXTENSION))
+}
+
+/// List the files inside an archive, as paths through the archive.
+/// Archives that can't be read are treated as empty.
+pub fn archived_files(archive: &Path) -> Vec<PathBuf> {
+    let zip = match File::open(archive).map(ZipArchive::new) {
+        Ok(Ok(zip)) => zip,
+        _ => return vec![],
+    };
+
+    zip.file_names()
+        .filter(|name| !name.ends_with('/'))
+        .map(|name| archive.join(name))
+        .collect()
+}
+
+/// Split a path through an archive into the archive and the name of the file inside it
+pub fn split_archive_path(path: &Path) -> Option<(PathBuf, String)> {
+    let archive = path
+        .ancestors()
+        .skip(1)
+        .find(|p| is_archive(p) && p.is_file())?;
+
+    // names inside an archive always use `/`, regardless of platform
+    let entry = path
+        .strip_prefix(archive)
+        .ok()?
+        .components()
+        .map(|c| c.as_os_str().to_str())
+        .collect::<Option<Vec<&str>>>()?
+        .join("/");
+
+    Some((archive.to_path_buf(), entry))
+}
+
+/// Extract a file inside an archive to a temporary file, so that it can be opened by other programs.
+/// Returns the path of the temporary file.
+pub fn extract_to_temp(path: &Path) -> Result<PathBuf, ArchiveError> {
+    let (archive, entry) =
+        split_archive_path(path).ok_or_else(|| ArchiveError::NotArchived(path.to_path_buf()))?;
+
+    let file = File::open(&archive).map_err(|e| ArchiveError::Io(archive.clone(), e))?;
+    let mut zip =
+        ZipArchive::new(file).map_err(|e| ArchiveError::Zip(archive.clone(), entry.clone(), e))?;
+    let mut contents = zip
+        .by_name(&entry)
+        .map_err(|e| ArchiveError::Zip(archive.clone(), entry.clone(), e))?;
+
+    // only the file name is kept, so that names inside the archive can't escape the temporary directory
+    let out_dir = env::temp_dir().join("quill");
+    let out_path = match path.file_name() {
+        Some(name) => out_dir.join(name),
+        None => return Err(ArchiveError::NotArchived(path.to_path_buf())),
+    };
+    fs::create_dir_all(&out_dir).map_err(|e| ArchiveError::Io(archive.clone(), e))?;
+    let mut out = File::create(&out_path).map_err(|e| ArchiveError::Io(archive.clone(), e))?;
+    io::copy(&mut contents, &mut out).map_err(|e| ArchiveError::Io(archive.clone(), e))?;
+
+    Ok(out_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::{write::SimpleFileOptions, ZipWriter};
+
+    #[test]
+    fn archive_extension() {
+        assert!(is_archive(Path::new("statements/2021.zip")));
+        assert!(is_archive(Path::new("statements/2021.ZIP")));
+        assert!(!is_archive(Path::new("statements/2021-01-01.pdf")));
+    }
+
+    #[test]
+    fn list_and_extract() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("2021.zip");
+
+        let mut zip = ZipWriter::new(File::create(&archive).unwrap());
+        zip.add_directory("2021/", SimpleFileOptions::default())
+            .unwrap();
+        zip.start_file("2021/2021-01-01.pdf", SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(b"January").unwrap();
+        zip.finish().unwrap();
+
+        let stmt = archive.join("2021").join("2021-01-01.pdf");
+        assert_eq!(vec![stmt.clone()], archived_files(&archive));
+        assert_eq!(
+            Some((archive.clone(), "2021/2021-01-01.pdf".to_string())),
+            split_archive_path(&stmt)
+        );
+
+        let extracted = extract_to_temp(&stmt).unwrap();
+        assert_eq!(
+            Some(std::ffi::OsStr::new("2021-01-01.pdf")),
+            extracted.file_name()
+        );
+        assert_eq!("January", fs::read_to_string(&extracted).unwrap());
+    }
+
+    #[test]
+    fn extract_outside_archive() {
+        assert!(matches!(
+            extract_to_temp(Path::new("statements/2021-01-01.pdf")),
+            Err(ArchiveError::NotArchived(_))
+        ));
+    }
+}
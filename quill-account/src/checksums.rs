@@ -0,0 +1,218 @@
+//! SHA-256 checksums of the statements in a directory, to find statements
+//! that have been modified or corrupted since they were downloaded.
+//!
+//! The checksums are kept in a manifest in the statement directory, with the
+//! checksum of each statement under its file name.
+
+use crate::error::ChecksumError;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+};
+
+/// Name of the manifest file in each statement directory
+pub const CHECKSUMS_FILE: &str = ".quillsums.toml";
+
+/// The result of checking a statement against its recorded checksum
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileCheck {
+    /// The statement hasn't changed since its checksum was recorded
+    Unchanged,
+    /// The statement's contents have changed, or it's been corrupted
+    Modified,
+    /// The statement no longer exists
+    Missing,
+}
+
+/// The checksums of the statements in a directory
+#[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Checksums {
+    /// Checksum of each statement, by file name
+    files: BTreeMap<String, String>,
+}
+
+impl Checksums {
+    /// Read the manifest in a directory.
+    /// A directory without one has no checksums recorded.
+    pub fn load(dir: &Path) -> Result<Self, ChecksumError> {
+        let path = checksums_path(dir);
+        let contents = match fs::read_to_string(&path) {
+            Ok(s) => s,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(ChecksumError::Io(path, e)),
+        };
+
+        toml::from_str(&contents).map_err(|e| ChecksumError::Invalid(path, e.to_string()))
+    }
+
+    /// Write the manifest to a directory
+    pub fn save(&self, dir: &Path) -> Result<(), ChecksumError> {
+        let path = checksums_path(dir);
+        let contents = toml::to_string(self)
+            .map_err(|e| ChecksumError::Invalid(path.clone(), e.to_string()))?;
+
+        fs::write(&path, contents).map_err(|e| ChecksumError::Write(path, e))
+    }
+
+    /// Return the number of statements with a recorded checksum
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Check if no checksums have been recorded
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Return the recorded checksum of a statement, by file name
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.files.get(name).map(String::as_str)
+    }
+
+    /// Record the checksum of a statement, by file name
+    pub fn insert(&mut self, name: &str, checksum: String) {
+        self.files.insert(name.to_string(), checksum);
+    }
+
+    /// Forget the checksum of a statement, returning it if it was recorded
+    pub fn remove(&mut self, name: &str) -> Option<String> {
+        self.files.remove(name)
+    }
+
+    /// Record the checksums of the statements that don't have one yet.
+    /// Statements that aren't files of their own, like those inside archives,
+    /// are skipped. Returns the statements that were recorded.
+    pub fn record_new<'p>(&mut self, files: &[&'p Path]) -> Result<Vec<&'p Path>, ChecksumError> {
+        let mut recorded = vec![];
+        for &path in files {
+            let name = match path.file_name().and_then(|n| n.to_str()) {
+                Some(name) if path.is_file() => name,
+                _ => continue,
+            };
+            if self.files.contains_key(name) {
+                continue;
+            }
+
+            self.files.insert(name.to_string(), hash_file(path)?);
+            recorded.push(path);
+        }
+
+        Ok(recorded)
+    }
+
+    /// Check every statement with a recorded checksum in a directory, by file name
+    pub fn verify(&self, dir: &Path) -> Vec<(&str, FileCheck)> {
+        self.files
+            .iter()
+            .map(|(name, expected)| {
+                let check = match hash_file(&dir.join(name)) {
+                    Ok(actual) if actual == *expected => FileCheck::Unchanged,
+                    Ok(_) => FileCheck::Modified,
+                    Err(_) => FileCheck::Missing,
+                };
+                (name.as_str(), check)
+            })
+            .collect()
+    }
+}
+
+/// Calculate the SHA-256 checksum of a file, as a hex string
+pub fn hash_file(path: &Path) -> Result<String, ChecksumError> {
+    let mut file = File::open(path).map_err(|e| ChecksumError::Io(path.to_path_buf(), e))?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher).map_err(|e| ChecksumError::Io(path.to_path_buf(), e))?;
+
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Path to the manifest in a statement directory
+pub fn checksums_path(dir: &Path) -> PathBuf {
+    dir.join(CHECKSUMS_FILE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn empty_file_hash() {
+        let tmp = tempdir().unwrap();
+        let dir = tmp.path();
+        let path = dir.join("empty.pdf");
+        fs::write(&path, b"").unwrap();
+
+        assert_eq!(
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            hash_file(&path).unwrap()
+        );
+    }
+
+    #[test]
+    fn record_and_verify() {
+        let tmp = tempdir().unwrap();
+        let dir = tmp.path();
+        let jan = dir.join("2021-01-15.pdf");
+        let feb = dir.join("2021-02-15.pdf");
+        let mar = dir.join("2021-03-15.pdf");
+        for path in [&jan, &feb, &mar] {
+            fs::write(path, path.to_str().unwrap()).unwrap();
+        }
+
+        let mut sums = Checksums::load(dir).unwrap();
+        assert!(sums.is_empty());
+        let recorded = sums.record_new(&[&jan, &feb, &mar]).unwrap();
+        assert_eq!(3, recorded.len());
+        sums.save(dir).unwrap();
+
+        // statements are only recorded once
+        let mut sums = Checksums::load(dir).unwrap();
+        assert!(sums.record_new(&[&jan]).unwrap().is_empty());
+
+        fs::write(&feb, b"corrupted").unwrap();
+        fs::remove_file(&mar).unwrap();
+        assert_eq!(
+            vec![
+                ("2021-01-15.pdf", FileCheck::Unchanged),
+                ("2021-02-15.pdf", FileCheck::Modified),
+                ("2021-03-15.pdf", FileCheck::Missing),
+            ],
+            sums.verify(dir)
+        );
+    }
+
+    #[test]
+    fn statements_in_subdirectories() {
+        let tmp = tempdir().unwrap();
+        let dir = tmp.path();
+        fs::create_dir_all(dir.join("2021")).unwrap();
+        let path = dir.join("2021").join("2021-01-15.pdf");
+        fs::write(&path, b"statement").unwrap();
+
+        let mut sums = Checksums::default();
+        sums.insert("2021/2021-01-15.pdf", hash_file(&path).unwrap());
+        assert_eq!(
+            vec![("2021/2021-01-15.pdf", FileCheck::Unchanged)],
+            sums.verify(dir)
+        );
+
+        assert!(sums.remove("2021/2021-01-15.pdf").is_some());
+        assert_eq!(None, sums.get("2021/2021-01-15.pdf"));
+    }
+
+    #[test]
+    fn invalid_manifest() {
+        let tmp = tempdir().unwrap();
+        let dir = tmp.path();
+        fs::write(checksums_path(dir), "files = 5").unwrap();
+
+        assert!(matches!(
+            Checksums::load(dir),
+            Err(ChecksumError::Invalid(..))
+        ));
+    }
+}
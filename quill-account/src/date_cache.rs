@@ -0,0 +1,178 @@
+//! Remember the dates parsed from statement file names between scans.
+
+use crate::account::parse_statement;
+use chrono::NaiveDate;
+use quill_statement::Statement;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tracing::trace;
+
+/// A statement file's date, and what the file looked like when it was parsed
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+struct ParsedFile {
+    modified: SystemTime,
+    size: u64,
+    date: NaiveDate,
+}
+
+/// The dates parsed from the names of an account's statement files.
+/// A file's date is only reused while its modification time and size are unchanged,
+/// and every date is forgotten if the statement format changes.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct DateCache {
+    /// The format the dates were parsed with
+    fmt: String,
+
+    /// The date of each statement file
+    files: HashMap<PathBuf, ParsedFile>,
+}
+
+impl DateCache {
+    /// Create an empty cache for dates parsed with a statement format
+    pub fn new(fmt: &str) -> Self {
+        Self {
+            fmt: fmt.to_string(),
+            files: HashMap::new(),
+        }
+    }
+
+    /// The number of files with a cached date
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Check if no dates are cached
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Get the statement for a file, parsing the date from its name with `fmt`
+    /// unless it was already parsed and the file hasn't changed since.
+    /// If `fold_case`, the letters of the name and the format don't need to have the same case.
+    /// Files that can't be read are parsed without being cached.
+    pub fn statement(&mut self, path: &Path, fmt: &str, fold_case: bool) -> Option<Statement> {
+        if self.fmt != fmt {
+            self.files.clear();
+            self.fmt = fmt.to_string();
+        }
+
+        let (modified, size) = match fs::metadata(path).and_then(|m| Ok((m.modified()?, m.len()))) {
+            Ok(meta) => meta,
+            Err(_) => return parse_statement(path, fmt, fold_case),
+        };
+
+        if let Some(parsed) = self.files.get(path) {
+            if parsed.modified == modified && parsed.size == size {
+                return Some(Statement::new(path, &parsed.date));
+            }
+        }
+
+        trace!(file = %path.display(), "parsing the statement's date");
+        let stmt = parse_statement(path, fmt, fold_case);
+        match &stmt {
+            Some(s) => {
+                let parsed = ParsedFile {
+                    modified,
+                    size,
+                    date: *s.date(),
+                };
+                self.files.insert(path.to_path_buf(), parsed);
+            }
+            None => {
+                self.files.remove(path);
+            }
+        }
+
+        stmt
+    }
+
+    /// Forget the dates of files that `keep` returns `false` for, like ones that were removed
+    pub fn retain(&mut self, mut keep: impl FnMut(&Path) -> bool) {
+        self.files.retain(|path, _| keep(path));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn reuses_unchanged_files() {
+        let tmp = tempdir().unwrap();
+        let dir = tmp.path();
+        let path = dir.join("2021-01-15.pdf");
+        fs::write(&path, "statement").unwrap();
+
+        let mut cache = DateCache::new("%Y-%m-%d.pdf");
+        let date = NaiveDate::from_ymd_opt(2021, 1, 15).unwrap();
+        assert_eq!(
+            Some(Statement::new(&path, &date)),
+            cache.statement(&path, "%Y-%m-%d.pdf", false)
+        );
+        assert_eq!(1, cache.len());
+
+        // a cached date is used even if it doesn't match the name, as long as the file is the same
+        cache.files.get_mut(&path).unwrap().date = date.succ_opt().unwrap();
+        assert_eq!(
+            Some(date.succ_opt().unwrap()),
+            cache
+                .statement(&path, "%Y-%m-%d.pdf", false)
+                .map(|s| *s.date())
+        );
+
+        // but not once the file changes
+        fs::write(&path, "a longer statement").unwrap();
+        assert_eq!(
+            Some(date),
+            cache
+                .statement(&path, "%Y-%m-%d.pdf", false)
+                .map(|s| *s.date())
+        );
+    }
+
+    #[test]
+    fn format_change_clears_dates() {
+        let tmp = tempdir().unwrap();
+        let dir = tmp.path();
+        let path = dir.join("2021-01-15.pdf");
+        fs::write(&path, "statement").unwrap();
+
+        let mut cache = DateCache::new("%Y-%m-%d.pdf");
+        cache.statement(&path, "%Y-%m-%d.pdf", false);
+        assert_eq!(None, cache.statement(&path, "%Y-%d-%m.pdf", false));
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn missing_files_are_parsed_without_caching() {
+        let tmp = tempdir().unwrap();
+        let dir = tmp.path();
+        let path = dir.join("2021-01-15.pdf");
+
+        let mut cache = DateCache::new("%Y-%m-%d.pdf");
+        assert!(cache.statement(&path, "%Y-%m-%d.pdf", false).is_some());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn retain_forgets_files() {
+        let tmp = tempdir().unwrap();
+        let dir = tmp.path();
+        let kept = dir.join("2021-01-15.pdf");
+        let removed = dir.join("2021-02-15.pdf");
+        fs::write(&kept, "statement").unwrap();
+        fs::write(&removed, "statement").unwrap();
+
+        let mut cache = DateCache::new("%Y-%m-%d.pdf");
+        cache.statement(&kept, "%Y-%m-%d.pdf", false);
+        cache.statement(&removed, "%Y-%m-%d.pdf", false);
+        cache.retain(|p| p == kept);
+
+        assert_eq!(1, cache.len());
+        assert!(cache.files.contains_key(&kept));
+    }
+}
@@ -1,5 +1,7 @@
 //! Error types for this library.
 
+use chrono::NaiveDate;
+use quill_utils::ExpandError;
 use std::path::PathBuf;
 use thiserror::Error;
 
@@ -11,17 +13,31 @@ pub enum AccountCreationError {
     MissingInstitutionName,
     #[error("Missing statement file name format")]
     MissingStatementFormat,
+    #[error("Invalid statement file name format `{0}`. Check that every `%` specifier is supported by `chrono`.")]
+    InvalidStatementFormat(String),
+    #[error("Statement file name format `{0}` doesn't contain enough information to determine a date.\nThe format must include a year, month, and day (e.g. `%Y-%m-%d`).")]
+    IncompleteStatementFormat(String),
     #[error("Missing first statement date")]
     MissingFirstDate,
     #[error("Invalid first statement date")]
     InvalidFirstDate(String),
+    #[error("Invalid last statement date `{0}`")]
+    InvalidLastDate(String),
+    #[error("Last statement date {1} is before the first statement date {0}")]
+    LastBeforeFirst(NaiveDate, NaiveDate),
     #[error("Missing statement directory")]
     MissingStatementDirectory,
     #[error("Statement directory `{0}` does not exist")]
     StatementDirectoryNotFound(PathBuf),
     #[error("Error converting statement directory `{0}` to an absolute path")]
     StatementDirectoryNonCanonical(PathBuf),
-    #[error("Missing statement period")]
+    #[error("Error expanding statement directory `{0}`: {1}")]
+    StatementDirectoryExpansion(String, ExpandError),
+    #[error("{0}")]
+    InvalidRemoteDirectory(String),
+    #[error("Error expanding environment variables in `{0}`: {1}")]
+    Interpolation(String, ExpandError),
+    #[error("Missing statement period. Give either `statement_period` or `statement_schedule`.")]
     MissingPeriod,
     #[error("Incorrect array length in statement period (should be 4, was {0}).\nThe required format is `[n, x, m, y]` where `n` is either a single integer or an array of integers; `m` is an integer; and `x` and `y` are strings.")]
     InvalidPeriodIncorrectLength(usize),
@@ -35,6 +51,152 @@ pub enum AccountCreationError {
     InvalidPeriodGrainString(String),
     #[error("Unknown error parsing the statement period.\nThe required format is `[n, x, m, y]` where `n` is either a single integer or an array of integers; `m` is an integer; and `x` and `y` are strings.")]
     InvalidPeriodUnknown,
+    #[error("Only one of `statement_period` and `statement_schedule` can be given.")]
+    PeriodAndSchedule,
+    #[error("Invalid statement schedule `{0}`. The schedule must be a string, like `\"LW * *\"`.")]
+    InvalidScheduleNotAString(String),
+    #[error(transparent)]
+    InvalidSchedule(#[from] ScheduleError),
+    #[error("Invalid schedules `{0}`. Each schedule must be a table, like `[Accounts.<key>.schedules.<name>]`.")]
+    InvalidSchedules(String),
+    #[error("Error in schedule `{0}`: {1}")]
+    InSchedule(String, Box<AccountCreationError>),
+    #[error("Invalid group `{0}`. The group must be a string.")]
+    InvalidGroup(String),
+    #[error("Invalid value `{0}` for `search_archives`. It must be `true` or `false`.")]
+    InvalidSearchArchives(String),
+    #[error("Invalid value `{0}` for `follow_symlinks`. It must be `true` or `false`.")]
+    InvalidFollowSymlinks(String),
+    #[error("Invalid value `{0}` for `exclude`. It must be a list of glob patterns, like `[\"*.tmp\", \"drafts/**\"]`.")]
+    InvalidExclude(String),
+    #[error("Invalid pattern `{0}` in `exclude`: {1}.")]
+    InvalidExcludePattern(String, String),
+    #[error("Invalid value `{0}` for `checksums`. It must be `true` or `false`.")]
+    InvalidChecksums(String),
+    #[error(
+        "Invalid value `{0}` for `grace_days`. It must be a number of days that isn't negative."
+    )]
+    InvalidGraceDays(String),
+    #[error("Invalid value `{0}` for `enabled`. It must be `true` or `false`.")]
+    InvalidEnabled(String),
+    #[error("Invalid date `{0}` for `paused_until`")]
+    InvalidPausedUntil(String),
+    #[error("Invalid value `{0}` for `fetch_cmd`. It must be a string.")]
+    InvalidFetchCmd(String),
+    #[error("Invalid value `{0}` for `open_cmd`. It must be a string.")]
+    InvalidOpenCmd(String),
+    #[error("Invalid value `{0}` for `ledger_account`. It must be an account name like `Assets:Bank:Chequing`.")]
+    InvalidLedgerAccount(String),
+    #[error("Invalid value `{0}` for `kind`. It must be one of `chequing`, `credit-card`, `investment`, or `utility`.")]
+    InvalidKind(String),
+    #[error(
+        "Invalid value `{0}` for `currency`. It must be a three letter currency code, like `CAD`."
+    )]
+    InvalidCurrency(String),
+    #[error("Invalid value `{0}` for `transaction_fmt`. It must be a string.")]
+    InvalidTransactionFmt(String),
+    #[error("A named schedule can't be called `transactions` when `transaction_fmt` is set.")]
+    DuplicateTransactionsSchedule,
     #[error("Unknown account data error. This should never happen, please file an issue.")]
     Unknown,
 }
+
+impl AccountCreationError {
+    /// The account property that caused the error, if it was caused by a single property
+    pub fn property(&self) -> Option<&'static str> {
+        match self {
+            Self::MissingAccountName => Some("name"),
+            Self::MissingInstitutionName => Some("institution"),
+            Self::MissingStatementFormat
+            | Self::InvalidStatementFormat(_)
+            | Self::IncompleteStatementFormat(_) => Some("statement_fmt"),
+            Self::MissingFirstDate | Self::InvalidFirstDate(_) => Some("first_date"),
+            Self::InvalidLastDate(_) | Self::LastBeforeFirst(..) => Some("last_date"),
+            Self::MissingStatementDirectory
+            | Self::StatementDirectoryNotFound(_)
+            | Self::StatementDirectoryNonCanonical(_)
+            | Self::StatementDirectoryExpansion(..)
+            | Self::InvalidRemoteDirectory(_) => Some("dir"),
+            Self::MissingPeriod
+            | Self::InvalidPeriodIncorrectLength(_)
+            | Self::InvalidPeriodNonIntOrArrayIntN
+            | Self::InvalidPeriodNonIntM
+            | Self::InvalidPeriodGrainNotAString(_)
+            | Self::InvalidPeriodGrainString(_)
+            | Self::InvalidPeriodUnknown => Some("statement_period"),
+            Self::PeriodAndSchedule
+            | Self::InvalidScheduleNotAString(_)
+            | Self::InvalidSchedule(_) => Some("statement_schedule"),
+            Self::InvalidSchedules(_) | Self::InSchedule(..) => Some("schedules"),
+            Self::InvalidGroup(_) => Some("group"),
+            Self::InvalidSearchArchives(_) => Some("search_archives"),
+            Self::InvalidFollowSymlinks(_) => Some("follow_symlinks"),
+            Self::InvalidExclude(_) | Self::InvalidExcludePattern(..) => Some("exclude"),
+            Self::InvalidChecksums(_) => Some("checksums"),
+            Self::InvalidGraceDays(_) => Some("grace_days"),
+            Self::InvalidEnabled(_) => Some("enabled"),
+            Self::InvalidPausedUntil(_) => Some("paused_until"),
+            Self::InvalidFetchCmd(_) => Some("fetch_cmd"),
+            Self::InvalidOpenCmd(_) => Some("open_cmd"),
+            Self::InvalidLedgerAccount(_) => Some("ledger_account"),
+            Self::InvalidKind(_) => Some("kind"),
+            Self::InvalidCurrency(_) => Some("currency"),
+            Self::InvalidTransactionFmt(_) | Self::DuplicateTransactionsSchedule => {
+                Some("transaction_fmt")
+            }
+            Self::Interpolation(..) | Self::Unknown => None,
+        }
+    }
+}
+
+/// Errors parsing a statement schedule
+#[derive(Debug, Error, PartialEq)]
+pub enum ScheduleError {
+    #[error("Statement schedule `{0}` has {1} field(s), but should have 3: `<day of month> <month> <day of week>`.")]
+    IncorrectFieldCount(String, usize),
+    #[error("Invalid {field} `{item}` in statement schedule. {reason}")]
+    InvalidField {
+        field: &'static str,
+        item: String,
+        reason: String,
+    },
+    #[error("Statement schedule `{0}` ({1}) never matches any date. Check that the day of month exists in the months given.")]
+    NeverMatches(String, String),
+}
+
+/// Errors reading statements stored inside an archive
+#[derive(Debug, Error)]
+pub enum ArchiveError {
+    #[error("`{0}` is not inside an archive")]
+    NotArchived(PathBuf),
+    #[error("Error reading archive `{0}`")]
+    Io(PathBuf, #[source] std::io::Error),
+    #[error("Error reading `{1}` from archive `{0}`")]
+    Zip(PathBuf, String, #[source] zip::result::ZipError),
+}
+
+/// Errors reading, writing, or checking the checksums of the statements in a directory
+#[derive(Debug, Error)]
+pub enum ChecksumError {
+    #[error("Error reading `{0}`")]
+    Io(PathBuf, #[source] std::io::Error),
+    #[error("Invalid checksums file `{0}`: {1}")]
+    Invalid(PathBuf, String),
+    #[error("Error writing checksums file `{0}`")]
+    Write(PathBuf, #[source] std::io::Error),
+}
+
+/// Errors listing or downloading the statements in a directory
+#[derive(Debug, Error)]
+pub enum StorageError {
+    #[error("Error reading `{0}`")]
+    Io(PathBuf, #[source] std::io::Error),
+    #[error("Remote directory `{0}` isn't supported by this build of quill")]
+    Unsupported(String),
+    #[error("Invalid remote directory `{0}`: {1}")]
+    InvalidUrl(String, String),
+    #[error("Error requesting `{0}`: {1}")]
+    Request(String, String),
+    #[error("Unexpected response from `{0}`: {1}")]
+    Response(String, String),
+}
@@ -0,0 +1,124 @@
+//! Patterns for files in a statement directory that are never statements.
+//!
+//! Patterns are globs matched against each file's path relative to the statement directory,
+//! like `*.tmp` or `drafts/**`.
+//! Files inside archives are also matched by their path inside the archive.
+
+use crate::archive::split_archive_path;
+use crate::error::AccountCreationError;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use std::path::Path;
+
+/// The files an account leaves out when looking for its statements
+#[derive(Clone, Debug)]
+pub struct ExcludePatterns {
+    /// The patterns, as they were written
+    patterns: Vec<String>,
+
+    /// The patterns compiled together, to match each file against all of them at once
+    set: GlobSet,
+}
+
+impl ExcludePatterns {
+    /// Compile the patterns, failing on the first one that isn't a valid glob
+    pub fn new(patterns: &[&str]) -> Result<Self, AccountCreationError> {
+        let invalid = |pattern: &str, e: globset::Error| {
+            AccountCreationError::InvalidExcludePattern(pattern.to_string(), e.kind().to_string())
+        };
+
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            builder.add(Glob::new(pattern).map_err(|e| invalid(pattern, e))?);
+        }
+        let set = builder
+            .build()
+            .map_err(|e| invalid(&patterns.join(", "), e))?;
+
+        Ok(Self {
+            patterns: patterns.iter().map(|p| p.to_string()).collect(),
+            set,
+        })
+    }
+
+    /// The patterns, as they were written
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
+    /// Check if there aren't any patterns
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Check if a file matches any of the patterns.
+    /// The file is matched by its path relative to the first of `dirs` that it's in, or by its
+    /// whole path if it isn't in any of them, and by its path inside an archive if it's in one.
+    pub fn excludes(&self, path: &Path, dirs: &[&Path]) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+
+        let relative = dirs
+            .iter()
+            .find_map(|dir| path.strip_prefix(dir).ok())
+            .unwrap_or(path);
+        if self.set.is_match(relative) {
+            return true;
+        }
+
+        match split_archive_path(path) {
+            Some((_, entry)) => self.set.is_match(entry),
+            None => false,
+        }
+    }
+}
+
+impl Default for ExcludePatterns {
+    fn default() -> Self {
+        Self {
+            patterns: vec![],
+            set: GlobSet::empty(),
+        }
+    }
+}
+
+impl PartialEq for ExcludePatterns {
+    fn eq(&self, other: &Self) -> bool {
+        self.patterns == other.patterns
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_to_directory() {
+        let exclude = ExcludePatterns::new(&["*.tmp", "drafts/**"]).unwrap();
+        let dir = Path::new("/statements");
+
+        assert!(exclude.excludes(Path::new("/statements/2021-01-01.pdf.tmp"), &[dir]));
+        assert!(exclude.excludes(Path::new("/statements/drafts/2021-01-01.pdf"), &[dir]));
+        assert!(!exclude.excludes(Path::new("/statements/2021-01-01.pdf"), &[dir]));
+        // only the directory's own `drafts` is excluded
+        assert!(!exclude.excludes(Path::new("/other/drafts/2021-01-01.pdf"), &[dir]));
+    }
+
+    #[test]
+    fn nothing_excluded() {
+        let exclude = ExcludePatterns::default();
+
+        assert!(exclude.is_empty());
+        assert!(!exclude.excludes(Path::new("/statements/x.tmp"), &[Path::new("/statements")]));
+    }
+
+    #[test]
+    fn invalid_pattern() {
+        match ExcludePatterns::new(&["*.tmp", "drafts/[a"]) {
+            Err(AccountCreationError::InvalidExcludePattern(pattern, _)) => {
+                assert_eq!("drafts/[a", pattern)
+            }
+            other => panic!("expected an invalid pattern, got {:?}", other),
+        }
+    }
+}
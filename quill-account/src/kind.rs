@@ -0,0 +1,85 @@
+//! What kind of account an account is.
+
+use serde::{Serialize, Serializer};
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// The kind of an account, given by its `kind`
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum AccountKind {
+    Chequing,
+    CreditCard,
+    Investment,
+    Utility,
+}
+
+impl AccountKind {
+    /// Every kind of account, in the order they're sorted in
+    pub const ALL: [AccountKind; 4] = [
+        Self::Chequing,
+        Self::CreditCard,
+        Self::Investment,
+        Self::Utility,
+    ];
+
+    /// The kind as it's written in the configuration file
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Chequing => "chequing",
+            Self::CreditCard => "credit-card",
+            Self::Investment => "investment",
+            Self::Utility => "utility",
+        }
+    }
+
+    /// A short label for the kind, shown next to the account's name
+    pub fn badge(&self) -> &'static str {
+        match self {
+            Self::Chequing => "CHQ",
+            Self::CreditCard => "CC",
+            Self::Investment => "INV",
+            Self::Utility => "UTIL",
+        }
+    }
+}
+
+impl Display for AccountKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AccountKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == s)
+            .ok_or(())
+    }
+}
+
+impl Serialize for AccountKind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for kind in AccountKind::ALL {
+            assert_eq!(Ok(kind), kind.to_string().parse());
+        }
+    }
+
+    #[test]
+    fn unknown_kind() {
+        assert_eq!(Err(()), "savings".parse::<AccountKind>());
+        assert_eq!(Err(()), "Credit-Card".parse::<AccountKind>());
+    }
+}
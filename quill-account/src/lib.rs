@@ -1,8 +1,23 @@
 //! Schema for accounts, dates, and statements.
 
 pub mod account;
+pub mod archive;
+pub mod checksums;
+pub mod date_cache;
 pub mod error;
+pub mod exclude;
+pub mod kind;
 pub mod parse;
+pub mod period;
+pub mod schedule;
+pub mod storage;
 
-pub use self::account::Account;
-pub use self::error::AccountCreationError;
+pub use self::account::{Account, AccountBuilder};
+pub use self::date_cache::DateCache;
+pub use self::error::{
+    AccountCreationError, ArchiveError, ChecksumError, ScheduleError, StorageError,
+};
+pub use self::exclude::ExcludePatterns;
+pub use self::kind::AccountKind;
+pub use self::period::StatementPeriod;
+pub use self::schedule::Schedule;
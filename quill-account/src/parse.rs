@@ -1,14 +1,20 @@
 //! Utilities for converting to and from models and data types.
 
-use crate::AccountCreationError;
-use chrono::NaiveDate;
-use kronos::{step_by, Grain, Grains, LastOf, NthOf, Shim, Union};
-use quill_utils::expand_tilde;
-use std::{
-    path::{Path, PathBuf},
-    str::FromStr,
+use crate::account::TRANSACTIONS_SCHEDULE;
+use crate::storage::{is_remote, storage_for};
+use crate::{AccountCreationError, AccountKind, ExcludePatterns, Schedule, StatementPeriod};
+use chrono::{
+    format::{Item, StrftimeItems},
+    NaiveDate,
+};
+use kronos::{Grain, Grains};
+use quill_statement::describe_period;
+use quill_utils::{expand_path, interpolate_vars};
+use std::{path::PathBuf, str::FromStr};
+use toml::{
+    value::{Index, Table},
+    Value,
 };
-use toml::{value::Index, Value};
 
 /// Generalized function to extract a string from a TOML value.
 /// If the key is not found as a property, then return the provided error.
@@ -26,6 +32,77 @@ where
     }
 }
 
+/// Properties whose values aren't interpolated.
+/// `dir` has its own, more thorough, expansion and the commands are left to the shell that runs them.
+const UNINTERPOLATED_PROPS: [&str; 3] = ["dir", "fetch_cmd", "open_cmd"];
+
+/// Replace every `${VAR}` environment variable in an account's string properties,
+/// including those of its named schedules.
+pub(super) fn interpolate_props(props: &Value) -> Result<Value, AccountCreationError> {
+    match props {
+        Value::String(s) => interpolate_vars(s)
+            .map(Value::String)
+            .map_err(|e| AccountCreationError::Interpolation(s.to_string(), e)),
+        Value::Array(arr) => arr
+            .iter()
+            .map(interpolate_props)
+            .collect::<Result<_, _>>()
+            .map(Value::Array),
+        Value::Table(tbl) => tbl
+            .iter()
+            .map(|(k, v)| match UNINTERPOLATED_PROPS.contains(&k.as_str()) {
+                true => Ok((k.clone(), v.clone())),
+                false => interpolate_props(v).map(|v| (k.clone(), v)),
+            })
+            .collect::<Result<_, _>>()
+            .map(Value::Table),
+        v => Ok(v.clone()),
+    }
+}
+
+/// The statement period as it was written in an account's properties,
+/// so that it can be written back out
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum PeriodSource {
+    /// A `statement_period` array
+    Period(Vec<Value>),
+    /// A `statement_schedule` string
+    Schedule(String),
+}
+
+impl PeriodSource {
+    /// Describe the period in words, like "1st day of every month"
+    pub(super) fn describe(&self) -> Option<String> {
+        match self {
+            Self::Period(arr) => describe_period_array(arr),
+            Self::Schedule(s) => s.parse::<Schedule>().ok().map(|s| s.describe()),
+        }
+    }
+}
+
+/// Describe a `statement_period` array in words, if it's valid
+fn describe_period_array(arr: &[Value]) -> Option<String> {
+    let nths = match arr.first()? {
+        Value::Integer(n) => vec![*n],
+        Value::Array(ns) => ns.iter().map(Value::as_integer).collect::<Option<_>>()?,
+        _ => return None,
+    };
+    let Grains(x) = value_to_grains(arr.get(1)?).ok()?;
+    let mth = parse_mth_value(arr.get(2)?).ok()?;
+    let Grains(y) = value_to_grains(arr.get(3)?).ok()?;
+
+    Some(describe_period(&nths, x, mth, y))
+}
+
+/// Convert a TOML date, or a string like `2021-01-31`, to a date
+fn value_to_date(v: &Value) -> Option<NaiveDate> {
+    match v {
+        Value::Datetime(d) => NaiveDate::from_str(&d.to_string()).ok(),
+        Value::String(s) => NaiveDate::from_str(s).ok(),
+        _ => None,
+    }
+}
+
 /// Extract the account name from a TOML Value
 pub(super) fn parse_account_name(props: &Value) -> Result<&str, AccountCreationError> {
     parse_str_from_toml("name", props, AccountCreationError::MissingAccountName)
@@ -42,11 +119,32 @@ pub(super) fn parse_institution_name(props: &Value) -> Result<&str, AccountCreat
 
 /// Extract the date format for a statement filename
 pub(super) fn parse_statement_format(props: &Value) -> Result<&str, AccountCreationError> {
-    parse_str_from_toml(
+    let fmt = parse_str_from_toml(
         "statement_fmt",
         props,
         AccountCreationError::MissingStatementFormat,
-    )
+    )?;
+    validate_statement_format(fmt)?;
+
+    Ok(fmt)
+}
+
+/// Check that a statement format string can be used to both write and read a date
+pub(super) fn validate_statement_format(fmt: &str) -> Result<(), AccountCreationError> {
+    if StrftimeItems::new(fmt).any(|item| matches!(item, Item::Error)) {
+        return Err(AccountCreationError::InvalidStatementFormat(
+            fmt.to_string(),
+        ));
+    }
+
+    // the date must be recoverable from a file name written with the format
+    let date = NaiveDate::from_ymd_opt(2001, 2, 3).unwrap();
+    match NaiveDate::parse_from_str(&date.format(fmt).to_string(), fmt) {
+        Ok(d) if d == date => Ok(()),
+        _ => Err(AccountCreationError::IncompleteStatementFormat(
+            fmt.to_string(),
+        )),
+    }
 }
 
 /// Extract the directory containing an account's statements
@@ -57,11 +155,14 @@ pub(super) fn parse_account_directory(props: &Value) -> Result<PathBuf, AccountC
         AccountCreationError::MissingStatementDirectory,
     ) {
         Ok(d) => {
-            // store the path
-            let path = Path::new(d);
+            let non_tilded_path = expand_directory(d)?;
 
-            // replace any tildes
-            let non_tilded_path = expand_tilde(path).unwrap_or_else(|| path.to_path_buf());
+            // remote directories are only requested once their statements are listed
+            if is_remote(&non_tilded_path) {
+                return storage_for(&non_tilded_path)
+                    .map(|_| non_tilded_path)
+                    .map_err(|e| AccountCreationError::InvalidRemoteDirectory(e.to_string()));
+            }
 
             // check that the path exists
             // need to do this since `.canonicalize()` will fail if it doesn't
@@ -83,25 +184,308 @@ pub(super) fn parse_account_directory(props: &Value) -> Result<PathBuf, AccountC
     }
 }
 
+/// Extract the directory containing an account's statements, without checking that it exists
+pub(super) fn parse_account_directory_unchecked(
+    props: &Value,
+) -> Result<PathBuf, AccountCreationError> {
+    parse_str_from_toml(
+        "dir",
+        props,
+        AccountCreationError::MissingStatementDirectory,
+    )
+    .and_then(expand_directory)
+}
+
+/// Replace any tildes and environment variables in a directory path
+fn expand_directory(d: &str) -> Result<PathBuf, AccountCreationError> {
+    expand_path(d).map_err(|e| AccountCreationError::StatementDirectoryExpansion(d.to_string(), e))
+}
+
+/// Extract the name of the group the account is shown under.
+/// This is optional, and accounts without one are grouped by their institution.
+pub(super) fn parse_group(props: &Value) -> Result<Option<&str>, AccountCreationError> {
+    match props.get("group") {
+        None => Ok(None),
+        Some(Value::String(s)) => Ok(Some(s.as_str())),
+        Some(v) => Err(AccountCreationError::InvalidGroup(v.to_string())),
+    }
+}
+
+/// Extract the kind of account, like `credit-card`.
+/// This is optional, and only used to sort and label accounts.
+pub(super) fn parse_kind(props: &Value) -> Result<Option<AccountKind>, AccountCreationError> {
+    match props.get("kind") {
+        None => Ok(None),
+        Some(Value::String(s)) => s
+            .parse()
+            .map(Some)
+            .map_err(|_| AccountCreationError::InvalidKind(s.to_string())),
+        Some(v) => Err(AccountCreationError::InvalidKind(v.to_string())),
+    }
+}
+
+/// Extract the currency of the account, as a three letter code like `CAD`.
+/// This is optional, and only used to label accounts.
+pub(super) fn parse_currency(props: &Value) -> Result<Option<String>, AccountCreationError> {
+    match props.get("currency") {
+        None => Ok(None),
+        Some(Value::String(s)) if s.len() == 3 && s.chars().all(|c| c.is_ascii_alphabetic()) => {
+            Ok(Some(s.to_ascii_uppercase()))
+        }
+        Some(v) => Err(AccountCreationError::InvalidCurrency(v.to_string())),
+    }
+}
+
+/// Extract the command that downloads the account's statements.
+/// This is optional, and accounts without one are only checked.
+pub(super) fn parse_fetch_cmd(props: &Value) -> Result<Option<&str>, AccountCreationError> {
+    match props.get("fetch_cmd") {
+        None => Ok(None),
+        Some(Value::String(s)) => Ok(Some(s.as_str())),
+        Some(v) => Err(AccountCreationError::InvalidFetchCmd(v.to_string())),
+    }
+}
+
+/// Extract the command that opens the account's statements, like `zathura {path}`.
+/// This is optional, and accounts without one open statements with the default program.
+pub(super) fn parse_open_cmd(props: &Value) -> Result<Option<&str>, AccountCreationError> {
+    match props.get("open_cmd") {
+        None => Ok(None),
+        Some(Value::String(s)) => Ok(Some(s.as_str())),
+        Some(v) => Err(AccountCreationError::InvalidOpenCmd(v.to_string())),
+    }
+}
+
+/// Extract the name of the account in a plain-text accounting journal, like `Assets:Bank:Chequing`.
+/// This is optional, and only used when exporting statements for the journal.
+pub(super) fn parse_ledger_account(props: &Value) -> Result<Option<&str>, AccountCreationError> {
+    match props.get("ledger_account") {
+        None => Ok(None),
+        Some(Value::String(s)) if is_ledger_account(s) => Ok(Some(s.as_str())),
+        Some(v) => Err(AccountCreationError::InvalidLedgerAccount(v.to_string())),
+    }
+}
+
+/// Check if a name is a valid account name for plain-text accounting journals.
+/// Each `:`-separated part must start with a capital letter or digit, and
+/// only contain letters, digits, and `-`.
+fn is_ledger_account(name: &str) -> bool {
+    name.split(':').count() > 1
+        && name.split(':').all(|part| {
+            part.starts_with(|c: char| c.is_uppercase() || c.is_ascii_digit())
+                && part.chars().all(|c| c.is_alphanumeric() || c == '-')
+        })
+}
+
+/// Extract whether archives in the statement directory should be searched for statements.
+/// This is optional, and defaults to `false`.
+pub(super) fn parse_search_archives(props: &Value) -> Result<bool, AccountCreationError> {
+    match props.get("search_archives") {
+        None => Ok(false),
+        Some(Value::Boolean(b)) => Ok(*b),
+        Some(v) => Err(AccountCreationError::InvalidSearchArchives(v.to_string())),
+    }
+}
+
+/// Extract whether symbolic links in the statement directory are followed to the statements they point to.
+/// This is optional, and defaults to `true`.
+pub(super) fn parse_follow_symlinks(props: &Value) -> Result<bool, AccountCreationError> {
+    match props.get("follow_symlinks") {
+        None => Ok(true),
+        Some(Value::Boolean(b)) => Ok(*b),
+        Some(v) => Err(AccountCreationError::InvalidFollowSymlinks(v.to_string())),
+    }
+}
+
+/// Extract the patterns for files in the statement directory that are never statements, like `*.tmp`.
+/// This is optional, and defaults to excluding nothing.
+pub(super) fn parse_exclude(props: &Value) -> Result<ExcludePatterns, AccountCreationError> {
+    match props.get("exclude") {
+        None => Ok(ExcludePatterns::default()),
+        Some(v @ Value::Array(arr)) => {
+            let patterns = arr
+                .iter()
+                .map(Value::as_str)
+                .collect::<Option<Vec<&str>>>()
+                .ok_or_else(|| AccountCreationError::InvalidExclude(v.to_string()))?;
+            ExcludePatterns::new(&patterns)
+        }
+        Some(v) => Err(AccountCreationError::InvalidExclude(v.to_string())),
+    }
+}
+
+/// Extract whether the checksums of the account's statements are recorded, to
+/// find statements that are later modified or corrupted.
+/// This is optional, and defaults to `false`.
+pub(super) fn parse_checksums(props: &Value) -> Result<bool, AccountCreationError> {
+    match props.get("checksums") {
+        None => Ok(false),
+        Some(Value::Boolean(b)) => Ok(*b),
+        Some(v) => Err(AccountCreationError::InvalidChecksums(v.to_string())),
+    }
+}
+
+/// Extract how many days after its date a statement may take to be published.
+/// Until then, a statement that hasn't been found is pending, rather than missing.
+/// This is optional, and defaults to `0`.
+pub(super) fn parse_grace_days(props: &Value) -> Result<u32, AccountCreationError> {
+    match props.get("grace_days") {
+        None => Ok(0),
+        Some(Value::Integer(n)) => {
+            u32::try_from(*n).map_err(|_| AccountCreationError::InvalidGraceDays(n.to_string()))
+        }
+        Some(v) => Err(AccountCreationError::InvalidGraceDays(v.to_string())),
+    }
+}
+
+/// Extract the named schedules of an account, along with their properties.
+/// This is optional, and accounts without any only track their own statements.
+pub(super) fn parse_schedules(props: &Value) -> Result<Vec<(&str, &Value)>, AccountCreationError> {
+    match props.get("schedules") {
+        None => Ok(vec![]),
+        Some(Value::Table(t)) => t
+            .iter()
+            .map(|(name, sched)| match sched {
+                Value::Table(_) => Ok((name.as_str(), sched)),
+                v => Err(AccountCreationError::InvalidSchedules(format!(
+                    "{} = {}",
+                    name, v
+                ))),
+            })
+            .collect(),
+        Some(v) => Err(AccountCreationError::InvalidSchedules(v.to_string())),
+    }
+}
+
+/// Build the properties of the schedule tracking the account's transaction files,
+/// like `.qfx` downloads, if it has a `transaction_fmt`.
+/// The files follow the account's statement period, unless it has its own
+/// `transaction_period` or `transaction_schedule`.
+pub(super) fn parse_transactions(props: &Value) -> Result<Option<Value>, AccountCreationError> {
+    let fmt = match props.get("transaction_fmt") {
+        None => return Ok(None),
+        Some(Value::String(s)) => s,
+        Some(v) => return Err(AccountCreationError::InvalidTransactionFmt(v.to_string())),
+    };
+
+    // the schedule is named `transactions`, so it can't also be given by hand
+    if props
+        .get("schedules")
+        .and_then(|s| s.get(TRANSACTIONS_SCHEDULE))
+        .is_some()
+    {
+        return Err(AccountCreationError::DuplicateTransactionsSchedule);
+    }
+
+    let mut sched = Table::new();
+    sched.insert("statement_fmt".to_string(), Value::String(fmt.clone()));
+    let period_keys = match (
+        props.get("transaction_period"),
+        props.get("transaction_schedule"),
+    ) {
+        (None, None) => [
+            ("statement_period", "statement_period"),
+            ("statement_schedule", "statement_schedule"),
+        ],
+        _ => [
+            ("transaction_period", "statement_period"),
+            ("transaction_schedule", "statement_schedule"),
+        ],
+    };
+    for (from, to) in period_keys {
+        if let Some(v) = props.get(from) {
+            sched.insert(to.to_string(), v.clone());
+        }
+    }
+
+    Ok(Some(Value::Table(sched)))
+}
+
+/// Extract whether the account is enabled.
+/// This is optional, and defaults to `true`.
+pub(super) fn parse_enabled(props: &Value) -> Result<bool, AccountCreationError> {
+    match props.get("enabled") {
+        None => Ok(true),
+        Some(Value::Boolean(b)) => Ok(*b),
+        Some(v) => Err(AccountCreationError::InvalidEnabled(v.to_string())),
+    }
+}
+
+/// Extract the date the account is paused until, if it's paused.
+/// This is optional.
+pub(super) fn parse_paused_until(props: &Value) -> Result<Option<NaiveDate>, AccountCreationError> {
+    match props.get("paused_until") {
+        None => Ok(None),
+        Some(v) => value_to_date(v)
+            .map(Some)
+            .ok_or_else(|| AccountCreationError::InvalidPausedUntil(v.to_string())),
+    }
+}
+
 /// Extract the date of the account's first statement
 pub(super) fn parse_first_statement_date(props: &Value) -> Result<NaiveDate, AccountCreationError> {
     match props.get("first_date") {
-        Some(Value::Datetime(d)) => match NaiveDate::from_str(&d.to_string()) {
-            Ok(d) => Ok(d),
-            Err(_) => Err(AccountCreationError::InvalidFirstDate(d.to_string())),
-        },
-        _ => Err(AccountCreationError::MissingFirstDate),
+        Some(v) => {
+            value_to_date(v).ok_or_else(|| AccountCreationError::InvalidFirstDate(v.to_string()))
+        }
+        None => Err(AccountCreationError::MissingFirstDate),
+    }
+}
+
+/// Extract the date of the account's last statement, if the account is closed.
+/// This is optional, and open accounts don't have one.
+pub(super) fn parse_last_statement_date(
+    props: &Value,
+) -> Result<Option<NaiveDate>, AccountCreationError> {
+    let last = match props.get("last_date") {
+        None => return Ok(None),
+        Some(v) => {
+            value_to_date(v).ok_or_else(|| AccountCreationError::InvalidLastDate(v.to_string()))?
+        }
+    };
+
+    match parse_first_statement_date(props) {
+        Ok(first) if last < first => Err(AccountCreationError::LastBeforeFirst(first, last)),
+        _ => Ok(Some(last)),
     }
 }
 
 /// Extract the statement period for an account
-pub(super) fn parse_statement_period<'a>(props: &Value) -> Result<Shim<'a>, AccountCreationError> {
-    match props.get("statement_period") {
-        Some(Value::Array(arr)) => parse_period_array(arr),
+/// This is either a `statement_period` array or a `statement_schedule` string.
+pub fn parse_statement_period(props: &Value) -> Result<StatementPeriod, AccountCreationError> {
+    match (
+        props.get("statement_period"),
+        props.get("statement_schedule"),
+    ) {
+        (Some(_), Some(_)) => Err(AccountCreationError::PeriodAndSchedule),
+        (Some(Value::Array(arr)), None) => parse_period_array(arr),
+        (None, Some(Value::String(s))) => Ok(StatementPeriod::from(s.parse::<Schedule>()?)),
+        (None, Some(v)) => Err(AccountCreationError::InvalidScheduleNotAString(
+            v.to_string(),
+        )),
         _ => Err(AccountCreationError::MissingPeriod),
     }
 }
 
+/// Describe the statement period for an account in words, if it's valid
+pub fn describe_statement_period(props: &Value) -> Option<String> {
+    parse_period_source(props)?.describe()
+}
+
+/// Extract the statement period as it was written, if it's valid
+pub(super) fn parse_period_source(props: &Value) -> Option<PeriodSource> {
+    parse_statement_period(props).ok()?;
+
+    match (
+        props.get("statement_period"),
+        props.get("statement_schedule"),
+    ) {
+        (Some(Value::Array(arr)), None) => Some(PeriodSource::Period(arr.clone())),
+        (None, Some(Value::String(s))) => Some(PeriodSource::Schedule(s.clone())),
+        _ => None,
+    }
+}
+
 /// Convert a TOML Value to a Grains, if possible
 fn value_to_grains(v: &Value) -> Result<Grains, AccountCreationError> {
     match v {
@@ -133,7 +517,7 @@ fn str_to_grains(s: &str) -> Result<Grains, AccountCreationError> {
 }
 
 /// Parse the entire array used to determine the statement period
-fn parse_period_array<'a>(v: &Vec<Value>) -> Result<Shim<'a>, AccountCreationError> {
+fn parse_period_array(v: &[Value]) -> Result<StatementPeriod, AccountCreationError> {
     if v.len() != 4 {
         return Err(AccountCreationError::InvalidPeriodIncorrectLength(v.len()));
     }
@@ -142,56 +526,31 @@ fn parse_period_array<'a>(v: &Vec<Value>) -> Result<Shim<'a>, AccountCreationErr
     let mth = parse_mth_value(&v[2])?;
     let y = value_to_grains(&v[3])?;
 
-    // return the TimeSequence object
     match &v[0] {
         Value::Array(arr) => parse_multiple_periods(arr, &x, &mth, &y),
-        Value::Integer(nth) => Ok(parse_single_period(nth, &x, &mth, &y)),
+        Value::Integer(nth) => Ok(StatementPeriod::nth_of(*nth, x.0, mth, y.0)),
         _ => Err(AccountCreationError::InvalidPeriodNonIntOrArrayIntN),
     }
 }
 
-/// Turn a single set of period parameters into a `TimeSequence`
-fn parse_single_period<'a>(n: &i64, x: &Grains, mth: &usize, y: &Grains) -> Shim<'a> {
-    let (nth, is_lastof) = parse_nth_value(n);
-    // if n is negative, it's supposed to be the last of the period
-    // if n is positive, it's supposed to be the first of the period
-    if is_lastof {
-        Shim::new(LastOf(nth, x.clone(), step_by(y.clone(), *mth)))
-    } else {
-        Shim::new(NthOf(nth, x.clone(), step_by(y.clone(), *mth)))
-    }
-}
-
-/// Turn an array of period `n`-th values into multiple `TimeSequence`s
-fn parse_multiple_periods<'a>(
-    arr: &Vec<Value>,
+/// Turn an array of period `n`-th values into a period covering each of them
+fn parse_multiple_periods(
+    arr: &[Value],
     x: &Grains,
     mth: &usize,
     y: &Grains,
-) -> Result<Shim<'a>, AccountCreationError> {
-    let periods: Result<Vec<Shim>, AccountCreationError> = arr
+) -> Result<StatementPeriod, AccountCreationError> {
+    let nths = arr
         .iter()
         .map(|i| match i {
-            Value::Integer(n) => Ok(parse_single_period(n, x, mth, y)),
-            _ => return Err(AccountCreationError::InvalidPeriodNonIntOrArrayIntN),
+            Value::Integer(n) => Ok(*n),
+            _ => Err(AccountCreationError::InvalidPeriodNonIntOrArrayIntN),
         })
-        .collect();
+        .collect::<Result<Vec<i64>, AccountCreationError>>()?;
 
-    match periods {
-        Err(e) => Err(e),
-        Ok(shims) => {
-            // take the union of each `Shim` and create a new `Shim`
-            // this ensures that the combined period is the union of all input periods
-            // I don't like how many `.clone()` calls there are, but I think
-            // this might be the best I can do
-            let shim_union = shims[2..].iter().fold(
-                Shim::new(Union(shims[0].clone(), shims[1].clone())),
-                |a, b| Shim::new(Union(a, b.clone())),
-            );
-
-            Ok(shim_union)
-        }
-    }
+    // an empty array has no dates at all
+    StatementPeriod::nths_of(&nths, x.0, *mth, y.0)
+        .ok_or(AccountCreationError::InvalidPeriodNonIntOrArrayIntN)
 }
 
 /// Parse the value stored as the `m`-th period input
@@ -203,8 +562,8 @@ fn parse_mth_value(v: &Value) -> Result<usize, AccountCreationError> {
 }
 
 /// Parse the value stored as the `n`-th period input
-fn parse_nth_value(n: &i64) -> (usize, bool) {
-    let val = (*n).abs() as usize;
+pub(crate) fn parse_nth_value(n: &i64) -> (usize, bool) {
+    let val = (*n).unsigned_abs() as usize;
     if *n < 0 {
         (val, true)
     } else {
@@ -216,7 +575,8 @@ fn parse_nth_value(n: &i64) -> (usize, bool) {
 mod tests {
     use super::*;
     use chrono::Local;
-    use kronos::{TimeSequence, Union};
+    use kronos::{NthOf, Shim, TimeSequence, Union};
+    use quill_utils::ExpandError;
     use toml::Value;
 
     #[test]
@@ -225,6 +585,119 @@ mod tests {
         assert_eq!(4, result);
     }
 
+    #[test]
+    fn ledger_accounts() {
+        for name in ["Assets:Bank:Chequing", "Liabilities:Visa-2", "Assets:2023"] {
+            assert!(is_ledger_account(name), "{}", name);
+        }
+        for name in [
+            "Assets",
+            "assets:Bank",
+            "Assets:",
+            "Assets:My Bank",
+            "Assets::Bank",
+        ] {
+            assert!(!is_ledger_account(name), "{}", name);
+        }
+
+        let props: Value = toml::from_str("ledger_account = 5").unwrap();
+        assert_eq!(
+            Err(AccountCreationError::InvalidLedgerAccount("5".to_string())),
+            parse_ledger_account(&props)
+        );
+    }
+
+    #[test]
+    fn interpolated_props() {
+        std::env::set_var("QUILL_ACCOUNT_TEST_BANK", "Tangerine");
+        let props: Value = toml::from_str(
+            r#"
+            institution = "${QUILL_ACCOUNT_TEST_BANK}"
+            statement_fmt = "%Y-%m-%d.pdf"
+            fetch_cmd = "echo ${QUILL_ACCOUNT_TEST_UNSET}"
+            [schedules.fees]
+            statement_fmt = "${QUILL_ACCOUNT_TEST_BANK}_%Y-%m-%d.pdf"
+            "#,
+        )
+        .unwrap();
+        let props = interpolate_props(&props).unwrap();
+
+        assert_eq!(Ok("Tangerine"), parse_institution_name(&props));
+        assert_eq!(Ok("%Y-%m-%d.pdf"), parse_statement_format(&props));
+        assert_eq!(
+            Ok(Some("echo ${QUILL_ACCOUNT_TEST_UNSET}")),
+            parse_fetch_cmd(&props)
+        );
+        assert_eq!(
+            Some("Tangerine_%Y-%m-%d.pdf"),
+            props["schedules"]["fees"]["statement_fmt"].as_str()
+        );
+
+        let props: Value = toml::from_str(r#"name = "${QUILL_ACCOUNT_TEST_UNSET}""#).unwrap();
+        assert_eq!(
+            Err(AccountCreationError::Interpolation(
+                "${QUILL_ACCOUNT_TEST_UNSET}".to_string(),
+                ExpandError::UnsetVariable("QUILL_ACCOUNT_TEST_UNSET".to_string()),
+            )),
+            interpolate_props(&props)
+        );
+    }
+
+    #[test]
+    fn kind_and_currency() {
+        let props: Value = toml::from_str(
+            r#"
+            kind = "credit-card"
+            currency = "cad"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(Ok(Some(AccountKind::CreditCard)), parse_kind(&props));
+        assert_eq!(Ok(Some("CAD".to_string())), parse_currency(&props));
+
+        let props: Value = toml::from_str(
+            r#"
+            kind = "savings"
+            currency = "dollars"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            Err(AccountCreationError::InvalidKind("savings".to_string())),
+            parse_kind(&props)
+        );
+        assert_eq!(
+            Err(AccountCreationError::InvalidCurrency(
+                "\"dollars\"".to_string()
+            )),
+            parse_currency(&props)
+        );
+    }
+
+    #[test]
+    fn check_validate_statement_format_good() {
+        assert_eq!(Ok(()), validate_statement_format("%Y-%m-%d.pdf"));
+        assert_eq!(Ok(()), validate_statement_format("Statement %d %B %Y.pdf"));
+    }
+
+    #[test]
+    fn check_validate_statement_format_invalid() {
+        let expected = Err(AccountCreationError::InvalidStatementFormat(
+            "%Y-%m-%Q".to_string(),
+        ));
+
+        assert_eq!(expected, validate_statement_format("%Y-%m-%Q"));
+    }
+
+    #[test]
+    fn check_validate_statement_format_incomplete() {
+        let expected = Err(AccountCreationError::IncompleteStatementFormat(
+            "%Y-%m.pdf".to_string(),
+        ));
+
+        assert_eq!(expected, validate_statement_format("%Y-%m.pdf"));
+    }
+
     #[test]
     fn check_parse_mth_value_good() {
         let input = Value::Integer(2i64);
@@ -273,7 +746,8 @@ mod tests {
         // `Shim` doesn't implement `Debug` or `PartialEq`, so just check that
         // the first few dates are correct
         match (expected, observed) {
-            (Ok(exp_shim), Ok(obs_shim)) => {
+            (Ok(exp_shim), Ok(obs_period)) => {
+                let obs_shim = obs_period.sequence();
                 let mut exp_fut = exp_shim.future(&t0);
                 let mut obs_fut = obs_shim.future(&t0);
                 for _i in 0..3 {
@@ -0,0 +1,270 @@
+//! How often an account's statements are issued.
+
+use crate::parse::parse_nth_value;
+use crate::Schedule;
+use chrono::NaiveDate;
+use kronos::{step_by, Grain, Grains, LastOf, NthOf, Shim, Union};
+use quill_statement::ExpectedDates;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::sync::Mutex;
+
+/// The most periods and first dates that dates are kept for.
+/// Each account only needs one, so this is only reached after many reloads of a
+/// configuration whose accounts keep changing.
+const MAX_EXPECTED_DATES: usize = 1024;
+
+/// Dates that were already generated for each period and first date, once any have been.
+/// Accounts are created again whenever the configuration is loaded, so the dates are kept here
+/// instead of in each account.
+static EXPECTED_DATES: Mutex<Option<HashMap<(StatementPeriod, NaiveDate), ExpectedDates>>> =
+    Mutex::new(None);
+
+/// The period of an account's statements, from either a `statement_period` array or a
+/// `statement_schedule` string.
+/// Unlike the `kronos` sequence it creates, it owns all of its data,
+/// so accounts can be shared between threads.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StatementPeriod(PeriodKind);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum PeriodKind {
+    /// The `n`-th `x` of every `mth` `y`s, for each `n`.
+    /// Negative `n`s count back from the end of each `y`.
+    Nth {
+        nths: Vec<i64>,
+        x: Grain,
+        mth: usize,
+        y: Grain,
+    },
+    /// A cron-like schedule
+    Schedule(Schedule),
+}
+
+impl StatementPeriod {
+    /// Create a period for the `n`-th `x` of every `mth` `y`s, like `[n, x, mth, y]`.
+    pub fn nth_of(n: i64, x: Grain, mth: usize, y: Grain) -> Self {
+        Self(PeriodKind::Nth {
+            nths: vec![n],
+            x,
+            mth,
+            y,
+        })
+    }
+
+    /// Create a period for each of the `n`-th `x`s of every `mth` `y`s, like `[[n1, n2], x, mth, y]`.
+    /// Returns `None` if there are no `n`s.
+    pub fn nths_of(nths: &[i64], x: Grain, mth: usize, y: Grain) -> Option<Self> {
+        match nths.is_empty() {
+            true => None,
+            false => Some(Self(PeriodKind::Nth {
+                nths: nths.to_vec(),
+                x,
+                mth,
+                y,
+            })),
+        }
+    }
+
+    /// Create the `kronos` sequence of the period's dates
+    pub fn sequence(&self) -> Shim<'static> {
+        match &self.0 {
+            PeriodKind::Nth { nths, x, mth, y } => {
+                let mut shims = nths.iter().map(|n| nth_sequence(*n, *x, *mth, *y));
+                // `nths` is never empty, so there's always a first sequence
+                let first = shims
+                    .next()
+                    .unwrap_or_else(|| nth_sequence(1, *x, *mth, *y));
+                // the union of each sequence is the combined period
+                shims.fold(first, |a, b| Shim::new(Union(a, b)))
+            }
+            PeriodKind::Schedule(s) => Shim::new(s.clone()),
+        }
+    }
+
+    /// List the dates from `first` until `today`, inclusive, that follow the period.
+    /// Dates that were listed before for the same first date and period are reused,
+    /// so only the ones since the last time are generated.
+    pub fn dates_until(&self, first: &NaiveDate, today: &NaiveDate) -> Vec<NaiveDate> {
+        let sequence = self.sequence();
+        let mut cache = match EXPECTED_DATES.lock() {
+            Ok(cache) => cache,
+            // a panic while the dates were being extended may have left them incomplete
+            Err(_) => return ExpectedDates::until(first, &sequence, today).into_dates(),
+        };
+        let cache = cache.get_or_insert_with(HashMap::new);
+
+        let key = (self.clone(), *first);
+        if let Some(dates) = cache.get_mut(&key) {
+            dates.extend_until(&sequence, today);
+            return dates.dates().to_vec();
+        }
+
+        // the dates of periods that are still used are quick to generate again
+        if cache.len() >= MAX_EXPECTED_DATES {
+            cache.clear();
+        }
+        let dates = ExpectedDates::until(first, &sequence, today);
+        let listed = dates.dates().to_vec();
+        cache.insert(key, dates);
+
+        listed
+    }
+}
+
+impl Hash for StatementPeriod {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(&self.0).hash(state);
+        match &self.0 {
+            // grains can't be hashed themselves, but have no fields
+            PeriodKind::Nth { nths, x, mth, y } => (nths, *x as u8, mth, *y as u8).hash(state),
+            PeriodKind::Schedule(schedule) => schedule.hash(state),
+        }
+    }
+}
+
+impl From<Schedule> for StatementPeriod {
+    fn from(schedule: Schedule) -> Self {
+        Self(PeriodKind::Schedule(schedule))
+    }
+}
+
+/// Create the sequence for a single `n`.
+/// If `n` is negative, it's the last of the period, otherwise it's the first.
+fn nth_sequence(n: i64, x: Grain, mth: usize, y: Grain) -> Shim<'static> {
+    let (nth, is_lastof) = parse_nth_value(&n);
+    if is_lastof {
+        Shim::new(LastOf(nth, Grains(x), step_by(Grains(y), mth)))
+    } else {
+        Shim::new(NthOf(nth, Grains(x), step_by(Grains(y), mth)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use kronos::TimeSequence;
+
+    fn first_dates(period: &StatementPeriod, count: usize) -> Vec<NaiveDate> {
+        let t0 = NaiveDate::from_ymd_opt(2021, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+
+        period
+            .sequence()
+            .future(&t0)
+            .take(count)
+            .map(|r| r.start.date())
+            .collect()
+    }
+
+    #[track_caller]
+    fn check_first_dates(period: &StatementPeriod, expected: &[(i32, u32, u32)]) {
+        let expected: Vec<NaiveDate> = expected
+            .iter()
+            .map(|(y, m, d)| NaiveDate::from_ymd_opt(*y, *m, *d).unwrap())
+            .collect();
+
+        assert_eq!(expected, first_dates(period, expected.len()));
+    }
+
+    #[test]
+    fn nth_of_month() {
+        let period = StatementPeriod::nth_of(15, Grain::Day, 1, Grain::Month);
+
+        check_first_dates(&period, &[(2021, 1, 15), (2021, 2, 15), (2021, 3, 15)]);
+    }
+
+    #[test]
+    fn last_of_month() {
+        let period = StatementPeriod::nth_of(-1, Grain::Day, 1, Grain::Month);
+
+        check_first_dates(&period, &[(2021, 1, 31), (2021, 2, 28), (2021, 3, 31)]);
+    }
+
+    #[test]
+    fn single_nth_in_array() {
+        let period = StatementPeriod::nths_of(&[15], Grain::Day, 1, Grain::Month).unwrap();
+
+        assert_eq!(
+            StatementPeriod::nth_of(15, Grain::Day, 1, Grain::Month),
+            period
+        );
+        check_first_dates(&period, &[(2021, 1, 15), (2021, 2, 15)]);
+    }
+
+    #[test]
+    fn union_of_nths() {
+        let period = StatementPeriod::nths_of(&[1, 15], Grain::Day, 1, Grain::Month).unwrap();
+
+        check_first_dates(&period, &[(2021, 1, 1), (2021, 1, 15), (2021, 2, 1)]);
+    }
+
+    #[test]
+    fn no_nths() {
+        assert_eq!(
+            None,
+            StatementPeriod::nths_of(&[], Grain::Day, 1, Grain::Month)
+        );
+    }
+
+    #[test]
+    fn schedule() {
+        let period = StatementPeriod::from("15 * *".parse::<Schedule>().unwrap());
+
+        check_first_dates(&period, &[(2021, 1, 15), (2021, 2, 15)]);
+    }
+
+    #[test]
+    fn dates_until_reuses_earlier_dates() {
+        let period = StatementPeriod::nth_of(15, Grain::Day, 1, Grain::Month);
+        let first = NaiveDate::from_ymd_opt(2021, 1, 15).unwrap();
+        let date = |m| NaiveDate::from_ymd_opt(2021, m, 15).unwrap();
+
+        assert_eq!(vec![date(1), date(2)], period.dates_until(&first, &date(2)));
+        assert_eq!(
+            vec![date(1), date(2), date(3), date(4)],
+            period.dates_until(&first, &date(4))
+        );
+        // going back a day doesn't keep the later dates
+        assert_eq!(
+            vec![date(1), date(2), date(3)],
+            period.dates_until(&first, &date(3))
+        );
+
+        // a different period with the same first date has its own dates
+        let other = StatementPeriod::nth_of(-1, Grain::Day, 1, Grain::Month);
+        assert_eq!(
+            ExpectedDates::until(&first, &other.sequence(), &date(4)).into_dates(),
+            other.dates_until(&first, &date(4))
+        );
+    }
+
+    #[test]
+    fn dates_until_stops_growing() {
+        let first = NaiveDate::from_ymd_opt(2021, 1, 1).unwrap();
+        let today = NaiveDate::from_ymd_opt(2021, 3, 1).unwrap();
+
+        // every period is a different day of the month, so none are reused
+        for day in 1..=28 {
+            for months in 1..=40 {
+                let period = StatementPeriod::nth_of(day, Grain::Day, months, Grain::Month);
+                period.dates_until(&first, &today);
+            }
+        }
+
+        let cache = EXPECTED_DATES.lock().unwrap();
+        assert!(cache.as_ref().unwrap().len() <= MAX_EXPECTED_DATES);
+    }
+
+    #[test]
+    fn shared_between_threads() {
+        fn assert_send_sync<T: Send + Sync + 'static>() {}
+
+        assert_send_sync::<StatementPeriod>();
+        assert_send_sync::<crate::Account>();
+    }
+}
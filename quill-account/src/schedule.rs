@@ -0,0 +1,643 @@
+//! Statement schedules written like the date fields of a cron expression.
+
+use crate::error::ScheduleError;
+use chrono::{Datelike, Duration, Month, NaiveDate, NaiveDateTime, Weekday};
+use kronos::{Grain, Range, TimeSequence};
+use quill_statement::{join_words, ordinal};
+use std::{iter, str::FromStr};
+
+/// Number of days in 400 years, after which the Gregorian calendar repeats itself
+const GREGORIAN_CYCLE_DAYS: usize = 146_097;
+
+/// Names that can be used in the month field, in order
+const MONTH_NAMES: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+
+/// Names that can be used in the day of week field, numbered from Sunday like cron
+const WEEKDAY_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+/// Days of the week, numbered from Sunday like cron
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Sun,
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+];
+
+/// A rule for the day of the month
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum DayRule {
+    /// A specific day of the month
+    Day(u32),
+    /// `n` days before the last day of the month (`L` or `L-n`)
+    FromLast(u32),
+    /// The weekday closest to a day of the month, without leaving the month (`nW`)
+    NearestWeekday(u32),
+    /// The last weekday of the month (`LW`)
+    LastWeekday,
+}
+
+impl DayRule {
+    /// Describe the rule in words, like "15th" or "last weekday"
+    fn describe(&self) -> String {
+        match *self {
+            Self::Day(n) => ordinal(n.into()),
+            Self::FromLast(0) => "last day".to_string(),
+            Self::FromLast(n) => format!("{}-last day", ordinal((n + 1).into())),
+            Self::NearestWeekday(n) => format!("weekday nearest the {}", ordinal(n.into())),
+            Self::LastWeekday => "last weekday".to_string(),
+        }
+    }
+
+    fn matches(&self, date: &NaiveDate) -> bool {
+        let last = last_day_of_month(date);
+
+        match *self {
+            Self::Day(n) => date.day() == n,
+            Self::FromLast(n) => (n < last) && (date.day() == last - n),
+            Self::NearestWeekday(n) if n > last => false,
+            Self::NearestWeekday(n) => {
+                let target = match date.with_day(n).map(|d| d.weekday()) {
+                    // move to Friday, unless that would leave the month
+                    Some(Weekday::Sat) if n == 1 => n + 2,
+                    Some(Weekday::Sat) => n - 1,
+                    // move to Monday, unless that would leave the month
+                    Some(Weekday::Sun) if n == last => n - 2,
+                    Some(Weekday::Sun) => n + 1,
+                    _ => n,
+                };
+                date.day() == target
+            }
+            Self::LastWeekday => {
+                let target = match date.with_day(last).map(|d| d.weekday()) {
+                    Some(Weekday::Sat) => last - 1,
+                    Some(Weekday::Sun) => last - 2,
+                    _ => last,
+                };
+                date.day() == target
+            }
+        }
+    }
+}
+
+/// A rule for the day of the week
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum WeekdayRule {
+    /// Every one of this day of the week
+    Every(Weekday),
+    /// The `n`th of this day of the week in the month (`d#n`)
+    Nth(Weekday, u32),
+    /// The last of this day of the week in the month (`dL`)
+    Last(Weekday),
+}
+
+impl WeekdayRule {
+    /// Describe the rule in words, like "Monday" or "2nd Friday"
+    fn describe(&self) -> String {
+        match *self {
+            Self::Every(w) => weekday_name(w).to_string(),
+            Self::Nth(w, n) => format!("{} {}", ordinal(n.into()), weekday_name(w)),
+            Self::Last(w) => format!("last {}", weekday_name(w)),
+        }
+    }
+
+    fn matches(&self, date: &NaiveDate) -> bool {
+        match *self {
+            Self::Every(w) => date.weekday() == w,
+            Self::Nth(w, n) => (date.weekday() == w) && ((date.day() - 1) / 7 + 1 == n),
+            Self::Last(w) => (date.weekday() == w) && (date.day() + 7 > last_day_of_month(date)),
+        }
+    }
+}
+
+/// When an account's statements are issued, written like the date fields of a
+/// cron expression: `<day of month> <month> <day of week>`.
+///
+/// Each field is `*` or a comma-separated list of values, ranges (`a-b`), and
+/// steps (`*/n` or `a-b/n`).
+/// Months and days of the week can also be given by their three letter names.
+/// The day of month also accepts `L` (the last day), `L-n` (`n` days before
+/// the last day), `nW` (the weekday closest to day `n`), and `LW` (the last
+/// weekday), and the day of week accepts `d#n` (the `n`th `d` of the month)
+/// and `dL` (the last `d` of the month).
+///
+/// Unlike cron, a date must match every field to be part of the schedule.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Schedule {
+    /// Rules for the day of the month, or `None` for any day
+    days: Option<Vec<DayRule>>,
+    /// Months of the year the schedule covers, from 1 to 12
+    months: Vec<u32>,
+    /// Rules for the day of the week, or `None` for any day
+    weekdays: Option<Vec<WeekdayRule>>,
+}
+
+impl Schedule {
+    /// Check if a date is part of the schedule
+    pub fn matches(&self, date: &NaiveDate) -> bool {
+        self.months.contains(&date.month())
+            && self
+                .days
+                .as_ref()
+                .map_or(true, |rules| rules.iter().any(|r| r.matches(date)))
+            && self
+                .weekdays
+                .as_ref()
+                .map_or(true, |rules| rules.iter().any(|r| r.matches(date)))
+    }
+
+    /// Describe the schedule in words, like "last weekday of every month"
+    pub fn describe(&self) -> String {
+        let months = self.describe_months();
+        let days = self.days.as_ref().map(|rules| {
+            // days of the month share a noun, unless they name their own
+            let words: Vec<String> = rules.iter().map(DayRule::describe).collect();
+            match rules.iter().all(|r| matches!(r, DayRule::Day(_))) {
+                true => format!("{} day", join_words(&words)),
+                false => join_words(&words),
+            }
+        });
+        let weekdays = self.weekdays.as_ref().map(|rules| {
+            let words: Vec<String> = rules.iter().map(WeekdayRule::describe).collect();
+            let every = rules.iter().all(|r| matches!(r, WeekdayRule::Every(_)));
+            (join_words(&words), every)
+        });
+
+        match (days, weekdays) {
+            (None, None) => format!("every day of {}", months),
+            (Some(days), None) => format!("{} of {}", days, months),
+            (None, Some((weekdays, true))) => format!("every {} of {}", weekdays, months),
+            (None, Some((weekdays, false))) => format!("{} of {}", weekdays, months),
+            (Some(days), Some((weekdays, _))) => {
+                format!("{} of {}, if it's a {}", days, months, weekdays)
+            }
+        }
+    }
+
+    /// Describe the months of the schedule, like "every 3rd month" or "February and April"
+    fn describe_months(&self) -> String {
+        if self.months.len() == 12 {
+            return "every month".to_string();
+        }
+
+        // steps that start in January, like `*/3`
+        if let [1, second, ..] = self.months[..] {
+            let step = second.saturating_sub(1).max(1);
+            let stepped = (1..=12).step_by(step as usize);
+            if self.months.iter().copied().eq(stepped) {
+                return format!("every {} month", ordinal(step.into()));
+            }
+        }
+
+        let names: Vec<String> = self.months.iter().map(|m| month_name(*m)).collect();
+        join_words(&names)
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = ScheduleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.split_whitespace().collect();
+        if fields.len() != 3 {
+            return Err(ScheduleError::IncorrectFieldCount(
+                s.to_string(),
+                fields.len(),
+            ));
+        }
+
+        let schedule = Schedule {
+            days: parse_days(fields[0])?,
+            months: parse_months(fields[1])?,
+            weekdays: parse_weekdays(fields[2])?,
+        };
+
+        // looking for the next date of a schedule that never matches would never end
+        let start = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
+        if !days_from(start, true)
+            .take(GREGORIAN_CYCLE_DAYS)
+            .any(|d| schedule.matches(&d))
+        {
+            return Err(ScheduleError::NeverMatches(
+                s.to_string(),
+                schedule.describe(),
+            ));
+        }
+
+        Ok(schedule)
+    }
+}
+
+impl TimeSequence for Schedule {
+    fn _future_raw(&self, t0: &NaiveDateTime) -> Box<dyn Iterator<Item = Range> + '_> {
+        Box::new(
+            days_from(t0.date(), true)
+                .filter(move |d| self.matches(d))
+                .map(day_range),
+        )
+    }
+
+    fn _past_raw(&self, t0: &NaiveDateTime) -> Box<dyn Iterator<Item = Range> + '_> {
+        Box::new(
+            days_from(t0.date(), false)
+                .filter(move |d| self.matches(d))
+                .map(day_range),
+        )
+    }
+}
+
+/// The full name of a month, from 1 to 12
+fn month_name(month: u32) -> String {
+    u8::try_from(month)
+        .ok()
+        .and_then(|m| Month::try_from(m).ok())
+        .map_or_else(|| month.to_string(), |m| m.name().to_string())
+}
+
+/// The full name of a day of the week
+fn weekday_name(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "Monday",
+        Weekday::Tue => "Tuesday",
+        Weekday::Wed => "Wednesday",
+        Weekday::Thu => "Thursday",
+        Weekday::Fri => "Friday",
+        Weekday::Sat => "Saturday",
+        Weekday::Sun => "Sunday",
+    }
+}
+
+/// Every day from `start`, going forwards or backwards in time
+fn days_from(start: NaiveDate, forward: bool) -> impl Iterator<Item = NaiveDate> {
+    iter::successors(Some(start), move |d| match forward {
+        true => d.succ_opt(),
+        false => d.pred_opt(),
+    })
+}
+
+/// The range of time covering a single day
+fn day_range(date: NaiveDate) -> Range {
+    Range {
+        start: date.and_hms_opt(0, 0, 0).unwrap(),
+        end: (date + Duration::days(1)).and_hms_opt(0, 0, 0).unwrap(),
+        grain: Grain::Day,
+    }
+}
+
+/// The last day of the month that a date is in
+fn last_day_of_month(date: &NaiveDate) -> u32 {
+    let (year, month) = match date.month() {
+        12 => (date.year() + 1, 1),
+        m => (date.year(), m + 1),
+    };
+
+    NaiveDate::from_ymd_opt(year, month, 1)
+        .and_then(|d| d.pred_opt())
+        .map_or(31, |d| d.day())
+}
+
+/// Check if a field matches anything
+fn is_any(field: &str) -> bool {
+    (field == "*") || (field == "?")
+}
+
+/// Parse the day of month field
+fn parse_days(field: &str) -> Result<Option<Vec<DayRule>>, ScheduleError> {
+    if is_any(field) {
+        return Ok(None);
+    }
+
+    let mut rules = vec![];
+    for item in field.split(',') {
+        let upper = item.to_uppercase();
+        match upper.as_str() {
+            "L" => rules.push(DayRule::FromLast(0)),
+            "LW" => rules.push(DayRule::LastWeekday),
+            _ if upper.starts_with("L-") => {
+                let n = parse_value("day of month", item, &upper[2..], 0, 30, &[], 0)?;
+                rules.push(DayRule::FromLast(n));
+            }
+            _ if upper.ends_with('W') => {
+                let n = parse_value(
+                    "day of month",
+                    item,
+                    &upper[..upper.len() - 1],
+                    1,
+                    31,
+                    &[],
+                    0,
+                )?;
+                rules.push(DayRule::NearestWeekday(n));
+            }
+            _ => rules.extend(
+                parse_values("day of month", item, 1, 31, &[], 0)?
+                    .into_iter()
+                    .map(DayRule::Day),
+            ),
+        }
+    }
+
+    Ok(Some(rules))
+}
+
+/// Parse the month field
+fn parse_months(field: &str) -> Result<Vec<u32>, ScheduleError> {
+    if is_any(field) {
+        return Ok((1..=12).collect());
+    }
+
+    let mut months = vec![];
+    for item in field.split(',') {
+        months.extend(parse_values("month", item, 1, 12, &MONTH_NAMES, 1)?);
+    }
+
+    Ok(months)
+}
+
+/// Parse the day of week field
+fn parse_weekdays(field: &str) -> Result<Option<Vec<WeekdayRule>>, ScheduleError> {
+    if is_any(field) {
+        return Ok(None);
+    }
+
+    let mut rules = vec![];
+    for item in field.split(',') {
+        let upper = item.to_uppercase();
+        if let Some((day, n)) = upper.split_once('#') {
+            let day = parse_value("day of week", item, day, 0, 7, &WEEKDAY_NAMES, 0)?;
+            let n = parse_value("day of week", item, n, 1, 5, &[], 0)?;
+            rules.push(WeekdayRule::Nth(WEEKDAYS[day as usize % 7], n));
+        } else if upper.len() > 1 && upper.ends_with('L') {
+            let day = parse_value(
+                "day of week",
+                item,
+                &upper[..upper.len() - 1],
+                0,
+                7,
+                &WEEKDAY_NAMES,
+                0,
+            )?;
+            rules.push(WeekdayRule::Last(WEEKDAYS[day as usize % 7]));
+        } else {
+            rules.extend(
+                parse_values("day of week", item, 0, 7, &WEEKDAY_NAMES, 0)?
+                    .into_iter()
+                    .map(|day| WeekdayRule::Every(WEEKDAYS[day as usize % 7])),
+            );
+        }
+    }
+
+    Ok(Some(rules))
+}
+
+/// Parse a single value, range, or step of a field into the values it covers.
+/// `names` can be used instead of numbers, and are numbered from `first_name`.
+fn parse_values(
+    field: &'static str,
+    item: &str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+    first_name: u32,
+) -> Result<Vec<u32>, ScheduleError> {
+    let (range, step) = match item.split_once('/') {
+        Some((range, step)) => match step.parse::<u32>() {
+            Ok(s) if s > 0 => (range, Some(s)),
+            _ => {
+                return Err(ScheduleError::InvalidField {
+                    field,
+                    item: item.to_string(),
+                    reason: format!("The step `{}` must be a positive whole number.", step),
+                })
+            }
+        },
+        None => (item, None),
+    };
+
+    let (start, end) = if is_any(range) {
+        (min, max)
+    } else if let Some((start, end)) = range.split_once('-') {
+        (
+            parse_value(field, item, start, min, max, names, first_name)?,
+            parse_value(field, item, end, min, max, names, first_name)?,
+        )
+    } else {
+        let start = parse_value(field, item, range, min, max, names, first_name)?;
+        // a step from a single value continues to the end of the field
+        (start, if step.is_some() { max } else { start })
+    };
+
+    if start > end {
+        return Err(ScheduleError::InvalidField {
+            field,
+            item: item.to_string(),
+            reason: "Ranges must go from the lower value to the higher one.".to_string(),
+        });
+    }
+
+    Ok((start..=end).step_by(step.unwrap_or(1) as usize).collect())
+}
+
+/// Parse a single number or name within a field
+fn parse_value(
+    field: &'static str,
+    item: &str,
+    value: &str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+    first_name: u32,
+) -> Result<u32, ScheduleError> {
+    if let Some(idx) = names.iter().position(|n| n.eq_ignore_ascii_case(value)) {
+        return Ok(idx as u32 + first_name);
+    }
+
+    match value.parse::<u32>() {
+        Ok(v) if (min..=max).contains(&v) => Ok(v),
+        _ => {
+            let mut reason = format!("Expected a number from {} to {}", min, max);
+            if !names.is_empty() {
+                reason.push_str(&format!(", or one of `{}`", names.join("`, `")));
+            }
+            reason.push('.');
+
+            Err(ScheduleError::InvalidField {
+                field,
+                item: item.to_string(),
+                reason,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kronos::Shim;
+    use quill_statement::next_date_from_given;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    /// Every date in a year that is part of the schedule
+    #[track_caller]
+    fn dates_in_year(schedule: &str, year: i32) -> Vec<NaiveDate> {
+        let schedule: Schedule = schedule.parse().unwrap();
+
+        days_from(date(year, 1, 1), true)
+            .take_while(|d| d.year() == year)
+            .filter(|d| schedule.matches(d))
+            .collect()
+    }
+
+    #[test]
+    fn last_business_day_of_even_months() {
+        assert_eq!(
+            vec![
+                date(2023, 2, 28),
+                date(2023, 4, 28),
+                date(2023, 6, 30),
+                date(2023, 8, 31),
+                date(2023, 10, 31),
+                date(2023, 12, 29),
+            ],
+            dates_in_year("LW FEB,APR,JUN,AUG,OCT,DEC *", 2023)
+        );
+        assert_eq!(
+            dates_in_year("LW FEB,APR,JUN,AUG,OCT,DEC *", 2023),
+            dates_in_year("LW 2-12/2 ?", 2023)
+        );
+    }
+
+    #[test]
+    fn days_from_the_end() {
+        assert_eq!(vec![date(2024, 2, 29)], dates_in_year("L FEB *", 2024));
+        assert_eq!(vec![date(2024, 2, 27)], dates_in_year("L-2 FEB *", 2024));
+        assert_eq!(vec![date(2023, 3, 29)], dates_in_year("L-2 MAR *", 2023));
+    }
+
+    #[test]
+    fn nearest_weekday() {
+        // 2023-04-15 is a Saturday, and 2023-10-01 is a Sunday
+        assert_eq!(vec![date(2023, 4, 14)], dates_in_year("15W APR *", 2023));
+        assert_eq!(vec![date(2023, 10, 2)], dates_in_year("1W OCT *", 2023));
+        // 2023-04-01 is a Saturday, so the closest weekday within April is the 3rd
+        assert_eq!(vec![date(2023, 4, 3)], dates_in_year("1W APR *", 2023));
+        // 2023-04-30 is a Sunday, so the closest weekday within April is the 28th
+        assert_eq!(vec![date(2023, 4, 28)], dates_in_year("30W APR *", 2023));
+    }
+
+    #[test]
+    fn nth_and_last_weekdays() {
+        assert_eq!(
+            vec![date(2023, 1, 9), date(2023, 7, 10)],
+            dates_in_year("* 1,7 MON#2", 2023)
+        );
+        assert_eq!(
+            vec![date(2023, 3, 31), date(2023, 6, 30)],
+            dates_in_year("* 3-6/3 5L", 2023)
+        );
+    }
+
+    #[test]
+    fn fields_must_all_match() {
+        assert_eq!(
+            vec![date(2023, 1, 13), date(2023, 10, 13)],
+            dates_in_year("13 * fri", 2023)
+        );
+    }
+
+    #[test]
+    fn sunday_is_0_or_7() {
+        assert_eq!(dates_in_year("* * 0", 2023), dates_in_year("* * 7", 2023));
+    }
+
+    #[test]
+    fn wrong_number_of_fields() {
+        assert_eq!(
+            Err(ScheduleError::IncorrectFieldCount(
+                "0 0 1 * *".to_string(),
+                5
+            )),
+            "0 0 1 * *".parse::<Schedule>()
+        );
+    }
+
+    #[test]
+    fn invalid_fields() {
+        assert!(matches!(
+            "32 * *".parse::<Schedule>(),
+            Err(ScheduleError::InvalidField {
+                field: "day of month",
+                ..
+            })
+        ));
+        assert!(matches!(
+            "1 FOO *".parse::<Schedule>(),
+            Err(ScheduleError::InvalidField { field: "month", .. })
+        ));
+        assert!(matches!(
+            "1 * MON#6".parse::<Schedule>(),
+            Err(ScheduleError::InvalidField {
+                field: "day of week",
+                ..
+            })
+        ));
+        assert!(matches!(
+            "1 12-1 *".parse::<Schedule>(),
+            Err(ScheduleError::InvalidField { field: "month", .. })
+        ));
+        assert!(matches!(
+            "*/0 * *".parse::<Schedule>(),
+            Err(ScheduleError::InvalidField {
+                field: "day of month",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn never_matches() {
+        assert_eq!(
+            Err(ScheduleError::NeverMatches(
+                "31 FEB *".to_string(),
+                "31st day of February".to_string()
+            )),
+            "31 FEB *".parse::<Schedule>()
+        );
+    }
+
+    #[track_caller]
+    fn check_describe(schedule: &str, expected: &str) {
+        assert_eq!(expected, schedule.parse::<Schedule>().unwrap().describe());
+    }
+
+    #[test]
+    fn describe() {
+        check_describe("LW * *", "last weekday of every month");
+        check_describe("1,15 * *", "1st and 15th day of every month");
+        check_describe("L-1 */3 *", "2nd-last day of every 3rd month");
+        check_describe(
+            "15W FEB,AUG *",
+            "weekday nearest the 15th of February and August",
+        );
+        check_describe("* * MON,FRI", "every Monday and Friday of every month");
+        check_describe("* JAN FRI#2", "2nd Friday of January");
+        check_describe("* * *", "every day of every month");
+    }
+
+    #[test]
+    fn next_date_with_shim() {
+        let period = Shim::new("LW * *".parse::<Schedule>().unwrap());
+
+        assert_eq!(
+            date(2023, 3, 31),
+            next_date_from_given(&date(2023, 2, 28), &period)
+        );
+    }
+}
@@ -0,0 +1,318 @@
+//! Where an account's statements are stored.
+//!
+//! Statements are usually in a local directory, but they can also be in a
+//! remote one, written as a URL:
+//!
+//! - `webdav://example.com/statements` for a directory on a WebDAV server, over HTTPS
+//!   (or `webdav+http://` for plain HTTP), and
+//! - `s3://bucket/statements` for a prefix in an S3 bucket.
+//!
+//! A statement in a remote directory is identified by the directory's URL
+//! joined with its file name, e.g. `s3://bucket/statements/2021-01-01.pdf`.
+
+use crate::error::StorageError;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+use walkdir::WalkDir;
+
+#[cfg(feature = "remote")]
+mod s3;
+#[cfg(feature = "remote")]
+mod webdav;
+
+/// Prefixes of the URLs for remote directories
+const REMOTE_SCHEMES: [&str; 3] = ["webdav://", "webdav+http://", "s3://"];
+
+/// A directory that statements are stored in
+pub trait StorageBackend {
+    /// List the files in the directory, without those in its subdirectories
+    fn list_files(&self) -> Result<Vec<PathBuf>, StorageError>;
+
+    /// Make a file in the directory available locally, so that it can be opened by other programs.
+    /// Returns the path of the local file, which is a temporary file for remote directories.
+    fn download_to_temp(&self, path: &Path) -> Result<PathBuf, StorageError>;
+}
+
+/// A directory on the local file system
+pub struct LocalDir {
+    dir: PathBuf,
+}
+
+impl StorageBackend for LocalDir {
+    fn list_files(&self) -> Result<Vec<PathBuf>, StorageError> {
+        WalkDir::new(&self.dir)
+            .max_depth(1)
+            .into_iter()
+            .filter_map(|p| p.ok())
+            .map(|p| p.into_path())
+            .filter(|p| p.is_file())
+            .map(Ok)
+            .collect()
+    }
+
+    fn download_to_temp(&self, path: &Path) -> Result<PathBuf, StorageError> {
+        Ok(path.to_path_buf())
+    }
+}
+
+/// Drop the symbolic links from files listed in a local directory, unless they're followed.
+/// Files reached through more than one path, like a link and the file it points to, are only
+/// kept once, preferring the path that isn't a link.
+/// Links that point to nothing are always dropped.
+pub fn resolve_links(files: Vec<PathBuf>, follow: bool) -> Vec<PathBuf> {
+    let mut files: Vec<(bool, PathBuf)> = files
+        .into_iter()
+        .filter_map(|p| {
+            let is_link = fs::symlink_metadata(&p).ok()?.file_type().is_symlink();
+            if is_link && !follow {
+                debug!(file = %p.display(), "not following symbolic link");
+                return None;
+            }
+            Some((is_link, p))
+        })
+        .collect();
+    files.sort();
+
+    let mut targets = HashSet::new();
+    files
+        .into_iter()
+        .filter_map(|(_, p)| {
+            let target = fs::canonicalize(&p).ok()?;
+            if !targets.insert(target) {
+                debug!(file = %p.display(), "file was already found through another path");
+                return None;
+            }
+            Some(p)
+        })
+        .collect()
+}
+
+/// Check if the file system a local file is on ignores the case of file names, by looking for
+/// the file with the case of its name swapped.
+/// Returns `None` if the file's name has no letters with a case to swap.
+pub fn is_case_insensitive(path: &Path) -> Option<bool> {
+    let name = path.file_name()?.to_str()?;
+    let swapped: String = name
+        .chars()
+        .map(|c| match c {
+            c if c.is_lowercase() => c.to_uppercase().next().unwrap_or(c),
+            c if c.is_uppercase() => c.to_lowercase().next().unwrap_or(c),
+            c => c,
+        })
+        .collect();
+    if swapped == name {
+        return None;
+    }
+
+    Some(same_file::is_same_file(path, path.with_file_name(swapped)).unwrap_or(false))
+}
+
+/// Check if a directory is a remote URL, rather than a local path
+pub fn is_remote(dir: &Path) -> bool {
+    dir.to_str()
+        .map_or(false, |d| REMOTE_SCHEMES.iter().any(|s| d.starts_with(s)))
+}
+
+/// The storage backend for a directory.
+/// Nothing is requested from remote directories until their files are listed or downloaded.
+pub fn storage_for(dir: &Path) -> Result<Box<dyn StorageBackend>, StorageError> {
+    let url = match dir.to_str() {
+        Some(url) if is_remote(dir) => url,
+        _ => {
+            return Ok(Box::new(LocalDir {
+                dir: dir.to_path_buf(),
+            }))
+        }
+    };
+
+    remote_storage(url)
+}
+
+#[cfg(feature = "remote")]
+fn remote_storage(url: &str) -> Result<Box<dyn StorageBackend>, StorageError> {
+    // statements are joined to the directory with a `/` of their own
+    let dir = url.trim_end_matches('/');
+
+    if let Some(location) = url.strip_prefix("s3://") {
+        Ok(Box::new(s3::S3Dir::new(dir, location)?))
+    } else if let Some(location) = url.strip_prefix("webdav+http://") {
+        Ok(Box::new(webdav::WebDavDir::new(dir, "http", location)?))
+    } else if let Some(location) = url.strip_prefix("webdav://") {
+        Ok(Box::new(webdav::WebDavDir::new(dir, "https", location)?))
+    } else {
+        Err(StorageError::Unsupported(url.to_string()))
+    }
+}
+
+#[cfg(not(feature = "remote"))]
+fn remote_storage(url: &str) -> Result<Box<dyn StorageBackend>, StorageError> {
+    Err(StorageError::Unsupported(url.to_string()))
+}
+
+/// Download a statement in a remote directory to a temporary file, so that it can be opened by other programs.
+/// Returns the path of the temporary file.
+pub fn download_to_temp(path: &Path) -> Result<PathBuf, StorageError> {
+    let dir = path.parent().ok_or_else(|| {
+        StorageError::InvalidUrl(path.display().to_string(), "no directory".to_string())
+    })?;
+
+    storage_for(dir)?.download_to_temp(path)
+}
+
+/// The name of a file in a remote directory, which is all that's kept of it locally
+#[cfg(feature = "remote")]
+fn file_name(path: &Path) -> Result<&str, StorageError> {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| {
+            StorageError::InvalidUrl(path.display().to_string(), "no file name".to_string())
+        })
+}
+
+/// The temporary file a remote file is downloaded to.
+/// Only the file name is kept, so that remote names can't escape the temporary directory.
+#[cfg(feature = "remote")]
+fn temp_path(name: &str) -> Result<PathBuf, StorageError> {
+    let out_dir = std::env::temp_dir().join("quill");
+    std::fs::create_dir_all(&out_dir).map_err(|e| StorageError::Io(out_dir.clone(), e))?;
+
+    Ok(out_dir.join(name))
+}
+
+/// Write a successful response to a file
+#[cfg(feature = "remote")]
+fn write_response(
+    url: &str,
+    resp: attohttpc::Response,
+    out_path: &Path,
+) -> Result<(), StorageError> {
+    let resp = check_response(url, resp)?;
+    let file =
+        std::fs::File::create(out_path).map_err(|e| StorageError::Io(out_path.to_path_buf(), e))?;
+    resp.write_to(file)
+        .map_err(|e| StorageError::Request(url.to_string(), e.to_string()))?;
+
+    Ok(())
+}
+
+/// Characters that are left as they are in each part of a URL's path or query,
+/// as required when signing S3 requests
+#[cfg(feature = "remote")]
+const UNRESERVED: &percent_encoding::AsciiSet = &percent_encoding::NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+/// Encode a single part of a URL's path or query
+#[cfg(feature = "remote")]
+fn encode_segment(segment: &str) -> String {
+    percent_encoding::utf8_percent_encode(segment, UNRESERVED).to_string()
+}
+
+/// Check that a request succeeded, keeping the body of the response as the reason if it didn't
+#[cfg(feature = "remote")]
+fn check_response(
+    url: &str,
+    resp: attohttpc::Response,
+) -> Result<attohttpc::Response, StorageError> {
+    if resp.is_success() {
+        return Ok(resp);
+    }
+
+    let status = resp.status();
+    let body = resp.text().unwrap_or_default();
+    Err(StorageError::Response(
+        url.to_string(),
+        format!("{} {}", status, body.trim()),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn remote_directories() {
+        assert!(is_remote(Path::new("s3://bucket/statements")));
+        assert!(is_remote(Path::new("webdav://example.com/statements")));
+        assert!(is_remote(Path::new(
+            "webdav+http://localhost:8080/statements"
+        )));
+        assert!(!is_remote(Path::new("/home/user/statements")));
+        assert!(!is_remote(Path::new("statements/s3://")));
+    }
+
+    #[test]
+    fn local_directory() {
+        let storage = storage_for(Path::new("tests")).unwrap();
+        let path = Path::new("tests/2021-01-01.pdf");
+
+        assert_eq!(path, storage.download_to_temp(path).unwrap());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn links_are_resolved() {
+        use std::os::unix::fs::symlink;
+
+        let tmp = tempdir().unwrap();
+        let dir = tmp.path();
+        let outside_tmp = tempdir().unwrap();
+        let outside = outside_tmp.path();
+        fs::write(dir.join("real.pdf"), "real").unwrap();
+        fs::write(outside.join("target.pdf"), "target").unwrap();
+        // two links to a file outside the directory, and one to a file inside it
+        symlink(outside.join("target.pdf"), dir.join("a.pdf")).unwrap();
+        symlink(outside.join("target.pdf"), dir.join("b.pdf")).unwrap();
+        symlink(dir.join("real.pdf"), dir.join("c.pdf")).unwrap();
+        symlink(dir.join("nothing.pdf"), dir.join("broken.pdf")).unwrap();
+
+        let files: Vec<PathBuf> = ["a.pdf", "b.pdf", "broken.pdf", "c.pdf", "real.pdf"]
+            .iter()
+            .map(|name| dir.join(name))
+            .collect();
+
+        assert_eq!(
+            vec![dir.join("real.pdf")],
+            resolve_links(files.clone(), false)
+        );
+        assert_eq!(
+            vec![dir.join("real.pdf"), dir.join("a.pdf")],
+            resolve_links(files, true)
+        );
+    }
+
+    #[test]
+    fn case_of_names_without_letters() {
+        assert_eq!(None, is_case_insensitive(Path::new("2021-01-01.123")));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn case_sensitive_file_system() {
+        let tmp = tempdir().unwrap();
+        let dir = tmp.path();
+        fs::write(dir.join("Statement.pdf"), "statement").unwrap();
+
+        assert_eq!(Some(false), is_case_insensitive(&dir.join("Statement.pdf")));
+
+        // a file whose name only differs by case is another file
+        fs::write(dir.join("sTATEMENT.PDF"), "another statement").unwrap();
+        assert_eq!(Some(false), is_case_insensitive(&dir.join("Statement.pdf")));
+    }
+
+    #[cfg(feature = "remote")]
+    #[test]
+    fn invalid_remote_directories() {
+        for url in ["s3://", "webdav://", "webdav://example.com:port/statements"] {
+            assert!(matches!(
+                storage_for(Path::new(url)),
+                Err(StorageError::InvalidUrl(..))
+            ));
+        }
+    }
+}
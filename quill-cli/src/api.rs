@@ -0,0 +1,396 @@
+//! Serve the statements found by `quill daemon` as JSON over HTTP, for dashboards and other tools.
+//!
+//! The API is read-only and only listens on localhost.
+//! Requests must also be addressed to localhost in their `Host` header, so that web pages
+//! can't read the statements by pointing their own domain at `127.0.0.1`.
+//! It has three endpoints, each answering with the time of the latest scan along with a list:
+//!
+//! - `/accounts`: every account, with its number of missing statements and next due date
+//! - `/missing`: every missing statement
+//! - `/upcoming`: the statements expected in the next 90 days, and the next one for each account
+
+use crate::cfg::Config;
+use anyhow::{bail, Context};
+use chrono::{Duration, Local};
+use serde_json::{json, Value};
+use std::{
+    io::{BufRead, BufReader, Read, Take, Write},
+    net::{Ipv4Addr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, RwLock,
+    },
+    thread,
+    time::{self, Instant},
+};
+use tracing::{debug, info, warn};
+
+/// How far ahead `/upcoming` lists statements, like the TUI's Upcoming tab
+const UPCOMING_HORIZON_DAYS: i64 = 90;
+
+/// How long a client has to send its whole request
+const READ_TIMEOUT: time::Duration = time::Duration::from_secs(5);
+
+/// The most of a request that's read, which is plenty for a request line and its headers
+const MAX_REQUEST_BYTES: u64 = 8 * 1024;
+
+/// The most requests answered at once, so many slow clients can't start unlimited threads
+const MAX_CONNECTIONS: usize = 16;
+
+/// The body of each endpoint's response, as of the latest scan
+#[derive(Debug)]
+struct Responses {
+    accounts: String,
+    missing: String,
+    upcoming: String,
+}
+
+/// A read-only HTTP API for the statements found by the latest scan.
+/// Each request is answered on its own thread, so that a slow client doesn't hold up the
+/// others, until quill exits.
+/// Connections beyond `MAX_CONNECTIONS` are closed without an answer.
+#[derive(Debug)]
+pub struct Api {
+    responses: Arc<RwLock<Option<Responses>>>,
+}
+
+impl Api {
+    /// Listen for requests on `port` of localhost.
+    /// Until the statements are first given to `update`, every request is answered with an error.
+    pub fn serve(port: u16) -> anyhow::Result<Self> {
+        let addr = (Ipv4Addr::LOCALHOST, port);
+        let listener = TcpListener::bind(addr).with_context(|| {
+            format!("Error listening for API requests on `localhost:{}`.", port)
+        })?;
+        info!(port, "serving the API");
+
+        let responses: Arc<RwLock<Option<Responses>>> = Arc::default();
+        let shared = Arc::clone(&responses);
+        let open = Arc::new(AtomicUsize::new(0));
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(e) => {
+                        warn!("couldn't accept an API request: {}", e);
+                        continue;
+                    }
+                };
+                let connection = match Connection::open(&open) {
+                    Some(connection) => connection,
+                    None => {
+                        warn!("too many API requests at once, closing one");
+                        continue;
+                    }
+                };
+                let shared = Arc::clone(&shared);
+                thread::spawn(move || {
+                    // one bad request shouldn't stop the others from being answered
+                    if let Err(e) = answer(stream, port, &shared) {
+                        warn!("couldn't answer an API request: {:#}", e);
+                    }
+                    drop(connection);
+                });
+            }
+        });
+
+        Ok(Self { responses })
+    }
+
+    /// Answer requests with the statements that were just found
+    pub fn update(&self, conf: &Config) {
+        let scanned = Local::now().naive_local().format("%Y-%m-%dT%H:%M:%S");
+        let body = |key: &str, list: Vec<Value>| {
+            json!({ "scanned": scanned.to_string(), key: list }).to_string()
+        };
+
+        let responses = Responses {
+            accounts: body("accounts", accounts(conf)),
+            missing: body("missing", missing(conf)),
+            upcoming: body("upcoming", upcoming(conf)),
+        };
+        // a panic while answering a request can't leave the responses half-written
+        let mut current = self
+            .responses
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        *current = Some(responses);
+    }
+}
+
+/// A request being answered, counted until it's dropped
+struct Connection(Arc<AtomicUsize>);
+
+impl Connection {
+    /// Count another request being answered, unless `MAX_CONNECTIONS` already are
+    fn open(open: &Arc<AtomicUsize>) -> Option<Self> {
+        open.fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| {
+            (n < MAX_CONNECTIONS).then_some(n + 1)
+        })
+        .ok()?;
+
+        Some(Self(Arc::clone(open)))
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Every account, in the order they're listed, with its number of missing statements,
+/// latest available statement, and next due date
+fn accounts(conf: &Config) -> Vec<Value> {
+    let stmts = conf.statements();
+
+    conf.keys()
+        .iter()
+        .filter_map(|key| {
+            let acct = conf.accounts().get(key.as_str())?;
+            let latest = stmts
+                .latest_available(key)
+                .map(|obs| obs.statement().date().to_string());
+            let next_due = stmts
+                .get_upcoming(key)
+                .and_then(|dates| dates.first())
+                .map(|date| date.to_string());
+            Some(json!({
+                "account": key,
+                "name": acct.name(),
+                "institution": acct.institution(),
+                "missing": stmts.missing_count_for(key),
+                "latest": latest,
+                "next_due": next_due,
+            }))
+        })
+        .collect()
+}
+
+/// Every missing statement, sorted by account key and then date
+fn missing(conf: &Config) -> Vec<Value> {
+    conf.statements()
+        .all_missing()
+        .into_iter()
+        .map(|(key, obs)| statement(conf, key, &obs.statement().date().to_string()))
+        .collect()
+}
+
+/// The statements expected soon, with the next closest dates first
+fn upcoming(conf: &Config) -> Vec<Value> {
+    let horizon = Local::now().naive_local().date() + Duration::days(UPCOMING_HORIZON_DAYS);
+
+    conf.statements()
+        .upcoming(&horizon)
+        .into_iter()
+        .map(|(key, date)| statement(conf, key, &date.to_string()))
+        .collect()
+}
+
+/// Describe a statement by its account and date, like the payloads of hooks
+fn statement(conf: &Config, key: &str, date: &str) -> Value {
+    let name = conf.accounts().get(key).map_or(key, |a| a.name());
+
+    json!({ "account": key, "name": name, "date": date })
+}
+
+/// Read a request to `port` and answer it with the matching response
+fn answer(
+    mut stream: TcpStream,
+    port: u16,
+    responses: &RwLock<Option<Responses>>,
+) -> anyhow::Result<()> {
+    // only the request line and `Host` header matter, but the other headers are read
+    // so the client isn't cut off
+    let deadline = Instant::now() + READ_TIMEOUT;
+    let mut reader = BufReader::new((&stream).take(MAX_REQUEST_BYTES));
+    let mut request = String::new();
+    read_line(&mut reader, &mut request, deadline)?;
+    let mut host = None;
+    let mut header = String::new();
+    while read_line(&mut reader, &mut header, deadline)? > 2 {
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("host") {
+                host = Some(value.trim().to_string());
+            }
+        }
+        header.clear();
+    }
+    debug!(request = request.trim_end(), host = ?host, "answering an API request");
+
+    let (status, body) = if is_localhost(host.as_deref(), port) {
+        let responses = responses
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        route(&request, responses.as_ref())
+    } else {
+        (
+            "403 Forbidden",
+            json!({ "error": "Only requests to localhost are answered." }).to_string(),
+        )
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    stream.flush()?;
+
+    Ok(())
+}
+
+/// Read a line of a request, giving up once the client has taken too long to send all of it.
+/// Nothing more is read once `MAX_REQUEST_BYTES` have been.
+fn read_line(
+    reader: &mut BufReader<Take<&TcpStream>>,
+    line: &mut String,
+    deadline: Instant,
+) -> anyhow::Result<usize> {
+    let left = deadline.saturating_duration_since(Instant::now());
+    if left.is_zero() {
+        bail!("The client took too long to send its request.");
+    }
+    reader.get_ref().get_ref().set_read_timeout(Some(left))?;
+
+    Ok(reader.read_line(line)?)
+}
+
+/// Check if a request's `Host` header is `localhost` or `127.0.0.1`, on `port`.
+/// The port can only be left out if it's the default port of HTTP.
+fn is_localhost(host: Option<&str>, port: u16) -> bool {
+    let host = match host {
+        Some(host) => host,
+        None => return false,
+    };
+    let (name, host_port) = match host.rsplit_once(':') {
+        Some((name, host_port)) => (name, host_port.parse().ok()),
+        None => (host, Some(80)),
+    };
+
+    (name.eq_ignore_ascii_case("localhost") || name == "127.0.0.1") && host_port == Some(port)
+}
+
+/// Find the status and body to answer a request line like `GET /missing HTTP/1.1` with
+fn route(request: &str, responses: Option<&Responses>) -> (&'static str, String) {
+    let error = |msg: &str| json!({ "error": msg }).to_string();
+
+    let mut parts = request.split_whitespace();
+    let (method, target) = match (parts.next(), parts.next()) {
+        (Some(method), Some(target)) => (method, target),
+        _ => {
+            return (
+                "400 Bad Request",
+                error("The request couldn't be understood."),
+            )
+        }
+    };
+    if method != "GET" {
+        return (
+            "405 Method Not Allowed",
+            error("Only GET requests are answered."),
+        );
+    }
+    let responses = match responses {
+        Some(r) => r,
+        None => {
+            return (
+                "503 Service Unavailable",
+                error("Statements haven't been scanned for yet."),
+            )
+        }
+    };
+
+    // queries aren't used by any endpoint
+    let path = target.split('?').next().unwrap_or_default();
+    match path.trim_end_matches('/') {
+        "/accounts" => ("200 OK", responses.accounts.clone()),
+        "/missing" => ("200 OK", responses.missing.clone()),
+        "/upcoming" => ("200 OK", responses.upcoming.clone()),
+        _ => (
+            "404 Not Found",
+            error("Try `/accounts`, `/missing`, or `/upcoming`."),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn responses() -> Responses {
+        Responses {
+            accounts: "accounts".to_string(),
+            missing: "missing".to_string(),
+            upcoming: "upcoming".to_string(),
+        }
+    }
+
+    #[test]
+    fn endpoints() {
+        let responses = responses();
+
+        for (request, body) in [
+            ("GET /accounts HTTP/1.1", "accounts"),
+            ("GET /missing/ HTTP/1.1", "missing"),
+            ("GET /upcoming?days=30 HTTP/1.1", "upcoming"),
+        ] {
+            assert_eq!(
+                ("200 OK", body.to_string()),
+                route(request, Some(&responses))
+            );
+        }
+    }
+
+    #[test]
+    fn only_localhost() {
+        assert!(is_localhost(Some("localhost:8080"), 8080));
+        assert!(is_localhost(Some("LocalHost:8080"), 8080));
+        assert!(is_localhost(Some("127.0.0.1:8080"), 8080));
+        assert!(is_localhost(Some("localhost"), 80));
+
+        assert!(!is_localhost(None, 8080));
+        assert!(!is_localhost(Some("localhost"), 8080));
+        assert!(!is_localhost(Some("localhost:8081"), 8080));
+        assert!(!is_localhost(Some("evil.example.com:8080"), 8080));
+        assert!(!is_localhost(Some("localhost.evil.example.com:8080"), 8080));
+        assert!(!is_localhost(Some("127.0.0.1:"), 8080));
+    }
+
+    #[test]
+    fn limit_connections() {
+        let open = Arc::new(AtomicUsize::new(0));
+        let mut connections: Vec<Connection> = (0..MAX_CONNECTIONS)
+            .map(|_| Connection::open(&open).unwrap())
+            .collect();
+        assert!(Connection::open(&open).is_none());
+
+        connections.pop();
+        assert!(Connection::open(&open).is_some());
+        drop(connections);
+        assert_eq!(0, open.load(Ordering::Acquire));
+    }
+
+    #[test]
+    fn errors() {
+        let responses = responses();
+        let status = |request: &str, responses: Option<&Responses>| route(request, responses).0;
+
+        assert_eq!("400 Bad Request", status("", Some(&responses)));
+        assert_eq!("400 Bad Request", status("GET", Some(&responses)));
+        assert_eq!(
+            "405 Method Not Allowed",
+            status("POST /missing HTTP/1.1", Some(&responses))
+        );
+        assert_eq!(
+            "503 Service Unavailable",
+            status("GET /missing HTTP/1.1", None)
+        );
+        assert_eq!(
+            "404 Not Found",
+            status("GET /statements HTTP/1.1", Some(&responses))
+        );
+    }
+}
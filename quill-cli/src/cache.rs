@@ -0,0 +1,257 @@
+//! Cache the statements found in each directory between runs.
+//!
+//! Listing a directory can be slow, especially on network drives, so the
+//! statements found in each account's directory are kept along with the
+//! directory's modification time.
+//! A directory is only listed again when it has been modified since.
+//! Archives that are modified in place don't change their directory's
+//! modification time, so replace an archive rather than editing it.
+//! Editing the ignore file in place doesn't change it either, so the ignore
+//! file's patterns for files that are never statements are kept too.
+//!
+//! When a directory is listed again, the dates parsed from its statements' file
+//! names are reused for the files that haven't changed.
+
+use anyhow::Context;
+use quill_account::{Account, DateCache};
+use quill_statement::Statement;
+use quill_utils::{create_parent_dir, project_dirs};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+use tracing::debug;
+
+const CACHE_FILE: &str = "state.json";
+
+/// Directories modified this recently may be modified again without their
+/// modification time changing, on file systems with a coarse resolution
+const MIN_AGE: Duration = Duration::from_secs(2);
+
+/// The statements found in a directory when it was last listed
+#[derive(Debug, Deserialize, Serialize)]
+struct CachedDirectory {
+    /// The directory that was listed
+    dir: PathBuf,
+
+    /// The format the statement file names were matched against
+    fmt: String,
+
+    /// Whether archives in the directory were searched
+    #[serde(default)]
+    search_archives: bool,
+
+    /// Whether symbolic links in the directory were followed
+    #[serde(default)]
+    follow_symlinks: bool,
+
+    /// The patterns for files that were left out
+    #[serde(default)]
+    exclude: Vec<String>,
+
+    /// The ignore file's patterns for files that are never statements
+    #[serde(default)]
+    ignored_files: Vec<String>,
+
+    /// When the directory was last modified
+    modified: SystemTime,
+
+    /// Statements found in the directory, earliest first
+    statements: Vec<Statement>,
+}
+
+/// Statements found in every account's directory on a previous run.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ScanCache {
+    directories: Vec<CachedDirectory>,
+
+    /// Dates parsed from the statement file names in each directory
+    #[serde(default)]
+    dates: HashMap<PathBuf, DateCache>,
+}
+
+impl ScanCache {
+    /// Load the cache, starting from an empty one if it doesn't exist or can't be read
+    pub fn load() -> Self {
+        cache_path()
+            .and_then(|path| File::open(path).ok())
+            .and_then(|file| serde_json::from_reader(BufReader::new(file)).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save the cache, creating its directory if necessary
+    pub fn save(&self) -> anyhow::Result<()> {
+        let path = match cache_path() {
+            Some(p) => p,
+            None => anyhow::bail!("Could not determine a directory to store the cache in."),
+        };
+
+        create_parent_dir(&path)
+            .with_context(|| format!("Error creating the directory for `{}`.", path.display()))?;
+
+        let file = File::create(&path)
+            .with_context(|| format!("Error creating cache file `{}`.", path.display()))?;
+        serde_json::to_writer(BufWriter::new(file), self)
+            .with_context(|| format!("Error writing cache file `{}`.", path.display()))?;
+
+        Ok(())
+    }
+
+    /// Get the statements in an account's directory, only listing the
+    /// directory if it has changed since it was cached
+    pub fn downloaded_statements(&mut self, acct: &Account) -> Vec<Statement> {
+        let dir = acct.directory();
+        let fmt = acct.format_string();
+        let search_archives = acct.search_archives();
+        let follow_symlinks = acct.follow_symlinks();
+        let exclude = acct.exclude().patterns();
+        let ignored_files = acct.ignored().file_patterns();
+        // archived statements are spread over a directory for each year, which
+        // the account's directory doesn't know about, so they're always listed
+        let modified = match acct.archive_dir() {
+            Some(_) => None,
+            None => modified_time(dir),
+        };
+
+        let pos = self.directories.iter().position(|c| {
+            (c.dir == dir)
+                && (c.fmt == fmt)
+                && (c.search_archives == search_archives)
+                && (c.follow_symlinks == follow_symlinks)
+                && (c.exclude == exclude)
+                && (c.ignored_files == ignored_files)
+        });
+
+        if let (Some(i), Some(m)) = (pos, modified) {
+            if self.directories[i].modified == m {
+                debug!(dir = %dir.display(), "directory unchanged, using cached statements");
+                return self.directories[i].statements.clone();
+            }
+        }
+
+        let statements = match acct.is_remote() {
+            true => acct.downloaded_statements(),
+            false => {
+                let dates = self
+                    .dates
+                    .entry(dir.to_path_buf())
+                    .or_insert_with(|| DateCache::new(fmt));
+                acct.downloaded_statements_cached(dates)
+            }
+        };
+
+        // forget the old entry, and only remember the new one if it can be trusted
+        if let Some(i) = pos {
+            self.directories.swap_remove(i);
+        }
+        if let Some(m) = modified.filter(|&m| is_settled(m)) {
+            self.directories.push(CachedDirectory {
+                dir: dir.to_path_buf(),
+                fmt: fmt.to_string(),
+                search_archives,
+                follow_symlinks,
+                exclude: exclude.to_vec(),
+                ignored_files: ignored_files.iter().map(|p| p.to_string()).collect(),
+                modified: m,
+                statements: statements.clone(),
+            });
+        }
+
+        statements
+    }
+}
+
+/// The location of the cache file
+fn cache_path() -> Option<PathBuf> {
+    project_dirs().cache_dir().map(|dir| dir.join(CACHE_FILE))
+}
+
+/// When a directory was last modified, if it can be determined
+fn modified_time(dir: &Path) -> Option<SystemTime> {
+    fs::metadata(dir).and_then(|m| m.modified()).ok()
+}
+
+/// Check that a modification time is old enough to be relied on
+fn is_settled(modified: SystemTime) -> bool {
+    SystemTime::now()
+        .duration_since(modified)
+        .map_or(false, |age| age >= MIN_AGE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use kronos::Grain;
+    use quill_account::StatementPeriod;
+    use tempfile::tempdir;
+
+    /// An account for the statements in a directory, reading its ignore file
+    fn account(dir: &Path) -> Account {
+        Account::builder()
+            .name("Name")
+            .institution("Institution")
+            .first(NaiveDate::from_ymd_opt(2021, 1, 1).unwrap())
+            .period(StatementPeriod::nth_of(1, Grain::Day, 1, Grain::Month))
+            .format("%Y-%m-%d.pdf")
+            .directory(dir)
+            .build()
+            .unwrap()
+    }
+
+    /// The file names of the statements found in an account's directory
+    fn file_names(cache: &mut ScanCache, acct: &Account) -> Vec<String> {
+        cache
+            .downloaded_statements(acct)
+            .iter()
+            .map(|stmt| {
+                stmt.path()
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn ignore_file_edited_in_place() {
+        let tmp = tempdir().unwrap();
+        let dir = tmp.path();
+        fs::write(dir.join("2021-01-01.pdf"), "").unwrap();
+        fs::write(dir.join("2021-02-01.pdf"), "").unwrap();
+        let ignore_path = dir.join(".quillignore.toml");
+        fs::write(&ignore_path, "files = [\"2021-01-*\"]\n").unwrap();
+
+        // an old enough modification time for the directory to be cached
+        let modified = SystemTime::now() - Duration::from_secs(60);
+        let settle = || File::open(dir).unwrap().set_modified(modified).unwrap();
+        settle();
+
+        let mut cache = ScanCache::default();
+        assert_eq!(
+            vec!["2021-02-01.pdf"],
+            file_names(&mut cache, &account(dir))
+        );
+        assert_eq!(1, cache.directories.len());
+
+        // editing the ignore file doesn't change the directory's modification time
+        fs::write(&ignore_path, "files = [\"2021-02-*\"]\n").unwrap();
+        settle();
+        assert_eq!(
+            vec!["2021-01-01.pdf"],
+            file_names(&mut cache, &account(dir))
+        );
+
+        fs::write(&ignore_path, "files = []\n").unwrap();
+        settle();
+        assert_eq!(
+            vec!["2021-01-01.pdf", "2021-02-01.pdf"],
+            file_names(&mut cache, &account(dir))
+        );
+    }
+}
@@ -0,0 +1,40 @@
+//! Settings for archiving statements out of the accounts' directories.
+
+use quill_account::Account;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Settings from the `[Archive]` table of the configuration file
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ArchiveConfig {
+    /// Directory that statements are archived under
+    #[serde(skip_serializing_if = "Option::is_none")]
+    root: Option<PathBuf>,
+}
+
+impl ArchiveConfig {
+    /// The directory that statements are archived under, if archiving is set up
+    pub fn root(&self) -> Option<&Path> {
+        self.root.as_deref()
+    }
+
+    /// Set the directory that statements are archived under
+    pub fn with_root(mut self, root: Option<PathBuf>) -> Self {
+        self.root = root;
+        self
+    }
+
+    /// The directory an account's statements are archived in, as
+    /// `<root>/<institution>/<account>`, if archiving is set up
+    pub fn account_dir(&self, acct: &Account) -> Option<PathBuf> {
+        self.root().map(|root| {
+            root.join(path_component(acct.institution()))
+                .join(path_component(acct.name()))
+        })
+    }
+}
+
+/// Make a name safe to use as a single directory name
+fn path_component(name: &str) -> String {
+    name.replace(['/', '\\'], "-")
+}
@@ -1,97 +1,304 @@
 //! Global account configuration details.
 
-use crate::cli::CliOpts;
+use super::{
+    ArchiveConfig, DaemonConfig, DigestConfig, DisplayConfig, HooksConfig, InboxConfig,
+    Institution, NotificationsConfig, Scanner, Theme, TuiConfig,
+};
 use anyhow::{bail, Context};
-use quill_account::Account;
-use quill_statement::StatementCollection;
-use quill_utils::parse_toml_file;
-use std::collections::HashMap;
+use quill_account::{Account, AccountCreationError};
+use quill_statement::{AccountKey, StatementCollection};
+use quill_utils::{expand_path, parse_toml_file};
+use serde::{ser::SerializeMap, Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap};
 use std::convert::TryFrom;
+use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 use toml::{map::Map, Value};
+use tracing::{debug, info, info_span, warn};
+
+/// Creates an account from its properties in the configuration file
+type NewAccountFn = fn(&Value) -> Result<Account, AccountCreationError>;
 
 /// Account and program configuration
 #[derive(Debug)]
-pub struct Config<'a> {
+pub struct Config {
     /// Absolute path of the config file
     path: PathBuf,
 
+    /// Paths of the other config files merged into this one, if any
+    merged: Vec<PathBuf>,
+
+    /// Name of the config file each account came from, when several are merged
+    sources: HashMap<AccountKey, String>,
+
+    /// Institutions that accounts refer to, by key
+    institutions: BTreeMap<String, Institution>,
+
     /// Account information
-    accounts: HashMap<String, Account<'a>>,
+    accounts: HashMap<AccountKey, Account>,
 
     /// Ordered index of accounts
-    account_order: Vec<String>,
+    account_order: Vec<AccountKey>,
 
     /// Fast-access number of accounts
     num_accounts: usize,
 
     /// Collection of account statements
     acct_stmts: StatementCollection,
+
+    /// Settings for the digest of statement changes
+    digest: DigestConfig,
+
+    /// Settings for running in the background
+    daemon: DaemonConfig,
+
+    /// Settings for desktop notifications
+    notifications: NotificationsConfig,
+
+    /// Hooks to run when statements change
+    hooks: HooksConfig,
+
+    /// Colours used to draw the TUI
+    theme: Theme,
+
+    /// Settings for the TUI
+    tui: TuiConfig,
+
+    /// Settings for archiving statements
+    archive: ArchiveConfig,
+
+    /// Settings for sorting downloaded statements
+    inbox: InboxConfig,
+
+    /// Settings for how dates are shown
+    display: DisplayConfig,
+
+    /// Keys of the accounts that couldn't be loaded, and why, when loaded leniently
+    skipped: Vec<(AccountKey, String)>,
+
+    /// Whether accounts that can't be created are skipped, instead of stopping
+    lenient: bool,
+
+    /// Name of the profile the configuration was loaded for, if any
+    profile: Option<String>,
 }
 
-impl<'a> Config<'a> {
+impl Config {
     /// Get the path of the config file
     /// By `new` implementation, it is assured that this is an absolute path
     pub fn path(&self) -> &Path {
         self.path.as_path()
     }
 
+    /// Get the paths of every config file, starting with the one whose settings are used
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        std::iter::once(self.path.as_path()).chain(self.merged.iter().map(PathBuf::as_path))
+    }
+
+    /// Return whether accounts from several config files are merged together
+    pub fn is_merged(&self) -> bool {
+        !self.merged.is_empty()
+    }
+
+    /// Return the name of the config file an account came from, if several are merged
+    pub fn source(&self, key: &str) -> Option<&str> {
+        self.sources.get(key).map(String::as_str)
+    }
+
     /// Get the list of accounts in the configuration
-    pub fn accounts(&self) -> &HashMap<String, Account<'a>> {
+    pub fn accounts(&self) -> &HashMap<AccountKey, Account> {
         // return required here because of the pointer
         &self.accounts
     }
 
+    /// Return the account with a given key, along with its key, if there is one
+    pub fn account(&self, key: &str) -> Option<(&AccountKey, &Account)> {
+        self.accounts.get_key_value(key)
+    }
+
+    /// Return the institution an account refers to as its `institution`, by its key or name,
+    /// if it's in the `[Institutions]` table
+    pub fn institution(&self, acct: &Account) -> Option<&Institution> {
+        let name = acct.institution();
+        self.institutions.get(name).or_else(|| {
+            self.institutions
+                .values()
+                .find(|inst| inst.name() == Some(name))
+        })
+    }
+
     /// Return the sorted account keys
-    pub fn keys(&self) -> &Vec<String> {
+    pub fn keys(&self) -> &[AccountKey] {
         &self.account_order
     }
 
+    /// Return the key and account at a position in the sorted account keys,
+    /// if there is one
+    pub fn account_by_index(&self, i: usize) -> Option<(&AccountKey, &Account)> {
+        let key = self.account_order.get(i)?;
+        self.accounts.get(key).map(|acct| (key, acct))
+    }
+
     /// Return the number of accounts in the configuration
     pub fn len(&self) -> usize {
         self.num_accounts
     }
 
-    /// Add a new account to the configuration
-    pub fn add_account(&mut self, key: &str, props: &toml::Value) -> anyhow::Result<()> {
-        // create account and push to conf
-        // can't use serialization here for the entire account because there is
-        // a more complex relationship between the Account struct and its
-        // components
-        let acct = Account::try_from(props)?;
+    /// Return whether there are no accounts in the configuration
+    pub fn is_empty(&self) -> bool {
+        self.num_accounts == 0
+    }
+
+    /// Return when any of the configuration files was last modified, if that can be determined
+    pub fn modified(&self) -> Option<SystemTime> {
+        self.paths()
+            .filter_map(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+            .max()
+    }
+
+    /// Load the configuration files again and scan for statements,
+    /// skipping accounts only if they were skipped the first time
+    pub fn reloaded(&self) -> anyhow::Result<Config> {
+        let mut conf = Self::read_all(&self.owned_paths(), self.lenient)?;
+        conf.refresh_account_statements()?;
+
+        Ok(conf)
+    }
+
+    /// Return the keys of the accounts that couldn't be loaded, and why.
+    /// This is only ever non-empty when the configuration is loaded leniently.
+    pub fn skipped_accounts(&self) -> &[(AccountKey, String)] {
+        &self.skipped
+    }
+
+    /// Add a new account to the configuration.
+    /// Each of the account's named schedules is added as its own account,
+    /// with the key `<key>/<schedule>`, so its statements are tracked separately.
+    /// If statements are archived, the account's archive directory is set here.
+    pub fn add_account(&mut self, key: &str, mut acct: Account) -> anyhow::Result<()> {
+        if acct.schedule().is_none() {
+            let archive_dir = self.archive.account_dir(&acct);
+            acct = acct.with_archive_dir(archive_dir.as_deref());
+        }
+
+        let key = AccountKey::new(key);
+        for sched in acct.schedules() {
+            let sched_key = key.schedule(sched.schedule().unwrap_or_default());
+            self.add_account(sched_key.as_str(), sched.clone())?;
+        }
 
         // update the account order with a binary search
-        match self.account_order.binary_search(&key.to_string()) {
+        match self.account_order.binary_search(&key) {
             Ok(_) => bail!(
                 "Account key `{}` is duplicated. Please check your configuration file to ensure keys are unique.",
                 &key
             ),
-            Err(pos) => self.account_order.insert(pos, key.to_string()),
+            Err(pos) => self.account_order.insert(pos, key.clone()),
         };
 
         // insert the account object into the configuration
-        self.accounts.insert(key.to_string(), acct);
+        self.accounts.insert(key, acct);
         self.num_accounts += 1;
 
         Ok(())
     }
 
-    /// Parse a TOML table for accounts and create Accounts
-    fn parse_accounts(&mut self, accounts: &Map<String, Value>) -> anyhow::Result<()> {
+    /// Parse a TOML table for accounts and create Accounts.
+    /// If `lenient` is set, accounts that can't be created are skipped and remembered,
+    /// instead of stopping at the first one.
+    fn parse_accounts(
+        &mut self,
+        accounts: &Map<String, Value>,
+        new_account: NewAccountFn,
+        lenient: bool,
+    ) -> anyhow::Result<()> {
         for (acct, props) in accounts {
             // add the account to the configuration
             // error out if any account isn't added properly
-            self.add_account(acct, props).with_context(|| {
-                format!(
-                    "Error adding account `{}` with the following properties:\n{:#?}",
-                    acct, props,
-                )
-            })?;
+            // can't use serialization here for the entire account because there is
+            // a more complex relationship between the Account struct and its
+            // components
+            let result = new_account(props)
+                .map_err(anyhow::Error::from)
+                .and_then(|a| self.add_account(acct, a));
+
+            match result {
+                Ok(()) => debug!(key = %acct, "parsed account"),
+                Err(e) if lenient => {
+                    warn!(key = %acct, "skipping account: {:#}", e);
+                    self.skipped
+                        .push((AccountKey::new(acct), format!("{:#}", e)));
+                }
+                Err(e) => {
+                    return Err(e).with_context(|| {
+                        format!(
+                            "Error adding account `{}` with the following properties:\n{:#?}",
+                            acct, props,
+                        )
+                    })
+                }
+            }
         }
 
         Ok(())
     }
 
+    /// Retrieve the settings for the digest of statement changes
+    pub fn digest(&self) -> &DigestConfig {
+        &self.digest
+    }
+
+    /// Retrieve the settings for running in the background
+    pub fn daemon(&self) -> &DaemonConfig {
+        &self.daemon
+    }
+
+    /// Retrieve the settings for archiving statements
+    pub fn archive(&self) -> &ArchiveConfig {
+        &self.archive
+    }
+
+    /// Retrieve the settings for sorting downloaded statements
+    pub fn inbox(&self) -> &InboxConfig {
+        &self.inbox
+    }
+
+    /// Retrieve the settings for how dates are shown
+    pub fn display(&self) -> &DisplayConfig {
+        &self.display
+    }
+
+    /// Retrieve the settings for desktop notifications
+    pub fn notifications(&self) -> &NotificationsConfig {
+        &self.notifications
+    }
+
+    /// Retrieve the hooks to run when statements change
+    pub fn hooks(&self) -> &HooksConfig {
+        &self.hooks
+    }
+
+    /// Retrieve the colours used to draw the TUI
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Retrieve the settings for the TUI
+    pub fn tui(&self) -> &TuiConfig {
+        &self.tui
+    }
+
+    /// Retrieve the name of the profile the configuration was loaded for, if any
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
+    /// Set the name of the profile the configuration was loaded for
+    pub fn set_profile(&mut self, profile: Option<&str>) {
+        self.profile = profile.map(String::from);
+    }
+
     /// Retrieve the statements for each account
     pub fn statements(&self) -> &StatementCollection {
         &self.acct_stmts
@@ -107,6 +314,16 @@ impl<'a> Config<'a> {
         StatementCollection::try_from(self)
     }
 
+    /// Something that scans for the statements of this configuration on another thread
+    pub fn scanner(&self) -> Scanner {
+        Scanner::new(&self.accounts)
+    }
+
+    /// Paths of every config file, to load them again
+    fn owned_paths(&self) -> Vec<PathBuf> {
+        self.paths().map(Path::to_path_buf).collect()
+    }
+
     /// Update the HashMap of all statements for each account
     pub fn refresh_account_statements(&mut self) -> anyhow::Result<()> {
         let new_sc = self.scan_account_statements()?;
@@ -114,59 +331,572 @@ impl<'a> Config<'a> {
 
         Ok(())
     }
-}
 
-impl TryFrom<CliOpts> for Config<'_> {
-    type Error = anyhow::Error;
+    /// Recreate the configuration from a recorded session, using the recorded
+    /// statements instead of scanning for them.
+    /// The accounts' directories don't need to exist on this computer.
+    pub fn from_recorded(
+        path: &Path,
+        config_str: &str,
+        statements: StatementCollection,
+    ) -> anyhow::Result<Self> {
+        let mut conf = Self::parse(path, config_str, Account::try_from_unchecked, false)?;
+        conf.acct_stmts = statements;
 
-    fn try_from(value: CliOpts) -> anyhow::Result<Self, Self::Error> {
-        if !value.config().exists() {
-            bail!(
-                "Configuration file `{}` does not exist.",
-                value.config().display()
-            );
-        }
+        Ok(conf)
+    }
+
+    /// Load a configuration file without checking that the accounts' directories
+    /// exist, and without scanning for statements.
+    pub fn load_unchecked(path: &Path) -> anyhow::Result<Self> {
+        let config_str = read_config_file(path)?;
+
+        Self::parse(path, &config_str, Account::try_from_unchecked, false)
+    }
+
+    /// Parse the contents of a configuration file, without scanning for statements
+    fn parse(
+        path: &Path,
+        config_str: &str,
+        new_account: NewAccountFn,
+        lenient: bool,
+    ) -> anyhow::Result<Self> {
+        let _span = info_span!("config", path = %path.display()).entered();
 
         // config to be returned, if parsed properly
         let mut conf = Self {
-            path: value.config().to_path_buf(),
+            path: path.to_path_buf(),
+            merged: Vec::new(),
+            sources: HashMap::new(),
+            institutions: BTreeMap::new(),
             accounts: HashMap::new(),
             account_order: Vec::new(),
             num_accounts: 0,
             acct_stmts: StatementCollection::new(),
+            digest: DigestConfig::default(),
+            daemon: DaemonConfig::default(),
+            notifications: NotificationsConfig::default(),
+            hooks: HooksConfig::default(),
+            theme: Theme::default(),
+            tui: TuiConfig::default(),
+            archive: ArchiveConfig::default(),
+            inbox: InboxConfig::default(),
+            display: DisplayConfig::default(),
+            skipped: Vec::new(),
+            lenient,
+            profile: None,
         };
 
-        let config_str = parse_toml_file(value.config()).with_context(|| {
-            format!(
-                "Error reading contents of configuration file `{}`.\nPlease check the configuration and try again.",
-                value.config().display()
-            )
-        })?;
-
         let config_toml = match config_str.parse() {
             Ok(Value::Table(s)) => s,
             Ok(_) => {
                 bail!(
                     "Error parsing configuration file `{}`.\nPlease check the configuration and try again.",
-                    value.config().display(),
+                    path.display(),
                 );
             }
-            Err(e) => return Err(e).with_context(|| format!("Error parsing configuration file `{}`.\nPlease check the configuration and try again.", value.config().display())),
+            Err(e) => return Err(e).with_context(|| format!("Error parsing configuration file `{}`.\nPlease check the configuration and try again.", path.display())),
         };
 
+        // parse the digest settings, if any
+        if let Some(digest) = config_toml.get("Digest") {
+            conf.digest = digest.clone().try_into().with_context(|| {
+                format!(
+                    "Error parsing the `[Digest]` table in configuration file `{}`.",
+                    path.display()
+                )
+            })?;
+        }
+
+        // parse the daemon settings, if any
+        if let Some(daemon) = config_toml.get("Daemon") {
+            conf.daemon = daemon.clone().try_into().with_context(|| {
+                format!(
+                    "Error parsing the `[Daemon]` table in configuration file `{}`.",
+                    path.display()
+                )
+            })?;
+        }
+
+        // parse the notification settings, if any
+        if let Some(notifications) = config_toml.get("Notifications") {
+            conf.notifications = notifications.clone().try_into().with_context(|| {
+                format!(
+                    "Error parsing the `[Notifications]` table in configuration file `{}`.",
+                    path.display()
+                )
+            })?;
+        }
+
+        // parse the hooks, if any
+        if let Some(hooks) = config_toml.get("Hooks") {
+            conf.hooks = hooks.clone().try_into().with_context(|| {
+                format!(
+                    "Error parsing the `[Hooks]` table in configuration file `{}`.",
+                    path.display()
+                )
+            })?;
+        }
+
+        // parse the colour theme, if any
+        if let Some(theme) = config_toml.get("Theme") {
+            conf.theme = theme.clone().try_into().with_context(|| {
+                format!(
+                    "Error parsing the `[Theme]` table in configuration file `{}`.",
+                    path.display()
+                )
+            })?;
+        }
+
+        // parse the TUI settings, if any
+        if let Some(tui) = config_toml.get("Tui") {
+            conf.tui = tui.clone().try_into().with_context(|| {
+                format!(
+                    "Error parsing the `[Tui]` table in configuration file `{}`.",
+                    path.display()
+                )
+            })?;
+        }
+
+        // parse the archive settings, if any, before the accounts that are archived with them
+        if let Some(archive) = config_toml.get("Archive") {
+            let archive: ArchiveConfig = archive.clone().try_into().with_context(|| {
+                format!(
+                    "Error parsing the `[Archive]` table in configuration file `{}`.",
+                    path.display()
+                )
+            })?;
+            let root = archive
+                .root()
+                .map(expand_path)
+                .transpose()
+                .context("Error expanding the `root` of the `[Archive]` table.")?;
+            conf.archive = archive.with_root(root);
+        }
+
+        // parse the inbox settings, if any
+        if let Some(inbox) = config_toml.get("Inbox") {
+            conf.inbox = inbox.clone().try_into().with_context(|| {
+                format!(
+                    "Error parsing the `[Inbox]` table in configuration file `{}`.",
+                    path.display()
+                )
+            })?;
+        }
+
+        // parse the display settings, if any
+        if let Some(display) = config_toml.get("Display") {
+            conf.display = display.clone().try_into().with_context(|| {
+                format!(
+                    "Error parsing the `[Display]` table in configuration file `{}`.",
+                    path.display()
+                )
+            })?;
+        }
+
+        // parse the institutions, if any
+        if let Some(institutions) = config_toml.get("Institutions") {
+            conf.institutions = institutions.clone().try_into().with_context(|| {
+                format!(
+                    "Error parsing the `[Institutions]` table in configuration file `{}`.",
+                    path.display()
+                )
+            })?;
+        }
+
         // parse accounts
         match config_toml.get("Accounts") {
             Some(Value::Table(table)) => {
-                conf.parse_accounts(table)?;
-                conf.refresh_account_statements()?;
+                conf.parse_accounts(table, new_account, lenient)?;
             },
-            Some(_) => bail!("Error parsing the `[Accounts]` table in configuration file `{}`.", value.config().display()),
+            Some(_) => bail!("Error parsing the `[Accounts]` table in configuration file `{}`.", path.display()),
             None => bail!(
                 "No `[Accounts]` table found in configuration file `{}`.\nPlease check the configuration and try again.",
-                value.config().display(),
+                path.display(),
             )
         }
 
+        info!(accounts = conf.len(), "parsed configuration");
+
         Ok(conf)
     }
 }
+
+/// The configuration is written with the same tables as the configuration file,
+/// so it can be read back in, along with the statements found for each account.
+impl Serialize for Config {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // named schedules are written with the accounts they belong to
+        let accounts: BTreeMap<&str, &Account> = self
+            .accounts
+            .iter()
+            .filter(|(_, acct)| acct.schedule().is_none())
+            .map(|(key, acct)| (key.as_str(), acct))
+            .collect();
+
+        let mut map = serializer.serialize_map(Some(12))?;
+        map.serialize_entry("Institutions", &self.institutions)?;
+        map.serialize_entry("Accounts", &accounts)?;
+        map.serialize_entry("Digest", &self.digest)?;
+        map.serialize_entry("Daemon", &self.daemon)?;
+        map.serialize_entry("Notifications", &self.notifications)?;
+        map.serialize_entry("Hooks", &self.hooks)?;
+        map.serialize_entry("Theme", &self.theme)?;
+        map.serialize_entry("Tui", &self.tui)?;
+        map.serialize_entry("Archive", &self.archive)?;
+        map.serialize_entry("Inbox", &self.inbox)?;
+        map.serialize_entry("Display", &self.display)?;
+        map.serialize_entry("Statements", &self.acct_stmts)?;
+
+        map.end()
+    }
+}
+
+impl Config {
+    /// Load a configuration file and scan for statements.
+    ///
+    /// If `lenient`, accounts that can't be created are skipped instead of
+    /// stopping at the first one, and are listed by `skipped_accounts`.
+    pub fn load(path: &Path, lenient: bool) -> anyhow::Result<Self> {
+        let mut conf = Self::read(path, lenient)?;
+        conf.refresh_account_statements()?;
+
+        Ok(conf)
+    }
+
+    /// Load a configuration file and scan for statements, skipping any accounts
+    /// that can't be created instead of stopping at the first one.
+    /// This is the same as `load` with `lenient` set.
+    pub fn try_from_lenient(path: &Path) -> anyhow::Result<Self> {
+        Self::load(path, true)
+    }
+
+    /// Load a configuration file without scanning for statements.
+    /// They can be found later with `refresh_account_statements` or a `Scanner`.
+    pub fn read(path: &Path, lenient: bool) -> anyhow::Result<Self> {
+        let config_str = read_config_file(path)?;
+
+        Self::parse(path, &config_str, |props| Account::try_from(props), lenient)
+    }
+
+    /// Load several configuration files as one and scan for statements.
+    /// See `read_all` for how they're merged.
+    pub fn load_all(paths: &[PathBuf], lenient: bool) -> anyhow::Result<Self> {
+        let mut conf = Self::read_all(paths, lenient)?;
+        conf.refresh_account_statements()?;
+
+        Ok(conf)
+    }
+
+    /// Load several configuration files as one, without scanning for statements.
+    /// The settings, like the `[Theme]`, come from the first file, and the accounts
+    /// and institutions of every file are merged, with each account remembering the
+    /// file it came from.
+    /// Account keys must be unique across every file.
+    pub fn read_all(paths: &[PathBuf], lenient: bool) -> anyhow::Result<Self> {
+        let (first, rest) = match paths.split_first() {
+            Some(split) => split,
+            None => bail!("No configuration files were given."),
+        };
+        let mut conf = Self::read(first, lenient)?;
+        if rest.is_empty() {
+            return Ok(conf);
+        }
+
+        let names = source_names(paths);
+        conf.sources = conf
+            .keys()
+            .iter()
+            .map(|key| (key.clone(), names[0].clone()))
+            .collect();
+        for (path, name) in rest.iter().zip(&names[1..]) {
+            let other = Self::read(path, lenient)?;
+            conf.merge(other, name)?;
+        }
+
+        Ok(conf)
+    }
+
+    /// Merge the accounts and institutions of another configuration into this one
+    fn merge(&mut self, other: Config, name: &str) -> anyhow::Result<()> {
+        for (key, inst) in other.institutions {
+            self.institutions.entry(key).or_insert(inst);
+        }
+
+        for (key, acct) in other.accounts {
+            match self.account_order.binary_search(&key) {
+                Ok(_) => bail!(
+                    "Account key `{}` in configuration file `{}` is also in `{}`. Please rename one of them, since keys must be unique across every configuration file.",
+                    &key,
+                    other.path.display(),
+                    self.source(&key).unwrap_or_default(),
+                ),
+                Err(pos) => self.account_order.insert(pos, key.clone()),
+            }
+            self.sources.insert(key.clone(), name.to_string());
+            self.accounts.insert(key, acct);
+            self.num_accounts += 1;
+        }
+        self.skipped.extend(other.skipped);
+        self.merged.push(other.path);
+
+        Ok(())
+    }
+}
+
+/// Name each configuration file by its file name, without the extension.
+/// If any of those are the same, like for `config.toml` files in different
+/// directories, the whole paths are used instead.
+fn source_names(paths: &[PathBuf]) -> Vec<String> {
+    let stems: Vec<String> = paths
+        .iter()
+        .map(|path| {
+            path.file_stem().map_or_else(
+                || path.display().to_string(),
+                |stem| stem.to_string_lossy().into_owned(),
+            )
+        })
+        .collect();
+
+    let unique = stems
+        .iter()
+        .enumerate()
+        .all(|(i, stem)| !stems[..i].contains(stem));
+    match unique {
+        true => stems,
+        false => paths
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect(),
+    }
+}
+
+/// Read the contents of a configuration file
+fn read_config_file(path: &Path) -> anyhow::Result<String> {
+    if !path.exists() {
+        bail!("Configuration file `{}` does not exist.", path.display());
+    }
+
+    parse_toml_file(path).with_context(|| {
+        format!(
+            "Error reading contents of configuration file `{}`.\nPlease check the configuration and try again.",
+            path.display()
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// An account with its statements in `dir`, using `period` as its statement period
+    fn account(key: &str, dir: &Path, period: &str) -> String {
+        format!(
+            r#"
+[Accounts.{key}]
+name = "{key}"
+institution = "Bank"
+statement_fmt = "%Y-%m-%d.pdf"
+dir = '{dir}'
+first_date = 2021-01-01
+statement_period = {period}
+"#,
+            key = key,
+            dir = dir.display(),
+            period = period,
+        )
+    }
+
+    /// Write a configuration file with the given contents to `dir`
+    fn write_config(dir: &TempDir, name: &str, contents: &str) -> PathBuf {
+        let path = dir.path().join(name);
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    const MONTHLY: &str = r#"[1, "Day", 1, "Month"]"#;
+
+    #[test]
+    fn lenient_skips_broken_accounts() {
+        let dir = tempfile::tempdir().unwrap();
+        let contents = [
+            account("Chequing", dir.path(), MONTHLY),
+            account("Savings", dir.path(), r#"[1, "Fortnight"]"#),
+            account("Visa", &dir.path().join("missing"), MONTHLY),
+        ]
+        .concat();
+        let path = write_config(&dir, "config.toml", &contents);
+
+        assert!(Config::read(&path, false).is_err());
+
+        let conf = Config::read(&path, true).unwrap();
+        assert_eq!(conf.keys(), [AccountKey::new("Chequing")]);
+        let skipped: Vec<&str> = conf
+            .skipped_accounts()
+            .iter()
+            .map(|(key, _)| key.as_str())
+            .collect();
+        assert_eq!(skipped, ["Savings", "Visa"]);
+    }
+
+    #[test]
+    fn merge_takes_settings_from_the_first_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let mine = write_config(
+            &dir,
+            "mine.toml",
+            &format!(
+                "[Daemon]\ninterval = 600\n{}",
+                account("Chequing", dir.path(), MONTHLY)
+            ),
+        );
+        let theirs = write_config(
+            &dir,
+            "theirs.toml",
+            &format!(
+                "[Daemon]\ninterval = 60\n{}",
+                account("Visa", dir.path(), MONTHLY)
+            ),
+        );
+
+        let conf = Config::read_all(&[mine.clone(), theirs.clone()], false).unwrap();
+        assert_eq!(conf.daemon().interval().as_secs(), 600);
+        assert_eq!(conf.path(), mine);
+
+        let conf = Config::read_all(&[theirs, mine], false).unwrap();
+        assert_eq!(conf.daemon().interval().as_secs(), 60);
+    }
+
+    #[test]
+    fn merge_keeps_the_first_institution_of_each_key() {
+        let dir = tempfile::tempdir().unwrap();
+        // both files have a `Bank`, and an institution of their own
+        let institutions = |owner: &str| {
+            format!(
+                "[Institutions.Bank]\nwebsite = \"{owner}\"\n\n[Institutions.{owner}]\nwebsite = \"{owner}\"\n",
+                owner = owner
+            )
+        };
+        let mine = write_config(
+            &dir,
+            "mine.toml",
+            &[
+                institutions("mine"),
+                account("Chequing", dir.path(), MONTHLY),
+            ]
+            .concat(),
+        );
+        let theirs = write_config(
+            &dir,
+            "theirs.toml",
+            &[institutions("theirs"), account("Visa", dir.path(), MONTHLY)].concat(),
+        );
+
+        let conf = Config::read_all(&[mine, theirs], false).unwrap();
+        let website = |key: &str| {
+            let acct = &conf.accounts()[key];
+            conf.institution(acct).and_then(Institution::website)
+        };
+        // accounts from either file use the first file's institution with their key
+        assert_eq!(website("Chequing"), Some("mine"));
+        assert_eq!(website("Visa"), Some("mine"));
+        // institutions with other keys are added
+        assert_eq!(conf.institutions.len(), 3);
+        assert!(conf.institutions.contains_key("theirs"));
+    }
+
+    #[test]
+    fn merge_tags_accounts_with_their_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let mine = write_config(
+            &dir,
+            "mine.toml",
+            &[
+                account("Savings", dir.path(), MONTHLY),
+                account("Chequing", dir.path(), MONTHLY),
+            ]
+            .concat(),
+        );
+        let theirs = write_config(&dir, "theirs.toml", &account("Visa", dir.path(), MONTHLY));
+
+        let conf = Config::read_all(&[mine.clone(), theirs.clone()], false).unwrap();
+        assert!(conf.is_merged());
+        assert_eq!(conf.paths().collect::<Vec<_>>(), [mine, theirs]);
+        assert_eq!(
+            conf.keys(),
+            ["Chequing", "Savings", "Visa"].map(AccountKey::new)
+        );
+        assert_eq!(conf.source("Chequing"), Some("mine"));
+        assert_eq!(conf.source("Savings"), Some("mine"));
+        assert_eq!(conf.source("Visa"), Some("theirs"));
+    }
+
+    #[test]
+    fn merge_single_file_has_no_sources() {
+        let dir = tempfile::tempdir().unwrap();
+        let mine = write_config(&dir, "mine.toml", &account("Chequing", dir.path(), MONTHLY));
+
+        let conf = Config::read_all(&[mine], false).unwrap();
+        assert!(!conf.is_merged());
+        assert_eq!(conf.source("Chequing"), None);
+    }
+
+    #[test]
+    fn merge_rejects_duplicate_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let mine = write_config(&dir, "mine.toml", &account("Chequing", dir.path(), MONTHLY));
+        let theirs = write_config(
+            &dir,
+            "theirs.toml",
+            &account("Chequing", dir.path(), MONTHLY),
+        );
+
+        let e = Config::read_all(&[mine, theirs], false).unwrap_err();
+        let msg = e.to_string();
+        assert!(msg.contains("`Chequing`"));
+        assert!(msg.contains("theirs.toml"));
+        assert!(msg.contains("`mine`"));
+    }
+
+    #[test]
+    fn merge_skips_broken_accounts_of_every_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let broken = dir.path().join("missing");
+        let mine = write_config(
+            &dir,
+            "mine.toml",
+            &[
+                account("Chequing", dir.path(), MONTHLY),
+                account("Savings", &broken, MONTHLY),
+            ]
+            .concat(),
+        );
+        let theirs = write_config(&dir, "theirs.toml", &account("Visa", &broken, MONTHLY));
+
+        assert!(Config::read_all(&[mine.clone(), theirs.clone()], false).is_err());
+
+        let conf = Config::read_all(&[mine, theirs], true).unwrap();
+        assert_eq!(conf.keys(), [AccountKey::new("Chequing")]);
+        let skipped: Vec<&str> = conf
+            .skipped_accounts()
+            .iter()
+            .map(|(key, _)| key.as_str())
+            .collect();
+        assert_eq!(skipped, ["Savings", "Visa"]);
+    }
+
+    #[test]
+    fn source_names_are_unique() {
+        let names = |paths: &[&str]| {
+            let paths: Vec<PathBuf> = paths.iter().map(PathBuf::from).collect();
+            source_names(&paths)
+        };
+
+        assert_eq!(names(&["a/mine.toml", "b/theirs.toml"]), ["mine", "theirs"]);
+        assert_eq!(
+            names(&["a/config.toml", "b/config.toml"]),
+            ["a/config.toml", "b/config.toml"]
+        );
+    }
+}
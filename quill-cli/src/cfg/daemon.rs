@@ -0,0 +1,55 @@
+//! Settings for running quill in the background.
+
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::time::Duration;
+
+/// How often the daemon scans for statements, in seconds, if not configured
+const DEFAULT_INTERVAL: u64 = 15 * 60;
+
+/// Settings from the `[Daemon]` table of the configuration file
+#[derive(Debug, Deserialize, Serialize)]
+pub struct DaemonConfig {
+    /// How often to scan for statements, in seconds
+    #[serde(default = "default_interval", deserialize_with = "parse_interval")]
+    interval: u64,
+
+    /// Port on localhost to serve the statements as JSON on, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    api_port: Option<u16>,
+}
+
+impl DaemonConfig {
+    /// How long the daemon waits between scans
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval)
+    }
+
+    /// Port on localhost that the API is served on, if it's enabled
+    pub fn api_port(&self) -> Option<u16> {
+        self.api_port
+    }
+}
+
+impl Default for DaemonConfig {
+    fn default() -> Self {
+        Self {
+            interval: DEFAULT_INTERVAL,
+            api_port: None,
+        }
+    }
+}
+
+fn default_interval() -> u64 {
+    DEFAULT_INTERVAL
+}
+
+/// Parse an interval, which must be at least 1 s
+fn parse_interval<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match u64::deserialize(deserializer)? {
+        0 => Err(de::Error::custom("`interval` must be at least 1 second")),
+        secs => Ok(secs),
+    }
+}
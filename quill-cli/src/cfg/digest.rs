@@ -0,0 +1,29 @@
+//! Settings for the periodic digest of statement changes.
+
+use chrono::Weekday;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Settings from the `[Digest]` table of the configuration file
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct DigestConfig {
+    /// Only produce a digest on this day of the week
+    #[serde(skip_serializing_if = "Option::is_none")]
+    day: Option<Weekday>,
+
+    /// File to append the digest to, instead of printing it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output: Option<PathBuf>,
+}
+
+impl DigestConfig {
+    /// The day of the week the digest is produced on, if restricted to one
+    pub fn day(&self) -> Option<Weekday> {
+        self.day
+    }
+
+    /// The file the digest is appended to, if not printed
+    pub fn output(&self) -> Option<&Path> {
+        self.output.as_deref()
+    }
+}
@@ -0,0 +1,103 @@
+//! Settings for how dates are shown in the TUI and exports.
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{Locale, NaiveDate};
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+/// How dates are shown without a locale, which is also how they're parsed
+const DEFAULT_DATE_FMT: &str = "%Y-%m-%d";
+
+/// Settings from the `[Display]` table of the configuration file
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct DisplayConfig {
+    /// Locale that dates are shown in, like `fr_FR`, which decides the order they're
+    /// written in and the names of months and days
+    #[serde(
+        default,
+        deserialize_with = "parse_locale",
+        skip_serializing_if = "Option::is_none"
+    )]
+    locale: Option<String>,
+    /// How dates are written, like `%d %b %Y` for `01 Jan 2023`, instead of the usual
+    /// format for the locale
+    #[serde(
+        default,
+        deserialize_with = "parse_date_fmt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    display_date_fmt: Option<String>,
+    /// Show how long ago or until dates are next to them, like "due in 4 days"
+    #[serde(default)]
+    relative_dates: bool,
+}
+
+impl DisplayConfig {
+    /// Locale that dates are shown in, if one is given
+    pub fn locale(&self) -> Option<Locale> {
+        // it was checked when it was parsed
+        self.locale.as_deref().and_then(|l| l.parse().ok())
+    }
+
+    /// Whether to show how long ago or until dates are next to them
+    pub fn relative_dates(&self) -> bool {
+        self.relative_dates
+    }
+
+    /// Show a date with `display_date_fmt` if it's given, otherwise the way it's usually
+    /// written in the locale, or as `YYYY-MM-DD` without one
+    pub fn date(&self, date: &NaiveDate) -> String {
+        match (&self.display_date_fmt, self.locale()) {
+            (Some(fmt), _) => self.format(date, fmt),
+            (None, Some(_)) => self.format(date, "%x"),
+            (None, None) => self.format(date, DEFAULT_DATE_FMT),
+        }
+    }
+
+    /// The most characters a date can take up when it's shown, to leave enough room for it
+    pub fn date_width(&self) -> usize {
+        // the 28th of each month in 2000 covers every month and every day of the week
+        (1..=12)
+            .filter_map(|month| NaiveDate::from_ymd_opt(2000, month, 28))
+            .map(|date| self.date(&date).chars().count())
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Format a date with the names of months and days in the locale, if one is given
+    pub fn format(&self, date: &NaiveDate, fmt: &str) -> String {
+        match self.locale() {
+            Some(locale) => date.format_localized(fmt, locale).to_string(),
+            None => date.format(fmt).to_string(),
+        }
+    }
+}
+
+/// Parse a locale like `fr_FR`, also accepting `fr-FR`
+fn parse_locale<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let locale = String::deserialize(deserializer)?.replace('-', "_");
+    match locale.parse::<Locale>() {
+        Ok(_) => Ok(Some(locale)),
+        Err(_) => Err(de::Error::custom(format!(
+            "`{}` is not a known locale, like `en_US` or `fr_FR`",
+            locale
+        ))),
+    }
+}
+
+/// Parse a date format like `%d %b %Y`, checking that chrono understands it
+fn parse_date_fmt<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let fmt = String::deserialize(deserializer)?;
+    match StrftimeItems::new(&fmt).any(|item| item == Item::Error) {
+        true => Err(de::Error::custom(format!(
+            "`{}` is not a valid date format, like `%Y-%m-%d` or `%d %b %Y`",
+            fmt
+        ))),
+        false => Ok(Some(fmt)),
+    }
+}
@@ -0,0 +1,73 @@
+//! Settings for the hooks run when statements change.
+
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::time::Duration;
+
+/// How long a hook can take, in seconds, if not configured
+const DEFAULT_TIMEOUT: u64 = 30;
+
+/// Settings from the `[Hooks]` table of the configuration file.
+///
+/// Each hook is either a URL, which is sent a JSON payload in a `POST` request,
+/// or a command, which is run by the shell with the JSON payload on its stdin.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct HooksConfig {
+    /// Hooks to run when statements go missing
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    missing: Vec<String>,
+
+    /// Hooks to run when statements become available
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    available: Vec<String>,
+
+    /// How long each hook can take, in seconds, before it's given up on
+    #[serde(default = "default_timeout", deserialize_with = "parse_timeout")]
+    timeout: u64,
+}
+
+impl HooksConfig {
+    /// Whether there are any hooks to run
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.available.is_empty()
+    }
+
+    /// The hooks to run when statements go missing
+    pub fn missing(&self) -> &[String] {
+        &self.missing
+    }
+
+    /// The hooks to run when statements become available
+    pub fn available(&self) -> &[String] {
+        &self.available
+    }
+
+    /// How long each hook can take before it's given up on, and its command killed
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout)
+    }
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            missing: vec![],
+            available: vec![],
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+fn default_timeout() -> u64 {
+    DEFAULT_TIMEOUT
+}
+
+/// Parse a timeout, which must be at least 1 s
+fn parse_timeout<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match u64::deserialize(deserializer)? {
+        0 => Err(de::Error::custom("`timeout` must be at least 1 second")),
+        secs => Ok(secs),
+    }
+}
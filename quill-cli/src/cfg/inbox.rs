@@ -0,0 +1,55 @@
+//! Settings for sorting downloaded statements into the accounts' directories.
+
+use quill_utils::expand_path;
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Directory that downloaded statements are sorted from, if not configured
+const DEFAULT_DIR: &str = "~/Downloads";
+
+/// Settings from the `[Inbox]` table of the configuration file
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct InboxConfig {
+    /// Directory that downloaded statements are sorted from
+    #[serde(
+        default,
+        deserialize_with = "parse_dir",
+        skip_serializing_if = "Option::is_none"
+    )]
+    dir: Option<PathBuf>,
+
+    /// Formats of the names institutions download each account's statements with,
+    /// besides its `statement_fmt`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    patterns: BTreeMap<String, Vec<String>>,
+}
+
+impl InboxConfig {
+    /// Directory that downloaded statements are sorted from, which is `~/Downloads`
+    /// unless another is given. It's `None` if there's no home directory to find it in.
+    pub fn dir(&self) -> Option<PathBuf> {
+        self.dir.clone().or_else(|| expand_path(DEFAULT_DIR).ok())
+    }
+
+    /// Formats of the names an account's statements are downloaded with, besides its `statement_fmt`
+    pub fn patterns(&self, key: &str) -> &[String] {
+        self.patterns.get(key).map_or(&[], Vec::as_slice)
+    }
+
+    /// Keys of the accounts that are given patterns
+    pub fn pattern_keys(&self) -> impl Iterator<Item = &str> {
+        self.patterns.keys().map(String::as_str)
+    }
+}
+
+/// Parse the inbox directory, expanding `~` and environment variables in it
+fn parse_dir<'de, D>(deserializer: D) -> Result<Option<PathBuf>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let dir = PathBuf::deserialize(deserializer)?;
+    expand_path(dir)
+        .map(Some)
+        .map_err(|e| de::Error::custom(format!("couldn't expand `dir`: {}", e)))
+}
@@ -0,0 +1,51 @@
+//! Institutions that issue the accounts' statements.
+
+use serde::{Deserialize, Serialize};
+
+/// An institution from the `[Institutions]` table of the configuration file.
+/// Accounts refer to it by its key, or its name, as their `institution`.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Institution {
+    /// Full name of the institution
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+
+    /// Address of the institution's website
+    #[serde(skip_serializing_if = "Option::is_none")]
+    website: Option<String>,
+
+    /// Phone number for the institution's customer support
+    #[serde(skip_serializing_if = "Option::is_none")]
+    support_phone: Option<String>,
+
+    /// Address of the page where statements are downloaded from
+    #[serde(alias = "url", skip_serializing_if = "Option::is_none")]
+    portal: Option<String>,
+}
+
+impl Institution {
+    /// Full name of the institution, if it's given
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Address of the institution's website, if it's given
+    pub fn website(&self) -> Option<&str> {
+        self.website.as_deref()
+    }
+
+    /// Phone number for the institution's customer support, if it's given
+    pub fn support_phone(&self) -> Option<&str> {
+        self.support_phone.as_deref()
+    }
+
+    /// Address of the page where statements are downloaded from, if it's given
+    pub fn portal(&self) -> Option<&str> {
+        self.portal.as_deref()
+    }
+
+    /// Address to go to for new statements: the statement portal, or else the website
+    pub fn download_page(&self) -> Option<&str> {
+        self.portal().or_else(|| self.website())
+    }
+}
@@ -1,6 +1,27 @@
 //! Configuration management for the accounts and global settings.
 
+pub mod archive;
 pub mod config;
+pub mod daemon;
+pub mod digest;
+pub mod display;
+pub mod hooks;
+pub mod inbox;
+pub mod institution;
+pub mod notifications;
+pub mod theme;
+pub mod tui;
 pub mod utils;
 
+pub use self::archive::ArchiveConfig;
 pub use self::config::Config;
+pub use self::daemon::DaemonConfig;
+pub use self::digest::DigestConfig;
+pub use self::display::DisplayConfig;
+pub use self::hooks::HooksConfig;
+pub use self::inbox::InboxConfig;
+pub use self::institution::Institution;
+pub use self::notifications::NotificationsConfig;
+pub use self::theme::Theme;
+pub use self::tui::TuiConfig;
+pub use self::utils::{ScanProgress, Scanner};
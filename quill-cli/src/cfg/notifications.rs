@@ -0,0 +1,18 @@
+//! Settings for desktop notifications.
+
+use serde::{Deserialize, Serialize};
+
+/// Settings from the `[Notifications]` table of the configuration file
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct NotificationsConfig {
+    /// Raise a desktop notification when statements go missing
+    #[serde(default)]
+    enabled: bool,
+}
+
+impl NotificationsConfig {
+    /// Whether desktop notifications have been turned on
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+}
@@ -0,0 +1,114 @@
+//! Colours used to draw the TUI.
+
+use ratatui::style::Color;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::str::FromStr;
+
+/// Colours from the `[Theme]` table of the configuration file.
+///
+/// Colours can be given by name (e.g. `"light-blue"`), as a hex code (e.g. `"#ff8c00"`),
+/// or as an index into the terminal's 256 colour palette (e.g. `"208"`).
+/// Any colour that isn't given keeps its default, as does `overdue_days`.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default)]
+pub struct Theme {
+    /// Selected items and headings
+    #[serde(deserialize_with = "parse_colour", serialize_with = "write_colour")]
+    primary: Color,
+
+    /// Behind everything else
+    #[serde(deserialize_with = "parse_colour", serialize_with = "write_colour")]
+    background: Color,
+
+    /// Less important text, such as ignored statements
+    #[serde(deserialize_with = "parse_colour", serialize_with = "write_colour")]
+    foreground_dimmed: Color,
+
+    /// Missing statements
+    #[serde(deserialize_with = "parse_colour", serialize_with = "write_colour")]
+    error: Color,
+
+    /// Statements that don't match any expected date
+    #[serde(deserialize_with = "parse_colour", serialize_with = "write_colour")]
+    warning: Color,
+
+    /// Missing statements that are more than `overdue_days` past their expected date
+    #[serde(deserialize_with = "parse_colour", serialize_with = "write_colour")]
+    overdue: Color,
+
+    /// How many days past its expected date a missing statement is drawn in the `overdue` colour
+    overdue_days: u32,
+}
+
+impl Theme {
+    /// Colour of selected items and headings
+    pub fn primary(&self) -> Color {
+        self.primary
+    }
+
+    /// Colour behind everything else
+    pub fn background(&self) -> Color {
+        self.background
+    }
+
+    /// Colour of less important text
+    pub fn foreground_dimmed(&self) -> Color {
+        self.foreground_dimmed
+    }
+
+    /// Colour of missing statements
+    pub fn error(&self) -> Color {
+        self.error
+    }
+
+    /// Colour of statements that don't match any expected date
+    pub fn warning(&self) -> Color {
+        self.warning
+    }
+
+    /// Colour of missing statements that are long overdue
+    pub fn overdue(&self) -> Color {
+        self.overdue
+    }
+
+    /// Days past its expected date that a missing statement is long overdue
+    pub fn overdue_days(&self) -> u32 {
+        self.overdue_days
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            primary: Color::Rgb(255, 140, 0),
+            background: Color::Black,
+            foreground_dimmed: Color::DarkGray,
+            error: Color::Red,
+            warning: Color::Yellow,
+            overdue: Color::LightMagenta,
+            overdue_days: 30,
+        }
+    }
+}
+
+/// Parse a colour from its name, hex code, or palette index
+fn parse_colour<'de, D>(deserializer: D) -> Result<Color, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    Color::from_str(&s).map_err(|_| {
+        de::Error::custom(format!(
+            "invalid colour `{}` (use a colour name like `light-blue`, a hex code like `#ff8c00`, or a palette index like `208`)",
+            s
+        ))
+    })
+}
+
+/// Write a colour in a form that `parse_colour` can read back
+fn write_colour<S>(colour: &Color, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.collect_str(colour)
+}
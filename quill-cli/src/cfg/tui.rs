@@ -0,0 +1,82 @@
+//! Settings for the terminal user interface.
+
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::env;
+use std::time::Duration;
+
+/// How often the TUI polls for input and redraws, in milliseconds, if not configured
+const DEFAULT_TICK_RATE: u64 = 200;
+
+/// Settings from the `[Tui]` table of the configuration file
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TuiConfig {
+    /// How often the TUI polls for input and redraws, in milliseconds
+    #[serde(default = "default_tick_rate", deserialize_with = "parse_tick_rate")]
+    tick_rate: u64,
+
+    /// Command that opens statements, for accounts without their own `open_cmd`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    open_cmd: Option<String>,
+
+    /// Whether files are shown instead of opened with the default program.
+    /// If it isn't given, it's detected from whether there's a display to open them on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    headless: Option<bool>,
+}
+
+impl TuiConfig {
+    /// How long the TUI waits for input before redrawing
+    pub fn tick_rate(&self) -> Duration {
+        Duration::from_millis(self.tick_rate)
+    }
+
+    /// Command that opens statements, if one is given
+    pub fn open_cmd(&self) -> Option<&str> {
+        self.open_cmd.as_deref()
+    }
+
+    /// Whether files are shown instead of opened with the default program,
+    /// since there's no display to open them on
+    pub fn headless(&self) -> bool {
+        self.headless.unwrap_or_else(|| !has_display())
+    }
+}
+
+impl Default for TuiConfig {
+    fn default() -> Self {
+        Self {
+            tick_rate: DEFAULT_TICK_RATE,
+            open_cmd: None,
+            headless: None,
+        }
+    }
+}
+
+/// Whether there's a display for graphical programs to open on.
+/// Over SSH, there's only one if X11 is forwarded.
+/// Otherwise, macOS and Windows always have one, and other systems need an X11 or Wayland display.
+fn has_display() -> bool {
+    let is_set = |var| env::var_os(var).map_or(false, |val| !val.is_empty());
+    let x11_or_wayland = is_set("DISPLAY") || is_set("WAYLAND_DISPLAY");
+
+    if is_set("SSH_CONNECTION") || is_set("SSH_TTY") {
+        x11_or_wayland
+    } else {
+        cfg!(any(target_os = "macos", windows)) || x11_or_wayland
+    }
+}
+
+fn default_tick_rate() -> u64 {
+    DEFAULT_TICK_RATE
+}
+
+/// Parse a tick rate, which must be at least 1 ms
+fn parse_tick_rate<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match u64::deserialize(deserializer)? {
+        0 => Err(de::Error::custom("`tick_rate` must be at least 1 ms")),
+        ms => Ok(ms),
+    }
+}
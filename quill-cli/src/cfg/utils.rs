@@ -1,76 +1,179 @@
 //! Utilities to load, parse, and manage the configuration.
 
+use crate::cache::ScanCache;
 use crate::cfg::Config;
-use clap::crate_name;
-use dirs_next::{config_dir, home_dir};
-use quill_statement::StatementCollection;
-use std::path::PathBuf;
-
-pub(crate) fn get_config_dir() -> Option<PathBuf> {
-    // get config from within $XDG_CONFIG_HOME
-    match config_dir() {
-        Some(mut dir) => {
-            dir.push(crate_name!().to_lowercase());
-
-            Some(dir)
-        },
-        // if not set, make it the default $HOME/.config
-        None => {
-            if let Some(mut dir) = home_dir() {
-                dir.push(".config");
-                dir.push(crate_name!().to_lowercase());
-
-                Some(dir)
-            } else {
-                None
-            }
+use crate::{history, signals};
+use anyhow::bail;
+use quill_account::{checksums::Checksums, Account};
+use quill_statement::{AccountKey, Statement, StatementCollection};
+use std::collections::HashMap;
+use std::path::Path;
+use tracing::{debug, debug_span, info_span, warn};
+
+/// Number of upcoming statements to find for each account
+const UPCOMING_STATEMENTS: usize = 12;
+
+/// How far a scan for statements has got
+#[derive(Clone, Debug)]
+pub struct ScanProgress {
+    key: AccountKey,
+    scanned: usize,
+    total: usize,
+}
+
+impl ScanProgress {
+    /// The key of the account being scanned
+    pub fn key(&self) -> &AccountKey {
+        &self.key
+    }
+
+    /// The number of accounts that have already been scanned
+    pub fn scanned(&self) -> usize {
+        self.scanned
+    }
+
+    /// The number of accounts to scan
+    pub fn total(&self) -> usize {
+        self.total
+    }
+}
+
+/// Scans for the statements of a configuration's accounts on any thread.
+///
+/// The scanner keeps its own copy of the accounts that were loaded, so the statements it
+/// finds belong to them even if the configuration files change in the meantime.
+/// This lets slow directories, like remote ones, be scanned in the background.
+#[derive(Clone, Debug)]
+pub struct Scanner {
+    accounts: HashMap<AccountKey, Account>,
+}
+
+impl Scanner {
+    /// Scan the statements of these accounts
+    pub fn new(accounts: &HashMap<AccountKey, Account>) -> Self {
+        Self {
+            accounts: accounts.clone(),
         }
     }
+
+    /// Scan for the statements of the accounts,
+    /// calling `on_progress` before each account is scanned
+    pub fn scan(
+        &self,
+        mut on_progress: impl FnMut(&ScanProgress),
+    ) -> anyhow::Result<StatementCollection> {
+        scan_statements(&self.accounts, &mut on_progress)
+    }
 }
 
-/// Check multiple locations for a configuration file and return the highest priority one
-pub fn get_config_path() -> PathBuf {
-    let mut cfg_path = get_config_dir().unwrap();
-    
-    cfg_path.push("config.toml");
-    match cfg_path.exists() {
-        true => cfg_path,
-        false => PathBuf::from("config.toml"),
+/// Find the statements for every account, using the cache for any
+/// directories that haven't changed since the last scan.
+/// `on_progress` is called before each account is scanned.
+fn scan_statements(
+    accounts: &HashMap<AccountKey, Account>,
+    on_progress: &mut dyn FnMut(&ScanProgress),
+) -> anyhow::Result<StatementCollection> {
+    let _span = info_span!("scan").entered();
+    let mut sc = StatementCollection::new();
+    let mut cache = ScanCache::load();
+
+    for (scanned, (key, acct)) in accounts.iter().enumerate() {
+        let _span = debug_span!("account", key = %key).entered();
+        on_progress(&ScanProgress {
+            key: key.clone(),
+            scanned,
+            total: accounts.len(),
+        });
+
+        // stop scanning early if the user wants to quit,
+        // but keep what's been scanned so far, so the next scan doesn't start over
+        if signals::shutdown_requested() {
+            save_cache(&cache);
+            bail!("Scanning account statements was interrupted.");
+        }
+
+        // paused accounts aren't scanned, so they have no statements to show
+        if acct.is_paused() {
+            debug!("account is paused, skipping");
+            sc.insert(key.as_str(), vec![]);
+            continue;
+        }
+
+        // generate the vec of required statement dates and statement files
+        // (if the statement is available for a given date)
+        let downloaded = cache.downloaded_statements(acct);
+        debug!(downloaded = downloaded.len(), "found downloaded statements");
+        if acct.checksums() && !acct.is_remote() {
+            record_checksums(acct, &downloaded);
+        }
+        sc.insert(key.as_str(), acct.match_downloaded(&downloaded));
+        sc.insert_upcoming(key.as_str(), acct.upcoming_statements(UPCOMING_STATEMENTS));
+    }
+
+    save_cache(&cache);
+
+    // the history is only a record, so failing to keep it shouldn't stop anything else,
+    // though statements whose files have disappeared can't be told apart from missing ones without it
+    if let Err(e) = history::record(accounts, &mut sc) {
+        warn!("couldn't record the statement history: {:#}", e);
     }
+
+    Ok(sc)
 }
 
-impl<'a> TryFrom<&Config<'a>> for StatementCollection {
-    type Error = anyhow::Error;
+/// Save the scan cache.
+/// The cache only speeds things up, so failing to save it isn't worth stopping for.
+fn save_cache(cache: &ScanCache) {
+    if let Err(e) = cache.save() {
+        warn!("couldn't save the scan cache: {:#}", e);
+    }
+}
 
-    fn try_from(value: &Config) -> Result<Self, Self::Error> {
-        let mut sc = Self::new();
+/// Record the checksums of any statements downloaded since the last scan.
+/// Checksums only help find modified statements later, so failing to record
+/// them isn't worth stopping for.
+fn record_checksums(acct: &Account, downloaded: &[Statement]) {
+    let dir = acct.directory();
+    let mut sums = match Checksums::load(dir) {
+        Ok(sums) => sums,
+        Err(e) => {
+            warn!("couldn't read the checksums: {:#}", e);
+            return;
+        }
+    };
 
-        for (key, acct) in value.accounts() {
-            // generate the vec of required statement dates and statement files
-            // (if the statement is available for a given date)
-            let matched_stmts = acct.match_statements();
-            sc.insert(key, matched_stmts);
+    // archived statements have their checksums recorded in the archive when they're moved
+    let files: Vec<&Path> = downloaded
+        .iter()
+        .map(|stmt| stmt.path())
+        .filter(|path| path.parent() == Some(dir))
+        .collect();
+    match sums.record_new(&files) {
+        Ok(recorded) if recorded.is_empty() => {}
+        Ok(recorded) => {
+            debug!(recorded = recorded.len(), "recorded new checksums");
+            if let Err(e) = sums.save(dir) {
+                warn!("couldn't save the checksums: {:#}", e);
+            }
         }
+        Err(e) => warn!("couldn't record the checksums: {:#}", e),
+    }
+}
+
+impl TryFrom<&Config> for StatementCollection {
+    type Error = anyhow::Error;
 
-        Ok(sc)
+    fn try_from(value: &Config) -> Result<Self, Self::Error> {
+        scan_statements(value.accounts(), &mut |_| {})
     }
 }
 
-// Need to reimplement this trait for `&mut Config<'a>` since &T and `&mut T` are different types.
+// Need to reimplement this trait for `&mut Config` since &T and `&mut T` are different types.
 // See https://libreddit.net/r/rust/comments/2a721y/a_safe_way_to_reuse_the_same_code_for_immutable/ for details.
-impl<'a> TryFrom<&mut Config<'a>> for StatementCollection {
+impl TryFrom<&mut Config> for StatementCollection {
     type Error = anyhow::Error;
 
     fn try_from(value: &mut Config) -> Result<Self, Self::Error> {
-        let mut sc = Self::new();
-
-        for (key, acct) in value.accounts() {
-            // generate the vec of required statement dates and statement files
-            // (if the statement is available for a given date)
-            let matched_stmts = acct.match_statements();
-            sc.insert(key, matched_stmts);
-        }
-
-        Ok(sc)
+        scan_statements(value.accounts(), &mut |_| {})
     }
 }
@@ -1,12 +1,24 @@
 //! Command line interface configuration.
 
-use clap::Parser;
+use anyhow::{bail, Context};
+use chrono::NaiveDate;
+use clap::{error::ErrorKind, ArgAction, Args, CommandFactory, Parser, Subcommand, ValueEnum};
 use lazy_static::lazy_static;
+use quill::{daemon, Config};
+use quill_utils::{project_dirs, PROFILE_ENV};
+use std::convert::TryFrom;
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
 use std::path::{Path, PathBuf};
-use crate::cfg::utils::get_config_path;
+use std::time::Duration;
 
 lazy_static! {
-    static ref DEFAULT_CFG_PATH: PathBuf = get_config_path();
+    static ref DEFAULT_CFG_PATH: PathBuf = project_dirs().default_config_file();
+    static ref CFG_HELP: String = format!(
+        "Configuration file with accounts and statements info. Give it more than once, or give a directory of configuration files, to view their accounts together. Defaults to the profile's, if one is given, or `{}`.",
+        DEFAULT_CFG_PATH.display()
+    );
 }
 
 #[derive(Debug, Parser)]
@@ -16,15 +28,564 @@ pub(crate) struct CliOpts {
         name = "cfg",
         short,
         long,
-        help = "Configuration file with accounts and statements info.",
-        default_value = (*DEFAULT_CFG_PATH).as_os_str()
+        global = true,
+        help = CFG_HELP.as_str(),
+        action = ArgAction::Append,
+        conflicts_with = "profile"
     )]
-    config: PathBuf,
+    config: Vec<PathBuf>,
+
+    /// Use the configuration file of a named profile, `<NAME>.toml` in the configuration directory.
+    /// Defaults to the profile named by `QUILL_PROFILE`, unless `--cfg` is given.
+    #[clap(long, global = true, value_name = "NAME", value_parser = parse_profile)]
+    profile: Option<String>,
+
+    /// Record the keys pressed and statements found to a file, to attach to a bug report.
+    #[clap(long, value_name = "FILE")]
+    record: Option<PathBuf>,
+
+    /// Replay a recorded session and print the final screen, instead of starting the TUI.
+    #[clap(long, value_name = "FILE", conflicts_with = "record")]
+    replay: Option<PathBuf>,
+
+    /// How often the TUI polls for input and redraws, in milliseconds.
+    /// Overrides `tick_rate` in the `[Tui]` table of the configuration file.
+    #[clap(long, value_name = "MS", value_parser = clap::value_parser!(u64).range(1..))]
+    tick_rate: Option<u64>,
+
+    /// Show the paths of statements and directories instead of opening them, for when there's no display,
+    /// like over SSH. This is detected automatically, unless `headless` is set in the `[Tui]` table.
+    #[clap(long)]
+    headless: bool,
+
+    /// Log what quill is doing. Use `-vv` for more detail, and `-vvv` for everything.
+    #[clap(short, long, global = true, action = ArgAction::Count)]
+    verbose: u8,
+
+    /// Write the log to a file instead of the terminal.
+    /// The TUI only writes a log when this is given.
+    #[clap(long, global = true, value_name = "FILE")]
+    log_file: Option<PathBuf>,
+
+    /// Load every account that can be loaded, and report the ones that can't, instead of stopping.
+    #[clap(long, global = true)]
+    lenient: bool,
+
+    /// Use the statements that `quill daemon` found most recently instead of scanning for them.
+    /// Statements are scanned for as usual if the daemon isn't running.
+    #[clap(long, global = true)]
+    from_daemon: bool,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
 }
 
 impl CliOpts {
-    /// Retrieve the config file path
-    pub fn config(&self) -> &Path {
-        &self.config
+    /// Parse the command line arguments.
+    /// The profile named by `QUILL_PROFILE` is used if no profile or configuration file is given.
+    pub fn parse_args() -> Self {
+        let mut opts = Self::parse();
+        if opts.profile.is_none() && opts.config.is_empty() {
+            opts.profile = env::var(PROFILE_ENV)
+                .ok()
+                .filter(|profile| !profile.is_empty())
+                .map(|profile| match parse_profile(&profile) {
+                    Ok(profile) => profile,
+                    Err(e) => <Self as CommandFactory>::command()
+                        .error(
+                            ErrorKind::InvalidValue,
+                            format!("invalid value '{}' for `{}`: {}", profile, PROFILE_ENV, e),
+                        )
+                        .exit(),
+                });
+        }
+
+        opts
+    }
+
+    /// Retrieve the config file paths: those given with `--cfg`, the profile's, or the default.
+    /// Directories given with `--cfg` are replaced by the configuration files in them.
+    pub fn configs(&self) -> anyhow::Result<Vec<PathBuf>> {
+        if self.config.is_empty() {
+            let path = self
+                .profile_config_file()
+                .unwrap_or_else(|| DEFAULT_CFG_PATH.clone());
+            return Ok(vec![path]);
+        }
+
+        let mut paths = Vec::new();
+        for path in &self.config {
+            match path.is_dir() {
+                true => paths.extend(config_files_in(path)?),
+                false => paths.push(path.clone()),
+            }
+        }
+
+        Ok(paths)
+    }
+
+    /// Retrieve the config file path, for commands that only work with a single configuration file
+    pub fn config(&self) -> anyhow::Result<PathBuf> {
+        let mut paths = self.configs()?;
+        match paths.len() {
+            1 => Ok(paths.remove(0)),
+            n => bail!(
+                "This command works with a single configuration file, but {} were given.",
+                n
+            ),
+        }
+    }
+
+    /// Retrieve the configuration file of the profile in use, if any.
+    /// Without a configuration directory, it's looked for in the current directory.
+    pub fn profile_config_file(&self) -> Option<PathBuf> {
+        self.profile().map(|profile| {
+            project_dirs()
+                .profile_config_file(profile)
+                .unwrap_or_else(|| PathBuf::from(format!("{}.toml", profile)))
+        })
+    }
+
+    /// Retrieve the name of the profile in use, if any
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
+    /// Retrieve the file to record the TUI session to, if any
+    pub fn record(&self) -> Option<&Path> {
+        self.record.as_deref()
+    }
+
+    /// Retrieve the recorded session to replay, if any
+    pub fn replay(&self) -> Option<&Path> {
+        self.replay.as_deref()
+    }
+
+    /// Retrieve how often the TUI polls for input and redraws, if given
+    pub fn tick_rate(&self) -> Option<Duration> {
+        self.tick_rate.map(Duration::from_millis)
+    }
+
+    /// Retrieve whether files are shown instead of opened, whatever the configuration
+    pub fn headless(&self) -> bool {
+        self.headless
+    }
+
+    /// Retrieve how many times the verbose flag was given
+    pub fn verbose(&self) -> u8 {
+        self.verbose
+    }
+
+    /// Retrieve the file to write the log to, if any
+    pub fn log_file(&self) -> Option<&Path> {
+        self.log_file.as_deref()
+    }
+
+    /// Retrieve whether accounts that can't be loaded are skipped
+    pub fn lenient(&self) -> bool {
+        self.lenient
+    }
+
+    /// Retrieve whether to use the statements found by `quill daemon`, if it's running
+    pub fn use_daemon(&self) -> bool {
+        self.from_daemon
+    }
+
+    /// Retrieve the subcommand to run, if any.
+    /// Without a subcommand, the TUI is started.
+    pub fn command(&self) -> Option<&Command> {
+        self.command.as_ref()
+    }
+}
+
+/// Load the configuration file given on the command line and scan for statements.
+/// Accounts that can't be created are skipped if `--lenient` is given, and the statements
+/// found by `quill daemon` are used instead of scanning if `--from-daemon` is given.
+impl TryFrom<&CliOpts> for Config {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &CliOpts) -> anyhow::Result<Self, Self::Error> {
+        if !value.use_daemon() {
+            return Config::load_all(&value.configs()?, value.lenient());
+        }
+
+        let mut conf = Config::read_all(&value.configs()?, value.lenient())?;
+        match daemon::latest_statements(&conf)? {
+            Some(statements) => *conf.mut_statements() = statements,
+            None => {
+                eprintln!("Warning: `quill daemon` hasn't scanned for these accounts recently, so scanning for statements instead.");
+                conf.refresh_account_statements()?;
+            }
+        }
+
+        Ok(conf)
+    }
+}
+
+/// Find the configuration files in a directory, in order of their names.
+/// Hidden files, like `.quillignore.toml`, are skipped.
+fn config_files_in(dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let entries = fs::read_dir(dir)
+        .with_context(|| format!("Error reading configuration directory `{}`.", dir.display()))?;
+
+    let mut paths = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        let hidden = path
+            .file_name()
+            .map_or(true, |name| name.to_string_lossy().starts_with('.'));
+        if path.is_file() && !hidden && path.extension() == Some(OsStr::new("toml")) {
+            paths.push(path);
+        }
+    }
+    if paths.is_empty() {
+        bail!(
+            "No configuration files were found in directory `{}`.",
+            dir.display()
+        );
+    }
+    paths.sort();
+
+    Ok(paths)
+}
+
+/// Check that a profile name can be used as a file name
+fn parse_profile(name: &str) -> Result<String, String> {
+    let valid = |c: char| c.is_alphanumeric() || c == '-' || c == '_';
+    match !name.is_empty() && name.chars().all(valid) {
+        true => Ok(name.to_string()),
+        false => Err("a profile name can only contain letters, numbers, `-`, and `_`".to_string()),
+    }
+}
+
+/// Tasks that can be run without starting the TUI
+#[derive(Debug, Subcommand)]
+pub(crate) enum Command {
+    /// List statements that have gone missing since the last check,
+    /// raising a desktop notification if notifications are enabled.
+    /// Exits with an error if more statements are missing than allowed, for use with cron or systemd timers.
+    Check {
+        /// Key of the account to check, along with its named schedules. Defaults to every account.
+        #[clap(long)]
+        account: Option<String>,
+
+        /// How many missing statements are allowed before exiting with an error.
+        #[clap(long, default_value = "0")]
+        max_missing: usize,
+    },
+
+    /// Show what changed since the last saved snapshot of your statements.
+    Diff {
+        /// Compare against the last snapshot without replacing it.
+        #[clap(long)]
+        no_save: bool,
+    },
+
+    /// Scan for statements regularly in the background, running notifications and hooks for any
+    /// that change, and keep the latest statements for the TUI and `--from-daemon` to use.
+    Daemon {
+        /// How often to scan, in seconds. Overrides `interval` in the `[Daemon]` table of the configuration file.
+        #[clap(long, value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(1..))]
+        interval: Option<u64>,
+
+        /// Serve the statements as JSON on this port of localhost.
+        /// Overrides `api_port` in the `[Daemon]` table of the configuration file.
+        #[clap(long, value_name = "PORT")]
+        api_port: Option<u16>,
+    },
+
+    /// Report statement changes since the last digest, for use with cron.
+    /// Nothing is reported if nothing has changed.
+    Digest {
+        /// Produce the digest even if today isn't the day set in the `[Digest]` configuration.
+        #[clap(long)]
+        force: bool,
+    },
+
+    /// List how statements have changed over time: when each one appeared, went missing,
+    /// was ignored, was renamed, or was removed.
+    History {
+        /// Key of the account to show, along with its named schedules. Defaults to every account.
+        account: Option<String>,
+
+        /// Only show the statement from this date, as `YYYY-MM-DD`.
+        #[clap(long)]
+        date: Option<NaiveDate>,
+    },
+
+    /// Rename statement files to follow an account's `statement_fmt`.
+    Rename {
+        /// Key of the account the statements belong to.
+        account: String,
+
+        /// Format of the current file names, e.g. `eStatement_%Y%m%d.pdf`.
+        #[clap(long)]
+        from: String,
+
+        /// Directory containing the files to rename. Defaults to the account's directory.
+        /// Renamed files are always moved into the account's directory.
+        #[clap(long)]
+        dir: Option<PathBuf>,
+
+        /// Show what would be renamed, without renaming anything.
+        #[clap(long)]
+        dry_run: bool,
+    },
+
+    /// Move downloaded statements into the directories of the accounts they belong to.
+    SortInbox {
+        /// Directory containing the files to sort. Defaults to the `dir` of the `[Inbox]` table.
+        #[clap(long)]
+        dir: Option<PathBuf>,
+
+        /// Show what would be moved, without moving anything.
+        #[clap(long)]
+        dry_run: bool,
+
+        /// Move the files without asking first.
+        #[clap(short, long)]
+        yes: bool,
+    },
+
+    /// Ignore the statements an account expects between two dates, in its `.quillignore.toml`.
+    Ignore {
+        /// Key of the account the statements belong to.
+        account: String,
+
+        /// Date to start ignoring statements from, as `YYYY-MM-DD`.
+        #[clap(long)]
+        from: NaiveDate,
+
+        /// Date to stop ignoring statements at, inclusive, as `YYYY-MM-DD`.
+        #[clap(long)]
+        to: NaiveDate,
+
+        /// Write a single date range, instead of each expected date.
+        #[clap(long)]
+        range: bool,
+
+        /// Why the statements are ignored, shown alongside them in the Log tab.
+        #[clap(long, conflicts_with = "range")]
+        reason: Option<String>,
+
+        /// Show what would be ignored, without changing the ignore file.
+        #[clap(long)]
+        dry_run: bool,
+    },
+
+    /// List the statements expected in the future, soonest first.
+    Upcoming {
+        /// How many days ahead to look. The next statement for each account is always listed.
+        #[clap(long, default_value = "90")]
+        days: u32,
+    },
+
+    /// Check the configuration file and report every problem found.
+    /// Without any problems, each account's statement period is described in words.
+    Validate,
+
+    /// Diagnose problems with the accounts' directories, statement files, and ignored dates.
+    Doctor,
+
+    /// Print the configuration and the statements found for each account as JSON, for use by other tools.
+    Export {
+        /// Format to print. `beancount` prints a `document` directive for each available statement,
+        /// and `csv` prints a row for each statement of each account.
+        #[clap(long, value_enum, default_value = "json")]
+        format: ExportFormat,
+    },
+
+    /// Download statements by running the `fetch_cmd` of each account, then list the new statements.
+    Fetch {
+        /// Key of the account to fetch statements for. Defaults to every account with a `fetch_cmd`.
+        account: Option<String>,
+    },
+
+    /// Check the statements of accounts with `checksums` enabled, and report any
+    /// that have been modified or corrupted since they were downloaded.
+    Verify {
+        /// Key of the account to check. Defaults to every account with `checksums` enabled.
+        account: Option<String>,
+    },
+
+    /// Move available statements into the read-only archive set by the `[Archive]`
+    /// table, under `<root>/<institution>/<account>/<year>/`.
+    /// Archived statements are still found for their accounts.
+    Archive {
+        /// Key of the account to archive statements for. Defaults to every account.
+        account: Option<String>,
+
+        /// Show what would be archived, without moving anything.
+        #[clap(long)]
+        dry_run: bool,
+    },
+
+    /// Interactively create a new configuration file.
+    Init {
+        /// Where to write the configuration file. Defaults to the user's configuration directory.
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+
+        /// Overwrite the configuration file if it already exists.
+        #[clap(long)]
+        force: bool,
+    },
+
+    /// Manage the accounts in the configuration file.
+    Account {
+        #[clap(subcommand)]
+        command: AccountCommand,
+    },
+
+    /// Try out statement periods without changing the configuration file.
+    Period {
+        #[clap(subcommand)]
+        command: PeriodCommand,
+    },
+
+    /// Summarize the statements of the accounts.
+    Report {
+        #[clap(subcommand)]
+        command: ReportCommand,
+    },
+}
+
+/// Formats the configuration and statements can be exported in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub(crate) enum ExportFormat {
+    /// The configuration and every statement, as JSON
+    Json,
+    /// The available statements, as directives for a beancount journal
+    Beancount,
+    /// Every statement, with its account, as comma-separated values
+    Csv,
+}
+
+/// Changes to the accounts in the configuration file
+#[derive(Debug, Subcommand)]
+pub(crate) enum AccountCommand {
+    /// Add a new account to the end of the configuration file, after checking it.
+    Add(AddAccountArgs),
+}
+
+/// Summaries of the accounts' statements
+#[derive(Debug, Subcommand)]
+pub(crate) enum ReportCommand {
+    /// Show how many of the expected statements are available, overall, in each year, and for
+    /// each account, along with how long ago the missing statements were due.
+    Completeness {
+        /// Key of the account to report on, along with its named schedules. Defaults to every account.
+        #[clap(long)]
+        account: Option<String>,
+    },
+}
+
+/// Ways to try out statement periods
+#[derive(Debug, Subcommand)]
+pub(crate) enum PeriodCommand {
+    /// List the dates a statement period or schedule would expect statements on.
+    Preview {
+        /// Statement period, as a TOML array like `[15, "Day", 1, "Month"]`.
+        #[clap(
+            long,
+            required_unless_present = "schedule",
+            conflicts_with = "schedule"
+        )]
+        period: Option<String>,
+
+        /// Statement schedule, like the date fields of a cron expression, e.g. `"LW * *"`.
+        #[clap(long)]
+        schedule: Option<String>,
+
+        /// Date of the first statement, as `YYYY-MM-DD`. Like `first_date`, it's always listed.
+        /// Defaults to today.
+        #[clap(long)]
+        from: Option<NaiveDate>,
+
+        /// How many dates to list.
+        #[clap(long, default_value = "12")]
+        count: usize,
+    },
+}
+
+/// Properties of a new account
+#[derive(Debug, Args)]
+pub(crate) struct AddAccountArgs {
+    /// Unique key for the account. Defaults to the name, with punctuation and spaces replaced by `-`.
+    #[clap(long)]
+    key: Option<String>,
+
+    /// Name of the account.
+    #[clap(long)]
+    name: String,
+
+    /// Name of the institution that issues the statements.
+    #[clap(long)]
+    institution: String,
+
+    /// Directory containing the statements.
+    #[clap(long)]
+    dir: String,
+
+    /// Date of the first statement, as `YYYY-MM-DD`.
+    #[clap(long)]
+    first_date: NaiveDate,
+
+    /// Statement period, as a TOML array like `[15, "Day", 1, "Month"]`.
+    #[clap(
+        long,
+        required_unless_present = "schedule",
+        conflicts_with = "schedule"
+    )]
+    period: Option<String>,
+
+    /// Statement schedule, like the date fields of a cron expression, e.g. `"LW * *"`.
+    #[clap(long)]
+    schedule: Option<String>,
+
+    /// Format of the statement file names.
+    #[clap(long, default_value = "%Y-%m-%d.pdf")]
+    fmt: String,
+}
+
+impl AddAccountArgs {
+    /// Retrieve the account key, if one was given
+    pub fn key(&self) -> Option<&str> {
+        self.key.as_deref()
+    }
+
+    /// Retrieve the account name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Retrieve the institution name
+    pub fn institution(&self) -> &str {
+        &self.institution
+    }
+
+    /// Retrieve the statement directory
+    pub fn dir(&self) -> &str {
+        &self.dir
+    }
+
+    /// Retrieve the date of the first statement
+    pub fn first_date(&self) -> &NaiveDate {
+        &self.first_date
+    }
+
+    /// Retrieve the statement period, if one was given
+    pub fn period(&self) -> Option<&str> {
+        self.period.as_deref()
+    }
+
+    /// Retrieve the statement schedule, if one was given
+    pub fn schedule(&self) -> Option<&str> {
+        self.schedule.as_deref()
+    }
+
+    /// Retrieve the statement file name format
+    pub fn fmt(&self) -> &str {
+        &self.fmt
     }
 }
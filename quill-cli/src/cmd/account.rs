@@ -0,0 +1,129 @@
+//! Manage the accounts in the configuration file.
+
+use crate::cli::AddAccountArgs;
+use anyhow::{bail, Context};
+use quill_account::Account;
+use quill_utils::TomlEditor;
+use std::path::Path;
+use toml::{map::Map, Value};
+use toml_edit::{Item, Table};
+
+/// Properties of an account in the order they're written, like the example configuration
+const ACCOUNT_PROPS: [&str; 7] = [
+    "name",
+    "institution",
+    "statement_fmt",
+    "dir",
+    "statement_period",
+    "statement_schedule",
+    "first_date",
+];
+
+/// Add a new account to the configuration file, after the accounts already in it.
+///
+/// The rest of the file is left exactly as it was, comments included, and nothing
+/// is written unless the new account is valid.
+pub fn add(path: &Path, args: &AddAccountArgs) -> anyhow::Result<()> {
+    let mut editor = TomlEditor::open(path)
+        .with_context(|| format!("Error reading configuration file `{}`.", path.display()))?;
+
+    let key = match args.key() {
+        Some(k) => k.to_string(),
+        None => default_key(args.name()),
+    };
+
+    // the account key must be unique
+    match editor.get(&["Accounts"]) {
+        Some(accounts) if accounts.get(&key).is_some() => bail!(
+            "Account key `{}` already exists in configuration file `{}`. Use `--key` to choose a different one.",
+            key,
+            path.display()
+        ),
+        Some(accounts) if accounts.is_table_like() => {}
+        None => {}
+        Some(_) => bail!(
+            "Error parsing the `[Accounts]` table in configuration file `{}`.",
+            path.display()
+        ),
+    }
+
+    let (period_key, period) = period_property(args.period(), args.schedule())?;
+
+    let mut props = Map::new();
+    props.insert("name".to_string(), Value::String(args.name().to_string()));
+    props.insert(
+        "institution".to_string(),
+        Value::String(args.institution().to_string()),
+    );
+    props.insert(
+        "statement_fmt".to_string(),
+        Value::String(args.fmt().to_string()),
+    );
+    props.insert("dir".to_string(), Value::String(args.dir().to_string()));
+    props.insert(period_key.to_string(), period);
+    props.insert(
+        "first_date".to_string(),
+        Value::Datetime(args.first_date().to_string().parse()?),
+    );
+    let props = Value::Table(props);
+
+    // report every problem at once, like `quill validate`
+    let errors = Account::validate(&props);
+    if !errors.is_empty() {
+        let messages: Vec<String> = errors.iter().map(|e| format!("    {}", e)).collect();
+        bail!("Account `{}` was not added:\n{}", key, messages.join("\n"));
+    }
+
+    editor.insert_table(&["Accounts", &key], account_table(&props)?)?;
+    editor
+        .save()
+        .with_context(|| format!("Error writing configuration file `{}`.", path.display()))?;
+
+    println!("Added account `{}` to `{}`.", key, path.display());
+
+    Ok(())
+}
+
+/// Create an account key from its name
+pub fn default_key(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+/// Parse a statement period given as a TOML array, like `[15, "Day", 1, "Month"]`
+pub fn parse_period(period: &str) -> Option<Value> {
+    match format!("statement_period = {}", period).parse::<Value>() {
+        Ok(Value::Table(mut t)) => t.remove("statement_period"),
+        _ => None,
+    }
+}
+
+/// The property and value for a statement period or schedule given on the command line
+pub fn period_property(
+    period: Option<&str>,
+    schedule: Option<&str>,
+) -> anyhow::Result<(&'static str, Value)> {
+    // the command line only allows one of the two
+    match (period, schedule) {
+        (Some(period), _) => match parse_period(period) {
+            Some(p) => Ok(("statement_period", p)),
+            None => bail!("Statement period `{}` is not a valid TOML array.", period),
+        },
+        (None, Some(schedule)) => Ok(("statement_schedule", Value::String(schedule.to_string()))),
+        (None, None) => bail!("Either a statement period or a statement schedule is required."),
+    }
+}
+
+/// The properties of an account as a table, in the same order as the example configuration
+pub fn account_table(props: &Value) -> anyhow::Result<Table> {
+    let mut table = Table::new();
+    for prop in ACCOUNT_PROPS {
+        if let Some(v) = props.get(prop) {
+            // every value written by `toml` can be read back by `toml_edit`
+            table[prop] = Item::Value(v.to_string().parse()?);
+        }
+    }
+
+    Ok(table)
+}
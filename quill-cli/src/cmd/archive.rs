@@ -0,0 +1,147 @@
+//! Move statements out of the accounts' directories into a read-only archive.
+
+use crate::{cfg::Config, signals};
+use anyhow::{bail, Context};
+use chrono::Datelike;
+use quill_account::{
+    checksums::{hash_file, Checksums},
+    Account,
+};
+use quill_statement::{AccountKey, Statement, StatementStatus};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Move the available statements of a single account, or of every account,
+/// into `<root>/<institution>/<account>/<year>/`, and make them read-only.
+/// Statements whose checksums no longer match are left where they are.
+pub fn archive(conf: &Config, account: Option<&str>, dry_run: bool) -> anyhow::Result<()> {
+    if conf.archive().root().is_none() {
+        bail!("No `root` is set in the `[Archive]` table of the configuration file.");
+    }
+
+    let keys: Vec<AccountKey> = match account {
+        Some(key) if conf.accounts().contains_key(key) => vec![AccountKey::new(key)],
+        Some(key) => bail!("No account with the key `{}` exists.", key),
+        None => conf.keys().to_vec(),
+    };
+
+    let mut archived = 0;
+    let mut skipped = 0;
+    for key in &keys {
+        // stop between accounts if the user wants to quit
+        if signals::shutdown_requested() {
+            bail!("Archiving statements was interrupted.");
+        }
+
+        let acct = &conf.accounts()[key];
+        let archive_dir = match acct.archive_dir() {
+            Some(dir) if !acct.is_remote() => dir,
+            _ => continue,
+        };
+
+        // only statements that are loose files in the account's directory can be moved
+        let stmts: Vec<&Statement> = conf
+            .statements()
+            .get(key)
+            .into_iter()
+            .flatten()
+            .filter(|obs| obs.status() == StatementStatus::Available)
+            .map(|obs| obs.statement())
+            .filter(|stmt| stmt.path().is_file() && stmt.path().parent() == Some(acct.directory()))
+            .collect();
+
+        for stmt in stmts {
+            match archive_statement(acct, archive_dir, stmt, dry_run) {
+                Ok(to) => {
+                    println!("{} -> {}", stmt.path().display(), to.display());
+                    archived += 1;
+                }
+                Err(e) => {
+                    println!("Skipped {}: {:#}", stmt.path().display(), e);
+                    skipped += 1;
+                }
+            }
+        }
+    }
+
+    let verb = if dry_run { "Would archive" } else { "Archived" };
+    println!("\n{} {} statement(s), skipped {}.", verb, archived, skipped);
+
+    Ok(())
+}
+
+/// Move a statement into the directory for its year in the account's archive
+/// directory, after checking it against its checksum if one was recorded.
+/// Returns where the statement was moved to.
+fn archive_statement(
+    acct: &Account,
+    archive_dir: &Path,
+    stmt: &Statement,
+    dry_run: bool,
+) -> anyhow::Result<PathBuf> {
+    let from = stmt.path();
+    let name = from
+        .file_name()
+        .and_then(|n| n.to_str())
+        .context("The file name isn't valid UTF-8.")?;
+    let year = stmt.date().year().to_string();
+    let to = archive_dir.join(&year).join(name);
+
+    if to.exists() {
+        bail!("`{}` already exists.", to.display());
+    }
+
+    // a statement that's changed since it was downloaded shouldn't be archived as it is
+    let mut sums = match acct.checksums() {
+        true => Some(Checksums::load(acct.directory())?),
+        false => None,
+    };
+    let checksum = match sums.as_ref().and_then(|s| s.get(name)) {
+        Some(expected) => {
+            if hash_file(from)? != expected {
+                bail!("It has been modified since its checksum was recorded. Check it with `quill verify`.");
+            }
+            Some(expected.to_string())
+        }
+        None => None,
+    };
+
+    if dry_run {
+        return Ok(to);
+    }
+
+    fs::create_dir_all(archive_dir.join(&year))
+        .with_context(|| format!("Error creating `{}`.", archive_dir.join(&year).display()))?;
+    move_file(from, &to)?;
+
+    let mut perms = fs::metadata(&to)?.permissions();
+    perms.set_readonly(true);
+    fs::set_permissions(&to, perms)
+        .with_context(|| format!("Error making `{}` read-only.", to.display()))?;
+
+    // the checksum follows the statement into the archive
+    if let (Some(sums), Some(checksum)) = (sums.as_mut(), checksum) {
+        let mut archived_sums = Checksums::load(archive_dir)?;
+        archived_sums.insert(&format!("{}/{}", year, name), checksum);
+        archived_sums.save(archive_dir)?;
+        sums.remove(name);
+        sums.save(acct.directory())?;
+    }
+
+    Ok(to)
+}
+
+/// Move a file, copying it if it's going to another file system
+fn move_file(from: &Path, to: &Path) -> anyhow::Result<()> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+
+    fs::copy(from, to)
+        .with_context(|| format!("Error copying `{}` to `{}`.", from.display(), to.display()))?;
+    fs::remove_file(from).with_context(|| format!("Error removing `{}`.", from.display()))?;
+
+    Ok(())
+}
@@ -0,0 +1,140 @@
+//! Check for statements that have gone missing since the last check, and for
+//! statements that don't match any expected date.
+
+use crate::{cfg::Config, hooks::run_hooks, notify};
+use anyhow::bail;
+
+/// List the statements that have gone missing since the last check, and raise
+/// a desktop notification for them if notifications are enabled.
+/// Any hooks are run for the statements that went missing or became available.
+/// Statements whose files have disappeared since they were found, and statements that don't
+/// match any expected date, are listed too.
+///
+/// Finally, summarize every missing statement, and fail if there are more than
+/// `max_missing` of them, so that scripts and timers can alert on it.
+/// If an account is given, only it and its named schedules are checked.
+pub fn check(conf: &Config, account: Option<&str>, max_missing: usize) -> anyhow::Result<()> {
+    if let Some(key) = account {
+        if conf.accounts().get(key).is_none() {
+            bail!("No account with the key `{}` exists.", key);
+        }
+    }
+    // an account's named schedules are keyed under it, like `<key>/<schedule>`
+    let in_scope = |key: &str| match account {
+        Some(acct) => key == acct || key.strip_prefix(acct).map_or(false, |k| k.starts_with('/')),
+        None => true,
+    };
+
+    let changes = notify::changes_since_last_check(conf)?;
+    let missing: Vec<_> = changes
+        .newly_missing()
+        .iter()
+        .filter(|(key, _)| in_scope(key))
+        .cloned()
+        .collect();
+    let available: Vec<_> = changes
+        .newly_available()
+        .iter()
+        .filter(|(key, _)| in_scope(key))
+        .cloned()
+        .collect();
+
+    if missing.is_empty() {
+        println!("No newly missing statements.");
+    } else {
+        println!("Newly missing statements:");
+        for (key, obs) in &missing {
+            let name = conf
+                .accounts()
+                .get(key.as_str())
+                .map_or(key.as_str(), |a| a.name());
+            println!(
+                "    {}  {}",
+                conf.display().date(obs.statement().date()),
+                name
+            );
+        }
+    }
+
+    // these may have been deleted by accident, so they're listed apart from the rest
+    let lost: Vec<_> = conf
+        .statements()
+        .lost()
+        .into_iter()
+        .filter(|(key, _)| in_scope(key))
+        .collect();
+    if !lost.is_empty() {
+        println!("\nStatements whose files have disappeared since they were found (restore them from the trash or a backup, or ignore them):");
+        for (key, obs) in &lost {
+            let name = conf.accounts().get(*key).map_or(*key, |a| a.name());
+            println!(
+                "    {}  {}  {}",
+                conf.display().date(obs.statement().date()),
+                name,
+                obs.statement().path().display()
+            );
+        }
+    }
+
+    // these usually mean that an account's statement period is wrong
+    let unexpected: Vec<_> = conf
+        .statements()
+        .unexpected()
+        .into_iter()
+        .filter(|(key, _)| in_scope(key))
+        .collect();
+    if !unexpected.is_empty() {
+        println!("\nStatements that don't match any expected date (check the `first_date` and `statement_period` of their accounts):");
+        for (key, obs) in &unexpected {
+            let name = conf.accounts().get(*key).map_or(*key, |a| a.name());
+            println!(
+                "    {}  {}  {}",
+                conf.display().date(obs.statement().date()),
+                name,
+                obs.statement().path().display()
+            );
+        }
+    }
+
+    if conf.notifications().enabled() && !missing.is_empty() {
+        notify::notify_missing(conf, &missing)?;
+    }
+
+    // a failed hook is reported, but shouldn't hide the summary below
+    if let Err(e) = run_hooks(conf, &missing, &available) {
+        eprintln!("Warning: {:?}", e);
+    }
+
+    // summarize the missing statements of each account, in the order they're listed
+    let mut n_missing = 0;
+    let mut n_accounts = 0;
+    for key in conf.keys().iter().filter(|key| in_scope(key)) {
+        let stmts = conf.statements().missing_for(key);
+        let oldest = match stmts.iter().map(|obs| obs.statement().date()).min() {
+            Some(oldest) => oldest,
+            None => continue,
+        };
+
+        if n_accounts == 0 {
+            println!("\nMissing statements:");
+        }
+        let name = conf.accounts().get(key).map_or(key.as_str(), |a| a.name());
+        println!("    {}  {} (oldest {})", name, stmts.len(), oldest);
+        n_missing += stmts.len();
+        n_accounts += 1;
+    }
+    println!(
+        "\n{} missing statement(s) across {} account(s).",
+        n_missing, n_accounts
+    );
+
+    if n_missing > max_missing {
+        bail!(
+            "{} statement(s) are missing, more than the {} allowed.",
+            n_missing,
+            max_missing
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,118 @@
+//! Scan for statements regularly in the background, without the TUI.
+
+use crate::{
+    api::Api,
+    cfg::{Config, DaemonConfig},
+    daemon::DaemonSnapshot,
+    ipc::{DaemonMessage, IpcServer},
+    notify, signals,
+    snapshot::{daemon_snapshot_path, daemon_socket_path},
+};
+use chrono::Local;
+use std::{
+    path::PathBuf,
+    thread,
+    time::{Duration, Instant},
+};
+use tracing::info;
+
+/// How often to check whether quill has been asked to stop while waiting
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Scan for statements every `interval`, or as often as the `[Daemon]` table says, until quill is stopped.
+/// After each scan, notifications and hooks are run for the statements that changed, like
+/// `quill check` does, and the statements are sent to any TUI that's connected and saved for
+/// `--from-daemon` to use.
+///
+/// If `api_port`, or the `[Daemon]` table's `api_port`, is given, the statements are also
+/// served as JSON on that port of localhost.
+///
+/// The configuration files are read again before each scan, so changes to them are picked up
+/// without restarting, except for the API's port.
+/// A scan that fails is reported, and tried again after the interval.
+pub fn daemon(
+    paths: &[PathBuf],
+    lenient: bool,
+    interval: Option<Duration>,
+    api_port: Option<u16>,
+) -> anyhow::Result<()> {
+    // only one daemon can send statements to the TUI
+    let ipc = IpcServer::listen(&daemon_socket_path()?)?;
+
+    // a port given on the command line can be checked before the first scan
+    let mut api = match api_port {
+        Some(port) => Some(Api::serve(port)?),
+        None => None,
+    };
+
+    loop {
+        let result = scan(paths, lenient, interval, &ipc);
+        if signals::shutdown_requested() {
+            return Ok(());
+        }
+
+        let wait = match result {
+            Ok(conf) => {
+                if let (None, Some(port)) = (&api, conf.daemon().api_port()) {
+                    api = Some(Api::serve(port)?);
+                }
+                if let Some(api) = &api {
+                    api.update(&conf);
+                }
+                interval.unwrap_or_else(|| conf.daemon().interval())
+            }
+            Err(e) => {
+                // the configuration or a directory may be fixed before the next scan
+                eprintln!("Error: {:?}", e);
+                interval.unwrap_or_else(|| DaemonConfig::default().interval())
+            }
+        };
+        info!(next_scan_secs = wait.as_secs(), "waiting for the next scan");
+        if !sleep(wait) {
+            return Ok(());
+        }
+    }
+}
+
+/// Load the configuration and scan for statements, then tell anyone who's interested
+fn scan(
+    paths: &[PathBuf],
+    lenient: bool,
+    interval: Option<Duration>,
+    ipc: &IpcServer,
+) -> anyhow::Result<Config> {
+    let conf = Config::load_all(paths, lenient)?;
+    let interval = interval.unwrap_or_else(|| conf.daemon().interval());
+
+    let latest = DaemonSnapshot::new(conf.statements(), interval);
+    latest.save(&daemon_snapshot_path()?)?;
+    ipc.broadcast(&DaemonMessage::Statements(latest))?;
+    // a failed notification or hook is reported, but shouldn't keep the statements from the API
+    if let Err(e) = notify::check_and_notify(&conf) {
+        eprintln!("Warning: {:?}", e);
+    }
+
+    println!(
+        "{}  {} missing statement(s) across {} account(s).",
+        Local::now().format("%Y-%m-%d %H:%M:%S"),
+        conf.statements().missing_count(),
+        conf.keys().len()
+    );
+
+    Ok(conf)
+}
+
+/// Wait for `duration`, unless quill is asked to stop first.
+/// Returns whether it waited for the whole time.
+fn sleep(duration: Duration) -> bool {
+    let until = Instant::now() + duration;
+    while !signals::shutdown_requested() {
+        let left = until.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return true;
+        }
+        thread::sleep(left.min(POLL_INTERVAL));
+    }
+
+    false
+}
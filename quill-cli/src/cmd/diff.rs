@@ -0,0 +1,84 @@
+//! Compare the current statements against the last saved snapshot.
+
+use crate::{
+    cfg::Config,
+    snapshot::{snapshot_path, Snapshot},
+};
+use quill_statement::{CollectionDiff, StatementChange};
+use std::fmt::Write;
+
+/// Print the statements that changed since the last snapshot, then replace it
+/// with the current statements (unless `no_save` is set).
+pub fn diff(conf: &Config, no_save: bool) -> anyhow::Result<()> {
+    let path = snapshot_path()?;
+    let previous = Snapshot::load(&path)?;
+    let current = Snapshot::new(conf.statements()).with_paused(conf.accounts(), previous.as_ref());
+
+    match previous {
+        Some(previous) => {
+            let changes = CollectionDiff::new(previous.statements(), current.statements());
+
+            if changes.is_empty() {
+                println!(
+                    "No changes since the last snapshot ({}).",
+                    previous.created().format("%Y-%m-%d %H:%M")
+                );
+            } else {
+                print!("{}", format_changes(conf, &previous, &changes));
+            }
+        }
+        None => println!(
+            "No previous snapshot found at `{}`. The current statements will be used as the baseline.",
+            path.display()
+        ),
+    }
+
+    if !no_save {
+        current.save(&path)?;
+    }
+
+    Ok(())
+}
+
+/// Describe all the changes since a previous snapshot
+pub fn format_changes(conf: &Config, previous: &Snapshot, changes: &CollectionDiff) -> String {
+    let mut out = format!(
+        "Changes since the last snapshot ({}):\n",
+        previous.created().format("%Y-%m-%d %H:%M")
+    );
+
+    format_change_group(&mut out, conf, "Newly available", changes.newly_available());
+    format_change_group(&mut out, conf, "Newly missing", changes.newly_missing());
+    format_change_group(&mut out, conf, "Removed", changes.removed());
+
+    out
+}
+
+/// Describe a group of changed statements under a heading
+fn format_change_group(
+    out: &mut String,
+    conf: &Config,
+    heading: &str,
+    changes: &[StatementChange],
+) {
+    if changes.is_empty() {
+        return;
+    }
+
+    // writing to a `String` can't fail, so the results are safe to ignore
+    let _ = writeln!(out, "\n{} ({}):", heading, changes.len());
+    for (key, obs_stmt) in changes {
+        let name = conf
+            .accounts()
+            .get(key.as_str())
+            .map_or(key.as_str(), |acct| acct.name());
+
+        let _ = writeln!(
+            out,
+            "  {:<20} {}  {}",
+            name,
+            obs_stmt.statement().date(),
+            obs_stmt.statement().path().display()
+        );
+    }
+}
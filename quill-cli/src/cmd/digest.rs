@@ -0,0 +1,56 @@
+//! Produce a digest of statement changes, designed to be run regularly by cron.
+
+use super::diff::format_changes;
+use crate::{
+    cfg::Config,
+    snapshot::{digest_snapshot_path, Snapshot},
+};
+use anyhow::Context;
+use chrono::{Datelike, Local};
+use quill_statement::CollectionDiff;
+use std::{fs::OpenOptions, io::Write};
+
+/// Emit a digest of the statements that changed since the last digest.
+///
+/// Nothing is written if nothing changed, or if today isn't the configured day
+/// (unless `force` is set).
+/// The digest snapshot is only replaced after the digest has been written,
+/// so that changes are never lost.
+pub fn digest(conf: &Config, force: bool) -> anyhow::Result<()> {
+    if let Some(day) = conf.digest().day() {
+        if !force && (Local::now().weekday() != day) {
+            return Ok(());
+        }
+    }
+
+    let path = digest_snapshot_path()?;
+    let previous = Snapshot::load(&path)?;
+    let current = Snapshot::new(conf.statements()).with_paused(conf.accounts(), previous.as_ref());
+
+    // the very first digest only records a baseline to compare against
+    if let Some(previous) = previous {
+        let changes = CollectionDiff::new(previous.statements(), current.statements());
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        let report = format_changes(conf, &previous, &changes);
+        match conf.digest().output() {
+            Some(out_path) => {
+                let mut file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(out_path)
+                    .with_context(|| {
+                        format!("Error opening digest output `{}`.", out_path.display())
+                    })?;
+                writeln!(file, "{}", report).with_context(|| {
+                    format!("Error writing digest output `{}`.", out_path.display())
+                })?;
+            }
+            None => print!("{}", report),
+        }
+    }
+
+    current.save(&path)
+}
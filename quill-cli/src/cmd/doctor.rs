@@ -0,0 +1,329 @@
+//! Diagnose common problems with the accounts in a configuration file.
+
+use crate::cfg::Config;
+use chrono::{Local, NaiveDate};
+use quill_account::{archive::is_archive, Account};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// How many statements in a row have to be missing to be worth a warning
+const LONG_GAP: usize = 3;
+
+/// A problem found with an account, and what to do about it
+struct Warning {
+    /// Key of the account the problem is with
+    key: String,
+
+    /// Description of the problem
+    message: String,
+}
+
+impl Warning {
+    fn new(key: &str, message: String) -> Self {
+        Self {
+            key: key.to_string(),
+            message,
+        }
+    }
+}
+
+/// Run every check against the accounts in the configuration file and print
+/// a warning for each problem found.
+pub fn doctor(path: &Path) -> anyhow::Result<()> {
+    // accounts whose directories are missing should be diagnosed, not rejected
+    let conf = Config::load_unchecked(path)?;
+    let today = Local::now().naive_local().date();
+
+    let mut warnings = vec![];
+    for key in conf.keys() {
+        if let Some(acct) = conf.accounts().get(key) {
+            // a named schedule shares its account's directory, which is checked once,
+            // and remote directories are only checked when they're listed
+            if acct.schedule().is_none() && !acct.is_remote() {
+                check_directory(key, acct, &mut warnings);
+            }
+            // every statement is missing from a directory that doesn't exist, which is already reported
+            if !acct.is_remote() && acct.directory().exists() {
+                check_gaps(key, acct, &mut warnings);
+            }
+            check_first_date(key, acct, &today, &mut warnings);
+            check_ignored_dates(key, acct, &mut warnings);
+        }
+    }
+    check_shared_directories(&conf, &mut warnings);
+
+    if warnings.is_empty() {
+        println!(
+            "No problems found with the accounts in `{}`.",
+            path.display()
+        );
+        return Ok(());
+    }
+
+    // keep each account's warnings together, in the order the accounts are listed
+    for key in conf.keys() {
+        let messages: Vec<&str> = warnings
+            .iter()
+            .filter(|w| w.key == key.as_str())
+            .map(|w| w.message.as_str())
+            .collect();
+        if messages.is_empty() {
+            continue;
+        }
+
+        let name = conf.accounts().get(key).map_or("", |a| a.name());
+        println!("[Accounts.{}] {}", key, name);
+        for message in messages {
+            println!("    {}", message);
+        }
+    }
+
+    println!("\nFound {} warning(s).", warnings.len());
+
+    Ok(())
+}
+
+/// Check that the account's directory can be read and is written canonically
+fn check_directory(key: &str, acct: &Account, warnings: &mut Vec<Warning>) {
+    let dir = acct.directory();
+
+    if !dir.exists() {
+        warnings.push(Warning::new(
+            key,
+            format!(
+                "Directory `{}` does not exist. Create it or fix `dir`.",
+                dir.display()
+            ),
+        ));
+        return;
+    }
+
+    if let Err(e) = fs::read_dir(dir) {
+        warnings.push(Warning::new(
+            key,
+            format!(
+                "Directory `{}` can't be read ({}). Check its permissions.",
+                dir.display(),
+                e
+            ),
+        ));
+        return;
+    }
+
+    match dir.canonicalize() {
+        Ok(abs_dir) if abs_dir != dir => warnings.push(Warning::new(
+            key,
+            format!(
+                "Directory `{}` is not canonical. Set `dir` to `{}` instead.",
+                dir.display(),
+                abs_dir.display()
+            ),
+        )),
+        Ok(_) => {}
+        Err(e) => warnings.push(Warning::new(
+            key,
+            format!(
+                "Directory `{}` can't be made absolute ({}).",
+                dir.display(),
+                e
+            ),
+        )),
+    }
+}
+
+/// Check that statements are expected for the account already
+fn check_first_date(key: &str, acct: &Account, today: &NaiveDate, warnings: &mut Vec<Warning>) {
+    if acct.first() > today {
+        warnings.push(Warning::new(
+            key,
+            format!(
+                "`first_date` {} is in the future, so no statements are expected yet.",
+                acct.first()
+            ),
+        ));
+    }
+}
+
+/// Check for long runs of missing statements, which are more likely to be a mistake in the
+/// account's properties than statements that haven't been downloaded
+fn check_gaps(key: &str, acct: &Account, warnings: &mut Vec<Warning>) {
+    for gap in acct.gaps() {
+        if gap.count() < LONG_GAP {
+            continue;
+        }
+
+        warnings.push(Warning::new(
+            key,
+            format!(
+                "{} statements in a row are missing, from {} to {}. If they were never issued, check `first_date`, `statement_period`, and `statement_fmt`, or ignore them.",
+                gap.count(),
+                gap.start(),
+                gap.end(),
+            ),
+        ));
+    }
+}
+
+/// Check that every ignored date is one that a statement is expected on, and that every
+/// ignored range is between two dates
+fn check_ignored_dates(key: &str, acct: &Account, warnings: &mut Vec<Warning>) {
+    // a named schedule shares its account's ignore file, whose ranges are checked once
+    if acct.schedule().is_none() {
+        for range in acct.ignored().invalid_ranges() {
+            warnings.push(Warning::new(
+                key,
+                format!(
+                    "Ignored range `{}` isn't between two dates, like `[2020-01-01, 2020-12-31]`, so it's skipped.",
+                    range
+                ),
+            ));
+        }
+    }
+
+    let expected = acct.statement_dates();
+
+    for date in acct.ignored().iter() {
+        if expected.binary_search(date).is_ok() {
+            continue;
+        }
+
+        let message = match nearest_date(&expected, date) {
+            Some(nearest) => format!(
+                "Ignored date {} isn't an expected statement date. Did you mean {}?",
+                date, nearest
+            ),
+            None => format!("Ignored date {} isn't an expected statement date.", date),
+        };
+        warnings.push(Warning::new(key, message));
+    }
+}
+
+/// Find the date in a sorted list that is closest to the given one
+fn nearest_date<'a>(dates: &'a [NaiveDate], date: &NaiveDate) -> Option<&'a NaiveDate> {
+    dates.iter().min_by_key(|d| (**d - *date).num_days().abs())
+}
+
+/// Check the accounts that share a directory, and the files in each directory
+/// that don't belong to any account
+fn check_shared_directories(conf: &Config, warnings: &mut Vec<Warning>) {
+    // the keys of the accounts in each directory, in the order they're listed
+    let mut dirs: BTreeMap<PathBuf, Vec<&str>> = BTreeMap::new();
+    for key in conf.keys() {
+        if let Some(acct) = conf.accounts().get(key) {
+            // compare directories as they're resolved, so different spellings match
+            let dir = acct
+                .directory()
+                .canonicalize()
+                .unwrap_or_else(|_| acct.directory().to_path_buf());
+            dirs.entry(dir).or_default().push(key);
+        }
+    }
+
+    for (dir, keys) in &dirs {
+        let accts: Vec<(&str, &Account)> = keys
+            .iter()
+            .filter_map(|&key| conf.accounts().get(key).map(|a| (key, a)))
+            .collect();
+
+        let files: Vec<PathBuf> = match fs::read_dir(dir) {
+            Ok(entries) => entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.is_file())
+                .collect(),
+            // unreadable directories are already reported for each account
+            Err(_) => continue,
+        };
+
+        if accts.len() > 1 {
+            check_overlapping_accounts(&accts, &files, warnings);
+        }
+
+        check_unmatched_files(&accts, &files, warnings);
+    }
+}
+
+/// Warn about accounts in the same directory whose formats match the same files,
+/// since those files are counted as statements for each of them
+fn check_overlapping_accounts(
+    accts: &[(&str, &Account)],
+    files: &[PathBuf],
+    warnings: &mut Vec<Warning>,
+) {
+    for (key, acct) in accts {
+        let matching: Vec<&PathBuf> = files
+            .iter()
+            .filter(|f| !acct.excludes_file(f) && acct.matches_file_name(f))
+            .collect();
+
+        for (other_key, other) in accts.iter().filter(|(k, _)| k != key) {
+            let overlapping = matching
+                .iter()
+                .filter(|f| !other.excludes_file(f) && other.matches_file_name(f))
+                .count();
+
+            if overlapping > 0 {
+                warnings.push(Warning::new(
+                    key,
+                    format!(
+                        "{} file(s) also match the `statement_fmt` of `{}`, which shares this directory, so they count as statements for both. Give the accounts separate directories or distinct formats.",
+                        overlapping, other_key
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+/// Warn about files in a directory that don't match any of its accounts' formats.
+/// These are reported with the first account in the directory.
+fn check_unmatched_files(
+    accts: &[(&str, &Account)],
+    files: &[PathBuf],
+    warnings: &mut Vec<Warning>,
+) {
+    let key = match accts.first() {
+        Some((key, _)) => *key,
+        None => return,
+    };
+
+    let mut unmatched: Vec<&Path> = files
+        .iter()
+        .map(|f| f.as_path())
+        // hidden files include the ignore file, and are never statements
+        .filter(|f| !is_hidden(f))
+        // files can be ignored on purpose, such as marketing material
+        .filter(|f| !accts.iter().any(|(_, a)| a.ignored().ignores_file(f)))
+        // or left out with `exclude`, such as drafts and temporary files
+        .filter(|f| !accts.iter().any(|(_, a)| a.excludes_file(f)))
+        // archives are searched, not matched, when `search_archives` is set
+        .filter(|f| !(is_archive(f) && accts.iter().any(|(_, a)| a.search_archives())))
+        .filter(|f| !accts.iter().any(|(_, a)| a.matches_file_name(f)))
+        .collect();
+    unmatched.sort();
+
+    let mut formats: Vec<&str> = accts.iter().map(|(_, a)| a.format_string()).collect();
+    formats.sort_unstable();
+    formats.dedup();
+    for file in unmatched {
+        warnings.push(Warning::new(
+            key,
+            format!(
+                "`{}` doesn't match the `statement_fmt` `{}`. Rename it with `quill rename {} --from <FORMAT>`, or move it elsewhere.",
+                file.display(),
+                formats.join("` or `"),
+                key
+            ),
+        ));
+    }
+}
+
+/// Check if a file is hidden, by its name starting with a `.`
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .map_or(false, |name| name.starts_with('.'))
+}
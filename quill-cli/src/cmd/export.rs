@@ -0,0 +1,152 @@
+//! Export the configuration and statements for use by other tools.
+
+use crate::{cfg::Config, cli::ExportFormat};
+use anyhow::Context;
+use chrono::NaiveDate;
+use quill_account::Account;
+use quill_statement::StatementStatus;
+use std::io::{self, Write};
+
+/// Print the configuration and the statements found for each account in the given format
+pub fn export(conf: &Config, format: ExportFormat) -> anyhow::Result<()> {
+    let mut stdout = io::stdout().lock();
+    match format {
+        ExportFormat::Json => {
+            serde_json::to_writer_pretty(&mut stdout, conf)
+                .context("Error writing the configuration as JSON.")?;
+            writeln!(stdout)?;
+        }
+        ExportFormat::Beancount => write_beancount(conf, &mut stdout)?,
+        ExportFormat::Csv => write_csv(conf, &mut stdout)?,
+    }
+
+    Ok(())
+}
+
+/// Write a `document` directive for each available statement, oldest first.
+/// Statements that aren't files of their own, like those inside archives or
+/// remote directories, can't be linked to, so an `event` is written instead.
+fn write_beancount(conf: &Config, out: &mut impl Write) -> anyhow::Result<()> {
+    let mut directives: Vec<(String, String)> = conf
+        .statements()
+        .iter_statements()
+        .filter(|(_, obs)| obs.status() == StatementStatus::Available)
+        .filter_map(|(key, obs)| {
+            let acct = conf.accounts().get(key)?;
+            let stmt = obs.statement();
+            let date = stmt.date().format("%Y-%m-%d").to_string();
+            let directive = if stmt.path().is_file() {
+                format!(
+                    "{} document {} \"{}\"",
+                    date,
+                    ledger_account(acct),
+                    escape(&stmt.path().display().to_string())
+                )
+            } else {
+                format!("{} event \"statement\" \"{}\"", date, escape(acct.name()))
+            };
+            Some((date, directive))
+        })
+        .collect();
+    directives.sort();
+
+    writeln!(
+        out,
+        "; Statements found by quill in `{}`",
+        conf.path().display()
+    )?;
+    for (_, directive) in directives {
+        writeln!(out, "{}", directive)?;
+    }
+
+    Ok(())
+}
+
+/// Write a row for each statement of each account, sorted by account key and then date,
+/// along with the account's name, institution, kind, and currency.
+/// Dates are written as they're shown in the TUI.
+/// Only statements that have been found have a path.
+fn write_csv(conf: &Config, out: &mut impl Write) -> anyhow::Result<()> {
+    let mut rows: Vec<(&str, NaiveDate, [String; 7])> = conf
+        .statements()
+        .iter_statements()
+        .filter_map(|(key, obs)| {
+            let acct = conf.accounts().get(key)?;
+            let stmt = obs.statement();
+            let path = match obs.status() {
+                StatementStatus::Available | StatementStatus::Unexpected => {
+                    stmt.path().display().to_string()
+                }
+                _ => String::new(),
+            };
+            let fields = [
+                acct.name().to_string(),
+                acct.institution().to_string(),
+                acct.kind().map_or(String::new(), |kind| kind.to_string()),
+                acct.currency().unwrap_or_default().to_string(),
+                conf.display().date(stmt.date()),
+                format!("{:?}", obs.status()),
+                path,
+            ];
+            Some((key, *stmt.date(), fields))
+        })
+        .collect();
+    rows.sort_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+
+    writeln!(
+        out,
+        "account,name,institution,kind,currency,date,status,path"
+    )?;
+    for (key, _, fields) in rows {
+        let row: Vec<String> = std::iter::once(key)
+            .chain(fields.iter().map(String::as_str))
+            .map(csv_field)
+            .collect();
+        writeln!(out, "{}", row.join(","))?;
+    }
+
+    Ok(())
+}
+
+/// Quote a CSV field if it contains a comma, quote, or line break
+fn csv_field(s: &str) -> String {
+    match s.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", s.replace('"', "\"\"")),
+        false => s.to_string(),
+    }
+}
+
+/// The journal account for an account's statements.
+/// Without a `ledger_account`, it's made from the institution and account names,
+/// like `Assets:My-Bank:Chequing`.
+fn ledger_account(acct: &Account) -> String {
+    match acct.ledger_account() {
+        Some(name) => name.to_string(),
+        None => format!(
+            "Assets:{}:{}",
+            account_component(acct.institution()),
+            account_component(acct.name())
+        ),
+    }
+}
+
+/// Turn a name into one part of a journal account, by joining its words with
+/// `-` and capitalizing it
+fn account_component(name: &str) -> String {
+    let words: Vec<&str> = name
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect();
+    let joined = words.join("-");
+
+    let mut chars = joined.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::from("Unnamed"),
+    }
+}
+
+/// Escape a string to be written between quotes in a journal
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -0,0 +1,91 @@
+//! Download statements by running each account's `fetch_cmd`.
+
+use crate::{
+    cfg::Config,
+    fetch::{fetch_command, fetched_statements},
+    signals,
+};
+use anyhow::{bail, Context};
+use quill_statement::AccountKey;
+
+/// Run the `fetch_cmd` of a single account, or of every account that has one,
+/// then scan for statements again and list the ones that were downloaded.
+/// Paused accounts are skipped, unless they're fetched by name.
+pub fn fetch(conf: &mut Config, account: Option<&str>) -> anyhow::Result<()> {
+    let keys: Vec<AccountKey> = match account {
+        Some(key) => match conf.accounts().get(key) {
+            Some(acct) if acct.fetch_cmd().is_some() => vec![AccountKey::new(key)],
+            Some(_) => bail!("Account `{}` doesn't have a `fetch_cmd`.", key),
+            None => bail!("No account with the key `{}` exists.", key),
+        },
+        // named schedules share their account's command, so only run it once
+        None => conf
+            .keys()
+            .iter()
+            .filter(|key| {
+                conf.accounts().get(*key).map_or(false, |acct| {
+                    acct.fetch_cmd().is_some() && acct.schedule().is_none() && !acct.is_paused()
+                })
+            })
+            .cloned()
+            .collect(),
+    };
+
+    if keys.is_empty() {
+        println!("No accounts have a `fetch_cmd`.");
+        return Ok(());
+    }
+
+    let before = conf.statements().clone();
+    let mut failed = vec![];
+    for key in &keys {
+        // stop between accounts if the user wants to quit
+        if signals::shutdown_requested() {
+            bail!("Fetching statements was interrupted.");
+        }
+
+        let acct = &conf.accounts()[key];
+        println!("Fetching statements for {}...", acct.name());
+
+        let mut cmd = match fetch_command(key, acct) {
+            Some(cmd) => cmd,
+            None => continue,
+        };
+        let status = cmd
+            .status()
+            .with_context(|| format!("Error running the `fetch_cmd` of `{}`.", key))?;
+        if !status.success() {
+            eprintln!("The `fetch_cmd` of `{}` failed ({}).", key, status);
+            failed.push(key.as_str());
+        }
+    }
+
+    conf.refresh_account_statements()?;
+
+    let fetched: Vec<_> = keys
+        .iter()
+        .flat_map(|key| fetched_statements(key, &before, conf.statements()))
+        .collect();
+    if fetched.is_empty() {
+        println!("\nNo new statements.");
+    } else {
+        println!("\nNew statements:");
+        for (key, obs) in &fetched {
+            let name = conf
+                .accounts()
+                .get(key.as_str())
+                .map_or(key.as_str(), |a| a.name());
+            println!(
+                "    {}  {}",
+                conf.display().date(obs.statement().date()),
+                name
+            );
+        }
+    }
+
+    if !failed.is_empty() {
+        bail!("The `fetch_cmd` failed for: {}.", failed.join(", "));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+//! List how statements have changed over time, from the ledger kept by each scan.
+
+use crate::{cfg::Config, history};
+use chrono::NaiveDate;
+use quill_statement::Transition;
+
+/// Print every recorded change to the statements, oldest first, with when it was noticed.
+/// If an account is given, only changes to it and its named schedules are shown, and if a
+/// date is given, only changes to the statements from that date.
+///
+/// Accounts that have since been removed from the configuration are shown by their keys.
+pub fn history(
+    conf: &Config,
+    account: Option<&str>,
+    date: Option<NaiveDate>,
+) -> anyhow::Result<()> {
+    // an account's named schedules are keyed under it, like `<key>/<schedule>`
+    let in_scope = |key: &str| match account {
+        Some(acct) => key == acct || key.strip_prefix(acct).map_or(false, |k| k.starts_with('/')),
+        None => true,
+    };
+
+    let entries: Vec<_> = history::load()?
+        .into_iter()
+        .filter(|entry| in_scope(entry.transition().account()))
+        .filter(|entry| date.map_or(true, |d| *entry.transition().date() == d))
+        .collect();
+    if entries.is_empty() {
+        println!("No statement changes have been recorded.");
+        return Ok(());
+    }
+
+    for entry in &entries {
+        let stmt = entry.transition();
+        let name = conf
+            .accounts()
+            .get(stmt.account())
+            .map_or(stmt.account(), |a| a.name());
+        let event = match stmt.transition() {
+            Transition::Appeared { path } => format!("appeared      {}", path.display()),
+            Transition::Missing => "went missing".to_string(),
+            Transition::Ignored => "was ignored".to_string(),
+            Transition::Renamed { from, to } => {
+                format!("was renamed   {} -> {}", from.display(), to.display())
+            }
+            Transition::Removed { path } => format!("was removed   {}", path.display()),
+        };
+        println!(
+            "{}  {}  {}  {}",
+            entry.time().format("%Y-%m-%d %H:%M"),
+            conf.display().date(stmt.date()),
+            name,
+            event
+        );
+    }
+
+    Ok(())
+}
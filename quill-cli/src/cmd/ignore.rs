@@ -0,0 +1,88 @@
+//! Ignore the statements an account expects over a range of dates.
+
+use crate::cfg::Config;
+use anyhow::{bail, Context};
+use chrono::NaiveDate;
+use quill_statement::extend_ignorefile;
+
+/// Add the statements `key` expects between `from` and `to` to the ignore file
+/// in its directory, either as individual dates with an optional reason, or as a single range.
+pub fn ignore(
+    conf: &Config,
+    key: &str,
+    from: &NaiveDate,
+    to: &NaiveDate,
+    range: bool,
+    reason: Option<&str>,
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    let acct = match conf.accounts().get(key) {
+        Some(a) => a,
+        None => bail!(
+            "No account with key `{}` was found. Available accounts are: {}.",
+            key,
+            conf.keys().join(", ")
+        ),
+    };
+
+    if acct.is_remote() {
+        bail!(
+            "`{}` keeps its statements in the remote directory `{}`, which can't have an ignore file.",
+            key,
+            acct.directory().display()
+        );
+    }
+
+    if from > to {
+        bail!("`--from` ({}) must not be after `--to` ({}).", from, to);
+    }
+
+    let (ignored, new): (Vec<NaiveDate>, Vec<NaiveDate>) = acct
+        .statement_dates_between(from, to)
+        .into_iter()
+        .partition(|d| acct.ignored().contains(d));
+    if new.is_empty() {
+        match ignored.is_empty() {
+            true => println!("No statements are expected between {} and {}.", from, to),
+            false => println!(
+                "All {} statement(s) expected between {} and {} are already ignored.",
+                ignored.len(),
+                from,
+                to
+            ),
+        }
+        return Ok(());
+    }
+
+    let verb = match dry_run {
+        true => "Would ignore",
+        false => "Ignoring",
+    };
+    println!("{} {} statement(s) for `{}`:", verb, new.len(), key);
+    for d in &new {
+        println!("    {}", d);
+    }
+    if !ignored.is_empty() {
+        println!(
+            "{} other statement(s) in the range are already ignored.",
+            ignored.len()
+        );
+    }
+
+    if dry_run {
+        println!(
+            "\nNothing was written. Run again without `--dry-run` to ignore these statements."
+        );
+        return Ok(());
+    }
+
+    let (dates, ranges) = match range {
+        true => (vec![], vec![(*from, *to)]),
+        false => (new, vec![]),
+    };
+    let path = extend_ignorefile(acct.directory(), &dates, reason, &ranges)
+        .with_context(|| format!("Error updating the ignore file for `{}`.", key))?;
+    println!("\nUpdated `{}`.", path.display());
+
+    Ok(())
+}
@@ -0,0 +1,352 @@
+//! Interactively create a new configuration file.
+
+use super::account::{account_table, default_key, parse_period};
+use anyhow::{bail, Context};
+use chrono::{Datelike, NaiveDate};
+use quill_account::Account;
+use quill_utils::{create_parent_dir, project_dirs, TomlEditor};
+use std::{
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+};
+use toml::{map::Map, Value};
+use toml_edit::Table;
+
+/// Walk the user through creating their first configuration file.
+pub fn init(output: Option<&Path>, force: bool) -> anyhow::Result<()> {
+    let path = match output {
+        Some(p) => p.to_path_buf(),
+        None => default_config_file()?,
+    };
+
+    if path.exists() && !force {
+        bail!(
+            "Configuration file `{}` already exists. Use `--force` to overwrite it.",
+            path.display()
+        );
+    }
+
+    let stdin = io::stdin();
+    write_config(&mut stdin.lock(), &path)?;
+    println!("\nConfiguration written to `{}`.", path.display());
+
+    Ok(())
+}
+
+/// Ask the user for their accounts, and write them to a configuration file at `path`
+fn write_config(input: &mut impl BufRead, path: &Path) -> anyhow::Result<()> {
+    println!("Creating a new configuration file at `{}`.", path.display());
+    println!("Press Enter to accept the default value shown in brackets.\n");
+
+    let mut accounts = vec![];
+    loop {
+        let account = prompt_account(input, &accounts)?;
+        accounts.push(account);
+
+        if !prompt_yes_no(input, "Add another account?", false)? {
+            break;
+        }
+        println!();
+    }
+
+    // any file already there was only ever going to be overwritten, so it isn't read
+    let mut editor = TomlEditor::new(path);
+    editor.insert_table(&["Accounts"], Table::new())?;
+    for (key, props) in &accounts {
+        editor.insert_table(&["Accounts", key], account_table(props)?)?;
+    }
+
+    create_parent_dir(path)
+        .with_context(|| format!("Error creating the directory for `{}`.", path.display()))?;
+    editor
+        .save()
+        .with_context(|| format!("Error writing configuration file `{}`.", path.display()))?;
+
+    Ok(())
+}
+
+/// The configuration file in the user's configuration directory
+fn default_config_file() -> anyhow::Result<PathBuf> {
+    match project_dirs().config_file() {
+        Some(path) => Ok(path),
+        None => bail!("Could not determine the configuration directory. Use `--output` to choose where to write the configuration file."),
+    }
+}
+
+/// Ask the user for every property of a single account, with a key that isn't used by any of
+/// the accounts already entered
+fn prompt_account(
+    input: &mut impl BufRead,
+    accounts: &[(String, Value)],
+) -> anyhow::Result<(String, Value)> {
+    let name = prompt_valid(input, "Account name", None, "name", |s| {
+        Value::String(s.to_string())
+    })?;
+    // the account key must be unique
+    let key = loop {
+        let key = prompt(
+            input,
+            "Account key (must be unique)",
+            Some(&default_key(&name)),
+        )?;
+        match accounts.iter().any(|(k, _)| *k == key) {
+            true => println!("Account key `{}` is already used by another account.", key),
+            false => break key,
+        }
+    };
+    let institution = prompt_valid(input, "Institution name", None, "institution", |s| {
+        Value::String(s.to_string())
+    })?;
+    let dir = prompt_valid(input, "Directory containing statements", None, "dir", |s| {
+        Value::String(s.to_string())
+    })?;
+    let fmt = prompt_valid(
+        input,
+        "Statement file name format",
+        Some("%Y-%m-%d.pdf"),
+        "statement_fmt",
+        |s| Value::String(s.to_string()),
+    )?;
+    let first = prompt_date(input, "Date of the first statement (YYYY-MM-DD)")?;
+    let period = prompt_period(input, &first)?;
+
+    let mut props = Map::new();
+    props.insert("name".to_string(), Value::String(name));
+    props.insert("institution".to_string(), Value::String(institution));
+    props.insert("statement_fmt".to_string(), Value::String(fmt));
+    props.insert("dir".to_string(), Value::String(dir));
+    props.insert("statement_period".to_string(), period);
+    props.insert(
+        "first_date".to_string(),
+        Value::Datetime(first.to_string().parse()?),
+    );
+
+    Ok((key, Value::Table(props)))
+}
+
+/// Ask the user how often statements are issued
+fn prompt_period(input: &mut impl BufRead, first: &NaiveDate) -> anyhow::Result<Value> {
+    let day = first.day();
+    println!("How often are statements issued?");
+    println!("  1) Monthly, on day {} of the month", day);
+    println!("  2) Quarterly, on day {} of every 3rd month", day);
+    println!("  3) Custom `[n, x, m, y]` period (the `n`th `x` of every `m`th `y`)");
+
+    loop {
+        let choice = prompt(input, "Choice", Some("1"))?;
+        let period_str = match choice.as_str() {
+            "1" => format!("[{}, \"Day\", 1, \"Month\"]", day),
+            "2" => format!("[{}, \"Day\", 3, \"Month\"]", day),
+            "3" => prompt(
+                input,
+                "Statement period",
+                Some("[1, \"Day\", 1, \"Month\"]"),
+            )?,
+            _ => {
+                println!("Please choose 1, 2, or 3.");
+                continue;
+            }
+        };
+
+        match parse_period(&period_str) {
+            Some(p) => match property_errors("statement_period", &p) {
+                Some(e) => println!("{}", e),
+                None => return Ok(p),
+            },
+            None => println!("`{}` is not a valid TOML array.", period_str),
+        }
+    }
+}
+
+/// Ask for a date until a valid one is given
+fn prompt_date(input: &mut impl BufRead, question: &str) -> anyhow::Result<NaiveDate> {
+    loop {
+        let answer = prompt(input, question, None)?;
+        match NaiveDate::parse_from_str(&answer, "%Y-%m-%d") {
+            Ok(d) => return Ok(d),
+            Err(_) => println!("`{}` is not a valid `YYYY-MM-DD` date.", answer),
+        }
+    }
+}
+
+/// Ask for a value until it is valid for the given account property
+fn prompt_valid(
+    input: &mut impl BufRead,
+    question: &str,
+    default: Option<&str>,
+    property: &str,
+    to_value: impl Fn(&str) -> Value,
+) -> anyhow::Result<String> {
+    loop {
+        let answer = prompt(input, question, default)?;
+        match property_errors(property, &to_value(&answer)) {
+            Some(e) => println!("{}", e),
+            None => return Ok(answer),
+        }
+    }
+}
+
+/// Check a single account property, returning a description of any problems
+fn property_errors(property: &str, value: &Value) -> Option<String> {
+    let mut props = Map::new();
+    props.insert(property.to_string(), value.clone());
+
+    // only the property being checked matters, since the others haven't been given yet
+    let errors: Vec<String> = Account::validate(&Value::Table(props))
+        .iter()
+        .filter(|e| e.property() == Some(property))
+        .map(|e| e.to_string())
+        .collect();
+
+    match errors.is_empty() {
+        true => None,
+        false => Some(errors.join("\n")),
+    }
+}
+
+/// Ask a yes or no question
+pub(super) fn prompt_yes_no(
+    input: &mut impl BufRead,
+    question: &str,
+    default: bool,
+) -> anyhow::Result<bool> {
+    let default_str = if default { "y" } else { "n" };
+    loop {
+        match prompt(input, &format!("{} (y/n)", question), Some(default_str))?
+            .to_lowercase()
+            .as_str()
+        {
+            "y" | "yes" => return Ok(true),
+            "n" | "no" => return Ok(false),
+            _ => println!("Please answer `y` or `n`."),
+        }
+    }
+}
+
+/// Ask the user a question and return their (non-empty) answer
+fn prompt(
+    input: &mut impl BufRead,
+    question: &str,
+    default: Option<&str>,
+) -> anyhow::Result<String> {
+    loop {
+        match default {
+            Some(d) => print!("{} [{}]: ", question, d),
+            None => print!("{}: ", question),
+        }
+        io::stdout().flush()?;
+
+        let mut answer = String::new();
+        if input.read_line(&mut answer)? == 0 {
+            bail!("No more input was given.");
+        }
+
+        match (answer.trim(), default) {
+            ("", Some(d)) => return Ok(d.to_string()),
+            ("", None) => println!("A value is required."),
+            (a, _) => return Ok(a.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// Answer the wizard with each line of `answers`, and read back the configuration it wrote
+    fn wizard(answers: &[&str]) -> anyhow::Result<String> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config").join("quill.toml");
+        let dir_str = dir.path().display().to_string();
+        let input = format!("{}\n", answers.join("\n")).replace("{dir}", &dir_str);
+
+        write_config(&mut input.as_bytes(), &path)?;
+        let written = fs::read_to_string(&path).unwrap();
+
+        Ok(written.replace(&dir_str, "{dir}"))
+    }
+
+    #[test]
+    fn single_account_with_defaults() {
+        let written =
+            wizard(&["My Chequing", "", "Bank", "{dir}", "", "2021-01-15", "", ""]).unwrap();
+
+        assert_eq!(
+            written,
+            r#"[Accounts]
+
+[Accounts.My-Chequing]
+name = "My Chequing"
+institution = "Bank"
+statement_fmt = "%Y-%m-%d.pdf"
+dir = "{dir}"
+statement_period = [15, "Day", 1, "Month"]
+first_date = 2021-01-15
+"#
+        );
+    }
+
+    #[test]
+    fn invalid_answers_are_asked_again() {
+        let written = wizard(&[
+            // first account, quarterly
+            "Chequing",
+            "chequing",
+            "Bank",
+            "{dir}",
+            "%Y-%m-%d.pdf",
+            "2021-01-01",
+            "2",
+            "y",
+            // second account, with a key that's taken, a missing directory, a date that
+            // isn't one, an unknown choice, and a period that isn't an array, which asks
+            // for the choice again
+            "Visa",
+            "chequing",
+            "visa",
+            "Bank",
+            "{dir}/does-not-exist",
+            "{dir}",
+            "",
+            "2021-02-30",
+            "2021-02-01",
+            "4",
+            "3",
+            "[1, \"Day\"",
+            "3",
+            "[1, \"Day\", 1, \"Month\"]",
+            "n",
+        ])
+        .unwrap();
+
+        assert_eq!(
+            written,
+            r#"[Accounts]
+
+[Accounts.chequing]
+name = "Chequing"
+institution = "Bank"
+statement_fmt = "%Y-%m-%d.pdf"
+dir = "{dir}"
+statement_period = [1, "Day", 3, "Month"]
+first_date = 2021-01-01
+
+[Accounts.visa]
+name = "Visa"
+institution = "Bank"
+statement_fmt = "%Y-%m-%d.pdf"
+dir = "{dir}"
+statement_period = [1, "Day", 1, "Month"]
+first_date = 2021-02-01
+"#
+        );
+    }
+
+    #[test]
+    fn input_ends_early() {
+        let e = wizard(&["Chequing", "", "Bank"]).unwrap_err();
+
+        assert_eq!(e.to_string(), "No more input was given.");
+    }
+}
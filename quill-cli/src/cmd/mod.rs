@@ -0,0 +1,121 @@
+//! Subcommands that run without the terminal user interface.
+
+use crate::{
+    cfg::Config,
+    cli::{AccountCommand, CliOpts, Command, PeriodCommand, ReportCommand},
+};
+use std::time::Duration;
+
+mod account;
+mod archive;
+mod check;
+mod daemon;
+mod diff;
+mod digest;
+mod doctor;
+mod export;
+mod fetch;
+mod history;
+mod ignore;
+mod init;
+mod period;
+mod rename;
+mod report;
+mod sort_inbox;
+mod upcoming;
+mod validate;
+mod verify;
+
+/// Run the subcommand given on the command line
+pub fn run(opts: &CliOpts, cmd: &Command) -> anyhow::Result<()> {
+    match cmd {
+        Command::Check {
+            account,
+            max_missing,
+        } => check::check(&Config::try_from(opts)?, account.as_deref(), *max_missing),
+        Command::Daemon { interval, api_port } => daemon::daemon(
+            &opts.configs()?,
+            opts.lenient(),
+            interval.map(Duration::from_secs),
+            *api_port,
+        ),
+        Command::Diff { no_save } => diff::diff(&Config::try_from(opts)?, *no_save),
+        Command::Digest { force } => digest::digest(&Config::try_from(opts)?, *force),
+        // the history is only read, so the statements don't need to be scanned for
+        Command::History { account, date } => history::history(
+            &Config::read_all(&opts.configs()?, opts.lenient())?,
+            account.as_deref(),
+            *date,
+        ),
+        Command::Rename {
+            account,
+            from,
+            dir,
+            dry_run,
+        } => rename::rename(
+            &Config::try_from(opts)?,
+            account,
+            from,
+            dir.as_deref(),
+            *dry_run,
+        ),
+        Command::SortInbox { dir, dry_run, yes } => {
+            sort_inbox::sort_inbox(&Config::try_from(opts)?, dir.as_deref(), *dry_run, *yes)
+        }
+        Command::Ignore {
+            account,
+            from,
+            to,
+            range,
+            reason,
+            dry_run,
+        } => ignore::ignore(
+            &Config::try_from(opts)?,
+            account,
+            from,
+            to,
+            *range,
+            reason.as_deref(),
+            *dry_run,
+        ),
+        Command::Upcoming { days } => upcoming::upcoming(&Config::try_from(opts)?, *days),
+        // the configuration can't be loaded before validating or creating it
+        Command::Validate => opts
+            .configs()?
+            .iter()
+            .try_for_each(|path| validate::validate(path)),
+        Command::Doctor => opts
+            .configs()?
+            .iter()
+            .try_for_each(|path| doctor::doctor(path)),
+        Command::Export { format } => export::export(&Config::try_from(opts)?, *format),
+        Command::Fetch { account } => {
+            fetch::fetch(&mut Config::try_from(opts)?, account.as_deref())
+        }
+        Command::Archive { account, dry_run } => {
+            archive::archive(&Config::try_from(opts)?, account.as_deref(), *dry_run)
+        }
+        Command::Verify { account } => verify::verify(&Config::try_from(opts)?, account.as_deref()),
+        Command::Init { output, force } => {
+            // a new profile's configuration file is written where it will be looked for
+            let profile_file = opts.profile_config_file();
+            init::init(output.as_deref().or(profile_file.as_deref()), *force)
+        }
+        Command::Account { command } => match command {
+            AccountCommand::Add(args) => account::add(&opts.config()?, args),
+        },
+        Command::Period { command } => match command {
+            PeriodCommand::Preview {
+                period,
+                schedule,
+                from,
+                count,
+            } => period::preview(period.as_deref(), schedule.as_deref(), *from, *count),
+        },
+        Command::Report { command } => match command {
+            ReportCommand::Completeness { account } => {
+                report::completeness(&Config::try_from(opts)?, account.as_deref())
+            }
+        },
+    }
+}
@@ -0,0 +1,37 @@
+//! Try out statement periods without changing the configuration file.
+
+use super::account::period_property;
+use anyhow::Context;
+use chrono::{Local, NaiveDate};
+use quill_account::parse::{describe_statement_period, parse_statement_period};
+use quill_statement::upcoming_dates;
+use toml::{map::Map, Value};
+
+/// Print the first `count` dates a statement period or schedule would expect statements on,
+/// starting with `from`, just as the dates of an account start with its `first_date`.
+pub fn preview(
+    period: Option<&str>,
+    schedule: Option<&str>,
+    from: Option<NaiveDate>,
+    count: usize,
+) -> anyhow::Result<()> {
+    let (period_key, period) = period_property(period, schedule)?;
+    let mut props = Map::new();
+    props.insert(period_key.to_string(), period);
+    let props = Value::Table(props);
+
+    let period = parse_statement_period(&props).context("Invalid statement period.")?;
+    let from = from.unwrap_or_else(|| Local::now().naive_local().date());
+
+    if let Some(description) = describe_statement_period(&props) {
+        println!("Statements are expected on the {}:", description);
+    }
+    for date in std::iter::once(from)
+        .chain(upcoming_dates(&from, &period.sequence()))
+        .take(count)
+    {
+        println!("    {} ({})", date, date.format("%a"));
+    }
+
+    Ok(())
+}
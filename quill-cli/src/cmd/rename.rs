@@ -0,0 +1,73 @@
+//! Rename downloaded statements to follow an account's file name format.
+
+use crate::cfg::Config;
+use anyhow::{bail, Context};
+use quill_statement::plan_renames;
+use std::{fs, path::Path};
+
+/// Rename the files in `dir` (or the account's directory) whose names contain
+/// a date following `from_fmt`, so that they follow the account's format and
+/// are in the account's directory.
+pub fn rename(
+    conf: &Config,
+    key: &str,
+    from_fmt: &str,
+    dir: Option<&Path>,
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    let acct = match conf.accounts().get(key) {
+        Some(a) => a,
+        None => bail!(
+            "No account with key `{}` was found. Available accounts are: {}.",
+            key,
+            conf.keys().join(", ")
+        ),
+    };
+
+    let src_dir = dir.unwrap_or_else(|| acct.directory());
+    let files: Vec<_> = fs::read_dir(src_dir)
+        .with_context(|| format!("Error reading directory `{}`.", src_dir.display()))?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|p| p.is_file())
+        .collect();
+
+    let renames = plan_renames(&files, from_fmt, acct.directory(), acct.format_string());
+    if renames.is_empty() {
+        println!(
+            "No files in `{}` need renaming from `{}` to `{}`.",
+            src_dir.display(),
+            from_fmt,
+            acct.format_string()
+        );
+        return Ok(());
+    }
+
+    let mut failed = 0;
+    for r in &renames {
+        println!("{} -> {}", r.from().display(), r.to().display());
+
+        if dry_run {
+            if r.to().exists() {
+                println!("    would be skipped, since the file already exists");
+            }
+        } else if let Err(e) = r.apply() {
+            println!("    {}", e);
+            failed += 1;
+        }
+    }
+
+    if failed > 0 {
+        bail!(
+            "{} of {} file(s) could not be renamed.",
+            failed,
+            renames.len()
+        );
+    }
+
+    if dry_run {
+        println!("\nNothing was renamed. Run again without `--dry-run` to rename these files.");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,122 @@
+//! Summarize how complete the accounts' statements are.
+
+use crate::cfg::Config;
+use anyhow::bail;
+use chrono::Local;
+use quill_statement::{completeness_by_year, missing_by_age, AccountKey, Completeness};
+
+/// How many characters wide the bars of the charts are
+const BAR_WIDTH: usize = 20;
+
+/// Print how many of the expected statements are available overall and in each year,
+/// a chart of how long ago the missing statements were due, and then the same for each account.
+/// If an account is given, only it and its named schedules are reported on.
+pub fn completeness(conf: &Config, account: Option<&str>) -> anyhow::Result<()> {
+    let title = match account {
+        Some(key) => match conf.accounts().get(key) {
+            Some(acct) => acct.name(),
+            None => bail!("No account with the key `{}` exists.", key),
+        },
+        None => "All accounts",
+    };
+    // an account's named schedules are keyed under it, like `<key>/<schedule>`
+    let in_scope = |key: &str| match account {
+        Some(acct) => key == acct || key.strip_prefix(acct).map_or(false, |k| k.starts_with('/')),
+        None => true,
+    };
+    let keys: Vec<&AccountKey> = conf
+        .keys()
+        .iter()
+        .filter(|key| in_scope(key.as_str()))
+        .collect();
+
+    let stmts = conf.statements();
+    let today = Local::now().naive_local().date();
+    let (counts, years, ages) = match account {
+        None => (
+            stmts.overall_completeness_counts(),
+            stmts.overall_completeness_by_year(),
+            stmts.missing_by_age(&today),
+        ),
+        Some(_) => {
+            let in_scope_stmts = || {
+                keys.iter()
+                    .filter_map(|key| stmts.get(key.as_str()))
+                    .flatten()
+            };
+            (
+                Completeness::tally(in_scope_stmts()),
+                completeness_by_year(in_scope_stmts()),
+                missing_by_age(in_scope_stmts(), &today),
+            )
+        }
+    };
+
+    println!("{}", describe(title, &counts));
+    print_years(&years);
+
+    if counts.missing() > 0 {
+        println!("\nMissing statements by how long ago they were due:");
+        let most = ages.iter().map(|(_, n)| *n).max().unwrap_or_default();
+        for (age, n) in &ages {
+            let fraction = match most {
+                0 => 0.0,
+                _ => *n as f64 / most as f64,
+            };
+            println!("  {:<11}  {}  {}", age.to_string(), bar(fraction), n);
+        }
+    }
+
+    // a single account is already described by the summary
+    if keys.len() > 1 {
+        for key in &keys {
+            let name = conf
+                .accounts()
+                .get(key.as_str())
+                .map_or(key.as_str(), |a| a.name());
+            println!("\n{}", describe(name, &stmts.completeness_counts(key)));
+            print_years(&stmts.completeness_by_year(key));
+        }
+    }
+
+    Ok(())
+}
+
+/// Describe how many statements are available, like "Bank: 90.0% complete, 18 of 20 expected
+/// statements (2 missing)"
+fn describe(title: &str, counts: &Completeness) -> String {
+    match counts.fraction() {
+        Some(fraction) => format!(
+            "{}: {:.1}% complete, {} of {} expected statements ({} missing)",
+            title,
+            fraction * 100.0,
+            counts.available(),
+            counts.expected(),
+            counts.missing()
+        ),
+        None => format!("{}: no statements are expected yet", title),
+    }
+}
+
+/// Print a bar for how complete each year is
+fn print_years(years: &[(i32, Completeness)]) {
+    for (year, counts) in years {
+        // every year that's listed has expected statements
+        let fraction = counts.fraction().unwrap_or_default();
+        println!(
+            "  {}  {}  {:>5.1}%  {} of {}",
+            year,
+            bar(fraction),
+            fraction * 100.0,
+            counts.available(),
+            counts.expected()
+        );
+    }
+}
+
+/// Draw a bar that's filled in for a fraction of its width, like `###############-----`
+fn bar(fraction: f64) -> String {
+    let filled = ((fraction * BAR_WIDTH as f64).round() as usize).min(BAR_WIDTH);
+
+    format!("{}{}", "#".repeat(filled), "-".repeat(BAR_WIDTH - filled))
+}
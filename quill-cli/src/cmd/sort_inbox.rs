@@ -0,0 +1,106 @@
+//! Sort downloaded statements into the directories of the accounts they belong to.
+
+use super::init::prompt_yes_no;
+use crate::{
+    cfg::Config,
+    inbox::{inbox_files, sort_inbox as sort_files, Sorting},
+};
+use anyhow::{bail, Context};
+use quill_statement::StatementRename;
+use std::{io, path::Path};
+
+/// Move the files in `dir` (or the inbox) whose names match the formats of a single account
+/// into its directory, named by its `statement_fmt`, once the user agrees to.
+pub fn sort_inbox(
+    conf: &Config,
+    dir: Option<&Path>,
+    dry_run: bool,
+    yes: bool,
+) -> anyhow::Result<()> {
+    let dir = match dir.map(Path::to_path_buf).or_else(|| conf.inbox().dir()) {
+        Some(dir) => dir,
+        None => bail!("No `dir` is set in the `[Inbox]` table of the configuration file."),
+    };
+
+    // patterns for accounts that don't exist are probably typos
+    for key in conf.inbox().pattern_keys() {
+        if !conf.accounts().contains_key(key) {
+            println!(
+                "The `[Inbox]` table has patterns for `{}`, but there's no account with that key.",
+                key
+            );
+        }
+    }
+
+    let files = inbox_files(&dir)
+        .with_context(|| format!("Error reading directory `{}`.", dir.display()))?;
+    let sorted = sort_files(conf, &files);
+
+    let mut moves: Vec<&StatementRename> = vec![];
+    let mut unmatched = 0;
+    for file in &sorted {
+        match file.sorting() {
+            Sorting::Move(key, rename) => {
+                println!(
+                    "{} -> {} ({})",
+                    rename.from().display(),
+                    rename.to().display(),
+                    conf.accounts()[key].name()
+                );
+                if rename.to().exists() {
+                    println!("    will be skipped, since the file already exists");
+                }
+                moves.push(rename);
+            }
+            Sorting::Ambiguous(keys) => {
+                let keys: Vec<&str> = keys.iter().map(|key| key.as_str()).collect();
+                println!(
+                    "{} could belong to {}, so it's left where it is",
+                    file.path().display(),
+                    keys.join(", ")
+                );
+            }
+            Sorting::Unmatched => unmatched += 1,
+        }
+    }
+    if unmatched > 0 {
+        println!(
+            "{} file(s) don't match the formats of any account.",
+            unmatched
+        );
+    }
+
+    if moves.is_empty() {
+        println!(
+            "No files in `{}` could be sorted into an account.",
+            dir.display()
+        );
+        return Ok(());
+    }
+
+    if dry_run {
+        println!("\nNothing was moved. Run again without `--dry-run` to move these files.");
+        return Ok(());
+    }
+
+    let question = format!("\nMove {} file(s)?", moves.len());
+    if !yes && !prompt_yes_no(&mut io::stdin().lock(), &question, false)? {
+        println!("Nothing was moved.");
+        return Ok(());
+    }
+
+    let mut failed = 0;
+    for rename in &moves {
+        if let Err(e) = rename.apply() {
+            println!("{}", e);
+            failed += 1;
+        }
+    }
+
+    if failed > 0 {
+        bail!("{} of {} file(s) could not be moved.", failed, moves.len());
+    }
+    println!("Moved {} file(s).", moves.len());
+
+    Ok(())
+}
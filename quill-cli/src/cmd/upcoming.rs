@@ -0,0 +1,18 @@
+//! List the statements that are expected in the future.
+
+use crate::cfg::Config;
+use chrono::{Duration, Local};
+
+/// Print the upcoming statements for every account, up to `days` from today.
+/// The next statement for each account is always shown.
+pub fn upcoming(conf: &Config, days: u32) -> anyhow::Result<()> {
+    let horizon = Local::now().naive_local().date() + Duration::days(days.into());
+
+    for (key, date) in conf.statements().upcoming(&horizon) {
+        if let Some(acct) = conf.accounts().get(key) {
+            println!("{}  {}", conf.display().date(date), acct.name());
+        }
+    }
+
+    Ok(())
+}
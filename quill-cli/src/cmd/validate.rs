@@ -0,0 +1,232 @@
+//! Check a configuration file for problems without loading it.
+
+use crate::cfg::{DaemonConfig, DigestConfig, Theme, TuiConfig};
+use anyhow::{bail, Context};
+use quill_account::Account;
+use quill_utils::parse_toml_file;
+use std::path::Path;
+use toml::Value;
+
+/// A single problem found in the configuration file
+struct Problem {
+    /// Line of the configuration file where the problem is, if known
+    line: Option<usize>,
+
+    /// Table of the configuration file where the problem is
+    section: String,
+
+    /// Description of the problem
+    message: String,
+}
+
+impl Problem {
+    fn new(line: Option<usize>, section: &str, message: &str) -> Self {
+        Self {
+            line,
+            section: section.to_string(),
+            message: message.to_string(),
+        }
+    }
+}
+
+/// Check the configuration file and report every problem found at once.
+pub fn validate(path: &Path) -> anyhow::Result<()> {
+    if !path.exists() {
+        bail!("Configuration file `{}` does not exist.", path.display());
+    }
+
+    let config_str = parse_toml_file(path).with_context(|| {
+        format!(
+            "Error reading contents of configuration file `{}`.",
+            path.display()
+        )
+    })?;
+
+    let problems = find_problems(&config_str);
+    if problems.is_empty() {
+        println!("No problems found in `{}`.", path.display());
+
+        // so the periods can be checked against what was intended
+        let periods = describe_periods(&config_str);
+        if !periods.is_empty() {
+            println!("\nStatements are expected on the:");
+            for (key, period) in periods {
+                println!("    {}: {}", key, period);
+            }
+        }
+        return Ok(());
+    }
+
+    for problem in &problems {
+        let location = match problem.line {
+            Some(line) => format!("{}:{}", path.display(), line),
+            None => path.display().to_string(),
+        };
+        // indent multi-line messages so each problem stands out
+        let message = problem.message.replace('\n', "\n    ");
+
+        println!("{} [{}]\n    {}", location, problem.section, message);
+    }
+
+    bail!(
+        "Found {} problem(s) in configuration file `{}`.",
+        problems.len(),
+        path.display()
+    )
+}
+
+/// Find every problem in the contents of a configuration file
+fn find_problems(config_str: &str) -> Vec<Problem> {
+    let config_toml = match config_str.parse::<Value>() {
+        Ok(Value::Table(t)) => t,
+        Ok(_) => {
+            return vec![Problem::new(
+                None,
+                "",
+                "The configuration is not a TOML table.",
+            )]
+        }
+        Err(e) => {
+            // nothing else can be checked if the TOML itself is invalid
+            let line = e.line_col().map(|(line, _)| line + 1);
+            return vec![Problem::new(line, "", &e.to_string())];
+        }
+    };
+
+    let mut problems = vec![];
+
+    if let Some(digest) = config_toml.get("Digest") {
+        if let Err(e) = digest.clone().try_into::<DigestConfig>() {
+            let line = find_table_line(config_str, "Digest");
+            problems.push(Problem::new(line, "Digest", &e.to_string()));
+        }
+    }
+
+    if let Some(daemon) = config_toml.get("Daemon") {
+        if let Err(e) = daemon.clone().try_into::<DaemonConfig>() {
+            let line = find_table_line(config_str, "Daemon");
+            problems.push(Problem::new(line, "Daemon", &e.to_string()));
+        }
+    }
+
+    if let Some(theme) = config_toml.get("Theme") {
+        if let Err(e) = theme.clone().try_into::<Theme>() {
+            let line = find_table_line(config_str, "Theme");
+            problems.push(Problem::new(line, "Theme", &e.to_string()));
+        }
+    }
+
+    if let Some(tui) = config_toml.get("Tui") {
+        if let Err(e) = tui.clone().try_into::<TuiConfig>() {
+            let line = find_table_line(config_str, "Tui");
+            problems.push(Problem::new(line, "Tui", &e.to_string()));
+        }
+    }
+
+    match config_toml.get("Accounts") {
+        Some(Value::Table(accounts)) => {
+            for (key, props) in accounts {
+                let section = format!("Accounts.{}", key);
+                let table_line = find_table_line(config_str, &section);
+
+                if !props.is_table() {
+                    problems.push(Problem::new(
+                        table_line,
+                        &section,
+                        "Account properties must be a table.",
+                    ));
+                    continue;
+                }
+
+                for err in Account::validate(props) {
+                    let line = match err.property() {
+                        Some(prop) => find_property_line(config_str, &section, prop),
+                        None => None,
+                    };
+
+                    problems.push(Problem::new(
+                        line.or(table_line),
+                        &section,
+                        &err.to_string(),
+                    ));
+                }
+            }
+        }
+        Some(_) => problems.push(Problem::new(
+            find_table_line(config_str, "Accounts"),
+            "Accounts",
+            "`Accounts` must be a table.",
+        )),
+        None => problems.push(Problem::new(
+            None,
+            "Accounts",
+            "No `[Accounts]` table found.",
+        )),
+    }
+
+    problems
+}
+
+/// Describe the statement period of each account, and of each of their named schedules, in words
+fn describe_periods(config_str: &str) -> Vec<(String, String)> {
+    let accounts = match config_str.parse::<Value>() {
+        Ok(Value::Table(mut config_toml)) => match config_toml.remove("Accounts") {
+            Some(Value::Table(accounts)) => accounts,
+            _ => return vec![],
+        },
+        _ => return vec![],
+    };
+
+    let mut periods = vec![];
+    for (key, props) in &accounts {
+        let acct = match Account::try_from_unchecked(props) {
+            Ok(acct) => acct,
+            Err(_) => continue,
+        };
+        if let Some(period) = acct.period_description() {
+            periods.push((key.clone(), period));
+        }
+        for sched in acct.schedules() {
+            if let (Some(name), Some(period)) = (sched.schedule(), sched.period_description()) {
+                periods.push((format!("{}.{}", key, name), period));
+            }
+        }
+    }
+
+    periods
+}
+
+/// Strip whitespace and quotes so that table headers can be compared
+fn normalize_header(s: &str) -> String {
+    s.chars()
+        .filter(|c| !c.is_whitespace() && (*c != '"') && (*c != '\''))
+        .collect()
+}
+
+/// Find the (1-based) line where a table is declared
+fn find_table_line(config_str: &str, section: &str) -> Option<usize> {
+    let header = format!("[{}]", normalize_header(section));
+
+    config_str
+        .lines()
+        .position(|line| normalize_header(line) == header)
+        .map(|idx| idx + 1)
+}
+
+/// Find the (1-based) line where a property of a table is set
+fn find_property_line(config_str: &str, section: &str, prop: &str) -> Option<usize> {
+    let start = find_table_line(config_str, section)?;
+
+    config_str
+        .lines()
+        .enumerate()
+        .skip(start)
+        // stop looking once the next table starts
+        .take_while(|(_, line)| !line.trim_start().starts_with('['))
+        .find(|(_, line)| {
+            line.trim_start()
+                .strip_prefix(prop)
+                .map_or(false, |rest| rest.trim_start().starts_with('='))
+        })
+        .map(|(idx, _)| idx + 1)
+}
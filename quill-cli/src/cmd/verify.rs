@@ -0,0 +1,89 @@
+//! Check the accounts' statements against their recorded checksums.
+
+use crate::cfg::Config;
+use anyhow::bail;
+use quill_account::checksums::{Checksums, FileCheck};
+use quill_statement::AccountKey;
+
+/// Check the statements of a single account, or of every account that records
+/// checksums, and report the ones that have been modified or have gone missing.
+/// Statements moved into the account's archive directory are checked there.
+/// The checksums of new statements were already recorded when the configuration was loaded.
+pub fn verify(conf: &Config, account: Option<&str>) -> anyhow::Result<()> {
+    let keys: Vec<AccountKey> = match account {
+        Some(key) => match conf.accounts().get(key) {
+            Some(acct) if acct.checksums() => vec![AccountKey::new(key)],
+            Some(_) => bail!("Account `{}` doesn't record `checksums`.", key),
+            None => bail!("No account with the key `{}` exists.", key),
+        },
+        // named schedules share their account's directory, so only check it once
+        None => conf
+            .keys()
+            .iter()
+            .filter(|key| {
+                conf.accounts().get(*key).map_or(false, |acct| {
+                    acct.checksums() && acct.schedule().is_none() && !acct.is_remote()
+                })
+            })
+            .cloned()
+            .collect(),
+    };
+
+    if keys.is_empty() {
+        println!("No accounts record `checksums`.");
+        return Ok(());
+    }
+
+    let mut checked = 0;
+    let mut problems = 0;
+    for key in &keys {
+        let acct = &conf.accounts()[key];
+        let dirs = std::iter::once(acct.directory()).chain(acct.archive_dir());
+        let mut results = vec![];
+        for dir in dirs {
+            let sums = Checksums::load(dir)?;
+            results.extend(
+                sums.verify(dir)
+                    .into_iter()
+                    .map(|(name, check)| (dir.join(name), check)),
+            );
+        }
+        checked += results.len();
+
+        let messages: Vec<String> = results
+            .into_iter()
+            .filter_map(|(path, check)| match check {
+                FileCheck::Unchanged => None,
+                FileCheck::Modified => Some(format!(
+                    "`{}` has been modified or corrupted since its checksum was recorded.",
+                    path.display()
+                )),
+                FileCheck::Missing => Some(format!(
+                    "`{}` has gone missing since its checksum was recorded.",
+                    path.display()
+                )),
+            })
+            .collect();
+        if messages.is_empty() {
+            continue;
+        }
+
+        println!("[Accounts.{}] {}", key, acct.name());
+        for message in &messages {
+            println!("    {}", message);
+        }
+        problems += messages.len();
+    }
+
+    if problems > 0 {
+        bail!(
+            "{} of {} statement(s) don't match their checksums.",
+            problems,
+            checked
+        );
+    }
+
+    println!("All {} statement(s) match their checksums.", checked);
+
+    Ok(())
+}
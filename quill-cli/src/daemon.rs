@@ -0,0 +1,109 @@
+//! Share the statements found by `quill daemon`, so they can be shown without scanning.
+
+use crate::{
+    cfg::Config,
+    snapshot::{daemon_snapshot_path, Snapshot},
+};
+use anyhow::Context;
+use chrono::{Local, NaiveDateTime};
+use quill_statement::StatementCollection;
+use quill_utils::create_parent_dir;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::Path,
+    time::Duration,
+};
+use tracing::debug;
+
+/// The statements found by the daemon's latest scan, and how often it scans
+#[derive(Debug, Deserialize, Serialize)]
+pub struct DaemonSnapshot {
+    /// How long the daemon waits between scans, in seconds
+    interval: u64,
+
+    /// The statements found by the latest scan
+    snapshot: Snapshot,
+}
+
+impl DaemonSnapshot {
+    /// Take a snapshot of the statements just found, by a daemon that scans every `interval`
+    pub fn new(statements: &StatementCollection, interval: Duration) -> Self {
+        Self {
+            interval: interval.as_secs(),
+            snapshot: Snapshot::new(statements),
+        }
+    }
+
+    /// The statements found by the latest scan
+    pub fn snapshot(&self) -> &Snapshot {
+        &self.snapshot
+    }
+
+    /// Check if the daemon should still be scanning regularly at `now`.
+    /// A slow scan can delay the next snapshot, so it's only stale after two intervals.
+    pub fn is_fresh(&self, now: &NaiveDateTime) -> bool {
+        match (*now - *self.snapshot.created()).to_std() {
+            Ok(age) => age <= Duration::from_secs(self.interval.saturating_mul(2)),
+            // the clock has gone backwards since, so it was taken very recently
+            Err(_) => true,
+        }
+    }
+
+    /// Check if the snapshot has statements for exactly the accounts in `conf`.
+    /// A daemon for another profile's accounts saves its snapshot in the same place.
+    pub fn matches(&self, conf: &Config) -> bool {
+        let stmts = self.snapshot.statements();
+
+        stmts.iter().count() == conf.keys().len()
+            && conf.keys().iter().all(|key| stmts.get(key).is_some())
+    }
+
+    /// Load the snapshot from a file, if one has been saved
+    pub fn load(path: &Path) -> anyhow::Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let file = File::open(path)
+            .with_context(|| format!("Error opening snapshot file `{}`.", path.display()))?;
+        let snapshot = serde_json::from_reader(BufReader::new(file))
+            .with_context(|| format!("Error reading snapshot file `{}`.", path.display()))?;
+
+        Ok(Some(snapshot))
+    }
+
+    /// Save the snapshot to a file, creating its directory if necessary
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        create_parent_dir(path)
+            .with_context(|| format!("Error creating the directory for `{}`.", path.display()))?;
+
+        let file = File::create(path)
+            .with_context(|| format!("Error creating snapshot file `{}`.", path.display()))?;
+        serde_json::to_writer(BufWriter::new(file), self)
+            .with_context(|| format!("Error writing snapshot file `{}`.", path.display()))?;
+
+        Ok(())
+    }
+}
+
+/// The statements found by the daemon's latest scan, if it's still scanning regularly
+/// for the same accounts as `conf`
+pub fn latest_statements(conf: &Config) -> anyhow::Result<Option<StatementCollection>> {
+    let latest = match DaemonSnapshot::load(&daemon_snapshot_path()?)? {
+        Some(latest) => latest,
+        None => return Ok(None),
+    };
+
+    if !latest.is_fresh(&Local::now().naive_local()) {
+        debug!("the daemon's snapshot is out of date");
+        return Ok(None);
+    }
+    if !latest.matches(conf) {
+        debug!("the daemon's snapshot is for different accounts");
+        return Ok(None);
+    }
+
+    Ok(Some(latest.snapshot().statements().clone()))
+}
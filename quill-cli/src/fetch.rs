@@ -0,0 +1,60 @@
+//! Run the commands that download accounts' statements.
+
+use crate::cfg::Config;
+use quill_account::Account;
+use quill_statement::{CollectionDiff, StatementChange, StatementCollection};
+use std::process::Command;
+
+/// The command that downloads an account's statements, if it has one.
+///
+/// The `fetch_cmd` is run by the shell from the account's directory, with
+/// `QUILL_ACCOUNT` set to the account's key and `QUILL_DIR` to its directory.
+/// For remote directories, it's run from the current directory instead.
+pub fn fetch_command(key: &str, acct: &Account) -> Option<Command> {
+    let mut cmd = shell_command(acct.fetch_cmd()?);
+    cmd.env("QUILL_ACCOUNT", key)
+        .env("QUILL_DIR", acct.directory());
+    if !acct.is_remote() {
+        cmd.current_dir(acct.directory());
+    }
+
+    Some(cmd)
+}
+
+/// A command line that's run by the shell
+pub fn shell_command(cmd_line: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", cmd_line]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", cmd_line]);
+        cmd
+    }
+}
+
+/// The key of the account whose `fetch_cmd` downloads an account's statements.
+/// Named schedules share their account's directory, so their statements are fetched by it.
+pub fn fetching_key<'k>(conf: &Config, key: &'k str) -> &'k str {
+    match conf.accounts().get(key).and_then(|acct| acct.schedule()) {
+        Some(_) => key.split('/').next().unwrap_or(key),
+        None => key,
+    }
+}
+
+/// The statements of an account, and its named schedules, that became available after fetching
+pub fn fetched_statements(
+    key: &str,
+    before: &StatementCollection,
+    after: &StatementCollection,
+) -> Vec<StatementChange> {
+    let schedule_prefix = format!("{}/", key);
+
+    CollectionDiff::new(before, after)
+        .newly_available()
+        .iter()
+        .filter(|(k, _)| k == key || k.starts_with(&schedule_prefix))
+        .cloned()
+        .collect()
+}
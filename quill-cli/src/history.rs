@@ -0,0 +1,167 @@
+//! Keep a ledger of how each statement has changed over time, for `quill history`.
+//!
+//! Every scan is compared against the one before it, and each statement that appeared,
+//! went missing, was ignored, was renamed, or was removed is appended to the ledger as a
+//! single line of JSON.
+//! The ledger is only ever appended to, so it lives in the data directory, unlike the
+//! snapshots that can be thrown away.
+
+use crate::snapshot::{history_snapshot_path, Snapshot};
+use anyhow::Context;
+use chrono::{Local, NaiveDateTime};
+use quill_account::{archive::split_archive_path, storage::is_remote, Account};
+use quill_statement::{
+    statement_transitions, AccountKey, StatementCollection, StatementTransition,
+};
+use quill_utils::{create_parent_dir, project_dirs};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+};
+use tracing::{debug, warn};
+
+const HISTORY_FILE: &str = "history.jsonl";
+
+/// A change to a statement, and when it was noticed
+#[derive(Debug, Deserialize, Serialize)]
+pub struct HistoryEntry {
+    /// When the scan that noticed the change finished
+    time: NaiveDateTime,
+
+    /// How the statement changed
+    #[serde(flatten)]
+    transition: StatementTransition,
+}
+
+impl HistoryEntry {
+    /// When the scan that noticed the change finished
+    pub fn time(&self) -> &NaiveDateTime {
+        &self.time
+    }
+
+    /// How the statement changed
+    pub fn transition(&self) -> &StatementTransition {
+        &self.transition
+    }
+}
+
+/// Append how the statements changed since the last scan to the ledger, and remember the
+/// `current` statements for the next one.
+/// Returns how many changes were recorded.
+///
+/// Statements whose files were found before, but have since disappeared, are marked as lost
+/// first, so that they don't quietly go back to being missing.
+/// Statements whose files are still there, but aren't matched anymore, are only missing.
+/// Nothing is recorded the first time this is run, since there is nothing to compare against.
+pub fn record(
+    accounts: &HashMap<AccountKey, Account>,
+    current: &mut StatementCollection,
+) -> anyhow::Result<usize> {
+    let snapshot_path = history_snapshot_path()?;
+    let previous = Snapshot::load(&snapshot_path)?;
+    if let Some(previous) = &previous {
+        let lost = current.mark_lost(previous.statements(), file_exists);
+        if lost > 0 {
+            warn!(lost, "statement files have disappeared since the last scan");
+        }
+    }
+    let mut current = Snapshot::new(current).with_paused(accounts, previous.as_ref());
+
+    let transitions = match &previous {
+        Some(previous) => statement_transitions(previous.statements(), current.statements()),
+        None => vec![],
+    };
+
+    // another profile's accounts keep their statements, so switching back to it
+    // doesn't look like they all appeared again
+    if let Some(previous) = &previous {
+        current = current.with_others(previous);
+    }
+
+    if !transitions.is_empty() {
+        let time = Local::now().naive_local();
+        let mut lines = String::new();
+        for transition in transitions.iter().cloned() {
+            let entry = HistoryEntry { time, transition };
+            lines += &serde_json::to_string(&entry).context("Error writing a history entry.")?;
+            lines.push('\n');
+        }
+        append(&history_path()?, &lines)?;
+        debug!(
+            changes = transitions.len(),
+            "recorded the statement history"
+        );
+    }
+
+    current.save(&snapshot_path)?;
+
+    Ok(transitions.len())
+}
+
+/// Read every entry of the ledger, oldest first.
+/// Lines that can't be read, like one cut short by a crash, are skipped with a warning.
+pub fn load() -> anyhow::Result<Vec<HistoryEntry>> {
+    let path = history_path()?;
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+
+    let file = File::open(&path)
+        .with_context(|| format!("Error opening history file `{}`.", path.display()))?;
+    let mut entries = Vec::new();
+    for (i, line) in BufReader::new(file).lines().enumerate() {
+        let line =
+            line.with_context(|| format!("Error reading history file `{}`.", path.display()))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => warn!(line = i + 1, "skipping an unreadable history entry: {}", e),
+        }
+    }
+
+    Ok(entries)
+}
+
+/// The location of the ledger that `quill history` reads
+pub fn history_path() -> anyhow::Result<PathBuf> {
+    match project_dirs().data_dir() {
+        Some(dir) => Ok(dir.join(HISTORY_FILE)),
+        None => anyhow::bail!("Could not determine a directory to store the statement history in."),
+    }
+}
+
+/// Check if a statement's file is still where it was found.
+/// Files in remote directories can't be checked without listing them again, so they're
+/// assumed to be, and a statement that isn't found there anymore is only missing.
+fn file_exists(path: &Path) -> bool {
+    if is_remote(path) {
+        return true;
+    }
+
+    match split_archive_path(path) {
+        Some((archive, _)) => archive.exists(),
+        None => path.exists(),
+    }
+}
+
+/// Append `lines` to the file at `path` in a single write, so that entries from
+/// two scans finishing at once aren't interleaved
+fn append(path: &Path, lines: &str) -> anyhow::Result<()> {
+    create_parent_dir(path)
+        .with_context(|| format!("Error creating the directory for `{}`.", path.display()))?;
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Error opening history file `{}`.", path.display()))?;
+    file.write_all(lines.as_bytes())
+        .with_context(|| format!("Error writing history file `{}`.", path.display()))?;
+
+    Ok(())
+}
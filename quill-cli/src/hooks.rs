@@ -0,0 +1,222 @@
+//! Run the hooks from the `[Hooks]` table when statements change.
+
+use crate::{cfg::Config, fetch::shell_command};
+use anyhow::{bail, Context};
+use quill_statement::StatementChange;
+use serde_json::json;
+use std::{
+    io::{self, Read, Write},
+    process::{Child, ExitStatus, Stdio},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+use tracing::{info, warn};
+
+/// How often to check whether a hook's command has finished
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Run the `missing` hooks for the statements that went missing, and the
+/// `available` hooks for the statements that became available.
+///
+/// Every hook is run, even if an earlier one fails, and the failures are reported together.
+/// A hook that takes longer than the `[Hooks]` table's `timeout` fails, and its command is killed.
+pub fn run_hooks(
+    conf: &Config,
+    missing: &[StatementChange],
+    available: &[StatementChange],
+) -> anyhow::Result<()> {
+    let mut failures = Vec::new();
+
+    for (event, hooks, changes) in [
+        ("missing", conf.hooks().missing(), missing),
+        ("available", conf.hooks().available(), available),
+    ] {
+        if changes.is_empty() {
+            continue;
+        }
+
+        let payload = payload(conf, event, changes);
+        for hook in hooks {
+            info!(event, hook = %hook, "running hook");
+            if let Err(e) = run_hook(hook, event, &payload, conf.hooks().timeout()) {
+                warn!(event, hook = %hook, "hook failed: {:#}", e);
+                failures.push(format!("`{}`: {:#}", hook, e));
+            }
+        }
+    }
+
+    if !failures.is_empty() {
+        bail!(
+            "{} hook(s) failed:\n    {}",
+            failures.len(),
+            failures.join("\n    ")
+        );
+    }
+
+    Ok(())
+}
+
+/// The JSON payload describing the changed statements
+fn payload(conf: &Config, event: &str, changes: &[StatementChange]) -> String {
+    let statements: Vec<_> = changes
+        .iter()
+        .map(|(key, obs)| {
+            let name = conf
+                .accounts()
+                .get(key.as_str())
+                .map_or(key.as_str(), |a| a.name());
+            // missing statements don't have a file
+            let path = obs.statement().path();
+            let path = (!path.as_os_str().is_empty()).then(|| path.display().to_string());
+            json!({
+                "account": key,
+                "name": name,
+                "date": obs.statement().date().to_string(),
+                "path": path,
+            })
+        })
+        .collect();
+
+    json!({ "event": event, "statements": statements }).to_string()
+}
+
+/// Send the payload to a URL, or to the stdin of a command, giving up after `timeout`
+fn run_hook(hook: &str, event: &str, payload: &str, timeout: Duration) -> anyhow::Result<()> {
+    if hook.starts_with("http://") || hook.starts_with("https://") {
+        post(hook, payload, timeout)
+    } else {
+        run_command(hook, event, payload, timeout)
+    }
+}
+
+/// `POST` the payload to a URL
+fn post(url: &str, payload: &str, timeout: Duration) -> anyhow::Result<()> {
+    let resp = attohttpc::post(url)
+        .header("Content-Type", "application/json")
+        .timeout(timeout)
+        .text(payload)
+        .send()
+        .context("Error sending the request")?;
+
+    if !resp.is_success() {
+        bail!("The request failed with status {}.", resp.status());
+    }
+
+    Ok(())
+}
+
+/// Run a command with the payload on its stdin, and `QUILL_EVENT` set to the kind of change.
+/// The command is killed if it hasn't finished after `timeout`.
+fn run_command(
+    cmd_line: &str,
+    event: &str,
+    payload: &str,
+    timeout: Duration,
+) -> anyhow::Result<()> {
+    let mut child = shell_command(cmd_line)
+        .env("QUILL_EVENT", event)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Error starting the command")?;
+
+    // the payload is written while the output is read, otherwise a command that fills
+    // its stdout before reading all of its stdin would wait on quill forever
+    let stdin = child.stdin.take();
+    let payload = payload.to_string();
+    let writer = thread::spawn(move || {
+        // a command that doesn't read its stdin closes it early, which isn't an error
+        if let Some(mut stdin) = stdin {
+            let _ = stdin.write_all(payload.as_bytes());
+        }
+    });
+    let stdout = read_to_end(child.stdout.take());
+    let stderr = read_to_end(child.stderr.take());
+
+    let status = match wait_until(&mut child, Instant::now() + timeout)
+        .context("Error waiting for the command")?
+    {
+        Some(status) => status,
+        None => {
+            // the command's own children may still hold its output open, so the
+            // threads reading it are left behind rather than waited for
+            let _ = child.kill();
+            let _ = child.wait();
+            bail!(
+                "The command didn't finish within {} second(s), so it was stopped.",
+                timeout.as_secs()
+            );
+        }
+    };
+    let _ = writer.join();
+    info!(
+        stdout = %stdout.join().unwrap_or_default().trim(),
+        stderr = %stderr.join().unwrap_or_default().trim(),
+        "hook finished"
+    );
+
+    if !status.success() {
+        bail!("The command exited with {}.", status);
+    }
+
+    Ok(())
+}
+
+/// Read everything from a command's output on another thread
+fn read_to_end<R: Read + Send + 'static>(pipe: Option<R>) -> JoinHandle<String> {
+    thread::spawn(move || {
+        let mut output = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut output);
+        }
+        String::from_utf8_lossy(&output).into_owned()
+    })
+}
+
+/// Wait for a command to finish, unless it's still running at the `deadline`
+fn wait_until(child: &mut Child, deadline: Instant) -> io::Result<Option<ExitStatus>> {
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Ok(None);
+        }
+        thread::sleep(left.min(POLL_INTERVAL));
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    #[test]
+    fn command_gets_payload_and_event() {
+        let cmd = r#"test "$QUILL_EVENT" = missing && test "$(cat)" = '{"event":"missing"}'"#;
+
+        assert!(run_command(cmd, "missing", r#"{"event":"missing"}"#, TIMEOUT).is_ok());
+        assert!(run_command(cmd, "available", r#"{"event":"missing"}"#, TIMEOUT).is_err());
+    }
+
+    #[test]
+    fn command_writing_before_reading() {
+        // more than a pipe's buffer is written each way, before the payload is read
+        let payload = "x".repeat(1 << 20);
+        let cmd = "head -c 1048576 /dev/zero; cat > /dev/null";
+
+        assert!(run_command(cmd, "missing", &payload, TIMEOUT).is_ok());
+    }
+
+    #[test]
+    fn command_killed_after_timeout() {
+        let started = Instant::now();
+        let e = run_command("sleep 10", "missing", "", Duration::from_millis(200)).unwrap_err();
+
+        assert!(e.to_string().contains("didn't finish"));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
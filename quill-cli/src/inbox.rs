@@ -0,0 +1,109 @@
+//! Sort downloaded statements into the directories of the accounts they belong to.
+//!
+//! Each file in the inbox is matched against the `statement_fmt` of every account,
+//! along with the other formats given for it in the `[Inbox]` table.
+//! A file that only matches one account is moved into its directory and named by its `statement_fmt`.
+
+use crate::cfg::Config;
+use quill_statement::{AccountKey, Statement, StatementRename};
+use std::cmp::Reverse;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Where a file in the inbox belongs
+#[derive(Clone, Debug, PartialEq)]
+pub enum Sorting {
+    /// It's a statement of a single account, so it can be moved into its directory
+    Move(AccountKey, StatementRename),
+    /// Its name matches the formats of several accounts, so it's left where it is
+    Ambiguous(Vec<AccountKey>),
+    /// Its name doesn't match the formats of any account
+    Unmatched,
+}
+
+/// A file in the inbox, and where it belongs
+#[derive(Clone, Debug, PartialEq)]
+pub struct InboxFile {
+    path: PathBuf,
+    sorting: Sorting,
+}
+
+impl InboxFile {
+    /// The path of the file in the inbox
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Where the file belongs
+    pub fn sorting(&self) -> &Sorting {
+        &self.sorting
+    }
+}
+
+/// List the files in the inbox, skipping hidden ones, with the most recently downloaded first
+pub fn inbox_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|p| p.is_file())
+        .filter(|p| {
+            p.file_name()
+                .and_then(|f| f.to_str())
+                .map_or(false, |f| !f.starts_with('.'))
+        })
+        .collect();
+    files.sort_by_cached_key(|p| Reverse(fs::metadata(p).and_then(|m| m.modified()).ok()));
+
+    Ok(files)
+}
+
+/// Work out which account each file belongs to, in the same order as `files`.
+/// Accounts in remote directories are skipped, since files can't be moved into them.
+pub fn sort_inbox(conf: &Config, files: &[PathBuf]) -> Vec<InboxFile> {
+    files
+        .iter()
+        .map(|path| InboxFile {
+            path: path.clone(),
+            sorting: sort_file(conf, path),
+        })
+        .collect()
+}
+
+/// Work out which account a file belongs to
+fn sort_file(conf: &Config, path: &Path) -> Sorting {
+    // only file names that are valid UTF-8 can be parsed for a date
+    if path.file_name().and_then(|f| f.to_str()).is_none() {
+        return Sorting::Unmatched;
+    }
+
+    let mut candidates: Vec<(&AccountKey, StatementRename)> = vec![];
+    for key in conf.keys() {
+        let acct = &conf.accounts()[key];
+        if acct.is_remote() {
+            continue;
+        }
+
+        let formats = std::iter::once(acct.format_string())
+            .chain(conf.inbox().patterns(key).iter().map(String::as_str));
+        let stmt = formats
+            .into_iter()
+            .find_map(|fmt| Statement::try_from((path, fmt)).ok());
+        if let Some(stmt) = stmt {
+            let rename = StatementRename::new(&stmt, acct.directory(), acct.format_string());
+            // accounts that would name it the same, like a schedule sharing its account's directory, agree
+            if !candidates.iter().any(|(_, r)| r.to() == rename.to()) {
+                candidates.push((key, rename));
+            }
+        }
+    }
+
+    match candidates.len() {
+        0 => Sorting::Unmatched,
+        1 => {
+            let (key, rename) = candidates.remove(0);
+            Sorting::Move(key.clone(), rename)
+        }
+        _ => Sorting::Ambiguous(candidates.into_iter().map(|(key, _)| key.clone()).collect()),
+    }
+}
@@ -0,0 +1,228 @@
+//! Send the statements found by `quill daemon` to the TUI as soon as they're found.
+//!
+//! The daemon listens on a Unix socket in the state directory, and clients like the TUI connect
+//! to it instead of scanning for statements themselves.
+//! Each message is a [`DaemonMessage`] written as a single line of JSON.
+//! The daemon sends the statements it found most recently as soon as a client connects,
+//! if it has finished a scan, and then again after every scan.
+//! Clients don't send anything.
+//!
+//! Only Unix-like systems are supported, so elsewhere the daemon doesn't listen for clients and
+//! the TUI always scans for statements itself.
+
+use crate::daemon::DaemonSnapshot;
+use serde::{Deserialize, Serialize};
+
+#[cfg(unix)]
+pub use self::unix::{DaemonClient, IpcServer};
+
+#[cfg(not(unix))]
+pub use self::unsupported::{DaemonClient, IpcServer};
+
+/// A message sent from the daemon to its clients
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DaemonMessage {
+    /// The statements found by the daemon's latest scan
+    Statements(DaemonSnapshot),
+}
+
+#[cfg(unix)]
+mod unix {
+    use super::DaemonMessage;
+    use anyhow::{bail, Context};
+    use quill_utils::create_parent_dir;
+    use std::{
+        fs,
+        io::{BufRead, BufReader, Write},
+        os::unix::net::{UnixListener, UnixStream},
+        path::{Path, PathBuf},
+        sync::{Arc, Mutex, MutexGuard},
+        thread,
+        time::Duration,
+    };
+    use tracing::{debug, warn};
+
+    /// How long to wait for a client to accept a message before giving up on it
+    const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+    /// The clients connected to the daemon, and the last message sent to them
+    #[derive(Debug, Default)]
+    struct Clients {
+        streams: Vec<UnixStream>,
+        latest: Option<String>,
+    }
+
+    /// The daemon's end of the socket, which sends messages to every connected client.
+    /// The socket is removed when this is dropped.
+    #[derive(Debug)]
+    pub struct IpcServer {
+        path: PathBuf,
+        clients: Arc<Mutex<Clients>>,
+    }
+
+    impl IpcServer {
+        /// Listen for clients at `path`, replacing a socket left behind by a daemon that
+        /// didn't stop cleanly.
+        /// Fails if another daemon is already listening there.
+        pub fn listen(path: &Path) -> anyhow::Result<Self> {
+            if UnixStream::connect(path).is_ok() {
+                bail!(
+                    "Another `quill daemon` is already running, listening at `{}`.",
+                    path.display()
+                );
+            }
+            let _ = fs::remove_file(path);
+            create_parent_dir(path).with_context(|| {
+                format!("Error creating the directory for `{}`.", path.display())
+            })?;
+            let listener = UnixListener::bind(path)
+                .with_context(|| format!("Error listening for the TUI at `{}`.", path.display()))?;
+            debug!(path = %path.display(), "listening for the TUI");
+
+            let clients: Arc<Mutex<Clients>> = Arc::default();
+            let shared = Arc::clone(&clients);
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    match stream {
+                        Ok(stream) => welcome(stream, &mut lock(&shared)),
+                        Err(e) => warn!("couldn't accept a connection from the TUI: {}", e),
+                    }
+                }
+            });
+
+            Ok(Self {
+                path: path.to_path_buf(),
+                clients,
+            })
+        }
+
+        /// Send a message to every connected client, and to any that connect later,
+        /// forgetting the clients that have disconnected
+        pub fn broadcast(&self, msg: &DaemonMessage) -> anyhow::Result<()> {
+            let mut line =
+                serde_json::to_string(msg).context("Error writing a message for the TUI.")?;
+            line.push('\n');
+
+            let mut clients = lock(&self.clients);
+            clients
+                .streams
+                .retain_mut(|stream| stream.write_all(line.as_bytes()).is_ok());
+            debug!(
+                clients = clients.streams.len(),
+                "sent the statements to the TUI"
+            );
+            clients.latest = Some(line);
+
+            Ok(())
+        }
+    }
+
+    impl Drop for IpcServer {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+
+    /// Send a new client the latest message, and keep it for the next ones
+    fn welcome(stream: UnixStream, clients: &mut Clients) {
+        // a client that stops reading shouldn't hold up the daemon
+        if stream.set_write_timeout(Some(WRITE_TIMEOUT)).is_err() {
+            return;
+        }
+        let mut stream = stream;
+        if let Some(latest) = &clients.latest {
+            if stream.write_all(latest.as_bytes()).is_err() {
+                return;
+            }
+        }
+        clients.streams.push(stream);
+    }
+
+    /// Lock the clients, even if a thread panicked while it had them, since they're always
+    /// left in a usable state
+    fn lock(clients: &Mutex<Clients>) -> MutexGuard<'_, Clients> {
+        clients
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// A client's connection to a running daemon
+    #[derive(Debug)]
+    pub struct DaemonClient {
+        reader: BufReader<UnixStream>,
+    }
+
+    impl DaemonClient {
+        /// Connect to the daemon listening at `path`, if there is one
+        pub fn connect(path: &Path) -> Option<Self> {
+            match UnixStream::connect(path) {
+                Ok(stream) => Some(Self {
+                    reader: BufReader::new(stream),
+                }),
+                Err(e) => {
+                    debug!(path = %path.display(), "no daemon to connect to: {}", e);
+                    None
+                }
+            }
+        }
+
+        /// Wait for the next message from the daemon, for up to `timeout` if one is given.
+        /// Once the daemon has stopped, there are no more messages.
+        pub fn recv(&mut self, timeout: Option<Duration>) -> anyhow::Result<Option<DaemonMessage>> {
+            self.reader.get_ref().set_read_timeout(timeout)?;
+
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            let msg = serde_json::from_str(&line)
+                .context("Error reading a message from `quill daemon`.")?;
+
+            Ok(Some(msg))
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod unsupported {
+    use super::DaemonMessage;
+    use std::{path::Path, time::Duration};
+    use tracing::debug;
+
+    /// The daemon's end of the socket, which doesn't listen for clients on this platform
+    #[derive(Debug)]
+    pub struct IpcServer;
+
+    impl IpcServer {
+        /// Nothing is listened for, since the TUI can't connect on this platform
+        pub fn listen(_path: &Path) -> anyhow::Result<Self> {
+            debug!("the TUI can't connect to the daemon on this platform");
+            Ok(Self)
+        }
+
+        /// Nothing is sent, since there aren't any clients
+        pub fn broadcast(&self, _msg: &DaemonMessage) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// A client's connection to a running daemon, which can't be made on this platform
+    #[derive(Debug)]
+    pub struct DaemonClient;
+
+    impl DaemonClient {
+        /// There's never a daemon to connect to on this platform
+        pub fn connect(_path: &Path) -> Option<Self> {
+            None
+        }
+
+        /// There are never any messages
+        pub fn recv(
+            &mut self,
+            _timeout: Option<Duration>,
+        ) -> anyhow::Result<Option<DaemonMessage>> {
+            Ok(None)
+        }
+    }
+}
@@ -0,0 +1,40 @@
+//! Track your financial statements without the TUI.
+//!
+//! This is the library behind the `quill` binary, for embedding quill's
+//! statement tracking in other programs.
+//! [`Config::load`] reads a configuration file and scans each account's
+//! directory for statements, which are then paired with the dates they're
+//! expected on and kept in a [`StatementCollection`].
+//! [`Config::try_from_lenient`] does the same, but skips any accounts that
+//! can't be created, listing them in [`Config::skipped_accounts`] instead.
+//!
+//! ```no_run
+//! use quill::Config;
+//! use std::path::Path;
+//!
+//! let conf = Config::load(Path::new("config.toml"), false)?;
+//! for (key, obs) in conf.statements().all_missing() {
+//!     println!("{} is missing a statement from {}", key, obs.statement().date());
+//! }
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+pub mod api;
+mod cache;
+pub mod cfg;
+pub mod daemon;
+pub mod fetch;
+pub mod history;
+pub mod hooks;
+pub mod inbox;
+pub mod ipc;
+pub mod notify;
+pub mod signals;
+pub mod snapshot;
+
+pub use cfg::Config;
+pub use quill_account::{Account, AccountCreationError, Schedule};
+pub use quill_statement::{
+    expected_statement_dates, pair_dates_statements, pair_dates_statements_with_grace,
+    CollectionDiff, ObservedStatement, Statement, StatementCollection, StatementStatus,
+};
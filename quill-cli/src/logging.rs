@@ -0,0 +1,125 @@
+//! Log what quill is doing, to help debug why statements aren't matched.
+
+use anyhow::Context;
+use lazy_static::lazy_static;
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{self, Write},
+    path::Path,
+    sync::Mutex,
+};
+use tracing::Level;
+use tracing_subscriber::{
+    filter::LevelFilter,
+    fmt::{self, writer::BoxMakeWriter, MakeWriter},
+    layer::SubscriberExt,
+    util::SubscriberInitExt,
+    Layer,
+};
+
+/// Number of recent events kept for the TUI's debug pane
+const RECENT_CAPACITY: usize = 200;
+
+lazy_static! {
+    /// The most recent events, oldest first
+    static ref RECENT: Mutex<VecDeque<String>> = Mutex::new(VecDeque::with_capacity(RECENT_CAPACITY));
+}
+
+/// Start logging at the level given by the number of `-v` flags.
+///
+/// The log is written to `log_file` if one is given, and to stderr otherwise.
+/// Nothing is logged to stderr while the TUI is running, since it would draw
+/// over the TUI. Instead, recent events are kept for the TUI's debug pane.
+pub fn init(verbose: u8, log_file: Option<&Path>, tui: bool) -> anyhow::Result<()> {
+    let output = match log_file {
+        Some(path) => {
+            let file = File::create(path)
+                .with_context(|| format!("Error creating log file `{}`.", path.display()))?;
+            Some((BoxMakeWriter::new(Mutex::new(file)), false))
+        }
+        None if tui => None,
+        None => Some((BoxMakeWriter::new(io::stderr), true)),
+    };
+
+    let output_layer = output.map(|(writer, ansi)| {
+        fmt::layer()
+            .with_writer(writer)
+            .with_ansi(ansi)
+            .with_filter(LevelFilter::from_level(level(verbose)))
+    });
+
+    // the debug pane is for finding mismatched statements, so it always shows debug events
+    let recent_layer = tui.then(|| {
+        fmt::layer()
+            .with_writer(RecentEvents)
+            .with_ansi(false)
+            .without_time()
+            .with_target(false)
+            .with_filter(LevelFilter::from_level(level(verbose.max(2))))
+    });
+
+    tracing_subscriber::registry()
+        .with(output_layer)
+        .with(recent_layer)
+        .init();
+
+    Ok(())
+}
+
+/// The most detailed level to log, given the number of `-v` flags
+fn level(verbose: u8) -> Level {
+    match verbose {
+        0 => Level::WARN,
+        1 => Level::INFO,
+        2 => Level::DEBUG,
+        _ => Level::TRACE,
+    }
+}
+
+/// The most recent events logged, oldest first
+pub fn recent_events() -> Vec<String> {
+    match RECENT.lock() {
+        Ok(recent) => recent.iter().cloned().collect(),
+        Err(_) => vec![],
+    }
+}
+
+/// Keeps each event in the ring buffer of recent events
+struct RecentEvents;
+
+impl<'a> MakeWriter<'a> for RecentEvents {
+    type Writer = RecentWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RecentWriter(Vec::new())
+    }
+}
+
+/// Collects a single event, and adds it to the recent events once it's written
+struct RecentWriter(Vec<u8>);
+
+impl Write for RecentWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for RecentWriter {
+    fn drop(&mut self) {
+        let event = String::from_utf8_lossy(&self.0);
+        if let Ok(mut recent) = RECENT.lock() {
+            for line in event.lines().filter(|l| !l.trim().is_empty()) {
+                if recent.len() == RECENT_CAPACITY {
+                    recent.pop_front();
+                }
+                recent.push_back(line.trim().to_string());
+            }
+        }
+    }
+}
@@ -1,24 +1,81 @@
 //! Query all your bills and accounts to check on your financial statements.
 
-use clap::Parser;
 use cli::CliOpts;
+use quill::{api, cfg, daemon, fetch, history, hooks, inbox, ipc, notify, signals, snapshot};
+use std::process;
 
-mod cfg;
 mod cli;
+mod cmd;
+mod logging;
 mod tui;
 
 use crate::cfg::Config;
-use crate::tui::{start_tui, stop_tui};
+use crate::tui::{buffer_text, start_tui, stop_tui, Session};
+
+fn main() {
+    let result = run();
+
+    // a termination signal takes priority over any error it may have caused
+    if let Some(code) = signals::exit_code() {
+        process::exit(code);
+    }
+
+    if let Err(e) = result {
+        eprintln!("Error: {:?}", e);
+        process::exit(1);
+    }
+}
+
+/// Run quill in the mode requested on the command line
+fn run() -> Result<(), Box<dyn std::error::Error>> {
+    // make sure SIGINT and SIGTERM don't leave the terminal in a broken state
+    signals::install()?;
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
     // parse and validate the CLI arguments
-    let opts = CliOpts::parse();
+    let opts = CliOpts::parse_args();
+
+    // the TUI owns the terminal, so it can only log to a file
+    let tui = opts.command().is_none() && opts.replay().is_none();
+    logging::init(opts.verbose(), opts.log_file(), tui)?;
 
-    let mut conf = Config::try_from(opts)?;
+    // run a subcommand instead of the TUI, if one was given
+    if let Some(cmd) = opts.command() {
+        return Ok(cmd::run(&opts, cmd)?);
+    }
 
-    // start the TUI and run it
-    let mut terminal = start_tui(&mut conf)?;
+    // replay a recorded session without a real terminal
+    if let Some(path) = opts.replay() {
+        let terminal = Session::load(path)?.replay()?;
+        println!("{}", buffer_text(terminal.backend().buffer()));
+        return Ok(());
+    }
+
+    // the TUI scans for statements itself, so it can show how far it's got
+    let mut conf = Config::read_all(&opts.configs()?, opts.lenient())?;
+    conf.set_profile(opts.profile());
+
+    // start recording, if requested
+    let mut session = match opts.record() {
+        Some(_) => Some(Session::new(&conf)?),
+        None => None,
+    };
+
+    // start the TUI and run it, polling as often as asked on the command line or in the config
+    let tick_rate = opts.tick_rate().unwrap_or_else(|| conf.tui().tick_rate());
+    let mut terminal = start_tui(&mut conf, session.as_mut(), tick_rate, opts.headless())?;
 
     // close everything down
-    stop_tui(&mut terminal)
+    stop_tui(&mut terminal)?;
+
+    // only save the session once the terminal is restored, so errors can be seen
+    if let (Some(path), Some(session)) = (opts.record(), session) {
+        session.save(path)?;
+    }
+
+    // the TUI hides any warnings, so list the skipped accounts once it's closed
+    for (key, reason) in conf.skipped_accounts() {
+        eprintln!("Warning: skipped account `{}`: {}", key, reason);
+    }
+
+    Ok(())
 }
@@ -0,0 +1,76 @@
+//! Raise desktop notifications for statements that have gone missing.
+
+use crate::{
+    cfg::Config,
+    hooks::run_hooks,
+    snapshot::{notify_snapshot_path, Snapshot},
+};
+use anyhow::Context;
+use notify_rust::Notification;
+use quill_statement::{CollectionDiff, StatementChange};
+
+/// Find how the statements have changed since the last check, and
+/// remember the current statements for the next one.
+///
+/// Nothing is reported the first time this is run, since there is nothing to compare against.
+pub fn changes_since_last_check(conf: &Config) -> anyhow::Result<CollectionDiff> {
+    let path = notify_snapshot_path()?;
+    let previous = Snapshot::load(&path)?;
+    let current = Snapshot::new(conf.statements()).with_paused(conf.accounts(), previous.as_ref());
+
+    let changes = match previous {
+        Some(previous) => CollectionDiff::new(previous.statements(), current.statements()),
+        None => CollectionDiff::default(),
+    };
+
+    current.save(&path)?;
+
+    Ok(changes)
+}
+
+/// Raise a single desktop notification listing the missing statements, if there are any
+pub fn notify_missing(conf: &Config, missing: &[StatementChange]) -> anyhow::Result<()> {
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    let summary = match missing.len() {
+        1 => "1 statement is missing".to_string(),
+        n => format!("{} statements are missing", n),
+    };
+    let body: Vec<String> = missing
+        .iter()
+        .map(|(key, obs)| {
+            let name = conf
+                .accounts()
+                .get(key.as_str())
+                .map_or(key.as_str(), |a| a.name());
+            format!("{}: {}", name, obs.statement().date())
+        })
+        .collect();
+
+    Notification::new()
+        .appname("quill")
+        .summary(&summary)
+        .body(&body.join("\n"))
+        .show()
+        .context("Error raising a desktop notification.")?;
+
+    Ok(())
+}
+
+/// Notify the user about statements that have gone missing since the last
+/// check, if notifications are enabled, and run any hooks for the statements
+/// that have changed
+pub fn check_and_notify(conf: &Config) -> anyhow::Result<()> {
+    if !conf.notifications().enabled() && conf.hooks().is_empty() {
+        return Ok(());
+    }
+
+    let changes = changes_since_last_check(conf)?;
+    if conf.notifications().enabled() {
+        notify_missing(conf, changes.newly_missing())?;
+    }
+
+    run_hooks(conf, changes.newly_missing(), changes.newly_available())
+}
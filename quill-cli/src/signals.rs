@@ -0,0 +1,54 @@
+//! Handle termination signals so that quill always exits cleanly.
+
+use lazy_static::lazy_static;
+use signal_hook::{consts::TERM_SIGNALS, flag};
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+lazy_static! {
+    /// Whether a termination signal has been received
+    static ref SHUTDOWN: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+
+    /// The most recent termination signal received
+    static ref SIGNAL: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(0));
+}
+
+/// Register handlers for SIGINT and SIGTERM (and SIGQUIT, where available).
+///
+/// The first signal only sets a flag, leaving it to the running mode to stop
+/// what it is doing, restore the terminal, and exit.
+/// A second signal terminates the program immediately, in case quill is stuck.
+pub fn install() -> io::Result<()> {
+    for &sig in TERM_SIGNALS {
+        // order matters here: the conditional shutdown must be registered
+        // before the flag is set, otherwise the first signal would terminate
+        flag::register_conditional_shutdown(sig, exit_code_for(sig), Arc::clone(&SHUTDOWN))?;
+        flag::register_usize(sig, Arc::clone(&SIGNAL), sig as usize)?;
+        flag::register(sig, Arc::clone(&SHUTDOWN))?;
+    }
+
+    Ok(())
+}
+
+/// Check if a termination signal has been received
+pub fn shutdown_requested() -> bool {
+    SHUTDOWN.load(Ordering::Relaxed)
+}
+
+/// The conventional exit code for the received signal, if one was received
+pub fn exit_code() -> Option<i32> {
+    match SIGNAL.load(Ordering::Relaxed) {
+        0 => None,
+        sig => Some(exit_code_for(sig as i32)),
+    }
+}
+
+/// Shells report processes killed by a signal with `128 + signal`
+fn exit_code_for(sig: i32) -> i32 {
+    128 + sig
+}
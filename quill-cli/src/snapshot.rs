@@ -0,0 +1,152 @@
+//! Save and load snapshots of the scanned statements between runs.
+
+use anyhow::Context;
+use chrono::{Local, NaiveDateTime};
+use quill_account::Account;
+use quill_statement::{AccountKey, StatementCollection};
+use quill_utils::{create_parent_dir, project_dirs};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+};
+
+const SNAPSHOT_FILE: &str = "snapshot.json";
+const DIGEST_SNAPSHOT_FILE: &str = "digest-snapshot.json";
+const NOTIFY_SNAPSHOT_FILE: &str = "notify-snapshot.json";
+const DAEMON_SNAPSHOT_FILE: &str = "daemon-snapshot.json";
+const DAEMON_SOCKET_FILE: &str = "daemon.sock";
+const HISTORY_SNAPSHOT_FILE: &str = "history-snapshot.json";
+
+/// The statements for every account, as they were at a point in time.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Snapshot {
+    /// When the snapshot was taken
+    created: NaiveDateTime,
+
+    /// The statements for every account
+    statements: StatementCollection,
+}
+
+impl Snapshot {
+    /// Take a snapshot of the statements right now
+    pub fn new(statements: &StatementCollection) -> Self {
+        Self {
+            created: Local::now().naive_local(),
+            statements: statements.clone(),
+        }
+    }
+
+    /// Keep the previous statements of paused accounts, since they aren't scanned.
+    /// Otherwise, pausing an account would look like its statements were removed.
+    pub fn with_paused(
+        mut self,
+        accounts: &HashMap<AccountKey, Account>,
+        previous: Option<&Snapshot>,
+    ) -> Self {
+        let previous = match previous {
+            Some(prev) => prev,
+            None => return self,
+        };
+
+        for (key, acct) in accounts {
+            if acct.is_paused() {
+                if let Some(stmts) = previous.statements().get(key.as_str()) {
+                    self.statements.insert(key.as_str(), stmts.clone());
+                }
+            }
+        }
+
+        self
+    }
+
+    /// Keep the statements of accounts in the `previous` snapshot that aren't in this one,
+    /// like those of another profile.
+    pub fn with_others(mut self, previous: &Snapshot) -> Self {
+        for (key, stmts) in previous.statements().iter() {
+            if self.statements.get(key).is_none() {
+                self.statements.insert(key, stmts.to_vec());
+            }
+        }
+
+        self
+    }
+
+    /// When the snapshot was taken
+    pub fn created(&self) -> &NaiveDateTime {
+        &self.created
+    }
+
+    /// The statements for every account
+    pub fn statements(&self) -> &StatementCollection {
+        &self.statements
+    }
+
+    /// Load the snapshot from a file, if one has been saved
+    pub fn load(path: &Path) -> anyhow::Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let file = File::open(path)
+            .with_context(|| format!("Error opening snapshot file `{}`.", path.display()))?;
+        let snapshot = serde_json::from_reader(BufReader::new(file))
+            .with_context(|| format!("Error reading snapshot file `{}`.", path.display()))?;
+
+        Ok(Some(snapshot))
+    }
+
+    /// Save the snapshot to a file, creating its directory if necessary
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        create_parent_dir(path)
+            .with_context(|| format!("Error creating the directory for `{}`.", path.display()))?;
+
+        let file = File::create(path)
+            .with_context(|| format!("Error creating snapshot file `{}`.", path.display()))?;
+        serde_json::to_writer(BufWriter::new(file), self)
+            .with_context(|| format!("Error writing snapshot file `{}`.", path.display()))?;
+
+        Ok(())
+    }
+}
+
+/// The default location of the snapshot file used by `quill diff`
+pub fn snapshot_path() -> anyhow::Result<PathBuf> {
+    state_file_path(SNAPSHOT_FILE)
+}
+
+/// The location of the snapshot file used by `quill digest`.
+/// This is kept separate so that running `quill diff` doesn't hide changes from the digest.
+pub fn digest_snapshot_path() -> anyhow::Result<PathBuf> {
+    state_file_path(DIGEST_SNAPSHOT_FILE)
+}
+
+/// The location of the snapshot file used to find newly missing statements for notifications.
+pub fn notify_snapshot_path() -> anyhow::Result<PathBuf> {
+    state_file_path(NOTIFY_SNAPSHOT_FILE)
+}
+
+/// The location of the latest snapshot taken by `quill daemon`, for others to read instead of scanning.
+pub fn daemon_snapshot_path() -> anyhow::Result<PathBuf> {
+    state_file_path(DAEMON_SNAPSHOT_FILE)
+}
+
+/// The location of the socket that `quill daemon` sends its statements to the TUI through.
+pub fn daemon_socket_path() -> anyhow::Result<PathBuf> {
+    state_file_path(DAEMON_SOCKET_FILE)
+}
+
+/// The location of the snapshot file that the statement history is recorded against.
+pub fn history_snapshot_path() -> anyhow::Result<PathBuf> {
+    state_file_path(HISTORY_SNAPSHOT_FILE)
+}
+
+/// Locate a file within the state directory
+fn state_file_path(file_name: &str) -> anyhow::Result<PathBuf> {
+    match project_dirs().state_dir() {
+        Some(dir) => Ok(dir.join(file_name)),
+        None => anyhow::bail!("Could not determine a directory to store the snapshot in."),
+    }
+}
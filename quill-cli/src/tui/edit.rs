@@ -0,0 +1,181 @@
+//! Edit the properties of an account from the TUI, writing them back to its configuration file.
+
+use super::input::TextInput;
+use crate::Config;
+use anyhow::{bail, Context};
+use quill_account::Account;
+use quill_statement::AccountKey;
+use quill_utils::TomlEditor;
+use std::path::{Path, PathBuf};
+use toml_edit::Item;
+
+/// A property of an account that can be edited from the TUI.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum AccountField {
+    Name,
+    Directory,
+    StatementFormat,
+}
+
+impl AccountField {
+    /// Every field, in the order they're edited
+    const ALL: [Self; 3] = [Self::Name, Self::Directory, Self::StatementFormat];
+
+    /// The account's property in the configuration file
+    fn prop(&self) -> &'static str {
+        match self {
+            Self::Name => "name",
+            Self::Directory => "dir",
+            Self::StatementFormat => "statement_fmt",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Name => "Name",
+            Self::Directory => "Directory",
+            Self::StatementFormat => "Statement format",
+        }
+    }
+}
+
+/// New values for the properties of an account, which are typed one after another
+/// before they're all written to the configuration file at once.
+#[derive(Clone, Debug)]
+pub(super) struct AccountEdit {
+    key: AccountKey,
+    /// The configuration file the account is in
+    path: PathBuf,
+    /// Position of the field being typed in `AccountField::ALL`
+    field: usize,
+    /// The value of each field, as written in the configuration file
+    values: [String; 3],
+}
+
+impl AccountEdit {
+    /// Start editing an account, starting from its properties as they're written in its
+    /// configuration file, before any variables in them are replaced
+    pub(super) fn start(conf: &Config, key: &AccountKey) -> anyhow::Result<Self> {
+        match conf.accounts().get(key) {
+            Some(acct) if acct.schedule().is_some() => bail!(
+                "Named schedules can't be edited on their own. Edit the account they belong to instead."
+            ),
+            Some(_) => {}
+            None => bail!("The account `{}` no longer exists.", key),
+        }
+
+        for path in conf.paths() {
+            let editor = TomlEditor::open(path).with_context(|| {
+                format!("Error reading configuration file `{}`.", path.display())
+            })?;
+            if let Some(props) = editor.get(&["Accounts", key.as_str()]) {
+                let values = AccountField::ALL.map(|field| {
+                    props
+                        .get(field.prop())
+                        .and_then(Item::as_str)
+                        .unwrap_or_default()
+                        .to_string()
+                });
+                return Ok(Self {
+                    key: key.clone(),
+                    path: path.to_path_buf(),
+                    field: 0,
+                    values,
+                });
+            }
+        }
+
+        bail!(
+            "The account `{}` wasn't found in the configuration file.",
+            key
+        )
+    }
+
+    /// The input for the field being typed, starting with its current value
+    pub(super) fn input(&self) -> TextInput {
+        let field = AccountField::ALL[self.field];
+        let title = format!(
+            "{} of `{}` ({} of {})",
+            field.label(),
+            self.key,
+            self.field + 1,
+            AccountField::ALL.len()
+        );
+
+        TextInput::new(title)
+            .with_value(self.values[self.field].clone())
+            .with_validator(validate_not_empty)
+    }
+
+    /// Keep the value typed for the current field, and move on to the next one.
+    /// Returns `false` once every field has been typed.
+    pub(super) fn submit(&mut self, text: &str) -> bool {
+        self.values[self.field] = text.to_string();
+        self.field += 1;
+
+        self.field < AccountField::ALL.len()
+    }
+
+    pub(super) fn key(&self) -> &AccountKey {
+        &self.key
+    }
+
+    /// The configuration file the account is in
+    pub(super) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Write the new values to the configuration file, leaving the rest of it as it was.
+    /// Nothing is written unless the account is still valid with them.
+    pub(super) fn save(&self) -> anyhow::Result<()> {
+        let key = self.key.as_str();
+
+        let mut editor = TomlEditor::open(&self.path).with_context(|| {
+            format!(
+                "Error reading configuration file `{}`.",
+                self.path.display()
+            )
+        })?;
+
+        // check the account with its new values, like `quill validate`
+        let config_toml: toml::Value = editor.document().to_string().parse()?;
+        let mut props = match config_toml.get("Accounts").and_then(|accts| accts.get(key)) {
+            Some(props @ toml::Value::Table(_)) => props.clone(),
+            _ => bail!(
+                "The account `{}` is no longer in configuration file `{}`.",
+                key,
+                self.path.display()
+            ),
+        };
+        for (field, val) in AccountField::ALL.iter().zip(&self.values) {
+            props[field.prop()] = toml::Value::String(val.clone());
+        }
+        let errors = Account::validate(&props);
+        if !errors.is_empty() {
+            let messages: Vec<String> = errors.iter().map(ToString::to_string).collect();
+            bail!("`{}` wasn't changed: {}", key, messages.join(" "));
+        }
+
+        // only write the values that changed, leaving the others as they're written
+        for (field, val) in AccountField::ALL.iter().zip(&self.values) {
+            let keys = ["Accounts", key, field.prop()];
+            if editor.get(&keys).and_then(Item::as_str) != Some(val.as_str()) {
+                editor.set_value(&keys, val.as_str())?;
+            }
+        }
+        editor.save().with_context(|| {
+            format!(
+                "Error writing configuration file `{}`.",
+                self.path.display()
+            )
+        })
+    }
+}
+
+/// Check that a property isn't left empty
+fn validate_not_empty(_: &Config, text: &str) -> Result<(), String> {
+    match text.trim().is_empty() {
+        true => Err("This can't be empty.".to_string()),
+        false => Ok(()),
+    }
+}
@@ -0,0 +1,244 @@
+//! Group accounts under collapsible headers, such as one for each institution.
+
+use super::state::{AccountSort, TuiState};
+use crate::cfg::Config;
+use chrono::NaiveDate;
+use quill_statement::AccountKey;
+use std::{cmp::Ordering, collections::HashSet};
+
+/// A row in a list of grouped accounts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum AccountRow<'a> {
+    /// The header of a group of accounts
+    Group(&'a str),
+    /// An account, by its key in the configuration
+    Account(&'a AccountKey),
+}
+
+/// Identifies a row by its group or account key, rather than its position,
+/// so that it can be found again after the configuration is reloaded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) enum RowKey {
+    Group(String),
+    Account(AccountKey),
+}
+
+/// The key identifying a row
+pub(super) fn row_key(row: AccountRow) -> RowKey {
+    match row {
+        AccountRow::Group(group) => RowKey::Group(group.to_string()),
+        AccountRow::Account(key) => RowKey::Account(key.clone()),
+    }
+}
+
+/// Find the position of a row among a list of rows, if it's still there
+pub(super) fn find_row(rows: &[AccountRow], key: &RowKey) -> Option<usize> {
+    rows.iter().position(|&row| row_key(row) == *key)
+}
+
+/// List the rows for every account, with each group of accounts under its own header.
+/// Groups are ordered by their first account, and the accounts in collapsed groups are hidden.
+/// An account's named schedules follow it, as sub-rows.
+pub(super) fn account_rows<'a>(
+    conf: &'a Config,
+    collapsed: &HashSet<String>,
+) -> Vec<AccountRow<'a>> {
+    sorted_account_rows(conf, collapsed, AccountSort::Config, false)
+}
+
+/// List the rows for every account in the "Accounts" tab, sorted and filtered as selected there
+pub(super) fn accounts_tab_rows<'a>(conf: &'a Config, state: &TuiState) -> Vec<AccountRow<'a>> {
+    sorted_account_rows(
+        conf,
+        state.collapsed(),
+        state.accounts().sort(),
+        state.accounts().missing_only(),
+    )
+}
+
+/// List the rows for every account, like `account_rows`, with the accounts in each group sorted.
+/// Groups are then ordered by their first account.
+/// If `missing_only` is set, accounts without missing statements are hidden, along with
+/// groups that have none left.
+pub(super) fn sorted_account_rows<'a>(
+    conf: &'a Config,
+    collapsed: &HashSet<String>,
+    sort: AccountSort,
+    missing_only: bool,
+) -> Vec<AccountRow<'a>> {
+    // each account is listed with its named schedules, as a block of rows
+    let mut groups: Vec<(&str, Vec<Vec<&AccountKey>>)> = vec![];
+    for (key, acct) in conf.keys().iter().filter_map(|key| conf.account(key)) {
+        // schedules are listed with the account they belong to
+        if acct.schedule().is_some() {
+            continue;
+        }
+
+        let mut block = vec![key];
+        block.extend(acct.schedules().iter().filter_map(|sched| {
+            let sched_key = key.schedule(sched.schedule().unwrap_or_default());
+            conf.account(&sched_key).map(|(k, _)| k)
+        }));
+        if missing_only && block_missing(conf, &block) == 0 {
+            continue;
+        }
+
+        let group = acct.group();
+        match groups.iter_mut().find(|(g, _)| *g == group) {
+            Some((_, blocks)) => blocks.push(block),
+            None => groups.push((group, vec![block])),
+        }
+    }
+
+    // sorting is stable, so accounts are otherwise kept in the order they're listed
+    for (_, blocks) in groups.iter_mut() {
+        blocks.sort_by(|a, b| compare_blocks(conf, sort, a, b));
+    }
+    groups.sort_by(|(_, a), (_, b)| compare_blocks(conf, sort, &a[0], &b[0]));
+
+    let mut rows = vec![];
+    for (group, blocks) in groups {
+        rows.push(AccountRow::Group(group));
+        if !collapsed.contains(group) {
+            rows.extend(blocks.into_iter().flatten().map(AccountRow::Account));
+        }
+    }
+
+    rows
+}
+
+/// Compare two accounts, each with their named schedules, in the given order
+fn compare_blocks(
+    conf: &Config,
+    sort: AccountSort,
+    a: &[&AccountKey],
+    b: &[&AccountKey],
+) -> Ordering {
+    let acct = |block: &[&AccountKey]| conf.account(block[0]).map(|(_, acct)| acct);
+    let name = |block: &[&AccountKey]| acct(block).map(|a| a.name().to_lowercase());
+    let institution = |block: &[&AccountKey]| acct(block).map(|a| a.institution().to_lowercase());
+    // accounts without a kind go last
+    let kind = |block: &[&AccountKey]| acct(block).map(|a| (a.kind().is_none(), a.kind()));
+
+    match sort {
+        AccountSort::Config => Ordering::Equal,
+        AccountSort::Name => name(a).cmp(&name(b)),
+        AccountSort::Institution => (institution(a), name(a)).cmp(&(institution(b), name(b))),
+        AccountSort::Kind => (kind(a), name(a)).cmp(&(kind(b), name(b))),
+        AccountSort::Missing => block_missing(conf, b).cmp(&block_missing(conf, a)),
+        // accounts that don't expect any more statements go last
+        AccountSort::NextDue => match (block_next_due(conf, a), block_next_due(conf, b)) {
+            (Some(a), Some(b)) => a.cmp(b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        },
+    }
+}
+
+/// Short labels for an account's kind and currency, like `CC CAD`, if it has either
+pub(super) fn account_badges(conf: &Config, key: &str) -> String {
+    let acct = match conf.account(key) {
+        Some((_, acct)) => acct,
+        None => return String::new(),
+    };
+    let badges: Vec<&str> = [acct.kind().map(|kind| kind.badge()), acct.currency()]
+        .into_iter()
+        .flatten()
+        .collect();
+
+    badges.join(" ")
+}
+
+/// Count the missing statements for an account and its named schedules
+fn block_missing(conf: &Config, block: &[&AccountKey]) -> usize {
+    block.iter().map(|key| missing_count(conf, key)).sum()
+}
+
+/// The date of the next statement expected for an account or its named schedules
+fn block_next_due<'a>(conf: &'a Config, block: &[&AccountKey]) -> Option<&'a NaiveDate> {
+    block.iter().filter_map(|key| next_due(conf, key)).min()
+}
+
+/// The group that a row belongs to
+pub(super) fn row_group<'a>(conf: &'a Config, row: AccountRow<'a>) -> &'a str {
+    match row {
+        AccountRow::Group(group) => group,
+        AccountRow::Account(key) => conf.account(key).map_or("", |(_, acct)| acct.group()),
+    }
+}
+
+/// The keys of the accounts in a group
+pub(super) fn group_accounts<'a>(conf: &'a Config, group: &str) -> Vec<&'a AccountKey> {
+    conf.keys()
+        .iter()
+        .filter(|key| {
+            conf.account(key)
+                .map_or(false, |(_, acct)| acct.group() == group)
+        })
+        .collect()
+}
+
+/// The label for an account's row, indented under its group header.
+/// Named schedules are indented further, under the account they belong to,
+/// and paused accounts are marked as such.
+/// When several configuration files are merged, accounts are tagged with the file they came from.
+pub(super) fn account_label(conf: &Config, key: &str) -> String {
+    let acct = match conf.account(key) {
+        Some((_, acct)) => acct,
+        None => return format!("  {}", key),
+    };
+    let label = match (acct.schedule(), conf.source(key)) {
+        (Some(sched), _) => format!("    \u{2514} {}", sched),
+        (None, Some(source)) => format!("  {} ({})", acct.name(), source),
+        (None, None) => format!("  {}", acct.name()),
+    };
+
+    match acct.is_paused() {
+        true => format!("{} [paused]", label),
+        false => label,
+    }
+}
+
+/// Count the missing statements for an account
+pub(super) fn missing_count(conf: &Config, key: &str) -> usize {
+    conf.statements().missing_count_for(key)
+}
+
+/// The date of the next statement expected for an account, if any are expected
+pub(super) fn next_due<'a>(conf: &'a Config, key: &str) -> Option<&'a NaiveDate> {
+    conf.statements()
+        .get_upcoming(key)
+        .and_then(|dates| dates.first())
+}
+
+/// The name of a group, marked with whether it's collapsed
+pub(super) fn group_title(group: &str, collapsed: &HashSet<String>) -> String {
+    let marker = match collapsed.contains(group) {
+        true => '\u{25b8}',
+        false => '\u{25be}',
+    };
+
+    format!("{} {}", marker, group)
+}
+
+/// Summarize a group by its number of accounts and missing statements.
+/// Named schedules aren't counted as accounts, but their missing statements are.
+pub(super) fn group_summary(conf: &Config, group: &str) -> String {
+    let accts = group_accounts(conf, group);
+    let missing: usize = accts.iter().map(|key| missing_count(conf, key)).sum();
+    let n_accts = accts
+        .iter()
+        .filter(|key| {
+            conf.account(key)
+                .map_or(false, |(_, acct)| acct.schedule().is_none())
+        })
+        .count();
+
+    format!(
+        "{} account{}, {} missing",
+        n_accts,
+        if n_accts == 1 { "" } else { "s" },
+        missing
+    )
+}
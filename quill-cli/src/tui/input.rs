@@ -0,0 +1,136 @@
+//! A single line of text typed by the user inside the TUI.
+
+use crate::Config;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Checks the text typed so far, explaining what's wrong with it if it can't be used
+pub(super) type Validator = fn(&Config, &str) -> Result<(), String>;
+
+/// What happened to the text after a key was pressed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum InputEvent {
+    /// The text is still being typed
+    Editing,
+    /// The text was accepted with Enter, after it passed validation
+    Submitted,
+    /// The text was thrown away with Esc
+    Cancelled,
+}
+
+/// A single line of text being typed, with a cursor that can be moved through it.
+#[derive(Clone, Debug)]
+pub(super) struct TextInput {
+    /// What's being asked for, shown as the title of the input
+    title: String,
+    value: String,
+    /// Position of the cursor, in characters from the start of the text
+    cursor: usize,
+    validator: Option<Validator>,
+    /// Why the text couldn't be submitted, until it's edited again
+    error: Option<String>,
+}
+
+impl TextInput {
+    /// Start typing with nothing entered yet
+    pub(super) fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            value: String::new(),
+            cursor: 0,
+            validator: None,
+            error: None,
+        }
+    }
+
+    /// Start with `value` already typed, and the cursor after it
+    pub(super) fn with_value(mut self, value: impl Into<String>) -> Self {
+        self.value = value.into();
+        self.cursor = self.len();
+        self
+    }
+
+    /// Check the text with `validator` before it can be submitted
+    pub(super) fn with_validator(mut self, validator: Validator) -> Self {
+        self.validator = Some(validator);
+        self
+    }
+
+    pub(super) fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub(super) fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Position of the cursor, in characters from the start of the text
+    pub(super) fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Why the text couldn't be submitted, if it was tried
+    pub(super) fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Edit the text or move the cursor for a key pressed by the user.
+    /// Enter only submits the text if it passes validation, and otherwise keeps the reason it didn't.
+    pub(super) fn handle_key(&mut self, key: KeyEvent, conf: &Config) -> InputEvent {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Enter => {
+                if let Some(validate) = self.validator {
+                    if let Err(e) = validate(conf, &self.value) {
+                        self.error = Some(e);
+                        return InputEvent::Editing;
+                    }
+                }
+                return InputEvent::Submitted;
+            }
+            KeyCode::Esc => return InputEvent::Cancelled,
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(self.len()),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.len(),
+            KeyCode::Char('a') if ctrl => self.cursor = 0,
+            KeyCode::Char('e') if ctrl => self.cursor = self.len(),
+            // delete everything before the cursor, like a shell
+            KeyCode::Char('u') if ctrl => {
+                self.value = self.value.chars().skip(self.cursor).collect();
+                self.cursor = 0;
+            }
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                let idx = self.byte_index(self.cursor);
+                self.value.remove(idx);
+            }
+            KeyCode::Delete if self.cursor < self.len() => {
+                let idx = self.byte_index(self.cursor);
+                self.value.remove(idx);
+            }
+            KeyCode::Char(c) if !ctrl => {
+                let idx = self.byte_index(self.cursor);
+                self.value.insert(idx, c);
+                self.cursor += 1;
+            }
+            _ => return InputEvent::Editing,
+        }
+
+        // the reason it couldn't be submitted may no longer hold
+        self.error = None;
+        InputEvent::Editing
+    }
+
+    /// Number of characters in the text
+    fn len(&self) -> usize {
+        self.value.chars().count()
+    }
+
+    /// Position in the text, in bytes, of the character at `char_idx`
+    fn byte_index(&self, char_idx: usize) -> usize {
+        self.value
+            .char_indices()
+            .nth(char_idx)
+            .map_or(self.value.len(), |(i, _)| i)
+    }
+}
@@ -0,0 +1,231 @@
+//! Keys bound to each action in the TUI.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Something the user can do with a key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum Action {
+    NextTab,
+    PrevTab,
+    GoToTab(usize),
+    Left,
+    Down,
+    Up,
+    Right,
+    PageUp,
+    PageDown,
+    First,
+    Last,
+    Open,
+    ToggleGroup,
+    CycleSort,
+    ToggleMissingOnly,
+    Refresh,
+    Reload,
+    Fetch,
+    OpenPortal,
+    Import,
+    SortInbox,
+    FindAccount,
+    EditAccount,
+    PrevYear,
+    NextYear,
+    JumpToDate,
+    Trash,
+    ToggleIgnore,
+    Undo,
+    Redo,
+    ToggleDebug,
+    ToggleHelp,
+    Close,
+    Quit,
+}
+
+impl Action {
+    /// Describe what the action does, for the help
+    pub(super) fn description(&self) -> String {
+        match self {
+            Self::NextTab => "Next tab".to_string(),
+            Self::PrevTab => "Previous tab".to_string(),
+            Self::GoToTab(n) => format!("Go to tab {}", n + 1),
+            Self::Left => "Move to the accounts (Log tab)".to_string(),
+            Self::Down => "Move down".to_string(),
+            Self::Up => "Move up".to_string(),
+            Self::Right => "Move to the statements (Log tab)".to_string(),
+            Self::PageUp => "Move up a page (Log tab)".to_string(),
+            Self::PageDown => "Move down a page (Log tab)".to_string(),
+            Self::First => "Move to the top (Log tab)".to_string(),
+            Self::Last => "Move to the bottom (Log tab)".to_string(),
+            Self::Open => "Open the selected statement or show account details".to_string(),
+            Self::ToggleGroup => "Collapse or expand the selected group".to_string(),
+            Self::CycleSort => {
+                "Sort by name, institution, kind, missing statements, or next due date (Accounts tab)"
+                    .to_string()
+            }
+            Self::ToggleMissingOnly => {
+                "Only show accounts with missing statements (Accounts tab)".to_string()
+            }
+            Self::Refresh => "Scan for statements again".to_string(),
+            Self::Reload => "Reload the configuration file".to_string(),
+            Self::Fetch => "Download statements for the selected account".to_string(),
+            Self::OpenPortal => {
+                "Open the website of the selected account's institution to download statements"
+                    .to_string()
+            }
+            Self::Import => {
+                "Import a downloaded file as the selected statement (Log tab)".to_string()
+            }
+            Self::SortInbox => {
+                "Move downloaded statements from the inbox into their accounts' directories"
+                    .to_string()
+            }
+            Self::FindAccount => "Find an account by its name".to_string(),
+            Self::EditAccount => {
+                "Edit the account's name, directory, and statement format (account details)"
+                    .to_string()
+            }
+            Self::PrevYear => {
+                "Only list the statements from the year before (Log tab)".to_string()
+            }
+            Self::NextYear => {
+                "Only list the statements from the year after, or from every year after the latest (Log tab)"
+                    .to_string()
+            }
+            Self::JumpToDate => "Select the statement nearest to a date (Log tab)".to_string(),
+            Self::Trash => {
+                "Move the selected statement to the trash (Log tab, Shift+d)".to_string()
+            }
+            Self::ToggleIgnore => {
+                "Ignore the selected statement, or stop ignoring it (Log tab)".to_string()
+            }
+            Self::Undo => "Undo the last change to statement files".to_string(),
+            Self::Redo => "Redo the last change that was undone".to_string(),
+            Self::ToggleDebug => "Show or hide recent log messages".to_string(),
+            Self::ToggleHelp => "Show or hide this help".to_string(),
+            Self::Close => "Hide this help or the account details".to_string(),
+            Self::Quit => "Quit".to_string(),
+        }
+    }
+}
+
+/// A key bound to an action.
+pub(super) struct Binding {
+    code: KeyCode,
+    /// Modifiers that must be held, or `None` if any modifiers can be held
+    modifiers: Option<KeyModifiers>,
+    action: Action,
+}
+
+impl Binding {
+    const fn new(code: KeyCode, action: Action) -> Self {
+        Self {
+            code,
+            modifiers: None,
+            action,
+        }
+    }
+
+    const fn with_modifiers(code: KeyCode, modifiers: KeyModifiers, action: Action) -> Self {
+        Self {
+            code,
+            modifiers: Some(modifiers),
+            action,
+        }
+    }
+
+    /// Check if a key pressed by the user triggers this binding
+    fn matches(&self, key: &KeyEvent) -> bool {
+        (key.code == self.code) && self.modifiers.map_or(true, |m| key.modifiers == m)
+    }
+
+    /// Name the key, as it's shown in the help
+    fn key_name(&self) -> String {
+        let name = match self.code {
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::Tab => "Tab".to_string(),
+            KeyCode::BackTab => "Shift+Tab".to_string(),
+            KeyCode::Left => "\u{2190}".to_string(),
+            KeyCode::Down => "\u{2193}".to_string(),
+            KeyCode::Up => "\u{2191}".to_string(),
+            KeyCode::Right => "\u{2192}".to_string(),
+            KeyCode::Enter => "Enter".to_string(),
+            KeyCode::Esc => "Esc".to_string(),
+            other => format!("{:?}", other),
+        };
+
+        match self.modifiers {
+            Some(m) if m.contains(KeyModifiers::CONTROL) => format!("Ctrl+{}", name),
+            _ => name,
+        }
+    }
+}
+
+/// Every key binding, in the order they're listed in the help
+const KEYMAP: [Binding; 43] = [
+    Binding::new(KeyCode::Tab, Action::NextTab),
+    Binding::new(KeyCode::BackTab, Action::PrevTab),
+    Binding::new(KeyCode::Char('1'), Action::GoToTab(0)),
+    Binding::new(KeyCode::Char('2'), Action::GoToTab(1)),
+    Binding::new(KeyCode::Char('3'), Action::GoToTab(2)),
+    Binding::new(KeyCode::Char('4'), Action::GoToTab(3)),
+    Binding::new(KeyCode::Char('5'), Action::GoToTab(4)),
+    Binding::new(KeyCode::Char('h'), Action::Left),
+    Binding::new(KeyCode::Left, Action::Left),
+    Binding::new(KeyCode::Char('j'), Action::Down),
+    Binding::new(KeyCode::Down, Action::Down),
+    Binding::new(KeyCode::Char('k'), Action::Up),
+    Binding::new(KeyCode::Up, Action::Up),
+    Binding::new(KeyCode::Char('l'), Action::Right),
+    Binding::new(KeyCode::Right, Action::Right),
+    Binding::new(KeyCode::PageUp, Action::PageUp),
+    Binding::new(KeyCode::PageDown, Action::PageDown),
+    Binding::new(KeyCode::Home, Action::First),
+    Binding::new(KeyCode::End, Action::Last),
+    Binding::new(KeyCode::Enter, Action::Open),
+    Binding::new(KeyCode::Char(' '), Action::ToggleGroup),
+    Binding::new(KeyCode::Char('s'), Action::CycleSort),
+    Binding::new(KeyCode::Char('m'), Action::ToggleMissingOnly),
+    // Ctrl+r comes before `r`, which matches it with any modifiers
+    Binding::with_modifiers(KeyCode::Char('r'), KeyModifiers::CONTROL, Action::Redo),
+    Binding::new(KeyCode::Char('r'), Action::Refresh),
+    Binding::new(KeyCode::Char('R'), Action::Reload),
+    Binding::new(KeyCode::Char('f'), Action::Fetch),
+    Binding::new(KeyCode::Char('b'), Action::OpenPortal),
+    Binding::new(KeyCode::Char('i'), Action::Import),
+    Binding::new(KeyCode::Char('I'), Action::SortInbox),
+    Binding::new(KeyCode::Char('/'), Action::FindAccount),
+    Binding::new(KeyCode::Char('e'), Action::EditAccount),
+    Binding::new(KeyCode::Char('['), Action::PrevYear),
+    Binding::new(KeyCode::Char(']'), Action::NextYear),
+    Binding::new(KeyCode::Char('g'), Action::JumpToDate),
+    Binding::new(KeyCode::Char('D'), Action::Trash),
+    Binding::new(KeyCode::Char('x'), Action::ToggleIgnore),
+    Binding::new(KeyCode::Char('u'), Action::Undo),
+    Binding::new(KeyCode::Char('d'), Action::ToggleDebug),
+    Binding::new(KeyCode::Char('?'), Action::ToggleHelp),
+    Binding::new(KeyCode::Esc, Action::Close),
+    Binding::new(KeyCode::Char('q'), Action::Quit),
+    Binding::with_modifiers(KeyCode::Char('c'), KeyModifiers::CONTROL, Action::Quit),
+];
+
+/// The action bound to a key pressed by the user, if any
+pub(super) fn action_for(key: &KeyEvent) -> Option<Action> {
+    KEYMAP.iter().find(|b| b.matches(key)).map(|b| b.action)
+}
+
+/// Every action, along with the names of the keys bound to it
+pub(super) fn bindings_by_action() -> Vec<(Action, Vec<String>)> {
+    let mut bindings: Vec<(Action, Vec<String>)> = vec![];
+    for binding in &KEYMAP {
+        match bindings
+            .iter_mut()
+            .find(|(action, _)| *action == binding.action)
+        {
+            Some((_, keys)) => keys.push(binding.key_name()),
+            None => bindings.push((binding.action, vec![binding.key_name()])),
+        }
+    }
+
+    bindings
+}
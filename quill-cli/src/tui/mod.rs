@@ -1,41 +1,399 @@
 //! The terminal user interface for quill.
 
+use crate::cfg::Institution;
+use crate::fetch::shell_command;
+use crate::inbox::{inbox_files, sort_inbox, Sorting};
 use crate::Config;
-use quill_statement::StatementStatus;
+use anyhow::{bail, Context};
+use chrono::{Datelike, NaiveDate};
+use quill_account::{
+    archive::extract_to_temp,
+    storage::{download_to_temp, is_remote},
+};
+use quill_statement::{AccountKey, ObservedStatement, Statement, StatementRename, StatementStatus};
+use state::{ImportPicker, Prompt};
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::thread;
+use tracing::warn;
+use undo::Change;
 
+mod edit;
+mod groups;
+mod input;
+mod keymap;
 mod render;
+mod session;
 mod start;
 mod state;
 mod stop;
+mod undo;
 
+pub use session::{buffer_text, Session};
 pub use start::start_tui;
 pub use stop::stop_tui;
 
-/// Open a PDF statement with the operating system as a separate process.
-fn open_stmt_external(conf: &Config, selected_acct: usize, selected_stmt: usize) {
-    // get the key for the selected account
-    let acct_name = conf.keys()[selected_acct].as_str();
-    // construct the path to the statement file
-    let obs_stmt = conf
-        .statements()
-        .get(acct_name)
-        .unwrap()
-        .iter()
+/// What was done with a file or address that was asked to be opened
+enum Opened {
+    /// It was opened with another program
+    Launched(String),
+    /// It's only shown, since there's no display to open it on
+    Shown(String),
+}
+
+/// Open a file or address with the operating system's default program as a separate process,
+/// unless in headless mode, where it's only shown
+fn open_default<T: AsRef<OsStr>>(target: T, headless: bool) -> Opened {
+    let shown = Path::new(target.as_ref()).display().to_string();
+    match headless {
+        true => Opened::Shown(shown),
+        false => {
+            open::that_in_background(target);
+            Opened::Launched(shown)
+        }
+    }
+}
+
+/// The statements of an account that are listed in the Log tab, latest first.
+/// Only the statements from `year` are listed if it's given.
+fn log_statements<'a>(
+    conf: &'a Config,
+    acct_key: &str,
+    year: Option<i32>,
+) -> Vec<&'a ObservedStatement> {
+    conf.statements()
+        .get(acct_key)
+        .into_iter()
+        .flatten()
         .rev()
-        .nth(selected_stmt)
-        .unwrap();
+        .filter(|obs| year.map_or(true, |y| obs.statement().date().year() == y))
+        .collect()
+}
+
+/// The years that an account has statements in, oldest first
+fn statement_years(conf: &Config, acct_key: &str) -> Vec<i32> {
+    let mut years: Vec<i32> = conf
+        .statements()
+        .get(acct_key)
+        .into_iter()
+        .flatten()
+        .map(|obs| obs.statement().date().year())
+        .collect();
+    years.sort_unstable();
+    years.dedup();
+
+    years
+}
+
+/// Open a PDF statement as a separate process, with the account's `open_cmd`,
+/// the `open_cmd` of the `[Tui]` table, or else the operating system's default program.
+/// Returns the file that was opened, which is a temporary copy for remote and archived statements.
+fn open_stmt_external(
+    conf: &Config,
+    acct_key: &str,
+    selected_stmt: usize,
+    year: Option<i32>,
+    headless: bool,
+) -> anyhow::Result<Opened> {
+    // construct the path to the statement file
+    let acct = match conf.accounts().get(acct_key) {
+        Some(acct) => acct,
+        None => bail!("The account `{}` no longer exists.", acct_key),
+    };
+    let obs_stmt = match log_statements(conf, acct_key, year).get(selected_stmt) {
+        Some(obs_stmt) => *obs_stmt,
+        None => bail!("The selected statement of `{}` no longer exists.", acct_key),
+    };
+
+    // unexpected statements have a file to open, too
+    if !matches!(
+        obs_stmt.status(),
+        StatementStatus::Available | StatementStatus::Unexpected
+    ) {
+        bail!(
+            "The statement from {} hasn't been downloaded, so there's nothing to open.",
+            obs_stmt.statement().date()
+        );
+    }
+    let path = obs_stmt.statement().path();
+
+    // open the statement with an external program, downloading it first if it's
+    // in a remote directory, or extracting it first if it's inside an archive
+    let opened = if path.exists() {
+        path.to_path_buf()
+    } else if is_remote(path) {
+        download_to_temp(path).context("Couldn't download the statement")?
+    } else {
+        extract_to_temp(path)
+            .with_context(|| format!("Couldn't extract `{}` from its archive", path.display()))?
+    };
+    match acct.open_cmd().or_else(|| conf.tui().open_cmd()) {
+        Some(cmd_line) => {
+            open_with(cmd_line, &opened)?;
+            Ok(Opened::Launched(opened.display().to_string()))
+        }
+        None => Ok(open_default(&opened, headless)),
+    }
+}
+
+/// Open a file with a command run by the shell.
+/// `{path}` in the command is replaced with the file's path, which is added to the end
+/// of the command if there's no `{path}`.
+fn open_with(cmd_line: &str, path: &Path) -> anyhow::Result<()> {
+    let quoted = shell_quote(&path.to_string_lossy());
+    let cmd_line = match cmd_line.contains("{path}") {
+        true => cmd_line.replace("{path}", &quoted),
+        false => format!("{} {}", cmd_line, quoted),
+    };
+
+    // the TUI owns the terminal, so the command can't use it
+    let mut child = shell_command(&cmd_line)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| format!("Couldn't run `{}`", cmd_line))?;
+    // wait for the command to finish in the background, like the default program
+    thread::spawn(move || child.wait());
+
+    Ok(())
+}
+
+/// Quote a path so the shell passes it to a command unchanged
+fn shell_quote(path: &str) -> String {
+    if cfg!(windows) {
+        format!("\"{}\"", path)
+    } else {
+        format!("'{}'", path.replace('\'', "'\\''"))
+    }
+}
+
+/// Find the file of a statement that can be moved to the trash.
+/// Only statements that have been downloaded to this computer, and aren't in an archive, can be.
+fn trashable_stmt(
+    conf: &Config,
+    acct_key: &str,
+    selected_stmt: usize,
+    year: Option<i32>,
+) -> anyhow::Result<PathBuf> {
+    let obs_stmt = match log_statements(conf, acct_key, year).get(selected_stmt) {
+        Some(obs_stmt) => *obs_stmt,
+        None => bail!("The selected statement of `{}` no longer exists.", acct_key),
+    };
+
+    let path = obs_stmt.statement().path();
+    if !path.is_file() {
+        bail!(
+            "The statement from {} isn't a file on this computer, so it can't be moved to the trash.",
+            obs_stmt.statement().date()
+        );
+    }
+
+    Ok(path.to_path_buf())
+}
+
+/// Move a statement file to the operating system's trash, so it can be restored if need be
+fn trash_stmt(path: &Path) -> anyhow::Result<()> {
+    trash::delete(path).with_context(|| format!("Couldn't move `{}` to the trash", path.display()))
+}
+
+/// Ignore an account's statement from `date` in the ignore file in its directory, or stop
+/// ignoring it if it already is, returning the change so it can be undone
+fn toggle_ignored(conf: &Config, acct_key: &str, date: &NaiveDate) -> anyhow::Result<Change> {
+    let acct = match conf.accounts().get(acct_key) {
+        Some(acct) => acct,
+        None => bail!("The account `{}` no longer exists.", acct_key),
+    };
+    if acct.is_remote() {
+        bail!(
+            "`{}` keeps its statements in the remote directory `{}`, which can't have an ignore file.",
+            acct_key,
+            acct.directory().display()
+        );
+    }
+
+    let dir = acct.directory().to_path_buf();
+    let ignored = acct.ignored();
+    let change = if ignored.iter().any(|d| d == date) {
+        Change::Unignored {
+            dir,
+            date: *date,
+            reason: ignored.reason(date).map(String::from),
+        }
+    } else if ignored.in_ranges(date) {
+        bail!(
+            "The statement from {} is ignored as part of a range, so edit the ignore file in `{}` to stop ignoring it.",
+            date,
+            dir.display()
+        );
+    } else {
+        Change::Ignored { dir, date: *date }
+    };
+    change.redo()?;
+
+    Ok(change)
+}
 
-    if obs_stmt.status() == StatementStatus::Available {
-        // open the statement with an external program
-        open::that_in_background(obs_stmt.statement().path());
+/// Start choosing a PDF from the inbox directory to import as a statement of an account.
+/// It's dated like the selected statement, or the latest missing statement listed if none is selected.
+/// The most recently downloaded files are listed first.
+fn import_picker(
+    conf: &Config,
+    acct_key: &AccountKey,
+    selected_stmt: Option<usize>,
+    year: Option<i32>,
+) -> anyhow::Result<ImportPicker> {
+    let acct = match conf.accounts().get(acct_key.as_str()) {
+        Some(acct) => acct,
+        None => bail!("The account `{}` no longer exists.", acct_key),
+    };
+    if acct.is_remote() {
+        bail!(
+            "`{}` is a remote directory, so statements can't be imported into it.",
+            acct.directory().display()
+        );
     }
+
+    let stmts = log_statements(conf, acct_key, year);
+    let date = match selected_stmt {
+        Some(idx) => stmts.get(idx).copied(),
+        None => stmts
+            .into_iter()
+            .find(|obs_stmt| obs_stmt.status().is_missing()),
+    };
+    let date = match date {
+        Some(obs_stmt) => *obs_stmt.statement().date(),
+        None => bail!(
+            "{} isn't missing any statements. Select a statement to import a file for it.",
+            acct.name()
+        ),
+    };
+
+    let inbox = match conf.inbox().dir() {
+        Some(inbox) => inbox,
+        None => bail!("There's no `dir` in the `[Inbox]` table to import statements from."),
+    };
+    let files: Vec<_> = inbox_files(&inbox)
+        .with_context(|| format!("Couldn't read the inbox `{}`", inbox.display()))?
+        .into_iter()
+        .filter(|p| {
+            p.extension()
+                .map_or(false, |ext| ext.eq_ignore_ascii_case("pdf"))
+        })
+        .collect();
+    if files.is_empty() {
+        bail!("There are no PDFs to import in `{}`.", inbox.display());
+    }
+
+    Ok(ImportPicker::new(acct_key.clone(), date, files))
+}
+
+/// Move a file into an account's directory as its statement from `date`, named by its `statement_fmt`.
+/// Returns the statement's new path.
+fn import_stmt(
+    conf: &Config,
+    acct_key: &str,
+    file: &Path,
+    date: &NaiveDate,
+) -> anyhow::Result<PathBuf> {
+    let acct = match conf.accounts().get(acct_key) {
+        Some(acct) => acct,
+        None => bail!("The account `{}` no longer exists.", acct_key),
+    };
+
+    let rename = StatementRename::new(
+        &Statement::new(file, date),
+        acct.directory(),
+        acct.format_string(),
+    );
+    rename.apply()?;
+
+    Ok(rename.to().to_path_buf())
+}
+
+/// Work out which files in the inbox can be sorted into the directories of the accounts
+/// they belong to, to ask before moving them
+fn sort_inbox_prompt(conf: &Config) -> anyhow::Result<Prompt> {
+    let dir = match conf.inbox().dir() {
+        Some(dir) => dir,
+        None => bail!("There's no `dir` in the `[Inbox]` table to sort statements from."),
+    };
+    let files = inbox_files(&dir)
+        .with_context(|| format!("Couldn't read the inbox `{}`", dir.display()))?;
+
+    let mut moves = vec![];
+    let mut unsorted = 0;
+    for file in sort_inbox(conf, &files) {
+        match file.sorting() {
+            Sorting::Move(_, rename) => moves.push(rename.clone()),
+            Sorting::Ambiguous(_) | Sorting::Unmatched => unsorted += 1,
+        }
+    }
+    if moves.is_empty() {
+        bail!(
+            "None of the files in `{}` could be sorted into an account.",
+            dir.display()
+        );
+    }
+
+    Ok(Prompt::SortInbox {
+        dir,
+        moves,
+        unsorted,
+    })
+}
+
+/// Move files from the inbox into the directories of the accounts they belong to.
+/// Returns where each file that was moved was moved from and to, logging why any others couldn't be.
+fn sort_inbox_files(moves: &[StatementRename]) -> Vec<(PathBuf, PathBuf)> {
+    moves
+        .iter()
+        .filter(|rename| match rename.apply() {
+            Ok(()) => true,
+            Err(e) => {
+                warn!("{}", e);
+                false
+            }
+        })
+        .map(|rename| (rename.from().to_path_buf(), rename.to().to_path_buf()))
+        .collect()
 }
 
 /// Open a file explorer in the account's directory.
-fn open_account_external(conf: &Config, selected_acct: usize) {
-    let acct_name = conf.keys()[selected_acct].as_str();
-    if let Some(acct) = conf.accounts().get(acct_name) {
-        // open the directory for the account
-        open::that_in_background(acct.directory());
+/// Returns the directory that was opened.
+fn open_account_external(conf: &Config, acct_key: &str, headless: bool) -> anyhow::Result<Opened> {
+    let acct = match conf.accounts().get(acct_key) {
+        Some(acct) => acct,
+        None => bail!("The account `{}` no longer exists.", acct_key),
+    };
+
+    // remote directories can only be listed, not browsed
+    if acct.is_remote() {
+        bail!(
+            "`{}` is a remote directory, which can't be opened.",
+            acct.directory().display()
+        );
+    }
+
+    // open the directory for the account
+    Ok(open_default(acct.directory(), headless))
+}
+
+/// Open the statement portal of an account's institution in the web browser,
+/// or its website if it has no portal.
+/// Returns the address that was opened.
+fn open_portal_external(conf: &Config, acct_key: &str, headless: bool) -> anyhow::Result<Opened> {
+    let acct = match conf.accounts().get(acct_key) {
+        Some(acct) => acct,
+        None => bail!("The account `{}` no longer exists.", acct_key),
+    };
+
+    match conf.institution(acct).and_then(Institution::download_page) {
+        Some(portal) => Ok(open_default(portal, headless)),
+        None => bail!(
+            "`{}` has no `portal` or `website` in the `[Institutions]` table.",
+            acct.institution()
+        ),
     }
 }
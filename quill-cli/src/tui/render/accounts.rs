@@ -1,61 +1,127 @@
 //! Functions for rendering the "Accounts" page.
 
-use std::io::Stdout;
-
-use super::{colours::BACKGROUND, PRIMARY};
-use crate::{cfg::Config, tui::state::TuiState};
+use super::scrollbar;
+use crate::{
+    cfg::{Config, Theme},
+    tui::{
+        groups::{
+            account_badges, account_label, accounts_tab_rows, group_summary, group_title,
+            missing_count, next_due, AccountRow,
+        },
+        state::TuiState,
+    },
+};
 use ratatui::{
-    backend::CrosstermBackend,
+    backend::Backend,
     layout::{Constraint, Rect},
     style::{Modifier, Style},
-    widgets::{Block, Borders, Row, Table},
+    widgets::{Block, Borders, Cell, Row, Table},
     Frame,
 };
 
 /// Block for rendering "Accounts" page
-fn accounts_widget<'a>(conf: &'a Config) -> Table<'a> {
-    let accts: Vec<Row> = conf
-        .keys()
-        .iter()
-        .map(|k| {
-            let acct = conf.accounts().get(k).unwrap();
-            Row::new(vec![
-                acct.name(),
-                acct.institution(),
-                acct.directory().to_str().unwrap_or(""),
+fn accounts_widget<'a>(
+    conf: &'a Config,
+    theme: &Theme,
+    state: &TuiState,
+    widths: &'a [Constraint],
+) -> Table<'a> {
+    let accts: Vec<Row> = accounts_tab_rows(conf, state)
+        .into_iter()
+        .map(|row| match row {
+            AccountRow::Group(group) => Row::new(vec![
+                group_title(group, state.collapsed()),
+                String::new(),
+                group_summary(conf, group),
+                String::new(),
+                String::new(),
+                String::new(),
             ])
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+            AccountRow::Account(key) => {
+                let acct = &conf.accounts()[key];
+                // accounts with missing statements stand out
+                let missing = match missing_count(conf, key) {
+                    0 => Cell::from("0"),
+                    n => Cell::from(n.to_string()).style(Style::default().fg(theme.error())),
+                };
+                let row = Row::new(vec![
+                    Cell::from(account_label(conf, key)),
+                    Cell::from(account_badges(conf, key)),
+                    Cell::from(acct.institution().to_string()),
+                    missing,
+                    Cell::from(
+                        next_due(conf, key).map_or(String::new(), |d| conf.display().date(d)),
+                    ),
+                    Cell::from(acct.directory().to_str().unwrap_or("").to_string()),
+                ]);
+
+                // closed accounts no longer expect statements, and paused ones aren't checked
+                if acct.is_closed() {
+                    row.style(Style::default().fg(theme.foreground_dimmed()))
+                } else if acct.is_paused() {
+                    row.style(Style::default().fg(theme.warning()))
+                } else {
+                    row
+                }
+            }
         })
         .collect();
+
+    let mut title = format!("Sorted by {}", state.accounts().sort().description());
+    if state.accounts().missing_only() {
+        title.push_str(", only accounts with missing statements");
+    }
+
     let acct_table = Table::new(accts)
         .header(
-            Row::new(vec!["Account Name", "Institution", "Directory"]).style(
+            Row::new(vec![
+                "Account Name",
+                "Kind",
+                "Institution",
+                "Missing",
+                "Next Due",
+                "Directory",
+            ])
+            .style(
                 Style::default()
-                    .fg(PRIMARY)
+                    .fg(theme.primary())
                     .add_modifier(Modifier::BOLD)
                     .add_modifier(Modifier::UNDERLINED),
             ),
         )
-        .block(Block::default().borders(Borders::ALL))
-        .widths(&[
-            Constraint::Min(20),
-            Constraint::Min(30),
-            Constraint::Min(20),
-        ])
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .widths(widths)
         .column_spacing(2)
-        .style(Style::default().bg(BACKGROUND))
-        .highlight_style(Style::default().fg(BACKGROUND).bg(PRIMARY));
+        .style(Style::default().bg(theme.background()))
+        .highlight_style(Style::default().fg(theme.background()).bg(theme.primary()));
     acct_table
 }
 
 /// Render the body for the "Accounts" tab
-pub fn accounts_body(
-    f: &mut Frame<CrosstermBackend<Stdout>>,
+pub fn accounts_body<B: Backend>(
+    f: &mut Frame<B>,
     conf: &Config,
+    theme: &Theme,
     state: &mut TuiState,
     area: &Rect,
 ) {
-    let widget = accounts_widget(conf);
+    // the "Next Due" column is as wide as the dates in it can be
+    let date_width = conf.display().date_width().max(8) as u16;
+    let widths = [
+        Constraint::Min(20),
+        Constraint::Length(8),
+        Constraint::Min(30),
+        Constraint::Length(7),
+        Constraint::Length(date_width),
+        Constraint::Min(20),
+    ];
+    let widget = accounts_widget(conf, theme, state, &widths);
+    // the header takes up a row, like each account does
+    let n_rows = accounts_tab_rows(conf, state).len() + 1;
     let widget_state = state.mut_accounts().mut_state();
 
     f.render_stateful_widget(widget, *area, widget_state);
+    let offset = widget_state.offset();
+    scrollbar(f, theme, *area, n_rows, offset);
 }
@@ -1,8 +0,0 @@
-//! Colour theme used throughout the TUI.
-
-use ratatui::style::Color;
-
-pub const PRIMARY: Color = Color::Rgb(255, 140, 0);
-pub const BACKGROUND: Color = Color::Black;
-pub const FOREGROUND_DIMMED: Color = Color::DarkGray;
-pub const ERROR: Color = Color::Red;
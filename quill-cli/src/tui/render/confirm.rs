@@ -0,0 +1,45 @@
+//! Render a question that must be confirmed before something is done.
+
+use super::centred;
+use crate::cfg::Theme;
+use ratatui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+/// Width of the confirmation popup, including its borders
+const CONFIRM_WIDTH: u16 = 70;
+
+/// Render a yes or no question in a popup in the centre of `area`
+pub fn confirm<B: Backend>(f: &mut Frame<B>, theme: &Theme, question: &str, area: Rect) {
+    let lines = vec![
+        Line::from(question.to_string()),
+        Line::from(""),
+        Line::from(vec![
+            Span::raw("Press "),
+            Span::styled(
+                "y",
+                Style::default()
+                    .fg(theme.primary())
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" to confirm, or any other key to cancel."),
+        ]),
+    ];
+
+    // leave room for the borders, and for a long question to wrap
+    let popup = centred(area, CONFIRM_WIDTH, lines.len() as u16 + 4);
+
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().title("Confirm").borders(Borders::ALL))
+        .wrap(Wrap { trim: false })
+        .style(Style::default().bg(theme.background()));
+
+    // clear what's underneath, so the tab doesn't show through
+    f.render_widget(Clear, popup);
+    f.render_widget(paragraph, popup);
+}
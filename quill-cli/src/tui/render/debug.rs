@@ -0,0 +1,42 @@
+//! Render the debug pane, showing the most recent log events.
+
+use crate::cfg::Theme;
+use ratatui::{
+    style::Style,
+    text::Line,
+    widgets::{Block, Borders, Paragraph},
+};
+
+/// Render the most recent `events` that fit in a pane of the given height, newest last
+pub fn debug_pane<'a>(events: &'a [String], theme: &Theme, height: u16) -> Paragraph<'a> {
+    // leave room for the borders
+    let n_lines = height.saturating_sub(2) as usize;
+    let shown = &events[events.len().saturating_sub(n_lines)..];
+
+    let lines: Vec<Line> = if shown.is_empty() {
+        vec![Line::styled(
+            "Nothing has been logged yet.",
+            Style::default().fg(theme.foreground_dimmed()),
+        )]
+    } else {
+        shown
+            .iter()
+            .map(|event| {
+                let colour = if event.starts_with("ERROR") {
+                    theme.error()
+                } else if event.starts_with("WARN") {
+                    theme.warning()
+                } else {
+                    theme.foreground_dimmed()
+                };
+                Line::styled(event.as_str(), Style::default().fg(colour))
+            })
+            .collect()
+    };
+
+    Paragraph::new(lines).block(
+        Block::default()
+            .title("Debug (press d to hide)")
+            .borders(Borders::ALL),
+    )
+}
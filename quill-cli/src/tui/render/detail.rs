@@ -0,0 +1,154 @@
+//! Render the details of an account over the "Accounts" tab.
+
+use super::centred;
+use crate::cfg::{Config, Theme};
+use quill_statement::StatementStatus;
+use ratatui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+/// Width of the details popup, including its borders
+const DETAIL_WIDTH: u16 = 90;
+
+/// Width of the labels in the details popup
+const LABEL_WIDTH: usize = 20;
+
+/// Everything there is to know about an account, as labelled values
+fn detail_lines(conf: &Config, key: &str) -> Vec<(&'static str, String)> {
+    let acct = match conf.accounts().get(key) {
+        Some(acct) => acct,
+        None => return vec![],
+    };
+
+    let mut lines = vec![("Name", acct.name().to_string())];
+    if let Some(sched) = acct.schedule() {
+        lines.push(("Schedule", sched.to_string()));
+    }
+    if let Some(source) = conf.source(key) {
+        lines.push(("Configuration", source.to_string()));
+    }
+    let institution = conf.institution(acct);
+    let institution_name = institution
+        .and_then(|inst| inst.name())
+        .unwrap_or_else(|| acct.institution());
+    lines.push(("Institution", institution_name.to_string()));
+    if let Some(inst) = institution {
+        let contacts = [
+            ("Website", inst.website()),
+            ("Support phone", inst.support_phone()),
+            ("Statement portal", inst.portal()),
+        ];
+        for (label, value) in contacts {
+            if let Some(value) = value {
+                lines.push((label, value.to_string()));
+            }
+        }
+    }
+    lines.push(("Directory", acct.directory().display().to_string()));
+    lines.push(("Statement format", acct.format_string().to_string()));
+    lines.push((
+        "Period",
+        acct.period_description()
+            .unwrap_or_else(|| "unknown".to_string()),
+    ));
+    let display = conf.display();
+    lines.push(("First statement", display.date(acct.first())));
+    if let Some(last) = acct.last() {
+        lines.push(("Last statement", display.date(last)));
+    }
+
+    // the previous date is only meaningful once statements are expected
+    let prev = acct.prev_statement();
+    let prev = match prev >= *acct.first() {
+        true => display.date(&prev),
+        false => "none yet".to_string(),
+    };
+    lines.push(("Previous statement", prev));
+    let next = conf
+        .statements()
+        .get_upcoming(key)
+        .and_then(|dates| dates.first())
+        .map_or_else(|| "none expected".to_string(), |d| display.date(d));
+    lines.push(("Next statement", next));
+
+    let count = |status: StatementStatus| {
+        conf.statements().get(key).map_or(0, |stmts| {
+            stmts.iter().filter(|s| s.status() == status).count()
+        })
+    };
+    lines.push((
+        "Statements",
+        format!(
+            "{} available, {} missing, {} lost, {} pending, {} ignored, {} unexpected",
+            count(StatementStatus::Available),
+            count(StatementStatus::Missing),
+            count(StatementStatus::Lost),
+            count(StatementStatus::Pending),
+            count(StatementStatus::Ignored),
+            count(StatementStatus::Unexpected),
+        ),
+    ));
+
+    let ignored: Vec<String> = acct
+        .ignored()
+        .iter()
+        .map(|d| d.to_string())
+        .chain(
+            acct.ignored()
+                .ranges()
+                .map(|(from, to)| format!("{} to {}", from, to)),
+        )
+        .collect();
+    let ignored = match ignored.is_empty() {
+        true => "none".to_string(),
+        false => ignored.join(", "),
+    };
+    lines.push(("Ignored", ignored));
+
+    lines
+}
+
+/// Render the details of the account with `key` in a popup in the centre of `area`
+pub fn account_detail<B: Backend>(
+    f: &mut Frame<B>,
+    conf: &Config,
+    theme: &Theme,
+    key: &str,
+    area: Rect,
+) {
+    let lines: Vec<Line> = detail_lines(conf, key)
+        .into_iter()
+        .map(|(label, value)| {
+            Line::from(vec![
+                Span::styled(
+                    format!("{:width$}", label, width = LABEL_WIDTH),
+                    Style::default()
+                        .fg(theme.primary())
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(value),
+            ])
+        })
+        .collect();
+
+    // leave room for the borders, and for long values to wrap
+    let popup = centred(area, DETAIL_WIDTH, lines.len() as u16 + 4);
+
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title("Account details (press e to edit, Esc to close)")
+                .borders(Borders::ALL),
+        )
+        .wrap(Wrap { trim: false })
+        .style(Style::default().bg(theme.background()));
+
+    // clear what's underneath, so the tab doesn't show through
+    f.render_widget(Clear, popup);
+    f.render_widget(paragraph, popup);
+}
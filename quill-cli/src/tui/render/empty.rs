@@ -0,0 +1,134 @@
+//! Guidance shown in place of a view that has nothing to display.
+
+use crate::cfg::{Config, Theme};
+use quill_account::Account;
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Wrap},
+};
+
+/// An example account to copy into the configuration file
+const EXAMPLE_ACCOUNT: [&str; 8] = [
+    "[Accounts.Chequing]",
+    "name = \"Chequing\"",
+    "institution = \"My Bank\"",
+    "statement_fmt = \"%Y-%m-%d.pdf\"",
+    "dir = \"~/statements/chequing\"",
+    "first_date = 2021-01-15",
+    "statement_period = [15, \"Day\", 1, \"Month\"]",
+    "",
+];
+
+/// Render an explanation of why a view is empty, followed by the steps that
+/// will fill it.
+pub fn empty_state<'a>(
+    title: &'a str,
+    summary: String,
+    steps: Vec<Line<'a>>,
+    theme: &Theme,
+) -> Paragraph<'a> {
+    let mut lines = vec![
+        Line::from(Span::styled(
+            summary,
+            Style::default()
+                .fg(theme.primary())
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+    ];
+    lines.extend(steps);
+
+    Paragraph::new(lines)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .style(Style::default().bg(theme.background()))
+        .wrap(Wrap { trim: false })
+}
+
+/// Guidance for when no accounts are configured
+pub fn no_accounts<'a>(conf: &Config, theme: &Theme) -> Paragraph<'a> {
+    let mut steps = vec![
+        Line::from(format!(
+            "The configuration file `{}` doesn't contain any accounts.",
+            conf.path().display()
+        )),
+        Line::from(""),
+        Line::from("Run `quill init` to add an account interactively, or add one to the `[Accounts]` table yourself, like this:"),
+        Line::from(""),
+    ];
+    steps.extend(
+        EXAMPLE_ACCOUNT
+            .iter()
+            .map(|&l| Line::from(Span::styled(format!("    {}", l), dimmed(theme)))),
+    );
+    steps.push(Line::from(
+        "Then check the configuration with `quill validate` and press `r` to refresh.",
+    ));
+
+    empty_state(
+        "No accounts",
+        "No accounts are configured".to_string(),
+        steps,
+        theme,
+    )
+}
+
+/// Guidance for when an account doesn't have any statements yet
+pub fn no_statements<'a>(acct: &Account, theme: &Theme) -> Paragraph<'a> {
+    let steps = vec![
+        Line::from(format!(
+            "The first statement is expected on {}.",
+            acct.first()
+        )),
+        Line::from(""),
+        Line::from(format!(
+            "Save statements in `{}` with file names matching `{}`, then press `r` to refresh.",
+            acct.directory().display(),
+            acct.format_string()
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            "If the first statement date is wrong, change `first_date` in the configuration file.",
+            dimmed(theme),
+        )),
+    ];
+
+    empty_state(
+        "Statements",
+        format!("No statements are expected for {} yet", acct.name()),
+        steps,
+        theme,
+    )
+}
+
+/// Guidance for when an account is paused, so its statements aren't checked
+pub fn account_paused<'a>(acct: &Account, theme: &Theme) -> Paragraph<'a> {
+    let (reason, fix) = match acct.paused_until() {
+        Some(until) if acct.enabled() => (
+            format!("This account is paused until {}.", until),
+            "Remove `paused_until` from the configuration file to check its statements now.",
+        ),
+        _ => (
+            "This account is disabled.".to_string(),
+            "Set `enabled = true` in the configuration file to check its statements again.",
+        ),
+    };
+
+    let steps = vec![
+        Line::from(reason),
+        Line::from(""),
+        Line::from(Span::styled(fix, dimmed(theme))),
+    ];
+
+    empty_state(
+        "Statements",
+        format!("Statements for {} aren't being checked", acct.name()),
+        steps,
+        theme,
+    )
+}
+
+/// Style for supporting text
+fn dimmed(theme: &Theme) -> Style {
+    Style::default().fg(theme.foreground_dimmed())
+}
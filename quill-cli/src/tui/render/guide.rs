@@ -1,6 +1,6 @@
 //! Render the guide keys on the screen.
 
-use super::colours::FOREGROUND_DIMMED;
+use crate::cfg::Theme;
 use ratatui::{
     style::Style,
     symbols::line::VERTICAL,
@@ -8,19 +8,18 @@ use ratatui::{
     widgets::{Block, Tabs},
 };
 
-const GUIDE_KEYS: [&str; 5] = [
+const GUIDE_KEYS: [&str; 4] = [
     "Next Tab [\u{21e5}]",
-    "Prev Tab [\u{21e4}]",
     "Navigate [\u{2190}\u{2193}\u{2191}\u{2192}/hjkl]",
-    "Refresh [r]",
+    "Help [?]",
     "Quit [q]",
 ];
 
 /// Render the key guide.
-pub fn guide() -> Tabs<'static> {
+pub fn guide(theme: &Theme) -> Tabs<'static> {
     let guide_lines: Vec<Line> = GUIDE_KEYS.iter().cloned().map(Line::from).collect();
     Tabs::new(guide_lines)
         .block(Block::default())
-        .style(Style::default().fg(FOREGROUND_DIMMED))
+        .style(Style::default().fg(theme.foreground_dimmed()))
         .divider(VERTICAL)
 }
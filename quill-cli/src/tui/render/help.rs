@@ -0,0 +1,52 @@
+//! Render the help, listing every key binding, over the current tab.
+
+use super::centred;
+use crate::{cfg::Theme, tui::keymap::bindings_by_action};
+use ratatui::{
+    backend::Backend,
+    layout::{Constraint, Rect},
+    style::{Modifier, Style},
+    widgets::{Block, Borders, Clear, Row, Table},
+    Frame,
+};
+
+/// Width of the help popup, including its borders
+const HELP_WIDTH: u16 = 76;
+
+/// Width of the column of keys in the help popup
+const KEYS_WIDTH: u16 = 18;
+
+/// Render the help in a popup in the centre of `area`
+pub fn help<B: Backend>(f: &mut Frame<B>, theme: &Theme, area: Rect) {
+    let rows: Vec<Row> = bindings_by_action()
+        .into_iter()
+        .map(|(action, keys)| Row::new(vec![keys.join(", "), action.description()]))
+        .collect();
+
+    // leave room for the borders and the header
+    let popup = centred(area, HELP_WIDTH, rows.len() as u16 + 3);
+
+    let table = Table::new(rows)
+        .header(
+            Row::new(vec!["Keys", "Action"]).style(
+                Style::default()
+                    .fg(theme.primary())
+                    .add_modifier(Modifier::BOLD),
+            ),
+        )
+        .block(
+            Block::default()
+                .title("Help (press Esc to close)")
+                .borders(Borders::ALL),
+        )
+        .widths(&[
+            Constraint::Length(KEYS_WIDTH),
+            Constraint::Length(HELP_WIDTH - KEYS_WIDTH - 3),
+        ])
+        .column_spacing(1)
+        .style(Style::default().bg(theme.background()));
+
+    // clear what's underneath, so the tab doesn't show through
+    f.render_widget(Clear, popup);
+    f.render_widget(table, popup);
+}
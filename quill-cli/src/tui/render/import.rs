@@ -0,0 +1,62 @@
+//! Render the files that can be imported as an account's statement.
+
+use super::centred;
+use crate::{
+    cfg::{Config, Theme},
+    tui::state::ImportPicker,
+};
+use ratatui::{
+    backend::Backend,
+    layout::Rect,
+    style::Style,
+    widgets::{Block, Borders, Clear, List, ListItem},
+    Frame,
+};
+
+/// Width of the import popup, including its borders
+const IMPORT_WIDTH: u16 = 80;
+
+/// Most files listed in the import popup at once
+const MAX_IMPORT_ROWS: u16 = 15;
+
+/// Render the files to choose from in a popup in the centre of `area`
+pub fn import_picker<B: Backend>(
+    f: &mut Frame<B>,
+    conf: &Config,
+    theme: &Theme,
+    picker: &mut ImportPicker,
+    area: Rect,
+) {
+    let name = conf
+        .accounts()
+        .get(picker.key().as_str())
+        .map_or(picker.key().as_str(), |acct| acct.name());
+    let title = format!(
+        "Import as the {} statement from {} (Enter to import, Esc to cancel)",
+        name,
+        conf.display().date(picker.date())
+    );
+
+    // only the file names are listed, since they're all in the inbox
+    let items: Vec<ListItem> = picker
+        .files()
+        .iter()
+        .map(|path| {
+            let file_name = path.file_name().unwrap_or(path.as_os_str());
+            ListItem::new(file_name.to_string_lossy().to_string())
+        })
+        .collect();
+
+    // leave room for the borders
+    let rows = (items.len() as u16).min(MAX_IMPORT_ROWS);
+    let popup = centred(area, IMPORT_WIDTH, rows + 2);
+
+    let list = List::new(items)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .style(Style::default().bg(theme.background()))
+        .highlight_style(Style::default().fg(theme.background()).bg(theme.primary()));
+
+    // clear what's underneath, so the tab doesn't show through
+    f.render_widget(Clear, popup);
+    f.render_stateful_widget(list, popup, picker.mut_state());
+}
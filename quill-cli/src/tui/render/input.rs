@@ -0,0 +1,45 @@
+//! Render a line of text being typed by the user.
+
+use super::centred;
+use crate::{cfg::Theme, tui::input::TextInput};
+use ratatui::{
+    backend::Backend,
+    layout::Rect,
+    style::Style,
+    text::Line,
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+/// Width of the text input popup, including its borders
+const INPUT_WIDTH: u16 = 60;
+
+/// Render the text being typed in a popup in the centre of `area`, with the cursor in it.
+/// Text too long to fit is scrolled to keep the cursor in view.
+pub fn text_input<B: Backend>(f: &mut Frame<B>, theme: &Theme, input: &TextInput, area: Rect) {
+    let mut lines = vec![];
+    let error = input
+        .error()
+        .map(|e| Line::styled(e.to_string(), Style::default().fg(theme.error())));
+
+    // leave room for the borders, and for the reason the text couldn't be submitted
+    let height = 3 + error.is_some() as u16;
+    let popup = centred(area, INPUT_WIDTH, height);
+    let width = popup.width.saturating_sub(2) as usize;
+
+    // keep the cursor on screen, with room for it after the last character
+    let start = (input.cursor() + 1).saturating_sub(width);
+    let visible: String = input.value().chars().skip(start).take(width).collect();
+    lines.push(Line::from(visible));
+    lines.extend(error);
+
+    let title = format!("{} (Enter to accept, Esc to cancel)", input.title());
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .style(Style::default().bg(theme.background()));
+
+    // clear what's underneath, so the tab doesn't show through
+    f.render_widget(Clear, popup);
+    f.render_widget(paragraph, popup);
+    f.set_cursor(popup.x + 1 + (input.cursor() - start) as u16, popup.y + 1);
+}
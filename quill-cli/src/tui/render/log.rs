@@ -1,58 +1,93 @@
 //! Functions for rendering the "Log" page.
 
-use std::io::Stdout;
-
 use super::{
-    colours::{BACKGROUND, ERROR, FOREGROUND_DIMMED},
-    PRIMARY,
+    empty::{account_paused, no_statements},
+    scrollbar,
+    timeline::{timeline_height, timeline_widget},
 };
 use crate::{
-    cfg::Config,
-    tui::state::{LogState, TuiState},
+    cfg::{Config, DisplayConfig, Theme},
+    tui::{
+        groups::{
+            account_label, account_rows, group_accounts, group_summary, group_title, missing_count,
+            AccountRow,
+        },
+        log_statements,
+        state::{LogState, TuiState},
+    },
 };
-use quill_statement::{ObservedStatement, StatementStatus};
+use chrono::NaiveDate;
+use quill_account::account::TRANSACTIONS_SCHEDULE;
+use quill_statement::{AccountKey, ObservedStatement, StatementStatus};
 use ratatui::{
-    backend::CrosstermBackend,
+    backend::Backend,
     layout::{Constraint, Direction, Layout, Rect},
     style::{Modifier, Style},
     widgets::{Block, Borders, List, ListItem},
     Frame,
 };
+use std::collections::{HashMap, HashSet};
 
 /// Create a block to render the "Log" page.
-fn log_widget<'a>(conf: &'a Config<'a>, state: &LogState) -> (List<'a>, List<'a>) {
-    let acct_names_ordered: Vec<ListItem> = conf
-        .keys()
+fn log_widget<'a>(
+    conf: &'a Config,
+    theme: &Theme,
+    state: &LogState,
+    collapsed: &HashSet<String>,
+) -> (List<'a>, List<'a>) {
+    let rows = account_rows(conf, collapsed);
+    let acct_names_ordered: Vec<ListItem> = rows
         .iter()
-        .map(|a| ListItem::new(conf.accounts().get(a.as_str()).unwrap().name()))
+        .map(|row| match *row {
+            AccountRow::Group(group) => ListItem::new(format!(
+                "{} ({})",
+                group_title(group, collapsed),
+                group_summary(conf, group)
+            ))
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+            AccountRow::Account(key) => ListItem::new(account_label(conf, key)),
+        })
         .collect();
 
     let mut accts = List::new(acct_names_ordered)
         .block(Block::default().title("Accounts").borders(Borders::ALL))
-        .highlight_style(Style::default().fg(BACKGROUND).bg(PRIMARY));
+        .highlight_style(Style::default().fg(theme.background()).bg(theme.primary()));
 
     // get the log of statements for the selected account
-    let rows: Vec<ListItem> = match state.selected_account() {
-        Some(acct_idx) => {
-            // get the HashMap key of the account that's highlighted
-            let acct_key = conf.keys()[acct_idx].as_str();
-            // convert the statements into formatted Rows
-            conf.statements()
-                .get(acct_key)
-                .unwrap()
-                .iter()
-                // go through in reverse chronological order so latest is at the top
-                .rev()
-                .map(stylize_obs_stmt)
+    let rows: Vec<ListItem> = match state.selected_account().and_then(|i| rows.get(i)) {
+        // summarize each account in the selected group
+        Some(AccountRow::Group(group)) => group_accounts(conf, group)
+            .into_iter()
+            .map(|key| stylize_acct_summary(conf, theme, key))
+            .collect(),
+        Some(&AccountRow::Account(acct_key)) => {
+            let acct = &conf.accounts()[acct_key];
+            let transactions = transaction_statuses(conf, acct_key);
+            // convert the statements into formatted Rows, with the latest at the top
+            log_statements(conf, acct_key, state.year())
+                .into_iter()
+                .map(|obs_stmt| {
+                    let date = obs_stmt.statement().date();
+                    let txn = transactions.get(date).copied();
+                    let reason = match obs_stmt.status() {
+                        StatementStatus::Ignored => acct.ignored().reason(date),
+                        _ => None,
+                    };
+                    stylize_obs_stmt(obs_stmt, txn, reason, conf.display(), theme)
+                })
                 .collect()
         }
         // return the template table if no Account is selected
         // this should never happen
         None => vec![ListItem::new("There are no accounts")],
     };
+    let title = match state.year() {
+        Some(year) => format!("Statements from {} ([ and ] to change the year)", year),
+        None => "Statements".to_string(),
+    };
     let mut log = List::new(rows)
-        .block(Block::default().title("Statements").borders(Borders::ALL))
-        .highlight_style(Style::default().fg(BACKGROUND).bg(PRIMARY));
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .highlight_style(Style::default().fg(theme.background()).bg(theme.primary()));
 
     // dim the side that is not selected
     if state.selected_log().is_some() {
@@ -66,20 +101,80 @@ fn log_widget<'a>(conf: &'a Config<'a>, state: &LogState) -> (List<'a>, List<'a>
     (accts, log)
 }
 
-/// Stylize the statement date strings in the log pane
-fn stylize_obs_stmt(obs_stmt: &ObservedStatement) -> ListItem {
+/// Stylize an account's name and number of missing statements in the log pane
+fn stylize_acct_summary<'a>(conf: &'a Config, theme: &Theme, key: &AccountKey) -> ListItem<'a> {
+    let acct = &conf.accounts()[key];
+    let missing = missing_count(conf, key);
+
+    let li = ListItem::new(format!("{} ({} missing)", acct.name(), missing));
+    match missing {
+        0 => li,
+        _ => li.style(Style::default().fg(theme.error())),
+    }
+}
+
+/// The status of the account's transaction files on each date, if the account tracks them
+fn transaction_statuses(conf: &Config, acct_key: &str) -> HashMap<NaiveDate, StatementStatus> {
+    let tracked = conf
+        .accounts()
+        .get(acct_key)
+        .and_then(|acct| acct.transaction_fmt())
+        .is_some();
+    if !tracked {
+        return HashMap::new();
+    }
+
+    conf.statements()
+        .get(&format!("{}/{}", acct_key, TRANSACTIONS_SCHEDULE))
+        .into_iter()
+        .flatten()
+        .map(|obs| (*obs.statement().date(), obs.status()))
+        .collect()
+}
+
+/// Stylize the statement date strings in the log pane, along with why it's ignored
+/// and the status of the transaction file due on the same date, if there are either
+fn stylize_obs_stmt<'a>(
+    obs_stmt: &'a ObservedStatement,
+    transactions: Option<StatementStatus>,
+    reason: Option<&str>,
+    display: &DisplayConfig,
+    theme: &Theme,
+) -> ListItem<'a> {
     // format the string to be printed
-    let li_str = format!(
+    let mut li_str = format!(
         "{} {}",
-        obs_stmt.statement().date(),
+        display.date(obs_stmt.statement().date()),
         String::from(obs_stmt.status())
     );
+    if let Some(reason) = reason {
+        li_str = format!("{}  ({})", li_str, reason);
+    }
+    if let Some(status) = transactions {
+        li_str = format!("{}  transactions {}", li_str, String::from(status));
+    }
 
     let mut li = ListItem::new(li_str);
-    // style the string based on the statement's status
-    match obs_stmt.status() {
-        StatementStatus::Ignored => li = li.style(Style::default().fg(FOREGROUND_DIMMED)),
-        StatementStatus::Missing => li = li.style(Style::default().fg(ERROR)),
+    // style the string based on the statement's status, or the transaction
+    // file's if it's missing
+    let status = match transactions {
+        Some(status @ (StatementStatus::Missing | StatementStatus::Lost)) => status,
+        _ => obs_stmt.status(),
+    };
+    match status {
+        StatementStatus::Ignored | StatementStatus::Pending => {
+            li = li.style(Style::default().fg(theme.foreground_dimmed()))
+        }
+        StatementStatus::Missing => li = li.style(Style::default().fg(theme.error())),
+        // a file that disappeared may have been deleted by accident, so it stands out more
+        StatementStatus::Lost => {
+            li = li.style(
+                Style::default()
+                    .fg(theme.error())
+                    .add_modifier(Modifier::BOLD),
+            )
+        }
+        StatementStatus::Unexpected => li = li.style(Style::default().fg(theme.warning())),
         _ => {}
     };
 
@@ -87,9 +182,10 @@ fn stylize_obs_stmt(obs_stmt: &ObservedStatement) -> ListItem {
 }
 
 /// Render the body for the "Log" tab
-pub fn log_body(
-    f: &mut Frame<CrosstermBackend<Stdout>>,
+pub fn log_body<B: Backend>(
+    f: &mut Frame<B>,
     conf: &Config,
+    theme: &Theme,
     state: &mut TuiState,
     area: &Rect,
 ) {
@@ -108,8 +204,54 @@ pub fn log_body(
         )
         .split(*area);
 
-    let (left, right) = log_widget(conf, state.log());
+    let (left, right) = log_widget(conf, theme, state.log(), state.collapsed());
+    let (n_accounts, n_stmts) = (left.len(), right.len());
+    // leave out the borders of the lists when paging through them
+    let accounts_height = log_chunks[0].height.saturating_sub(2).into();
+    let log_height = log_chunks[1].height.saturating_sub(2).into();
+    state.mut_log().set_heights(accounts_height, log_height);
 
     f.render_stateful_widget(left, log_chunks[0], state.mut_log().mut_accounts());
+    let offset = state.mut_log().mut_accounts().offset();
+    scrollbar(f, theme, log_chunks[0], n_accounts, offset);
+
+    // explain why there aren't any statements to show, if that's the case
+    let selected_row = state
+        .log()
+        .selected_account()
+        .and_then(|i| account_rows(conf, state.collapsed()).get(i).copied());
+    if let Some(AccountRow::Account(acct_key)) = selected_row {
+        let acct = &conf.accounts()[acct_key];
+        if acct.is_paused() {
+            f.render_widget(account_paused(acct, theme), log_chunks[1]);
+            return;
+        }
+        if conf.statements().get(acct_key).map_or(true, Vec::is_empty) {
+            f.render_widget(no_statements(acct, theme), log_chunks[1]);
+            return;
+        }
+
+        // show the account's timeline above its statements, using at most half of the pane
+        let timeline = conf.statements().timeline(acct_key);
+        let height = timeline_height(&timeline).min(log_chunks[1].height / 2);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(height), Constraint::Min(0)].as_ref())
+            .split(log_chunks[1]);
+        let log_height = chunks[1].height.saturating_sub(2).into();
+        state.mut_log().set_heights(accounts_height, log_height);
+
+        f.render_widget(
+            timeline_widget(&timeline, conf.display(), theme, height),
+            chunks[0],
+        );
+        f.render_stateful_widget(right, chunks[1], state.mut_log().mut_log());
+        let offset = state.mut_log().mut_log().offset();
+        scrollbar(f, theme, chunks[1], n_stmts, offset);
+        return;
+    }
+
     f.render_stateful_widget(right, log_chunks[1], state.mut_log().mut_log());
+    let offset = state.mut_log().mut_log().offset();
+    scrollbar(f, theme, log_chunks[1], n_stmts, offset);
 }
@@ -1,37 +1,48 @@
 //! Functions for rendering the "Missing" page.
 
-use super::colours::FOREGROUND_DIMMED;
-use crate::{cfg::Config, tui::state::TuiState};
-use quill_statement::{ObservedStatement, StatementStatus};
-use std::io::Stdout;
+use crate::{
+    cfg::{Config, DisplayConfig, Theme},
+    tui::{groups::account_badges, state::TuiState},
+};
+use chrono::{Datelike, Local, NaiveDate};
+use quill_statement::{DateDiff, Gap};
 use ratatui::{
-    backend::CrosstermBackend,
+    backend::Backend,
     layout::Rect,
-    style::{Color, Style},
+    style::{Modifier, Style},
+    text::{Line, Span},
     widgets::{Block, Borders, List, ListItem},
     Frame,
 };
 
 /// Create a block to render the "Missing" page for account statements.
-fn missing_widget<'a>(conf: &'a Config<'a>) -> List<'a> {
+fn missing_widget<'a>(conf: &'a Config, theme: &Theme) -> List<'a> {
+    let today = Local::now().naive_local().date();
+
+    // the runs of missing statements of each account, most overdue first
+    let mut missing: Vec<(&str, Vec<Gap>)> = conf
+        .keys()
+        .iter()
+        .map(|key| (key.as_str(), conf.statements().gaps(key)))
+        .filter(|(_, gaps)| !gaps.is_empty())
+        .collect();
+    // list the account with the most overdue statement first
+    missing.sort_by_key(|(_, gaps)| *gaps[0].start());
+
     // render list of accounts with missing statements
     let mut accts_with_missing: Vec<ListItem> = vec![];
-    for acct_key in conf.keys() {
-        let this_acct = conf.accounts().get(acct_key.as_str()).unwrap();
-        let missing_stmts: Vec<ListItem> = conf
-            .statements()
-            .get(acct_key.as_str())
-            .unwrap()
-            .iter()
-            .filter(|&obs_stmt| obs_stmt.status() == StatementStatus::Missing)
-            .map(stylize_missing_stmt)
-            .collect();
-
-        if !missing_stmts.is_empty() {
-            accts_with_missing.push(ListItem::new(this_acct.name()));
-            for li in missing_stmts {
-                accts_with_missing.push(li);
-            }
+    for (acct_key, gaps) in missing {
+        let this_acct = conf.accounts().get(acct_key).unwrap();
+        accts_with_missing.push(ListItem::new(Line::from(vec![
+            Span::raw(this_acct.name()),
+            Span::raw("  "),
+            Span::styled(
+                account_badges(conf, acct_key),
+                Style::default().fg(theme.foreground_dimmed()),
+            ),
+        ])));
+        for gap in &gaps {
+            accts_with_missing.push(stylize_gap(gap, &today, conf.display(), theme));
         }
     }
 
@@ -39,31 +50,76 @@ fn missing_widget<'a>(conf: &'a Config<'a>) -> List<'a> {
     if accts_with_missing.is_empty() {
         accts_with_missing.push(
             // dim the colour so it displays differently than when accounts have missing statements
-            ListItem::new("No missing statements").style(Style::default().fg(FOREGROUND_DIMMED)),
+            ListItem::new("No missing statements")
+                .style(Style::default().fg(theme.foreground_dimmed())),
         );
     }
 
     let accts_list = List::new(accts_with_missing)
         .block(Block::default().borders(Borders::ALL))
-        .style(Style::default().bg(Color::Black))
+        .style(Style::default().bg(theme.background()))
         .highlight_style(Style::default());
 
     accts_list
 }
 
-/// Stylize the observed statement
-fn stylize_missing_stmt(obs_stmt: &ObservedStatement) -> ListItem {
-    ListItem::new(format!("  {}", obs_stmt.statement().date()))
+/// Stylize a run of missing statements with how many days overdue its oldest statement is,
+/// or how long ago it was due with `relative_dates`,
+/// escalating its colour once it's past the theme's threshold.
+/// A run of several statements is collapsed into a single row.
+fn stylize_gap<'a>(
+    gap: &Gap,
+    today: &NaiveDate,
+    display: &DisplayConfig,
+    theme: &Theme,
+) -> ListItem<'a> {
+    let days = (*today - *gap.start()).num_days().max(0);
+    let overdue = match display.relative_dates() {
+        true => format!("due {}", DateDiff::between(today, gap.start())),
+        false if days == 1 => "1 day overdue".to_string(),
+        false => format!("{} days overdue", days),
+    };
+    let li = ListItem::new(match gap.count() {
+        1 => format!("  {}  ({})", display.date(gap.start()), overdue),
+        _ => format!("  {}  (oldest {})", describe_gap(gap, display), overdue),
+    });
+
+    match days > i64::from(theme.overdue_days()) {
+        true => li.style(
+            Style::default()
+                .fg(theme.overdue())
+                .add_modifier(Modifier::BOLD),
+        ),
+        false => li,
+    }
+}
+
+/// Describe a run of missing statements by the months it covers, like "Mar–Aug 2020: 6 missing",
+/// with the names of the months in the locale
+fn describe_gap(gap: &Gap, display: &DisplayConfig) -> String {
+    let start = display.format(gap.start(), "%b %Y");
+    let end = display.format(gap.end(), "%b %Y");
+
+    let months = if start == end {
+        start
+    } else if gap.start().year() == gap.end().year() {
+        format!("{}\u{2013}{}", display.format(gap.start(), "%b"), end)
+    } else {
+        format!("{}\u{2013}{}", start, end)
+    };
+
+    format!("{}: {} missing", months, gap.count())
 }
 
 /// Render the body for the "Missing" tab
-pub fn missing_body(
-    f: &mut Frame<CrosstermBackend<Stdout>>,
+pub fn missing_body<B: Backend>(
+    f: &mut Frame<B>,
     conf: &Config,
+    theme: &Theme,
     state: &mut TuiState,
     area: &Rect,
 ) {
-    let widget = missing_widget(conf);
+    let widget = missing_widget(conf, theme);
     let widget_state = state.mut_missing().mut_state();
     f.render_stateful_widget(widget, *area, widget_state);
 }
@@ -1,22 +1,96 @@
 //! Functions to render different parts of the UI.
 
 mod accounts;
-mod colours;
+mod confirm;
+mod debug;
+mod detail;
+mod empty;
 mod guide;
+mod help;
+mod import;
+mod input;
 mod log;
 mod missing;
+mod notice;
+mod splash;
+mod status;
+mod summary;
 mod tabs;
+mod timeline;
 mod upcoming;
 
 pub use self::log::log_body;
 pub use accounts::accounts_body;
-pub use colours::PRIMARY;
+pub use confirm::confirm;
+pub use debug::debug_pane;
+pub use detail::account_detail;
+pub use empty::no_accounts;
 pub use guide::guide;
+pub use help::help;
+pub use import::import_picker;
+pub use input::text_input;
 pub use missing::missing_body;
+pub use notice::{fetch_notice, reload_error_notice, scan_notice, skipped_notice};
+pub use splash::splash;
+pub use status::status;
+pub use summary::summary_body;
 pub use tabs::tabs;
 pub use tabs::MenuItem;
 pub use upcoming::upcoming_body;
 
+use crate::cfg::Theme;
+use ratatui::{
+    backend::Backend,
+    layout::{Margin, Rect},
+    style::Style,
+    symbols,
+    widgets::{Scrollbar, ScrollbarOrientation, ScrollbarState},
+    Frame,
+};
+
+/// Draw a scrollbar over the right border of a list in `area` with `len` items,
+/// if they don't all fit.
+/// `offset` is the first item that's shown, which the list's state has after it's rendered.
+fn scrollbar<B: Backend>(f: &mut Frame<B>, theme: &Theme, area: Rect, len: usize, offset: usize) {
+    // the borders take up a row above and below the items
+    let visible = usize::from(area.height.saturating_sub(2));
+    if len <= visible {
+        return;
+    }
+
+    // the thumb reaches the bottom of the track once the last item is shown
+    let position = offset.min(len - visible) * len / (len - visible);
+    let to_u16 = |n: usize| u16::try_from(n).unwrap_or(u16::MAX);
+    let mut state = ScrollbarState::default()
+        .content_length(to_u16(len))
+        .viewport_content_length(to_u16(visible))
+        .position(to_u16(position));
+    let bar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+        .symbols(symbols::scrollbar::VERTICAL)
+        .begin_symbol(None)
+        .end_symbol(None)
+        .thumb_style(Style::default().fg(theme.primary()));
+
+    let track = area.inner(&Margin {
+        vertical: 1,
+        horizontal: 0,
+    });
+    f.render_stateful_widget(bar, track, &mut state);
+}
+
+/// A rectangle of at most the given size in the centre of `area`
+fn centred(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
+
 /// Modular arithmetic with a given modulo, current value, step size, and direction.
 pub fn step(modulo: usize, n: usize, size: usize, positive: bool) -> usize {
     match positive {
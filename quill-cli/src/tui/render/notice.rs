@@ -0,0 +1,48 @@
+//! Render notices about problems loading the configuration, and the results of fetching and scanning for statements.
+
+use crate::cfg::{ScanProgress, Theme};
+use quill_statement::AccountKey;
+use ratatui::{style::Style, widgets::Paragraph};
+
+/// Render a single line explaining why the configuration file couldn't be reloaded
+pub fn reload_error_notice<'a>(error: &str, theme: &Theme) -> Paragraph<'a> {
+    let text = format!(
+        " The configuration file couldn't be reloaded: {}. Press d for the debug pane.",
+        error
+    );
+
+    Paragraph::new(text).style(Style::default().fg(theme.error()))
+}
+
+/// Render a single line describing what happened when statements were fetched
+pub fn fetch_notice<'a>(result: &str, theme: &Theme) -> Paragraph<'a> {
+    Paragraph::new(format!(" {}", result)).style(Style::default().fg(theme.primary()))
+}
+
+/// Render a single line showing how far the background scan for statements has got
+pub fn scan_notice<'a>(progress: Option<&ScanProgress>, theme: &Theme) -> Paragraph<'a> {
+    let text = match progress {
+        Some(progress) => format!(
+            " Scanning for statements: {} of {} accounts done, now scanning {}…",
+            progress.scanned(),
+            progress.total(),
+            progress.key()
+        ),
+        None => " Scanning for statements…".to_string(),
+    };
+
+    Paragraph::new(text).style(Style::default().fg(theme.foreground_dimmed()))
+}
+
+/// Render a single line listing the accounts that couldn't be loaded
+pub fn skipped_notice<'a>(skipped: &[(AccountKey, String)], theme: &Theme) -> Paragraph<'a> {
+    let keys: Vec<&str> = skipped.iter().map(|(key, _)| key.as_str()).collect();
+    let text = format!(
+        " {} account{} couldn't be loaded: {}. Run `quill validate` for details.",
+        skipped.len(),
+        if skipped.len() == 1 { "" } else { "s" },
+        keys.join(", ")
+    );
+
+    Paragraph::new(text).style(Style::default().fg(theme.warning()))
+}
@@ -0,0 +1,74 @@
+//! Render the progress of the first scan for statements, in place of the views that need them.
+
+use crate::cfg::{Config, ScanProgress, Theme};
+use ratatui::{
+    backend::Backend,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::Style,
+    widgets::{Block, Borders, Gauge, Paragraph},
+    Frame,
+};
+
+/// Render which account is being scanned, and how many are left
+pub fn splash<B: Backend>(
+    f: &mut Frame<B>,
+    conf: &Config,
+    theme: &Theme,
+    progress: Option<&ScanProgress>,
+    area: &Rect,
+) {
+    let block = Block::default()
+        .title("Scanning for statements")
+        .borders(Borders::ALL)
+        .style(Style::default().bg(theme.background()));
+    let inner = block.inner(*area);
+    f.render_widget(block, *area);
+
+    let (text, ratio) = match progress {
+        Some(progress) => {
+            let key = progress.key();
+            let name = conf
+                .accounts()
+                .get(key)
+                .map_or(key.as_str(), |acct| acct.name());
+            let text = format!(
+                "Scanning account {}/{}: {}…",
+                progress.scanned() + 1,
+                progress.total(),
+                name
+            );
+            let ratio = progress.scanned() as f64 / progress.total().max(1) as f64;
+            (text, ratio)
+        }
+        None => ("Scanning for statements…".to_string(), 0.0),
+    };
+
+    // the message and the progress bar go in the middle of the pane, with a margin on either side
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .horizontal_margin(4)
+        .constraints(
+            [
+                Constraint::Min(0),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Min(0),
+            ]
+            .as_ref(),
+        )
+        .split(inner);
+
+    f.render_widget(
+        Paragraph::new(text)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(theme.primary())),
+        rows[1],
+    );
+    f.render_widget(
+        Gauge::default()
+            .gauge_style(Style::default().fg(theme.primary()).bg(theme.background()))
+            .ratio(ratio),
+        rows[3],
+    );
+}
@@ -0,0 +1,17 @@
+//! Render the status message in the footer, in place of the key guide.
+
+use crate::{
+    cfg::Theme,
+    tui::state::{StatusKind, StatusMessage},
+};
+use ratatui::{style::Style, widgets::Paragraph};
+
+/// Render a single line with the status message
+pub fn status<'a>(message: &StatusMessage, theme: &Theme) -> Paragraph<'a> {
+    let colour = match message.kind() {
+        StatusKind::Info => theme.primary(),
+        StatusKind::Error => theme.error(),
+    };
+
+    Paragraph::new(message.text().to_string()).style(Style::default().fg(colour))
+}
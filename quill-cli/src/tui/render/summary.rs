@@ -0,0 +1,194 @@
+//! Functions for rendering the "Summary" page, with statistics about every account.
+
+use crate::{
+    cfg::{Config, Theme},
+    tui::state::TuiState,
+};
+use chrono::{Local, NaiveDate};
+use quill_statement::{ObservedStatement, StatementStatus};
+use ratatui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Row, Table},
+    Frame,
+};
+
+/// Number of lines of statistics, not counting the borders around them
+const N_STATS: u16 = 5;
+
+/// Create a block with the statistics for every account together
+fn stats_widget<'a>(conf: &'a Config, theme: &Theme, today: &NaiveDate) -> Paragraph<'a> {
+    let stmts = conf.statements();
+
+    let n_missing = conf
+        .keys()
+        .iter()
+        .filter(|key| !stmts.missing_for(key).is_empty())
+        .count();
+    let accounts = format!(
+        "{} ({} with missing statements)",
+        conf.keys().len(),
+        n_missing
+    );
+
+    let counts = stmts.counts_by_status();
+    let expected = [
+        StatementStatus::Available,
+        StatementStatus::Missing,
+        StatementStatus::Lost,
+    ]
+    .iter()
+    .filter_map(|status| counts.get(status))
+    .sum::<usize>();
+    let complete = match stmts.overall_completeness() {
+        Some(fraction) => format!("{} of {} expected statements", percent(fraction), expected),
+        None => String::from("No statements are expected yet"),
+    };
+
+    let overdue = match stmts.most_overdue() {
+        Some((key, obs)) => format!(
+            "{}  {} ({} days ago)",
+            conf.display().date(obs.statement().date()),
+            account_name(conf, key),
+            (*today - *obs.statement().date()).num_days()
+        ),
+        None => String::from("Nothing is missing"),
+    };
+
+    // the longest gap of any account, choosing the first account if there are several
+    let mut gap: Option<(&str, Vec<&ObservedStatement>)> = None;
+    for key in conf.keys() {
+        let acct_gap = stmts.longest_gap(key);
+        if acct_gap.len() > gap.as_ref().map_or(0, |(_, g)| g.len()) {
+            gap = Some((key, acct_gap));
+        }
+    }
+    let gap = match gap {
+        Some((key, gap)) => format!("{}, {}", account_name(conf, key), gap_summary(conf, &gap)),
+        None => String::from("Nothing is missing"),
+    };
+
+    let next = match stmts.next_upcoming() {
+        Some((key, date)) => format!(
+            "{}  {} (in {} days)",
+            conf.display().date(date),
+            account_name(conf, key),
+            (*date - *today).num_days()
+        ),
+        None => String::from("No statements are expected"),
+    };
+
+    let label = Style::default()
+        .fg(theme.primary())
+        .add_modifier(Modifier::BOLD);
+    let lines: Vec<Line> = [
+        ("Accounts", accounts),
+        ("Complete", complete),
+        ("Most overdue", overdue),
+        ("Longest gap", gap),
+        ("Next expected", next),
+    ]
+    .into_iter()
+    .map(|(name, value)| {
+        Line::from(vec![
+            Span::styled(format!("{:<15}", name), label),
+            Span::raw(value),
+        ])
+    })
+    .collect();
+
+    Paragraph::new(lines).block(Block::default().title("Summary").borders(Borders::ALL))
+}
+
+/// Create a table with the statistics for each account
+fn accounts_widget<'a>(conf: &'a Config, theme: &Theme) -> Table<'a> {
+    let stmts = conf.statements();
+    let rows: Vec<Row> = conf
+        .keys()
+        .iter()
+        .map(|key| {
+            let missing = stmts.missing_for(key).len();
+            let complete = stmts.completeness(key).map_or(String::from("-"), percent);
+            let gap = stmts.longest_gap(key);
+            let gap = match gap.is_empty() {
+                true => String::from("-"),
+                false => gap_summary(conf, &gap),
+            };
+
+            let row = Row::new(vec![
+                account_name(conf, key).to_string(),
+                missing.to_string(),
+                complete,
+                gap,
+            ]);
+            match missing {
+                0 => row,
+                _ => row.style(Style::default().fg(theme.error())),
+            }
+        })
+        .collect();
+
+    Table::new(rows)
+        .header(
+            Row::new(vec!["Account Name", "Missing", "Complete", "Longest gap"]).style(
+                Style::default()
+                    .fg(theme.primary())
+                    .add_modifier(Modifier::BOLD)
+                    .add_modifier(Modifier::UNDERLINED),
+            ),
+        )
+        .block(Block::default().borders(Borders::ALL))
+        .widths(&[
+            Constraint::Min(20),
+            Constraint::Length(8),
+            Constraint::Length(9),
+            Constraint::Length(45),
+        ])
+        .column_spacing(2)
+        .style(Style::default().bg(theme.background()))
+}
+
+/// The name of the account with a given key, or the key if it doesn't exist
+fn account_name<'a>(conf: &'a Config, key: &'a str) -> &'a str {
+    conf.accounts().get(key).map_or(key, |acct| acct.name())
+}
+
+/// Describe a run of missing statements
+fn gap_summary(conf: &Config, gap: &[&ObservedStatement]) -> String {
+    let display = conf.display();
+    match (gap.first(), gap.last()) {
+        (Some(first), Some(last)) if gap.len() > 1 => format!(
+            "{} statements from {} to {}",
+            gap.len(),
+            display.date(first.statement().date()),
+            display.date(last.statement().date())
+        ),
+        (Some(first), _) => format!("1 statement on {}", display.date(first.statement().date())),
+        _ => String::new(),
+    }
+}
+
+/// Format a fraction as a percentage
+fn percent(fraction: f64) -> String {
+    format!("{:.1}%", fraction * 100.0)
+}
+
+/// Render the body for the "Summary" tab
+pub fn summary_body<B: Backend>(
+    f: &mut Frame<B>,
+    conf: &Config,
+    theme: &Theme,
+    _state: &mut TuiState,
+    area: &Rect,
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(N_STATS + 2), Constraint::Min(0)].as_ref())
+        .split(*area);
+
+    let today = Local::now().naive_local().date();
+    f.render_widget(stats_widget(conf, theme, &today), chunks[0]);
+    f.render_widget(accounts_widget(conf, theme), chunks[1]);
+}
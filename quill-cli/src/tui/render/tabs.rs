@@ -1,12 +1,13 @@
 //! Handle tab navigation within the TUI.
 
+use super::step;
+use crate::cfg::Theme;
 use ratatui::{
     style::{Modifier, Style},
     symbols::DOT,
-    text::Line,
+    text::{Line, Span},
     widgets::{Block, Borders, Tabs},
 };
-use super::{colours::BACKGROUND, step, PRIMARY};
 
 /// The page selected from the tab menu.
 #[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq)]
@@ -16,9 +17,10 @@ pub enum MenuItem {
     Upcoming,
     Log,
     Accounts,
+    Summary,
 }
 
-const N_MENU_ITEMS: usize = 4;
+const N_MENU_ITEMS: usize = 5;
 
 impl MenuItem {
     /// Switch from one MenuItem to an adjacent one by a given step size
@@ -44,6 +46,7 @@ impl From<MenuItem> for usize {
             MenuItem::Upcoming => 1,
             MenuItem::Log => 2,
             MenuItem::Accounts => 3,
+            MenuItem::Summary => 4,
         }
     }
 }
@@ -55,21 +58,53 @@ impl From<usize> for MenuItem {
             1 => MenuItem::Upcoming,
             2 => MenuItem::Log,
             3 => MenuItem::Accounts,
+            4 => MenuItem::Summary,
             _ => MenuItem::Missing,
         }
     }
 }
 
 /// Create a stylized Span for a selected MenuItem.
-pub fn tabs(selected: MenuItem) -> Tabs<'static> {
-    let menu_titles = ["[1] Missing", "[2] Upcoming", "[3] Log", "[4] Accounts"];
-    let menu_title_lines: Vec<Line> = menu_titles.iter().cloned().map(Line::from).collect();
+/// The Missing tab is badged with the number of missing statements, if there are any,
+/// and the active profile, if any, is shown in the title.
+pub fn tabs(
+    selected: MenuItem,
+    n_missing: usize,
+    profile: Option<&str>,
+    theme: &Theme,
+) -> Tabs<'static> {
+    let menu_titles = [
+        "[1] Missing",
+        "[2] Upcoming",
+        "[3] Log",
+        "[4] Accounts",
+        "[5] Summary",
+    ];
+    let mut menu_title_lines: Vec<Line> = menu_titles.iter().cloned().map(Line::from).collect();
+    if n_missing > 0 {
+        menu_title_lines[usize::from(MenuItem::Missing)] = Line::from(vec![
+            Span::raw(menu_titles[0]),
+            Span::styled(
+                format!(" ({})", n_missing),
+                Style::default().fg(theme.error()),
+            ),
+        ]);
+    }
+
+    let title = match profile {
+        Some(profile) => format!("Tabs (profile: {})", profile),
+        None => "Tabs".to_string(),
+    };
 
     // convert tab menu items into spans to be rendered
     Tabs::new(menu_title_lines)
         .select(selected.into())
-        .block(Block::default().title("Tabs").borders(Borders::ALL))
-        .style(Style::default().bg(BACKGROUND))
-        .highlight_style(Style::default().fg(PRIMARY).add_modifier(Modifier::BOLD))
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .style(Style::default().bg(theme.background()))
+        .highlight_style(
+            Style::default()
+                .fg(theme.primary())
+                .add_modifier(Modifier::BOLD),
+        )
         .divider(DOT)
 }
@@ -0,0 +1,77 @@
+//! Render the timeline of an account's statements, with a bar for each year.
+
+use crate::cfg::{DisplayConfig, Theme};
+use chrono::NaiveDate;
+use quill_statement::{StatementStatus, YearTimeline};
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+};
+
+/// Number of lines the timeline needs, including the header and borders
+pub fn timeline_height(timeline: &[YearTimeline]) -> u16 {
+    timeline.len() as u16 + 3
+}
+
+/// Create a block with a bar for each year of an account's statements,
+/// coloured by the status of the statements in each month
+pub fn timeline_widget<'a>(
+    timeline: &[YearTimeline],
+    display: &DisplayConfig,
+    theme: &Theme,
+    height: u16,
+) -> Paragraph<'a> {
+    let header: String = (1..=12)
+        .map(|m| format!("{}  ", month_initial(display, m)))
+        .collect();
+    let mut lines = vec![Line::styled(
+        format!("     {}", header),
+        Style::default().add_modifier(Modifier::BOLD),
+    )];
+
+    // show the most recent years if they don't all fit, leaving room for the header and borders
+    let n_years = height.saturating_sub(3) as usize;
+    let shown = &timeline[timeline.len().saturating_sub(n_years)..];
+    for year in shown {
+        let mut spans = vec![Span::raw(format!("{} ", year.year()))];
+        spans.extend(
+            year.months()
+                .iter()
+                .map(|status| month_span(*status, theme)),
+        );
+        lines.push(Line::from(spans));
+    }
+
+    Paragraph::new(lines).block(Block::default().title("Timeline").borders(Borders::ALL))
+}
+
+/// Initial of a month in the locale, to label its column of the timeline
+fn month_initial(display: &DisplayConfig, month: u32) -> String {
+    let first = NaiveDate::from_ymd_opt(2000, month, 1).unwrap_or_default();
+    display
+        .format(&first, "%B")
+        .chars()
+        .next()
+        .map_or_else(String::new, |c| c.to_uppercase().collect())
+}
+
+/// A segment of a year's bar, for the statements in a single month
+fn month_span<'a>(status: Option<StatementStatus>, theme: &Theme) -> Span<'a> {
+    let colour = match status {
+        Some(StatementStatus::Available) => theme.primary(),
+        Some(StatementStatus::Missing | StatementStatus::Lost) => theme.error(),
+        Some(StatementStatus::Unexpected) => theme.warning(),
+        Some(StatementStatus::Ignored | StatementStatus::Pending) | None => {
+            theme.foreground_dimmed()
+        }
+    };
+    let segment = match status {
+        // pending statements may still arrive, so they're drawn lighter than the rest
+        Some(StatementStatus::Pending) => "░░ ",
+        Some(_) => "██ ",
+        None => "·  ",
+    };
+
+    Span::styled(segment, Style::default().fg(colour))
+}
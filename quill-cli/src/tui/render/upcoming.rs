@@ -1,54 +1,181 @@
 //! Display the upcoming statements for each account.
 
-use std::io::Stdout;
-
-use chrono::NaiveDate;
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use quill_statement::DateDiff;
 use ratatui::{
-    backend::CrosstermBackend,
-    layout::Rect,
-    style::{Color, Style},
-    widgets::{Block, Borders, List, ListItem},
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{
+        calendar::{CalendarEventStore, Monthly},
+        Block, Borders, List, ListItem,
+    },
     Frame,
 };
 
-use crate::{cfg::Config, tui::state::TuiState};
+use crate::{
+    cfg::{Config, Theme},
+    tui::state::TuiState,
+};
 
-/// Create a block to render the "Upcoming" page for account statements.
-fn upcoming_widget<'a>(conf: &'a Config<'a>) -> List<'a> {
-    // get the next statment date for each account
-    let mut next_statements: Vec<(&str, NaiveDate)> = conf
-        .accounts()
-        .iter()
-        .map(|(_, acct)| (acct.name(), acct.next_statement()))
-        .collect();
+/// How far ahead to show upcoming statements
+const UPCOMING_HORIZON_DAYS: i64 = 90;
+
+/// How wide a month's calendar is, with its borders
+const CALENDAR_WIDTH: u16 = 23;
 
-    // sort them by date so that the next closest dates are at the beginning
-    next_statements.sort_by(|a, b| a.1.cmp(&b.1));
+/// How tall a month's calendar is, with its borders, for months that span six weeks
+const CALENDAR_HEIGHT: u16 = 10;
 
+/// How wide the list of upcoming statements needs to be before calendars are shown beside it
+const MIN_LIST_WIDTH: u16 = 40;
+
+/// Get the upcoming statement dates for each account until `horizon`, with the next closest
+/// dates at the beginning
+fn upcoming_statements<'a>(conf: &'a Config, horizon: &NaiveDate) -> Vec<(&'a str, &'a NaiveDate)> {
+    conf.statements()
+        .upcoming(horizon)
+        .into_iter()
+        .filter_map(|(key, date)| conf.accounts().get(key).map(|acct| (acct.name(), date)))
+        .collect()
+}
+
+/// Create a block to render the "Upcoming" page for account statements.
+fn upcoming_widget<'a>(
+    conf: &'a Config,
+    theme: &Theme,
+    today: &NaiveDate,
+    next_statements: &[(&str, &NaiveDate)],
+) -> List<'a> {
     // convert items into `ListItem`s for display
     let next_stmt_items: Vec<ListItem> = next_statements
         .iter()
-        .map(|(name, date)| ListItem::new(format!("{}  {}", date.format("%Y-%m-%d"), name)))
+        .map(|(name, date)| {
+            let mut spans = vec![Span::raw(format!(
+                "{}  {}",
+                conf.display().date(date),
+                name
+            ))];
+            if conf.display().relative_dates() {
+                spans.push(Span::styled(
+                    format!("  due {}", DateDiff::between(today, date)),
+                    Style::default().fg(theme.foreground_dimmed()),
+                ));
+            }
+            ListItem::new(Line::from(spans))
+        })
         .collect();
 
     // create the `List` that will be rendered by the TUI
     let accts_list = List::new(next_stmt_items)
         .block(Block::default().borders(Borders::ALL))
-        .style(Style::default().bg(Color::Black))
+        .style(Style::default().bg(theme.background()))
         .highlight_style(Style::default());
 
     accts_list
 }
 
 /// Render the body for the "Upcoming" tab
-pub fn upcoming_body(
-    f: &mut Frame<CrosstermBackend<Stdout>>,
+pub fn upcoming_body<B: Backend>(
+    f: &mut Frame<B>,
     conf: &Config,
+    theme: &Theme,
     state: &mut TuiState,
     area: &Rect,
 ) {
-    let widget = upcoming_widget(conf);
+    let today = Local::now().naive_local().date();
+    let horizon = today + Duration::days(UPCOMING_HORIZON_DAYS);
+    let next_statements = upcoming_statements(conf, &horizon);
+
+    // show calendars beside the list when there's room for both
+    let (list_area, calendar_area) = match area.width >= MIN_LIST_WIDTH + CALENDAR_WIDTH {
+        true => {
+            let chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Min(0), Constraint::Length(CALENDAR_WIDTH)])
+                .split(*area);
+            (chunks[0], Some(chunks[1]))
+        }
+        false => (*area, None),
+    };
+
+    let widget = upcoming_widget(conf, theme, &today, &next_statements);
     let widget_state = state.mut_missing().mut_state();
+    f.render_stateful_widget(widget, list_area, widget_state);
+
+    if let Some(calendar_area) = calendar_area {
+        let due: Vec<&NaiveDate> = next_statements.iter().map(|(_, date)| *date).collect();
+        calendars(f, theme, calendar_area, &today, &horizon, &due);
+    }
+}
+
+/// Stack a calendar for each month from this one until `horizon`, as many as fit,
+/// with the days that statements are due on highlighted
+fn calendars<B: Backend>(
+    f: &mut Frame<B>,
+    theme: &Theme,
+    area: Rect,
+    today: &NaiveDate,
+    horizon: &NaiveDate,
+    due: &[&NaiveDate],
+) {
+    let mut events = CalendarEventStore::default();
+    if let Some(today) = to_time_date(today) {
+        events.add(today, Style::default().add_modifier(Modifier::REVERSED));
+    }
+    for date in due.iter().filter_map(|d| to_time_date(d)) {
+        // a statement that's due today stays highlighted as today too
+        let style = events
+            .0
+            .get(&date)
+            .copied()
+            .unwrap_or_default()
+            .fg(theme.warning())
+            .add_modifier(Modifier::BOLD);
+        events.add(date, style);
+    }
+
+    let mut month = today.with_day(1);
+    let mut top = area.y;
+    while let Some(first) = month {
+        if first > *horizon || top + CALENDAR_HEIGHT > area.y + area.height {
+            break;
+        }
+        let date = match to_time_date(&first) {
+            Some(d) => d,
+            None => break,
+        };
+
+        let calendar = Monthly::new(date, &events)
+            .show_month_header(
+                Style::default()
+                    .fg(theme.primary())
+                    .add_modifier(Modifier::BOLD),
+            )
+            .show_weekdays_header(Style::default().fg(theme.foreground_dimmed()))
+            .default_style(Style::default().bg(theme.background()))
+            .block(Block::default().borders(Borders::ALL));
+        f.render_widget(
+            calendar,
+            Rect::new(area.x, top, area.width, CALENDAR_HEIGHT),
+        );
+
+        top += CALENDAR_HEIGHT;
+        month = next_month(&first);
+    }
+}
+
+/// The first day of the month after the one `first` is in
+fn next_month(first: &NaiveDate) -> Option<NaiveDate> {
+    match first.month() {
+        12 => NaiveDate::from_ymd_opt(first.year() + 1, 1, 1),
+        m => NaiveDate::from_ymd_opt(first.year(), m + 1, 1),
+    }
+}
 
-    f.render_stateful_widget(widget, *area, widget_state);
+/// Convert a date to the type that calendars are drawn with
+fn to_time_date(date: &NaiveDate) -> Option<time::Date> {
+    let month = time::Month::try_from(date.month() as u8).ok()?;
+    time::Date::from_calendar_date(date.year(), month, date.day() as u8).ok()
 }
@@ -1,32 +1,74 @@
 //! Start the terminal user interface, draw it, and manage keystrokes.
 
 use super::{
-    open_account_external, open_stmt_external,
+    edit::AccountEdit,
+    groups::{account_rows, accounts_tab_rows, find_row, row_group, row_key, AccountRow, RowKey},
+    import_picker, import_stmt,
+    input::{InputEvent, TextInput},
+    keymap::{action_for, Action},
+    log_statements, open_account_external, open_portal_external, open_stmt_external,
     render::{self, MenuItem},
-    state::TuiState,
+    session::{Session, SessionEvent},
+    sort_inbox_files, sort_inbox_prompt,
+    state::{AccountsState, InputPurpose, Jump, Prompt, StatusKind, TuiState},
+    statement_years,
+    stop::{install_panic_hook, restore_terminal, stop_tui},
+    toggle_ignored, trash_stmt, trashable_stmt,
+    undo::Change,
+    Opened,
 };
-use crate::cfg::Config;
+use crate::{
+    cfg::{Config, ScanProgress, Theme},
+    daemon::{latest_statements, DaemonSnapshot},
+    fetch::{fetch_command, fetched_statements, fetching_key},
+    hooks::run_hooks,
+    ipc::{DaemonClient, DaemonMessage},
+    logging, notify, signals,
+    snapshot::daemon_socket_path,
+};
+use chrono::{Datelike, NaiveDate};
 use crossterm::{
+    cursor::Hide,
     event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
-    terminal::enable_raw_mode,
+    execute,
+    terminal::{enable_raw_mode, EnterAlternateScreen},
 };
-use std::{
-    io::{self, Stdout},
-    sync::mpsc::Receiver,
-    sync::mpsc::{channel, Sender},
-    thread,
-    time::{Duration, Instant},
+use quill_statement::{
+    AccountKey, CollectionDiff, StatementCollection, StatementRename, StatementStatus,
 };
 use ratatui::{
-    backend::CrosstermBackend,
+    backend::{Backend, CrosstermBackend},
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Style},
+    style::Style,
     widgets::Block,
     Frame, Terminal,
 };
+use std::{
+    io::{self, Stdout},
+    ops::ControlFlow,
+    path::PathBuf,
+    process::Stdio,
+    sync::mpsc::Receiver,
+    sync::mpsc::{channel, Sender, TryRecvError},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant, SystemTime},
+};
+use tracing::{info, warn};
+
+/// How long the first scan for statements can take before its progress is shown.
+/// Shorter scans would only make the splash flicker.
+const SPLASH_DELAY: Duration = Duration::from_millis(200);
 
-/// Delay between TUI redraws
-const TICK_RATE: Duration = Duration::from_millis(200);
+/// Longest the input thread waits for a keystroke before checking whether to stop.
+/// Slow tick rates would otherwise hold up closing the TUI.
+const SHUTDOWN_CHECK: Duration = Duration::from_millis(100);
+
+/// Height of the debug pane, including its borders
+const DEBUG_PANE_HEIGHT: u16 = 10;
+
+/// Longest to wait for a running daemon to send its statements before scanning for them instead.
+/// The daemon sends them straight away, unless it hasn't finished its first scan.
+const DAEMON_TIMEOUT: Duration = Duration::from_millis(500);
 
 /// An event specified by the user.
 /// Is either a type of input (i.e. a keystroke), or an empty time frame
@@ -34,111 +76,398 @@ const TICK_RATE: Duration = Duration::from_millis(200);
 enum UserEvent<I> {
     Input(I),
     Tick,
+    /// The terminal changed size, so the TUI has to be drawn again
+    Resize,
+    /// Input from the terminal can no longer be read
+    InputFailed(io::Error),
+    /// News from statements being scanned for in the background
+    Scan(ScanUpdate),
+    /// News from the daemon the TUI is connected to
+    Daemon(DaemonUpdate),
 }
 
+/// Why statements are being scanned for in the background
+enum ScanTask {
+    /// The TUI was just opened, so there aren't any statements yet
+    Startup,
+    /// The user asked to scan for statements again
+    Refresh,
+    /// An account's `fetch_cmd` was run, and failed if there's a reason
+    Fetch {
+        key: String,
+        name: String,
+        failure: Option<String>,
+    },
+}
+
+/// News from statements being scanned for in the background
+enum ScanUpdate {
+    /// Another account is being scanned
+    Progress(ScanProgress),
+    /// Every account has been scanned
+    Finished(ScanTask, anyhow::Result<StatementCollection>),
+}
+
+/// News from the daemon the TUI is connected to
+enum DaemonUpdate {
+    /// The daemon scanned for statements again
+    Statements(Box<DaemonSnapshot>),
+    /// The daemon has stopped, or couldn't be understood if there's a reason
+    Stopped(Option<String>),
+}
+
+/// What to do in response to a key being pressed.
+pub(super) enum KeyAction {
+    /// Nothing beyond updating the TUI state
+    Continue,
+    /// Scan for statements again
+    Refresh,
+    /// Load the configuration file again
+    Reload,
+    /// Download statements for the selected account
+    Fetch(AccountKey),
+    /// Close the TUI
+    Quit,
+    /// Open the selected account's directory
+    OpenAccount(AccountKey),
+    /// Open the selected statement of the selected account
+    OpenStatement(AccountKey, usize),
+    /// Open the statement portal of the selected account's institution
+    OpenPortal(AccountKey),
+    /// Move a statement file of an account to the trash, once it's been confirmed
+    Trash(AccountKey, PathBuf),
+    /// Ignore an account's statement from a date, or stop ignoring it
+    ToggleIgnore(AccountKey, NaiveDate),
+    /// Move a file into an account's directory as its statement from a date
+    Import(AccountKey, PathBuf, NaiveDate),
+    /// Move files from the inbox into the directories of the accounts they belong to,
+    /// once it's been confirmed
+    SortInbox(Vec<StatementRename>),
+    /// Undo the last change to statement files
+    Undo,
+    /// Redo the last change to statement files that was undone
+    Redo,
+    /// Write the new properties of an account to the configuration file
+    EditAccount(AccountEdit),
+}
+
+/// Start the TUI, recording what happens in `session`, if given.
+/// The TUI polls for input and redraws every `tick_rate`.
+/// If `headless`, files are shown instead of opened with the default program, whatever the configuration.
 pub fn start_tui(
     conf: &mut Config,
+    session: Option<&mut Session>,
+    tick_rate: Duration,
+    headless: bool,
 ) -> Result<Terminal<CrosstermBackend<Stdout>>, Box<dyn std::error::Error>> {
     // set up a multi-producer single consumer channel to communicate between the input handler and the TUI rendering loop
     let (tx, rx): (Sender<UserEvent<KeyEvent>>, Receiver<UserEvent<KeyEvent>>) = channel();
 
-    // construct the TUI from the user event sender channel
-    let mut terminal = initiate_tui(tx)?;
+    // construct the TUI
+    let mut terminal = match initiate_tui() {
+        Ok(terminal) => terminal,
+        Err(e) => {
+            restore_terminal();
+            return Err(e.into());
+        }
+    };
+    // a panic anywhere would leave the terminal in raw mode, so put it back first
+    install_panic_hook();
 
+    // keystrokes and ticks are sent from their own thread, until it's told to stop.
+    // statements are scanned for in the background, and send their progress along the same channel
+    let (shutdown_tx, shutdown_rx) = channel();
+    let input = spawn_input_thread(tx.clone(), tick_rate, shutdown_rx);
+    let result = run_tui(&mut terminal, &rx, &tx, conf, session, headless);
+
+    // the input thread checks whether to stop between polls
+    drop(shutdown_tx);
+    if input.join().is_err() {
+        warn!("the input thread panicked");
+    }
+
+    // an error leaves the TUI unusable, so close it before the error is reported
+    if let Err(e) = result {
+        stop_tui(&mut terminal)?;
+        let e = anyhow::anyhow!("{}", e).context("The TUI stopped because of an error.");
+        return Err(e.into());
+    }
+
+    Ok(terminal)
+}
+
+/// Draw the TUI on any backend and respond to the user until they quit
+fn run_tui<B: Backend>(
+    terminal: &mut Terminal<B>,
+    rx: &Receiver<UserEvent<KeyEvent>>,
+    tx: &Sender<UserEvent<KeyEvent>>,
+    conf: &mut Config,
+    mut session: Option<&mut Session>,
+    headless: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
     // persistent state of the entire TUI
-    let mut state = TuiState::default();
+    let mut state = initial_state(conf);
+    state.set_headless(headless);
 
-    if conf.len() > 0 {
-        state.mut_log().select_account(Some(0));
-        state.mut_accounts().select(Some(0));
+    // the configuration is reloaded whenever the file changes
+    let mut config_modified = conf.modified();
+
+    // a running daemon sends its statements as soon as it finds them, so they don't need to be
+    // scanned for here, except in recordings, since they're replayed from the scans alone
+    if session.is_some() || !connect_to_daemon(conf, tx) {
+        // scanning can take a while, so do it once the TUI can show how far it's got.
+        // the statements the daemon saved last can be shown in the meantime
+        let from_daemon = match session {
+            Some(_) => None,
+            None => latest_statements(conf).unwrap_or_else(|e| {
+                warn!("{:#}", e);
+                None
+            }),
+        };
+        match from_daemon {
+            Some(statements) => {
+                info!("showing the daemon's statements until the scan finishes");
+                *conf.mut_statements() = statements;
+            }
+            None => state.start_first_scan(),
+        }
+        scan_in_background(conf, &mut state, tx, || ScanTask::Startup);
     }
 
+    // nothing is drawn until the TUI state changes, so idling doesn't use the CPU
+    state.mark_dirty();
     loop {
-        terminal.draw(|f| draw_tui(f, conf, &mut state))?;
-        if process_user_events(&rx, conf, &mut state).is_err() {
-            break;
+        if state.take_dirty() {
+            terminal.draw(|f| draw_tui(f, conf, &mut state))?;
+        }
+        let flow = process_user_events(
+            rx,
+            tx,
+            conf,
+            &mut state,
+            session.as_deref_mut(),
+            &mut config_modified,
+        )?;
+        if flow.is_break() {
+            return Ok(());
         }
     }
-    Ok(terminal)
 }
 
-/// Construct the TUI from the user event sender channel
-///
-/// Creates the user event thread and determines where the output buffer is written
-fn initiate_tui(tx: Sender<UserEvent<KeyEvent>>) -> io::Result<Terminal<CrosstermBackend<Stdout>>> {
+/// The state of the TUI when it is first opened
+pub(super) fn initial_state(conf: &Config) -> TuiState {
+    let mut state = TuiState::default();
+
+    if !conf.is_empty() {
+        state.mut_log().select_account(Some(0));
+        state.mut_accounts().select(Some(0));
+    }
+
+    state
+}
+
+/// Construct the TUI, determining where the output buffer is written
+fn initiate_tui() -> io::Result<Terminal<CrosstermBackend<Stdout>>> {
     // enable raw mode to avoid waiting for ENTER to respond to keystrokes
     enable_raw_mode()?;
 
-    // start the threading
+    // Initialize the TUI to send to STDOUT
+    let stdout = io::stdout();
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+
+    // draw on the alternate screen, so the shell is left as it was when the TUI is closed
+    execute!(terminal.backend_mut(), EnterAlternateScreen, Hide)?;
+    terminal.clear()?;
+
+    Ok(terminal)
+}
+
+/// Send keystrokes, and a tick every `tick_rate` that nothing is pressed, along `tx`.
+///
+/// The thread stops soon after `shutdown` is dropped or sent to, or once the TUI stops listening.
+fn spawn_input_thread(
+    tx: Sender<UserEvent<KeyEvent>>,
+    tick_rate: Duration,
+    shutdown: Receiver<()>,
+) -> JoinHandle<()> {
     thread::spawn(move || {
         // record the time of the last Tick sent
         let mut last_tick = Instant::now();
-        loop {
+        while let Err(TryRecvError::Empty) = shutdown.try_recv() {
             // set a polling period to accept an input event from the user
-            let timeout = TICK_RATE
+            let timeout = tick_rate
                 .checked_sub(last_tick.elapsed())
-                .unwrap_or_else(|| Duration::from_secs(0));
+                .unwrap_or_else(|| Duration::from_secs(0))
+                .min(SHUTDOWN_CHECK);
 
-            // poll the user for the given time, and if there is an input event, return it
-            if event::poll(timeout).expect("poll works") {
-                if let Event::Key(key) = event::read().expect("can read events") {
-                    tx.send(UserEvent::Input(key)).expect("can send events");
+            // poll the user for the given time, and if there is an input event, send it
+            let event = match event::poll(timeout) {
+                Ok(true) => match event::read() {
+                    Ok(Event::Key(key)) => Some(UserEvent::Input(key)),
+                    Ok(Event::Resize(..)) => Some(UserEvent::Resize),
+                    Ok(_) => None,
+                    Err(e) => Some(UserEvent::InputFailed(e)),
+                },
+                Ok(false) => None,
+                Err(e) => Some(UserEvent::InputFailed(e)),
+            };
+            if let Some(event) = event {
+                let failed = matches!(event, UserEvent::InputFailed(_));
+                if tx.send(event).is_err() || failed {
+                    return;
                 }
             }
 
-            // if enough time has elapsed, return a Tick, since no Input has been triggered
-            if (last_tick.elapsed() >= TICK_RATE) && (tx.send(UserEvent::Tick).is_ok()) {
+            // if enough time has elapsed, send a Tick, since no Input has been triggered
+            if last_tick.elapsed() >= tick_rate {
+                if tx.send(UserEvent::Tick).is_err() {
+                    return;
+                }
                 last_tick = Instant::now();
             }
         }
-    });
-
-    // Initialize the TUI to send to STDOUT
-    let stdout = io::stdout();
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
-
-    // clear the screen before displaying anything
-    terminal.clear()?;
-
-    Ok(terminal)
+    })
 }
 
 /// Draw the TUI elements
-fn draw_tui(f: &mut Frame<CrosstermBackend<Stdout>>, conf: &Config, state: &mut TuiState) {
+pub(super) fn draw_tui<B: Backend>(f: &mut Frame<B>, conf: &Config, state: &mut TuiState) {
     // get terminal window dimensions
     let size = f.size();
 
-    // draw a full black rectangle to hide everything
+    // colours for everything that is drawn
+    let theme = conf.theme();
+
+    // draw a full rectangle in the background colour to hide everything
     f.render_widget(
-        Block::default().style(Style::default().bg(Color::Black)),
+        Block::default().style(Style::default().bg(theme.background())),
         size,
     );
 
     // create the chunks where the tab bar, main body, and footer are located
-    let chunks = create_tab_body_footer(state, size, f);
+    let n_missing = conf.statements().missing_count();
+    let mut chunks = create_tab_body_footer(state, n_missing, conf.profile(), theme, size, f);
+
+    // problems loading the configuration are listed above the body, one per line
+    let mut notices = vec![];
+    if !conf.skipped_accounts().is_empty() {
+        notices.push(render::skipped_notice(conf.skipped_accounts(), theme));
+    }
+    if let Some(error) = state.reload_error() {
+        notices.push(render::reload_error_notice(error, theme));
+    }
+    if let Some(result) = state.fetch_result() {
+        notices.push(render::fetch_notice(result, theme));
+    }
+    if state.is_scanning() && !state.is_first_scan() {
+        notices.push(render::scan_notice(state.scan_progress(), theme));
+    }
+    if !notices.is_empty() {
+        let mut constraints = vec![Constraint::Length(1); notices.len()];
+        constraints.push(Constraint::Min(0));
+        let body = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(constraints)
+            .split(chunks[1]);
+        chunks[1] = body[notices.len()];
+
+        for (notice, area) in notices.into_iter().zip(body.iter()) {
+            f.render_widget(notice, *area);
+        }
+    }
+
+    // the debug pane takes its room from the bottom of the body
+    if state.debug_visible() {
+        let body = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(DEBUG_PANE_HEIGHT)].as_ref())
+            .split(chunks[1]);
+        chunks[1] = body[0];
+
+        let events = logging::recent_events();
+        f.render_widget(render::debug_pane(&events, theme, body[1].height), body[1]);
+    }
 
     // render the main block depending on what tab is selected
-    match state.active_tab() {
-        MenuItem::Missing => render::missing_body(f, conf, state, &chunks[1]),
-        MenuItem::Log => render::log_body(f, conf, state, &chunks[1]),
-        MenuItem::Upcoming => render::upcoming_body(f, conf, state, &chunks[1]),
-        MenuItem::Accounts => render::accounts_body(f, conf, state, &chunks[1]),
-    };
+    // every tab is empty without any accounts, so explain how to add some instead
+    if state.is_first_scan() {
+        // a quick scan is over before the splash would be seen
+        if state
+            .scan_elapsed()
+            .map_or(false, |elapsed| elapsed >= SPLASH_DELAY)
+        {
+            render::splash(f, conf, theme, state.scan_progress(), &chunks[1]);
+        }
+    } else if conf.is_empty() {
+        f.render_widget(render::no_accounts(conf, theme), chunks[1]);
+    } else {
+        match state.active_tab() {
+            MenuItem::Missing => render::missing_body(f, conf, theme, state, &chunks[1]),
+            MenuItem::Log => render::log_body(f, conf, theme, state, &chunks[1]),
+            MenuItem::Upcoming => render::upcoming_body(f, conf, theme, state, &chunks[1]),
+            MenuItem::Accounts => render::accounts_body(f, conf, theme, state, &chunks[1]),
+            MenuItem::Summary => render::summary_body(f, conf, theme, state, &chunks[1]),
+        }
+    }
+
+    // a status message takes the place of the key guide until it expires
+    match state.status() {
+        Some(message) => f.render_widget(render::status(message, theme), chunks[2]),
+        None => f.render_widget(render::guide(theme), chunks[2]),
+    }
+
+    // account details go over the tab, and the help goes over everything else
+    if let Some(key) = state.detail() {
+        render::account_detail(f, conf, theme, key, chunks[1]);
+    }
+    if let Some(prompt) = state.prompt() {
+        render::confirm(f, theme, &prompt_question(prompt), chunks[1]);
+    }
+    if let Some(picker) = state.mut_import_picker() {
+        render::import_picker(f, conf, theme, picker, chunks[1]);
+    }
+    if let Some(input) = state.input() {
+        render::text_input(f, theme, input, chunks[1]);
+    }
+    if state.help_visible() {
+        render::help(f, theme, size);
+    }
+}
 
-    let guide = render::guide();
-    f.render_widget(guide, chunks[2]);
+/// The question to confirm before something is done
+fn prompt_question(prompt: &Prompt) -> String {
+    match prompt {
+        Prompt::Trash(_, path) => format!("Move `{}` to the trash?", path.display()),
+        Prompt::SortInbox {
+            dir,
+            moves,
+            unsorted,
+        } => {
+            let question = format!(
+                "Move {} file(s) from `{}` into their accounts' directories?",
+                moves.len(),
+                dir.display()
+            );
+            match unsorted {
+                0 => question,
+                n => format!("{} The other {} couldn't be sorted.", question, n),
+            }
+        }
+    }
 }
 
 /// Create chunks for the tab bar and the main body view
 ///
-/// Takes the TUI state to determine which tab is active, the size of the window frame to render, and the frame that is rendering the chunks.
-fn create_tab_body_footer(
+/// Takes the TUI state to determine which tab is active, the number of missing statements to badge the Missing tab with, the active profile to title the tabs with, the size of the window frame to render, and the frame that is rendering the chunks.
+fn create_tab_body_footer<B: Backend>(
     state: &mut TuiState,
+    n_missing: usize,
+    profile: Option<&str>,
+    theme: &Theme,
     size: Rect,
-    f: &mut Frame<CrosstermBackend<Stdout>>,
+    f: &mut Frame<B>,
 ) -> Vec<Rect> {
-    let tabs = render::tabs(state.active_tab());
+    let tabs = render::tabs(state.active_tab(), n_missing, profile, theme);
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .margin(1)
@@ -147,7 +476,7 @@ fn create_tab_body_footer(
                 // tab row
                 Constraint::Length(3),
                 // body
-                Constraint::Length(size.height - 6),
+                Constraint::Length(size.height.saturating_sub(6)),
                 // footer
                 Constraint::Length(1),
             ]
@@ -163,95 +492,1046 @@ fn create_tab_body_footer(
 }
 
 /// Receive and process any keys pressed by the user.
-/// Results in an Err() if the user quits or an error is reached internally.
+/// Breaks if the user quits, and results in an Err() if an error is reached internally.
 fn process_user_events(
     rx: &Receiver<UserEvent<KeyEvent>>,
+    tx: &Sender<UserEvent<KeyEvent>>,
     conf: &mut Config,
     state: &mut TuiState,
-) -> Result<(), Box<dyn std::error::Error>> {
+    mut session: Option<&mut Session>,
+    config_modified: &mut Option<SystemTime>,
+) -> Result<ControlFlow<()>, Box<dyn std::error::Error>> {
     // receive input from the user about what to do next
-    match rx.recv()? {
-        // destruct KeyCode and KeyModifiers for more legible match cases
-        UserEvent::Input(KeyEvent { code, modifiers }) => match (code, modifiers) {
-            // Refresh
-            (KeyCode::Char('r'), _) => conf.refresh_account_statements()?,
-            // Quit
-            (KeyCode::Char('q'), _) | (KeyCode::Char('c'), KeyModifiers::CONTROL) => {
-                return Err(Box::new(io::Error::new(io::ErrorKind::Interrupted, "")));
-            }
-            // Tab to move forward one tab
-            (KeyCode::Tab, _) => state.next_tab(),
-            // Shift + Tab to move backward one tab
-            (KeyCode::BackTab, _) => state.prev_tab(),
-            (KeyCode::Char('1'), _) => state.set_active_tab(0.into()),
-            (KeyCode::Char('2'), _) => state.set_active_tab(1.into()),
-            (KeyCode::Char('3'), _) => state.set_active_tab(2.into()),
-            (KeyCode::Char('4'), _) => state.set_active_tab(3.into()),
-            (KeyCode::Char('h'), _) | (KeyCode::Left, _) => {
-                if state.active_tab() == MenuItem::Log {
-                    state.mut_log().select_log(None);
+    let event = rx.recv()?;
+
+    // anything but a tick changes what's shown
+    if !matches!(event, UserEvent::Tick) {
+        state.mark_dirty();
+    }
+
+    match event {
+        UserEvent::Input(key) => {
+            let action = handle_key(key, conf, state);
+
+            if let Some(session) = &mut session {
+                session.push(SessionEvent::Key(key));
+            }
+
+            match action {
+                KeyAction::Continue => {}
+                // only one scan runs at a time
+                KeyAction::Refresh | KeyAction::Fetch(_) if state.is_scanning() => {}
+                KeyAction::Refresh => {
+                    state.set_fetch_result(None);
+                    scan_in_background(conf, state, tx, || ScanTask::Refresh);
+                }
+                KeyAction::Reload => {
+                    reload_config(conf, state, session)?;
+                    *config_modified = conf.modified();
+                }
+                KeyAction::Fetch(selected_acct) => fetch_account(conf, state, tx, &selected_acct),
+                KeyAction::Quit => {
+                    return Ok(ControlFlow::Break(()));
+                }
+                KeyAction::OpenAccount(selected_acct) => {
+                    // open the file explorer for this account in its specified directory
+                    let headless = state.is_headless() || conf.tui().headless();
+                    let opened = open_account_external(conf, &selected_acct, headless);
+                    report_opened(state, opened);
+                }
+                KeyAction::OpenStatement(selected_acct, selected_stmt) => {
+                    // open the statement PDF
+                    let headless = state.is_headless() || conf.tui().headless();
+                    let year = state.log().year();
+                    let opened =
+                        open_stmt_external(conf, &selected_acct, selected_stmt, year, headless);
+                    report_opened(state, opened);
+                }
+                KeyAction::OpenPortal(selected_acct) => {
+                    // open the institution's website in the browser
+                    let headless = state.is_headless() || conf.tui().headless();
+                    let opened = open_portal_external(conf, &selected_acct, headless);
+                    report_opened(state, opened);
+                }
+                KeyAction::Trash(selected_acct, path) => match trash_stmt(&path) {
+                    Ok(()) => {
+                        info!(account = %selected_acct, path = %path.display(), "moved statement to the trash");
+                        state.mut_undo().push(Change::Trashed(path.clone()));
+                        state.set_status(
+                            format!("Moved `{}` to the trash.", path.display()),
+                            StatusKind::Info,
+                        );
+                        // the statement is missing now, unless there's another copy of it
+                        if !state.is_scanning() {
+                            scan_in_background(conf, state, tx, || ScanTask::Refresh);
+                        }
+                    }
+                    Err(e) => {
+                        warn!("{:#}", e);
+                        state.set_status(format!("{:#}", e), StatusKind::Error);
+                    }
+                },
+                KeyAction::ToggleIgnore(selected_acct, date) => {
+                    match toggle_ignored(conf, &selected_acct, &date) {
+                        Ok(change) => {
+                            info!(account = %selected_acct, date = %date, "changed whether the statement is ignored");
+                            let status = format!("Finished {}.", change.description());
+                            state.mut_undo().push(change);
+                            // accounts only read their ignore files when they're loaded
+                            reload_config(conf, state, session)?;
+                            *config_modified = conf.modified();
+                            state.set_status(status, StatusKind::Info);
+                        }
+                        Err(e) => {
+                            warn!("{:#}", e);
+                            state.set_status(format!("{:#}", e), StatusKind::Error);
+                        }
+                    }
+                }
+                KeyAction::Import(selected_acct, file, date) => {
+                    match import_stmt(conf, &selected_acct, &file, &date) {
+                        Ok(imported) => {
+                            info!(account = %selected_acct, from = %file.display(), to = %imported.display(), "imported statement");
+                            state
+                                .mut_undo()
+                                .push(Change::Moved(vec![(file.clone(), imported.clone())]));
+                            state.set_status(
+                                format!(
+                                    "Imported `{}` as `{}`.",
+                                    file.display(),
+                                    imported.display()
+                                ),
+                                StatusKind::Info,
+                            );
+                            if !state.is_scanning() {
+                                scan_in_background(conf, state, tx, || ScanTask::Refresh);
+                            }
+                        }
+                        Err(e) => {
+                            warn!("{:#}", e);
+                            state.set_status(format!("{:#}", e), StatusKind::Error);
+                        }
+                    }
+                }
+                KeyAction::SortInbox(moves) => {
+                    let moved = sort_inbox_files(&moves);
+                    info!(moved = moved.len(), "sorted the inbox");
+                    let n_moved = moved.len();
+                    if !moved.is_empty() {
+                        state.mut_undo().push(Change::Moved(moved));
+                    }
+                    match n_moved == moves.len() {
+                        true => state.set_status(
+                            format!("Moved {} file(s) from the inbox.", n_moved),
+                            StatusKind::Info,
+                        ),
+                        false => state.set_status(
+                            format!(
+                                "Moved {} of {} file(s) from the inbox. Press d for the debug pane.",
+                                n_moved,
+                                moves.len()
+                            ),
+                            StatusKind::Error,
+                        ),
+                    }
+                    if !state.is_scanning() {
+                        scan_in_background(conf, state, tx, || ScanTask::Refresh);
+                    }
+                }
+                KeyAction::Undo => {
+                    let undone = state.mut_undo().undo();
+                    if changed_ignore_file(&undone) {
+                        reload_config(conf, state, session)?;
+                        *config_modified = conf.modified();
+                    }
+                    report_undo(conf, state, tx, undone, "Undid", "undo");
+                }
+                KeyAction::Redo => {
+                    let redone = state.mut_undo().redo();
+                    if changed_ignore_file(&redone) {
+                        reload_config(conf, state, session)?;
+                        *config_modified = conf.modified();
+                    }
+                    report_undo(conf, state, tx, redone, "Redid", "redo");
+                }
+                KeyAction::EditAccount(edit) => match edit.save() {
+                    Ok(()) => {
+                        info!(account = %edit.key(), path = %edit.path().display(), "edited account");
+                        // check the account again, and show its new properties
+                        reload_config(conf, state, session)?;
+                        *config_modified = conf.modified();
+                        state.set_status(
+                            format!(
+                                "Saved the changes to `{}` in `{}`.",
+                                edit.key(),
+                                edit.path().display()
+                            ),
+                            StatusKind::Info,
+                        );
+                    }
+                    Err(e) => {
+                        warn!("{:#}", e);
+                        state.set_status(format!("{:#}", e), StatusKind::Error);
+                    }
+                },
+            }
+        }
+        UserEvent::Resize => {}
+        UserEvent::InputFailed(e) => {
+            return Err(Box::new(e));
+        }
+        UserEvent::Tick => {
+            state.tick();
+
+            // log messages arrive and the splash appears without any other event to draw them
+            if state.debug_visible() || state.is_first_scan() {
+                state.mark_dirty();
+            }
+
+            // quit the same way as pressing `q` if a termination signal was received
+            if signals::shutdown_requested() {
+                return Ok(ControlFlow::Break(()));
+            }
+
+            // reload the configuration if it was edited, but only once per edit,
+            // and not while it's being scanned for statements in the background
+            let modified = conf.modified();
+            if modified != *config_modified && !state.is_scanning() {
+                *config_modified = modified;
+                reload_config(conf, state, session)?;
+                state.mark_dirty();
+            }
+        }
+        UserEvent::Daemon(DaemonUpdate::Statements(latest)) => {
+            // the configuration may have been reloaded with other accounts since connecting
+            if latest.matches(conf) {
+                *conf.mut_statements() = latest.snapshot().statements().clone();
+                state.set_status(
+                    format!(
+                        "The daemon scanned for statements: {} missing.",
+                        conf.statements().missing_count()
+                    ),
+                    StatusKind::Info,
+                );
+            }
+        }
+        UserEvent::Daemon(DaemonUpdate::Stopped(reason)) => {
+            if let Some(reason) = reason {
+                warn!("lost the connection to the daemon: {}", reason);
+            }
+            state.set_status(
+                "The daemon stopped, so statements are only scanned for when you refresh them.",
+                StatusKind::Info,
+            );
+        }
+        UserEvent::Scan(ScanUpdate::Progress(progress)) => state.set_scan_progress(progress),
+        UserEvent::Scan(ScanUpdate::Finished(task, result)) => {
+            state.finish_scan();
+            let statements = match result {
+                Ok(statements) => statements,
+                Err(e) => {
+                    warn!("scanning for statements failed: {:#}", e);
+                    state.set_status(
+                        format!(
+                            "Scanning for statements failed ({}). Press d for the debug pane.",
+                            e
+                        ),
+                        StatusKind::Error,
+                    );
+                    return Ok(ControlFlow::Continue(()));
+                }
+            };
+            // the configuration may have been reloaded with other accounts while scanning
+            if !matches_accounts(conf, &statements) {
+                warn!(
+                    "the accounts changed while scanning, so the statements found were discarded"
+                );
+                state.set_status(
+                    "The accounts changed while scanning for statements. Press r to scan again.",
+                    StatusKind::Info,
+                );
+                return Ok(ControlFlow::Continue(()));
+            }
+
+            let before = std::mem::replace(conf.mut_statements(), statements);
+            match task {
+                // there's nothing to compare the first statements to, so compare them to the last check
+                ScanTask::Startup => {
+                    if let Err(e) = notify::check_and_notify(conf) {
+                        warn!("{:#}", e);
+                        state.set_status(
+                            format!("Notifying failed ({}). Press d for the debug pane.", e),
+                            StatusKind::Error,
+                        );
+                    }
+                }
+                ScanTask::Refresh => {
+                    run_refresh_hooks(conf, &before);
+                    state.set_status(
+                        format!(
+                            "Scanned for statements: {} missing.",
+                            conf.statements().missing_count()
+                        ),
+                        StatusKind::Info,
+                    );
+                }
+                ScanTask::Fetch { .. } => {
+                    run_refresh_hooks(conf, &before);
+                    state.set_fetch_result(scan_result(&task, &before, conf.statements()));
+                }
+            }
+            // a file disappearing is easy to miss among the missing statements, so it's shown instead
+            let lost = newly_lost(&before, conf.statements());
+            if lost > 0 {
+                state.set_status(
+                    format!(
+                        "{} statement file(s) have disappeared since they were found, and are marked with ! in the Log tab. Press d for the debug pane.",
+                        lost
+                    ),
+                    StatusKind::Error,
+                );
+            }
+            // replays can't scan for statements or run commands, so keep what was found
+            if let Some(session) = &mut session {
+                session.push(SessionEvent::Scan(conf.statements().clone()));
+            }
+        }
+    }
+    Ok(ControlFlow::Continue(()))
+}
+
+/// Run the `fetch_cmd` of the selected account, or of the account a selected
+/// schedule belongs to, then scan for statements again and report what was downloaded.
+/// Both happen in the background, so the TUI can still be used while they run.
+/// The command's output is logged instead of printed, since it would draw over the TUI.
+fn fetch_account(
+    conf: &Config,
+    state: &mut TuiState,
+    tx: &Sender<UserEvent<KeyEvent>>,
+    selected_acct: &AccountKey,
+) {
+    let key = fetching_key(conf, selected_acct).to_string();
+    let acct = match conf.accounts().get(key.as_str()) {
+        Some(acct) => acct,
+        None => return,
+    };
+    let name = acct.name().to_string();
+
+    let mut cmd = match fetch_command(&key, acct) {
+        Some(cmd) => cmd,
+        None => {
+            state.set_fetch_result(Some(format!(
+                "{} doesn't have a `fetch_cmd` to download statements with.",
+                name
+            )));
+            return;
+        }
+    };
+
+    state.set_fetch_result(Some(format!("Fetching statements for {}…", name)));
+    scan_in_background(conf, state, tx, move || {
+        info!(account = %key, "running fetch_cmd");
+        let failure = match cmd.stdin(Stdio::null()).output() {
+            Ok(output) => {
+                let stdout = String::from_utf8_lossy(&output.stdout);
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                for line in stdout.lines().chain(stderr.lines()) {
+                    info!(account = %key, "{}", line);
+                }
+
+                (!output.status.success()).then(|| output.status.to_string())
+            }
+            Err(e) => Some(e.to_string()),
+        };
+
+        // some statements may have been downloaded before a failure, so scan either way
+        ScanTask::Fetch { key, name, failure }
+    });
+}
+
+/// Connect to a running daemon and show the statements it found, then listen for the ones it
+/// finds after each scan on another thread.
+/// Returns whether it's connected, since the TUI has to scan for statements itself otherwise.
+fn connect_to_daemon(conf: &mut Config, tx: &Sender<UserEvent<KeyEvent>>) -> bool {
+    let mut client = match daemon_socket_path().map(|path| DaemonClient::connect(&path)) {
+        Ok(Some(client)) => client,
+        _ => return false,
+    };
+    let latest = match client.recv(Some(DAEMON_TIMEOUT)) {
+        Ok(Some(DaemonMessage::Statements(latest))) => latest,
+        Ok(None) => return false,
+        Err(e) => {
+            warn!("couldn't get the statements from the daemon: {:#}", e);
+            return false;
+        }
+    };
+    // the daemon may have been started for another profile's accounts
+    if !latest.matches(conf) {
+        info!("the daemon is scanning for other accounts");
+        return false;
+    }
+    info!("showing the statements found by the daemon");
+    *conf.mut_statements() = latest.snapshot().statements().clone();
+
+    let tx = tx.clone();
+    thread::spawn(move || loop {
+        let update = match client.recv(None) {
+            Ok(Some(DaemonMessage::Statements(latest))) => {
+                DaemonUpdate::Statements(Box::new(latest))
+            }
+            Ok(None) => DaemonUpdate::Stopped(None),
+            Err(e) => DaemonUpdate::Stopped(Some(format!("{:#}", e))),
+        };
+        let stopped = matches!(update, DaemonUpdate::Stopped(_));
+        // the TUI may have been closed in the meantime, so there's nobody to tell
+        if tx.send(UserEvent::Daemon(update)).is_err() || stopped {
+            return;
+        }
+    });
+
+    true
+}
+
+/// Do some `work` on another thread, then scan for statements there too,
+/// sending the progress of the scan back to the TUI as it goes
+fn scan_in_background(
+    conf: &Config,
+    state: &mut TuiState,
+    tx: &Sender<UserEvent<KeyEvent>>,
+    work: impl FnOnce() -> ScanTask + Send + 'static,
+) {
+    state.start_scan();
+
+    let scanner = conf.scanner();
+    let tx = tx.clone();
+    thread::spawn(move || {
+        let task = work();
+        let result = scanner.scan(|progress| {
+            let _ = tx.send(UserEvent::Scan(ScanUpdate::Progress(progress.clone())));
+        });
+        // the TUI may have been closed in the meantime, so there's nothing to tell
+        let _ = tx.send(UserEvent::Scan(ScanUpdate::Finished(task, result)));
+    });
+}
+
+/// Check that scanned statements belong to exactly the accounts that are loaded
+fn matches_accounts(conf: &Config, statements: &StatementCollection) -> bool {
+    statements.iter().count() == conf.len()
+        && statements
+            .iter()
+            .all(|(key, _)| conf.account(key).is_some())
+}
+
+/// Count the statements that are lost `after` a scan, but weren't `before` it.
+/// Each of them is logged along with where its file was last found.
+fn newly_lost(before: &StatementCollection, after: &StatementCollection) -> usize {
+    let was_lost = |key: &str, date: &NaiveDate| {
+        before.get(key).map_or(false, |stmts| {
+            stmts
+                .iter()
+                .any(|obs| obs.statement().date() == date && obs.status() == StatementStatus::Lost)
+        })
+    };
+
+    after
+        .lost()
+        .into_iter()
+        .filter(|(key, obs)| !was_lost(key, obs.statement().date()))
+        .inspect(|(key, obs)| {
+            warn!(
+                account = %key,
+                date = %obs.statement().date(),
+                path = %obs.statement().path().display(),
+                "statement file has disappeared"
+            );
+        })
+        .count()
+}
+
+/// What to tell the user once a background scan has finished
+fn scan_result(
+    task: &ScanTask,
+    before: &StatementCollection,
+    after: &StatementCollection,
+) -> Option<String> {
+    let (key, name, failure) = match task {
+        ScanTask::Startup | ScanTask::Refresh => return None,
+        ScanTask::Fetch { key, name, failure } => (key, name, failure),
+    };
+    let fetched = fetched_statements(key, before, after).len();
+
+    let result = match failure {
+        Some(reason) => {
+            warn!(account = %key, "fetch_cmd failed: {}", reason);
+            format!(
+                "The `fetch_cmd` of {} failed ({}), but found {} new statement(s). Press d for the debug pane.",
+                name, reason, fetched
+            )
+        }
+        None => format!("Fetched {} new statement(s) for {}.", fetched, name),
+    };
+
+    Some(result)
+}
+
+/// Run any hooks for the statements that changed when scanning for statements again.
+/// A failed hook is logged instead of printed, since it would draw over the TUI.
+fn run_refresh_hooks(conf: &Config, before: &StatementCollection) {
+    if conf.hooks().is_empty() {
+        return;
+    }
+
+    let changes = CollectionDiff::new(before, conf.statements());
+    if let Err(e) = run_hooks(conf, changes.newly_missing(), changes.newly_available()) {
+        warn!("{:#}", e);
+    }
+}
+
+/// Show what was opened in the status line, or why it couldn't be.
+/// What couldn't be opened in headless mode is shown for long enough to copy it.
+fn report_opened(state: &mut TuiState, opened: anyhow::Result<Opened>) {
+    match opened {
+        Ok(Opened::Launched(opened)) => {
+            state.set_status(format!("Opening `{}`.", opened), StatusKind::Info)
+        }
+        Ok(Opened::Shown(shown)) => state.set_lasting_status(
+            format!("No display to open it on: {}", shown),
+            StatusKind::Info,
+        ),
+        Err(e) => {
+            warn!("{:#}", e);
+            state.set_status(format!("{:#}", e), StatusKind::Error);
+        }
+    }
+}
+
+/// Show what was undone or redone in the status line, or why it couldn't be,
+/// and scan for statements again if any files moved
+fn report_undo(
+    conf: &Config,
+    state: &mut TuiState,
+    tx: &Sender<UserEvent<KeyEvent>>,
+    result: anyhow::Result<Option<Change>>,
+    done: &str,
+    verb: &str,
+) {
+    match result {
+        Ok(Some(change)) => {
+            info!("{} {}", verb, change.description());
+            state.set_status(
+                format!("{} {}.", done, change.description()),
+                StatusKind::Info,
+            );
+            // the statements were already scanned for when an ignore file's account was reloaded
+            if !change.changes_ignore_file() && !state.is_scanning() {
+                scan_in_background(conf, state, tx, || ScanTask::Refresh);
+            }
+        }
+        Ok(None) => state.set_status(format!("There's nothing to {}.", verb), StatusKind::Info),
+        Err(e) => {
+            warn!("{:#}", e);
+            state.set_status(format!("{:#}", e), StatusKind::Error);
+        }
+    }
+}
+
+/// Check if undoing or redoing a change changed an ignore file, which accounts only read
+/// when they're loaded
+fn changed_ignore_file(result: &anyhow::Result<Option<Change>>) -> bool {
+    matches!(result, Ok(Some(change)) if change.changes_ignore_file())
+}
+
+/// Load the configuration file again.
+/// If it can't be loaded, the current configuration is kept and the problem is shown instead.
+fn reload_config(
+    conf: &mut Config,
+    state: &mut TuiState,
+    session: Option<&mut Session>,
+) -> anyhow::Result<()> {
+    match conf.reloaded() {
+        Ok(new_conf) => {
+            info!("reloaded the configuration file");
+            replace_config(conf, new_conf, state);
+            state.set_status("Reloaded the configuration file.", StatusKind::Info);
+
+            // replays can't read the configuration file, so keep what was loaded
+            if let Some(session) = session {
+                session.push_reload(conf)?;
+            }
+        }
+        Err(e) => {
+            warn!("couldn't reload the configuration file: {:#}", e);
+            state.set_reload_error(Some(e.root_cause().to_string()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Replace the configuration with one that has been reloaded, keeping the
+/// same rows selected where they still exist
+pub(super) fn replace_config(conf: &mut Config, mut new_conf: Config, state: &mut TuiState) {
+    let accounts_key = selected_key(&accounts_tab_rows(conf, state), state.accounts().selected());
+    let log_key = selected_key(
+        &account_rows(conf, state.collapsed()),
+        state.log().selected_account(),
+    );
+
+    // the profile isn't part of the configuration file, so it's kept
+    new_conf.set_profile(conf.profile());
+    *conf = new_conf;
+
+    let accounts_row = reselect(&accounts_tab_rows(conf, state), accounts_key);
+    let log_row = reselect(&account_rows(conf, state.collapsed()), log_key);
+
+    state.mut_accounts().select(accounts_row);
+    state.mut_log().select_account(log_row);
+    // the statements may have changed, so start at the account again
+    state.mut_log().select_log(None);
+    state.set_reload_error(None);
+}
+
+/// The key of the selected row, if any
+fn selected_key(rows: &[AccountRow], idx: Option<usize>) -> Option<RowKey> {
+    idx.and_then(|i| rows.get(i)).map(|&row| row_key(row))
+}
+
+/// Find the position of a previously selected row, falling back to the first row,
+/// as if the TUI was just opened
+fn reselect(rows: &[AccountRow], key: Option<RowKey>) -> Option<usize> {
+    let first_row = match rows.is_empty() {
+        true => None,
+        false => Some(0),
+    };
+
+    key.and_then(|key| find_row(rows, &key)).or(first_row)
+}
+
+/// Update the TUI state for a key pressed by the user, and determine what else needs to be done.
+pub(super) fn handle_key(key: KeyEvent, conf: &Config, state: &mut TuiState) -> KeyAction {
+    // while something is being confirmed, any key but `y` cancels it
+    if let Some(prompt) = state.take_prompt() {
+        return match (key.code, prompt) {
+            (KeyCode::Char('y') | KeyCode::Char('Y'), Prompt::Trash(acct_key, path)) => {
+                KeyAction::Trash(acct_key, path)
+            }
+            (KeyCode::Char('y') | KeyCode::Char('Y'), Prompt::SortInbox { moves, .. }) => {
+                KeyAction::SortInbox(moves)
+            }
+            _ => KeyAction::Continue,
+        };
+    }
+
+    // while text is being typed, every key goes to it, except Ctrl+c to quit
+    if let Some((purpose, input)) = state.mut_input() {
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            return KeyAction::Quit;
+        }
+        match input.handle_key(key, conf) {
+            InputEvent::Editing => {}
+            InputEvent::Cancelled => state.set_input(None),
+            InputEvent::Submitted => {
+                let (purpose, text) = (purpose.clone(), input.value().to_string());
+                state.set_input(None);
+                match purpose {
+                    InputPurpose::FindAccount => find_account(conf, state, &text),
+                    InputPurpose::JumpToDate => jump_to_date(conf, state, &text),
+                    // move on to the next property, until they've all been typed
+                    InputPurpose::EditAccount(mut edit) => match edit.submit(&text) {
+                        true => {
+                            let input = edit.input();
+                            state.set_input(Some((InputPurpose::EditAccount(edit), input)));
+                        }
+                        false => return KeyAction::EditAccount(edit),
+                    },
+                }
+            }
+        }
+        return KeyAction::Continue;
+    }
+
+    // while choosing a file to import, keys move through the files, and Esc cancels
+    if let Some(picker) = state.mut_import_picker() {
+        match action_for(&key) {
+            Some(Action::Down) => picker.select_next(),
+            Some(Action::Up) => picker.select_prev(),
+            Some(Action::Open) => {
+                let import = picker.selected().map(|file| {
+                    KeyAction::Import(picker.key().clone(), file.to_path_buf(), *picker.date())
+                });
+                state.set_import_picker(None);
+                return import.unwrap_or(KeyAction::Continue);
+            }
+            Some(Action::Close) | Some(Action::Quit) => state.set_import_picker(None),
+            _ => {}
+        }
+        return KeyAction::Continue;
+    }
+
+    // keys that aren't bound to anything are ignored
+    let action = match action_for(&key) {
+        Some(action) => action,
+        None => return KeyAction::Continue,
+    };
+
+    // while the help is shown, it can only be hidden, or quill quit
+    if state.help_visible() {
+        match action {
+            Action::ToggleHelp | Action::Close => state.set_help_visible(false),
+            Action::Quit => return KeyAction::Quit,
+            _ => {}
+        }
+        return KeyAction::Continue;
+    }
+
+    // likewise for an account's details, though the help can be shown over them
+    if state.detail().is_some() {
+        match action {
+            Action::Open | Action::Close => state.set_detail(None),
+            Action::ToggleHelp => state.set_help_visible(true),
+            Action::OpenPortal => {
+                if let Some(key) = state.detail() {
+                    return KeyAction::OpenPortal(key.clone());
                 }
             }
-            (KeyCode::Char('j'), _) | (KeyCode::Down, _) => match state.active_tab() {
-                MenuItem::Accounts => {
-                    if state.accounts().selected().is_some() {
-                        state.mut_accounts().select_next(conf.len());
+            Action::EditAccount => {
+                if let Some(key) = state.detail() {
+                    match AccountEdit::start(conf, key) {
+                        Ok(edit) => {
+                            let input = edit.input();
+                            state.set_input(Some((InputPurpose::EditAccount(edit), input)));
+                        }
+                        Err(e) => state.set_status(format!("{:#}", e), StatusKind::Error),
                     }
                 }
-                MenuItem::Log => match state.log().selected() {
-                    (Some(_), None) => state.mut_log().select_next_account(conf.len()),
-                    (Some(acct_row_selected), Some(_)) => {
+            }
+            Action::Quit => return KeyAction::Quit,
+            _ => {}
+        }
+        return KeyAction::Continue;
+    }
+
+    match action {
+        Action::Refresh => return KeyAction::Refresh,
+        Action::Undo => return KeyAction::Undo,
+        Action::Redo => return KeyAction::Redo,
+        Action::SortInbox => match sort_inbox_prompt(conf) {
+            Ok(prompt) => state.set_prompt(Some(prompt)),
+            Err(e) => state.set_status(format!("{:#}", e), StatusKind::Error),
+        },
+        Action::Reload => return KeyAction::Reload,
+        Action::Fetch => {
+            if let Some(AccountRow::Account(selected_acct)) = selected_row(conf, state) {
+                return KeyAction::Fetch(selected_acct.clone());
+            }
+        }
+        Action::OpenPortal => {
+            if let Some(AccountRow::Account(selected_acct)) = selected_row(conf, state) {
+                return KeyAction::OpenPortal(selected_acct.clone());
+            }
+        }
+        Action::Quit => return KeyAction::Quit,
+        Action::ToggleHelp => state.set_help_visible(true),
+        Action::ToggleDebug => state.toggle_debug(),
+        Action::NextTab => state.next_tab(),
+        Action::PrevTab => state.prev_tab(),
+        Action::GoToTab(n) => state.set_active_tab(n.into()),
+        Action::Left if state.active_tab() == MenuItem::Log => {
+            state.mut_log().select_log(None);
+        }
+        Action::Down => match state.active_tab() {
+            MenuItem::Accounts if state.accounts().selected().is_some() => {
+                let n_rows = accounts_tab_rows(conf, state).len();
+                state.mut_accounts().select_next(n_rows);
+            }
+            MenuItem::Log => match state.log().selected() {
+                (Some(_), None) => {
+                    let n_rows = account_rows(conf, state.collapsed()).len();
+                    state.mut_log().select_next_account(n_rows);
+                }
+                (Some(_), Some(_)) => {
+                    if let Some(AccountRow::Account(acct_key)) = selected_row(conf, state) {
                         // get the number of statements for this account
-                        let acct_key = conf.keys()[acct_row_selected].as_str();
-                        state
-                            .mut_log()
-                            .select_next_log(conf.statements().get(acct_key).unwrap().len());
+                        let n_stmts = log_statements(conf, acct_key, state.log().year()).len();
+                        state.mut_log().select_next_log(n_stmts);
                     }
-                    _ => {}
-                },
+                }
                 _ => {}
             },
-            (KeyCode::Char('k'), _) | (KeyCode::Up, _) => match state.active_tab() {
-                MenuItem::Accounts => state.mut_accounts().select_prev(conf.len()),
-                MenuItem::Log => match state.log().selected() {
-                    (Some(_), None) => {
-                        state.mut_log().select_prev_account(conf.len());
-                    }
-                    (Some(acct_row_selected), Some(_)) => {
+            _ => {}
+        },
+        Action::Up => match state.active_tab() {
+            MenuItem::Accounts => {
+                let n_rows = accounts_tab_rows(conf, state).len();
+                state.mut_accounts().select_prev(n_rows);
+            }
+            MenuItem::Log => match state.log().selected() {
+                (Some(_), None) => {
+                    let n_rows = account_rows(conf, state.collapsed()).len();
+                    state.mut_log().select_prev_account(n_rows);
+                }
+                (Some(_), Some(_)) => {
+                    if let Some(AccountRow::Account(acct_key)) = selected_row(conf, state) {
                         // get the number of statements for this account
-                        let acct_key = conf.keys()[acct_row_selected].as_str();
-                        state
-                            .mut_log()
-                            .select_prev_log(conf.statements().get(acct_key).unwrap().len());
+                        let n_stmts = log_statements(conf, acct_key, state.log().year()).len();
+                        state.mut_log().select_prev_log(n_stmts);
                     }
-                    _ => {}
-                },
+                }
                 _ => {}
             },
-            (KeyCode::Char('l'), _) | (KeyCode::Right, _) => {
-                if state.active_tab() == MenuItem::Log {
-                    state.mut_log().select_log(Some(0));
+            _ => {}
+        },
+        Action::PageUp | Action::PageDown | Action::First | Action::Last
+            if state.active_tab() == MenuItem::Log =>
+        {
+            let jump = match action {
+                Action::PageUp => Jump::PageUp,
+                Action::PageDown => Jump::PageDown,
+                Action::First => Jump::First,
+                _ => Jump::Last,
+            };
+            let len = match (state.log().selected_log(), selected_row(conf, state)) {
+                (Some(_), Some(AccountRow::Account(acct_key))) => {
+                    log_statements(conf, acct_key, state.log().year()).len()
                 }
+                _ => account_rows(conf, state.collapsed()).len(),
+            };
+            state.mut_log().jump(len, jump);
+        }
+        // only accounts have statements to move over to
+        Action::Right if state.active_tab() == MenuItem::Log => {
+            if let Some(AccountRow::Account(_)) = selected_row(conf, state) {
+                state.mut_log().select_log(Some(0));
             }
-            (KeyCode::Enter, _) => {
-                if state.active_tab() == MenuItem::Log {
-                    match state.log().selected() {
-                        (Some(selected_acct), None) => {
-                            // open the file explorer for this account in its specified directory
-                            open_account_external(conf, selected_acct);
-                        }
-                        (Some(selected_acct), Some(selected_stmt)) => {
-                            // open the statement PDF
-                            open_stmt_external(conf, selected_acct, selected_stmt);
-                        }
-                        (_, _) => {}
-                    }
+        }
+        Action::Open if state.active_tab() == MenuItem::Log => {
+            match (selected_row(conf, state), state.log().selected_log()) {
+                (Some(AccountRow::Account(selected_acct)), None) => {
+                    return KeyAction::OpenAccount(selected_acct.clone());
                 }
+                (Some(AccountRow::Account(selected_acct)), Some(selected_stmt)) => {
+                    return KeyAction::OpenStatement(selected_acct.clone(), selected_stmt);
+                }
+                (_, _) => {}
             }
-            // if the KeyCode alone doesn't match, look for modifiers
-            _ => {}
+        }
+        // import into the selected account, as the selected statement if there is one
+        Action::Import if state.active_tab() == MenuItem::Log => {
+            if let Some(AccountRow::Account(selected_acct)) = selected_row(conf, state) {
+                let (selected_stmt, year) = (state.log().selected_log(), state.log().year());
+                match import_picker(conf, selected_acct, selected_stmt, year) {
+                    Ok(picker) => state.set_import_picker(Some(picker)),
+                    Err(e) => state.set_status(format!("{:#}", e), StatusKind::Error),
+                }
+            }
+        }
+        // step through the years of the selected account's statements, where every year
+        // comes after the latest one
+        Action::PrevYear | Action::NextYear if state.active_tab() == MenuItem::Log => {
+            if let Some(AccountRow::Account(acct_key)) = selected_row(conf, state) {
+                let years = statement_years(conf, acct_key);
+                let year = match (action, state.log().year()) {
+                    (Action::PrevYear, None) => years.last().copied(),
+                    (Action::PrevYear, Some(y)) => years
+                        .iter()
+                        .rev()
+                        .find(|&&other| other < y)
+                        .copied()
+                        .or(Some(y)),
+                    (_, None) => None,
+                    (_, Some(y)) => years.iter().find(|&&other| other > y).copied(),
+                };
+                state.mut_log().set_year(year);
+            }
+        }
+        Action::JumpToDate if state.active_tab() == MenuItem::Log => {
+            if let Some(AccountRow::Account(_)) = selected_row(conf, state) {
+                let input = TextInput::new("Jump to date (YYYY-MM-DD, YYYY-MM, or YYYY)")
+                    .with_validator(validate_jump_date);
+                state.set_input(Some((InputPurpose::JumpToDate, input)));
+            }
+        }
+        Action::FindAccount if matches!(state.active_tab(), MenuItem::Log | MenuItem::Accounts) => {
+            let input = TextInput::new("Find an account").with_validator(validate_account_search);
+            state.set_input(Some((InputPurpose::FindAccount, input)));
+        }
+        Action::Trash if state.active_tab() == MenuItem::Log => {
+            if let (Some(AccountRow::Account(selected_acct)), Some(selected_stmt)) =
+                (selected_row(conf, state), state.log().selected_log())
+            {
+                match trashable_stmt(conf, selected_acct, selected_stmt, state.log().year()) {
+                    Ok(path) => state.set_prompt(Some(Prompt::Trash(selected_acct.clone(), path))),
+                    Err(e) => state.set_status(format!("{:#}", e), StatusKind::Error),
+                }
+            }
+        }
+        Action::ToggleIgnore if state.active_tab() == MenuItem::Log => {
+            if let (Some(AccountRow::Account(selected_acct)), Some(selected_stmt)) =
+                (selected_row(conf, state), state.log().selected_log())
+            {
+                let year = state.log().year();
+                if let Some(obs) = log_statements(conf, selected_acct, year).get(selected_stmt) {
+                    return KeyAction::ToggleIgnore(selected_acct.clone(), *obs.statement().date());
+                }
+            }
+        }
+        Action::Open if state.active_tab() == MenuItem::Accounts => {
+            if let Some(AccountRow::Account(selected_acct)) = selected_row(conf, state) {
+                state.set_detail(Some(selected_acct.clone()));
+            }
+        }
+        // Space to collapse or expand the group of the selected account
+        Action::ToggleGroup
+            if state.log().selected_log().is_none() || state.active_tab() == MenuItem::Accounts =>
+        {
+            if let Some(row) = selected_row(conf, state) {
+                let group = row_group(conf, row);
+                state.toggle_group(group);
+
+                // both tabs share the collapsed groups, so select the group's header in each
+                let header = |rows: Vec<AccountRow>| {
+                    rows.iter().position(|r| *r == AccountRow::Group(group))
+                };
+                let accounts_header = header(accounts_tab_rows(conf, state));
+                let log_header = header(account_rows(conf, state.collapsed()));
+                state.mut_accounts().select(accounts_header);
+                state.mut_log().select_account(log_header);
+                state.mut_log().select_log(None);
+            }
+        }
+        // keep the same account selected after the accounts are sorted or filtered
+        Action::CycleSort if state.active_tab() == MenuItem::Accounts => {
+            relist_accounts(conf, state, AccountsState::next_sort);
+        }
+        Action::ToggleMissingOnly if state.active_tab() == MenuItem::Accounts => {
+            relist_accounts(conf, state, AccountsState::toggle_missing_only);
+        }
+        _ => {}
+    }
+
+    KeyAction::Continue
+}
+
+/// Change how the "Accounts" tab is sorted or filtered, keeping the same row selected
+/// if it's still listed
+fn relist_accounts(conf: &Config, state: &mut TuiState, change: impl FnOnce(&mut AccountsState)) {
+    let key = selected_key(&accounts_tab_rows(conf, state), state.accounts().selected());
+    change(state.mut_accounts());
+    let row = reselect(&accounts_tab_rows(conf, state), key);
+    state.mut_accounts().select(row);
+}
+
+/// The first account whose name contains `text`, ignoring case
+fn matching_account<'a>(conf: &'a Config, text: &str) -> Option<&'a AccountKey> {
+    let text = text.to_lowercase();
+    conf.keys()
+        .iter()
+        .find(|key| conf.accounts()[*key].name().to_lowercase().contains(&text))
+}
+
+/// Check that an account's name contains the text being searched for
+fn validate_account_search(conf: &Config, text: &str) -> Result<(), String> {
+    if text.is_empty() {
+        return Err("Type part of an account's name.".to_string());
+    }
+
+    match matching_account(conf, text) {
+        Some(_) => Ok(()),
+        None => Err(format!("No account's name contains `{}`.", text)),
+    }
+}
+
+/// Select the first account whose name contains `text` in the current tab,
+/// expanding its group if it's collapsed
+fn find_account(conf: &Config, state: &mut TuiState, text: &str) {
+    let key = match matching_account(conf, text) {
+        Some(key) => key,
+        None => return,
+    };
+    let group = row_group(conf, AccountRow::Account(key));
+    if state.collapsed().contains(group) {
+        state.toggle_group(group);
+    }
+
+    let found = RowKey::Account(key.clone());
+    match state.active_tab() {
+        MenuItem::Accounts => match find_row(&accounts_tab_rows(conf, state), &found) {
+            Some(row) => state.mut_accounts().select(Some(row)),
+            None => state.set_status(
+                format!(
+                    "{} is hidden, since it isn't missing any statements.",
+                    conf.accounts()[key].name()
+                ),
+                StatusKind::Info,
+            ),
         },
-        UserEvent::Tick => {}
+        _ => {
+            let row = find_row(&account_rows(conf, state.collapsed()), &found);
+            state.mut_log().select_account(row);
+            state.mut_log().select_log(None);
+        }
     }
-    Ok(())
+}
+
+/// Parse a date to jump to, where a month or year without a day means its first day
+fn parse_jump_date(text: &str) -> Option<NaiveDate> {
+    let text = text.trim();
+    NaiveDate::parse_from_str(text, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(&format!("{}-01", text), "%Y-%m-%d"))
+        .or_else(|_| NaiveDate::parse_from_str(&format!("{}-01-01", text), "%Y-%m-%d"))
+        .ok()
+}
+
+/// Check that the text is a date that can be jumped to
+fn validate_jump_date(_: &Config, text: &str) -> Result<(), String> {
+    match parse_jump_date(text) {
+        Some(_) => Ok(()),
+        None => Err(format!(
+            "`{}` isn't a date like `2021-06-30`, `2021-06`, or `2021`.",
+            text
+        )),
+    }
+}
+
+/// Select the statement of the selected account that's nearest to the date in `text`,
+/// listing every year again if the date is outside of the year being listed
+fn jump_to_date(conf: &Config, state: &mut TuiState, text: &str) {
+    let (acct_key, date) = match (selected_row(conf, state), parse_jump_date(text)) {
+        (Some(AccountRow::Account(acct_key)), Some(date)) => (acct_key, date),
+        _ => return,
+    };
+    if state.log().year().map_or(false, |year| year != date.year()) {
+        state.mut_log().set_year(None);
+    }
+
+    // the later statement is chosen if two are equally near
+    let nearest = log_statements(conf, acct_key, state.log().year())
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, obs)| (*obs.statement().date() - date).num_days().abs())
+        .map(|(i, _)| i);
+    match nearest {
+        Some(i) => state.mut_log().select_log(Some(i)),
+        None => state.set_status(
+            format!(
+                "{} doesn't have any statements yet.",
+                conf.accounts()[acct_key].name()
+            ),
+            StatusKind::Info,
+        ),
+    }
+}
+
+/// The row selected in the accounts list of the current tab, if any
+fn selected_row<'a>(conf: &'a Config, state: &TuiState) -> Option<AccountRow<'a>> {
+    let (rows, row_idx) = match state.active_tab() {
+        MenuItem::Accounts => (accounts_tab_rows(conf, state), state.accounts().selected()),
+        MenuItem::Log => (
+            account_rows(conf, state.collapsed()),
+            state.log().selected_account(),
+        ),
+        _ => return None,
+    };
+
+    rows.get(row_idx?).copied()
 }
@@ -1,8 +1,17 @@
 //! Manage the current state of the terminal user interface.
 
+use crate::cfg::ScanProgress;
+use chrono::NaiveDate;
+use quill_statement::{AccountKey, StatementRename};
 use ratatui::widgets::{ListState, TableState};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
+use super::edit::AccountEdit;
+use super::input::TextInput;
 use super::render::{step_next, step_prev, MenuItem};
+use super::undo::UndoStack;
 
 /// Application state for the "Missing" tab.
 #[derive(Debug)]
@@ -53,6 +62,12 @@ impl Default for MissingState {
 pub struct LogState {
     accounts: ListState,
     log: ListState,
+    /// The only year that the selected account's statements are listed from, if any
+    year: Option<i32>,
+    /// How many rows of accounts fit on the screen, as of the last time they were drawn
+    accounts_height: usize,
+    /// How many rows of statements fit on the screen, as of the last time they were drawn
+    log_height: usize,
 }
 
 impl LogState {
@@ -64,7 +79,13 @@ impl LogState {
         &mut self.accounts
     }
 
+    /// Select an account, listing all of its statements if it's a different account
     pub fn select_account(&mut self, index: Option<usize>) {
+        if index != self.accounts.selected() {
+            self.year = None;
+            // a different account's statements are listed from the top
+            *self.log.offset_mut() = 0;
+        }
         self.accounts.select(index);
     }
 
@@ -115,12 +136,114 @@ impl LogState {
     pub fn selected(&self) -> (Option<usize>, Option<usize>) {
         (self.selected_account(), self.selected_log())
     }
+
+    /// The only year that statements are listed from, if any
+    pub fn year(&self) -> Option<i32> {
+        self.year
+    }
+
+    /// List the statements from a single year, or from every year, starting from the latest
+    /// statement again if one was selected
+    pub fn set_year(&mut self, year: Option<i32>) {
+        self.year = year;
+        *self.log.offset_mut() = 0;
+        if self.selected_log().is_some() {
+            self.select_log(Some(0));
+        }
+    }
+
+    /// Keep track of how many rows of accounts and statements fit on the screen,
+    /// so that a page of them can be skipped at once
+    pub fn set_heights(&mut self, accounts: usize, log: usize) {
+        self.accounts_height = accounts;
+        self.log_height = log;
+    }
+
+    /// Jump through the statements if one is selected, or through the accounts otherwise,
+    /// where `len` is how many rows are in that list
+    pub fn jump(&mut self, len: usize, jump: Jump) {
+        let (selected, height) = match self.selected_log() {
+            Some(n) => (Some(n), self.log_height),
+            None => (self.selected_account(), self.accounts_height),
+        };
+        let (n, last) = match (selected, len.checked_sub(1)) {
+            (Some(n), Some(last)) => (n, last),
+            _ => return,
+        };
+
+        // keep the previously selected row on the screen after moving by a page
+        let page = height.saturating_sub(1).max(1);
+        let index = match jump {
+            Jump::PageUp => n.saturating_sub(page),
+            Jump::PageDown => (n + page).min(last),
+            Jump::First => 0,
+            Jump::Last => last,
+        };
+        match self.selected_log() {
+            Some(_) => self.select_log(Some(index)),
+            None => self.select_account(Some(index)),
+        }
+    }
+}
+
+/// Ways to move through a long list more than one row at a time
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Jump {
+    PageUp,
+    PageDown,
+    First,
+    Last,
+}
+
+/// The order accounts are listed in on the "Accounts" tab.
+/// Named schedules are always listed with the account they belong to.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum AccountSort {
+    /// The order they're listed in the configuration file
+    #[default]
+    Config,
+    Name,
+    Institution,
+    /// Grouped by kind, like credit cards, then by name
+    Kind,
+    /// Most missing statements first
+    Missing,
+    /// Soonest next statement first
+    NextDue,
+}
+
+impl AccountSort {
+    /// The next order to sort by, cycling back to the configuration file's order
+    pub fn next(self) -> Self {
+        match self {
+            Self::Config => Self::Name,
+            Self::Name => Self::Institution,
+            Self::Institution => Self::Kind,
+            Self::Kind => Self::Missing,
+            Self::Missing => Self::NextDue,
+            Self::NextDue => Self::Config,
+        }
+    }
+
+    /// Describe the order, for the title of the table
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Config => "configuration file",
+            Self::Name => "name",
+            Self::Institution => "institution",
+            Self::Kind => "kind",
+            Self::Missing => "missing statements",
+            Self::NextDue => "next due date",
+        }
+    }
 }
 
 /// Application state for the "Accounts" tab.
 #[derive(Debug, Default)]
 pub struct AccountsState {
     state: TableState,
+    sort: AccountSort,
+    missing_only: bool,
 }
 
 impl AccountsState {
@@ -150,6 +273,139 @@ impl AccountsState {
     pub fn selected(&self) -> Option<usize> {
         self.state.selected()
     }
+
+    /// The order accounts are listed in
+    pub fn sort(&self) -> AccountSort {
+        self.sort
+    }
+
+    pub fn next_sort(&mut self) {
+        self.sort = self.sort.next();
+    }
+
+    /// Whether only accounts with missing statements are listed
+    pub fn missing_only(&self) -> bool {
+        self.missing_only
+    }
+
+    pub fn toggle_missing_only(&mut self) {
+        self.missing_only = !self.missing_only;
+    }
+}
+
+/// How long a status message is shown for, however often the TUI ticks
+const STATUS_DURATION: Duration = Duration::from_secs(5);
+
+/// Something that must be confirmed before it's done.
+#[derive(Debug)]
+pub enum Prompt {
+    /// Move a statement file of an account to the trash
+    Trash(AccountKey, PathBuf),
+    /// Move files from the inbox into the directories of the accounts they belong to,
+    /// leaving behind the number of files that couldn't be sorted
+    SortInbox {
+        dir: PathBuf,
+        moves: Vec<StatementRename>,
+        unsorted: usize,
+    },
+}
+
+/// What text is being typed for.
+#[derive(Clone, Debug)]
+pub enum InputPurpose {
+    /// Part of the name of an account to select
+    FindAccount,
+    /// A new value for one of an account's properties
+    EditAccount(AccountEdit),
+    /// A date to select the nearest statement to
+    JumpToDate,
+}
+
+/// Files to choose from to import as one of an account's statements.
+#[derive(Debug)]
+pub struct ImportPicker {
+    key: AccountKey,
+    date: NaiveDate,
+    files: Vec<PathBuf>,
+    state: ListState,
+}
+
+impl ImportPicker {
+    /// Choose from `files` to import as the statement of `key` from `date`, starting with the first
+    pub fn new(key: AccountKey, date: NaiveDate, files: Vec<PathBuf>) -> Self {
+        let mut state = ListState::default();
+        state.select(Some(0));
+        ImportPicker {
+            key,
+            date,
+            files,
+            state,
+        }
+    }
+
+    pub fn key(&self) -> &AccountKey {
+        &self.key
+    }
+
+    pub fn date(&self) -> &NaiveDate {
+        &self.date
+    }
+
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    pub fn mut_state(&mut self) -> &mut ListState {
+        &mut self.state
+    }
+
+    pub fn select_next(&mut self) {
+        if let Some(n) = self.state.selected() {
+            self.state.select(Some(step_next(self.files.len(), n)));
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        if let Some(n) = self.state.selected() {
+            self.state.select(Some(step_prev(self.files.len(), n)));
+        }
+    }
+
+    /// The file that's selected to import
+    pub fn selected(&self) -> Option<&Path> {
+        self.state
+            .selected()
+            .and_then(|n| self.files.get(n))
+            .map(PathBuf::as_path)
+    }
+}
+
+/// How long a status message that may be copied, like a path, is shown for
+const LASTING_STATUS_DURATION: Duration = Duration::from_secs(60);
+
+/// Whether a status message reports something that worked or something that failed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatusKind {
+    Info,
+    Error,
+}
+
+/// A message shown in the footer until it expires
+#[derive(Clone, Debug)]
+pub struct StatusMessage {
+    text: String,
+    kind: StatusKind,
+    expires: Instant,
+}
+
+impl StatusMessage {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn kind(&self) -> StatusKind {
+        self.kind
+    }
 }
 
 /// Combined application state for the terminal user interface.
@@ -159,6 +415,23 @@ pub struct TuiState {
     missing: MissingState,
     log: LogState,
     accounts: AccountsState,
+    collapsed: HashSet<String>,
+    help_visible: bool,
+    detail: Option<AccountKey>,
+    debug_visible: bool,
+    reload_error: Option<String>,
+    fetch_result: Option<String>,
+    scanning: bool,
+    scan_started: Option<Instant>,
+    first_scan: bool,
+    scan_progress: Option<ScanProgress>,
+    status: Option<StatusMessage>,
+    dirty: bool,
+    headless: bool,
+    prompt: Option<Prompt>,
+    import_picker: Option<ImportPicker>,
+    undo: UndoStack,
+    input: Option<(InputPurpose, TextInput)>,
 }
 
 impl TuiState {
@@ -201,4 +474,200 @@ impl TuiState {
     pub fn mut_accounts(&mut self) -> &mut AccountsState {
         &mut self.accounts
     }
+
+    /// The groups of accounts that are collapsed in the "Accounts" and "Log" tabs
+    pub fn collapsed(&self) -> &HashSet<String> {
+        &self.collapsed
+    }
+
+    /// Whether the help is shown over the current tab
+    pub fn help_visible(&self) -> bool {
+        self.help_visible
+    }
+
+    pub fn set_help_visible(&mut self, visible: bool) {
+        self.help_visible = visible;
+    }
+
+    /// Key of the account whose details are shown, if any
+    pub fn detail(&self) -> Option<&AccountKey> {
+        self.detail.as_ref()
+    }
+
+    pub fn set_detail(&mut self, key: Option<AccountKey>) {
+        self.detail = key;
+    }
+
+    /// Whether the debug pane is shown below the current tab
+    pub fn debug_visible(&self) -> bool {
+        self.debug_visible
+    }
+
+    pub fn toggle_debug(&mut self) {
+        self.debug_visible = !self.debug_visible;
+    }
+
+    /// Why the configuration file couldn't be reloaded, if it couldn't
+    pub fn reload_error(&self) -> Option<&str> {
+        self.reload_error.as_deref()
+    }
+
+    pub fn set_reload_error(&mut self, error: Option<String>) {
+        self.reload_error = error;
+    }
+
+    /// What happened the last time statements were fetched, until they're scanned for again
+    pub fn fetch_result(&self) -> Option<&str> {
+        self.fetch_result.as_deref()
+    }
+
+    pub fn set_fetch_result(&mut self, result: Option<String>) {
+        self.fetch_result = result;
+    }
+
+    /// Whether statements are being scanned for in the background
+    pub fn is_scanning(&self) -> bool {
+        self.scanning
+    }
+
+    /// How far the background scan has got, once it's started on the first account
+    pub fn scan_progress(&self) -> Option<&ScanProgress> {
+        self.scan_progress.as_ref()
+    }
+
+    pub fn start_scan(&mut self) {
+        self.scanning = true;
+        self.scan_started = Some(Instant::now());
+        self.scan_progress = None;
+    }
+
+    /// How long the background scan has been running for
+    pub fn scan_elapsed(&self) -> Option<Duration> {
+        match self.scanning {
+            true => self.scan_started.map(|started| started.elapsed()),
+            false => None,
+        }
+    }
+
+    pub fn set_scan_progress(&mut self, progress: ScanProgress) {
+        self.scan_progress = Some(progress);
+    }
+
+    pub fn finish_scan(&mut self) {
+        self.scanning = false;
+        self.first_scan = false;
+        self.scan_progress = None;
+    }
+
+    /// Whether statements are being scanned for the first time since the TUI was opened,
+    /// so there aren't any statements to show yet
+    /// Draw the TUI again, since something shown in it has changed
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Whether the TUI needs to be drawn again, which it won't until something else changes
+    pub fn take_dirty(&mut self) -> bool {
+        std::mem::take(&mut self.dirty)
+    }
+
+    pub fn is_first_scan(&self) -> bool {
+        self.first_scan
+    }
+
+    /// The message shown in the footer, until it expires
+    pub fn status(&self) -> Option<&StatusMessage> {
+        self.status.as_ref()
+    }
+
+    /// Show a message in the footer for a few seconds, replacing any that's already shown
+    pub fn set_status(&mut self, text: impl Into<String>, kind: StatusKind) {
+        self.set_status_for(text, kind, STATUS_DURATION);
+    }
+
+    /// Show a message in the footer for long enough to copy it, replacing any that's already shown
+    pub fn set_lasting_status(&mut self, text: impl Into<String>, kind: StatusKind) {
+        self.set_status_for(text, kind, LASTING_STATUS_DURATION);
+    }
+
+    fn set_status_for(&mut self, text: impl Into<String>, kind: StatusKind, duration: Duration) {
+        self.status = Some(StatusMessage {
+            text: text.into(),
+            kind,
+            expires: Instant::now() + duration,
+        });
+    }
+
+    /// What's waiting to be confirmed before it's done, if anything
+    pub fn prompt(&self) -> Option<&Prompt> {
+        self.prompt.as_ref()
+    }
+
+    /// Ask to confirm something before it's done
+    pub fn set_prompt(&mut self, prompt: Option<Prompt>) {
+        self.prompt = prompt;
+    }
+
+    /// Stop asking for confirmation, returning what was asked about
+    pub fn take_prompt(&mut self) -> Option<Prompt> {
+        self.prompt.take()
+    }
+
+    /// The files being chosen from to import as a statement, if any
+    pub fn mut_import_picker(&mut self) -> Option<&mut ImportPicker> {
+        self.import_picker.as_mut()
+    }
+
+    pub fn set_import_picker(&mut self, picker: Option<ImportPicker>) {
+        self.import_picker = picker;
+    }
+
+    /// The text being typed, and what it's for, if any
+    pub fn input(&self) -> Option<&TextInput> {
+        self.input.as_ref().map(|(_, input)| input)
+    }
+
+    pub fn mut_input(&mut self) -> Option<&mut (InputPurpose, TextInput)> {
+        self.input.as_mut()
+    }
+
+    /// Start or stop typing text
+    pub fn set_input(&mut self, input: Option<(InputPurpose, TextInput)>) {
+        self.input = input;
+    }
+
+    /// Changes to statement files that can be undone or redone
+    pub fn mut_undo(&mut self) -> &mut UndoStack {
+        &mut self.undo
+    }
+
+    /// Whether files are never opened with the operating system's default program,
+    /// regardless of the configuration
+    pub fn is_headless(&self) -> bool {
+        self.headless
+    }
+
+    pub fn set_headless(&mut self, headless: bool) {
+        self.headless = headless;
+    }
+
+    /// Clear the status message once it's expired
+    pub fn tick(&mut self) {
+        if matches!(&self.status, Some(status) if status.expires <= Instant::now()) {
+            self.status = None;
+            self.dirty = true;
+        }
+    }
+
+    pub fn start_first_scan(&mut self) {
+        self.start_scan();
+        self.first_scan = true;
+    }
+
+    /// Collapse a group of accounts, or expand it if it's already collapsed
+    pub fn toggle_group(&mut self, group: &str) {
+        if !self.collapsed.remove(group) {
+            self.collapsed.insert(group.to_string());
+        }
+    }
 }
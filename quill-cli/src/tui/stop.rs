@@ -1,16 +1,48 @@
 //! Gracefully close down the terminal user interface.
 
-use std::io::Stdout;
+use std::{
+    io::{self, Write},
+    panic, process,
+};
 
-use crossterm::terminal::disable_raw_mode;
-use ratatui::{backend::CrosstermBackend, Terminal};
+use crossterm::{
+    cursor::Show,
+    execute,
+    terminal::{disable_raw_mode, LeaveAlternateScreen},
+};
+use ratatui::{backend::Backend, Terminal};
 
-/// Disable terminal raw mode, clear the screen, and restore the cursor.
-pub fn stop_tui(
-    term: &mut Terminal<CrosstermBackend<Stdout>>,
+/// Exit code for a panic, the same as Rust's own
+const PANIC_EXIT_CODE: i32 = 101;
+
+/// Disable terminal raw mode, leave the alternate screen, and restore the cursor.
+/// The backend has to write to the terminal, like `CrosstermBackend` does.
+pub fn stop_tui<B: Backend + Write>(
+    term: &mut Terminal<B>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     disable_raw_mode()?;
-    term.clear()?;
+    execute!(term.backend_mut(), LeaveAlternateScreen, Show)?;
     term.show_cursor()?;
+
+    // the TUI is closed, so panics no longer need to restore the terminal
+    let _ = panic::take_hook();
     Ok(())
 }
+
+/// Restore the terminal before a panic's message is printed, so that it can be read.
+/// The TUI can't recover from a panic on any of its threads, so quill exits afterwards.
+pub(super) fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        restore_terminal();
+        default_hook(info);
+        process::exit(PANIC_EXIT_CODE);
+    }));
+}
+
+/// Disable terminal raw mode, leave the alternate screen, and restore the cursor without a `Terminal`.
+/// This is a last resort, so any errors are ignored.
+pub(super) fn restore_terminal() {
+    let _ = disable_raw_mode();
+    let _ = execute!(io::stdout(), LeaveAlternateScreen, Show);
+}
@@ -0,0 +1,376 @@
+//! Undo and redo changes made to statement files from the TUI.
+
+use anyhow::{bail, Context};
+use chrono::NaiveDate;
+use quill_statement::{extend_ignorefile, remove_from_ignorefile};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Most changes that are remembered to undo
+const MAX_UNDO: usize = 50;
+
+/// A change to statement files made from the TUI, which can be undone.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Change {
+    /// Files were moved, from the first path of each pair to the second
+    Moved(Vec<(PathBuf, PathBuf)>),
+    /// A file was moved to the operating system's trash
+    Trashed(PathBuf),
+    /// A statement was ignored, by adding its date to the ignore file in its account's directory
+    Ignored { dir: PathBuf, date: NaiveDate },
+    /// A statement stopped being ignored, by removing its date from the ignore file in its
+    /// account's directory, along with why it was ignored
+    Unignored {
+        dir: PathBuf,
+        date: NaiveDate,
+        reason: Option<String>,
+    },
+}
+
+impl Change {
+    /// Describe the change, to say what was undone or redone
+    pub fn description(&self) -> String {
+        match self {
+            Self::Moved(moves) if moves.len() == 1 => {
+                format!("moving `{}`", moves[0].0.display())
+            }
+            Self::Moved(moves) => format!("moving {} files", moves.len()),
+            Self::Trashed(path) => format!("moving `{}` to the trash", path.display()),
+            Self::Ignored { date, .. } => format!("ignoring the statement from {}", date),
+            Self::Unignored { date, .. } => {
+                format!("no longer ignoring the statement from {}", date)
+            }
+        }
+    }
+
+    /// Check if the change was to an ignore file, which accounts only read when they're loaded
+    pub fn changes_ignore_file(&self) -> bool {
+        matches!(self, Self::Ignored { .. } | Self::Unignored { .. })
+    }
+
+    /// Put the files back where they were before the change
+    fn undo(&self) -> anyhow::Result<()> {
+        match self {
+            // move them back in the opposite order, in case one was moved where another had been
+            Self::Moved(moves) => {
+                let back: Vec<(PathBuf, PathBuf)> = moves
+                    .iter()
+                    .rev()
+                    .map(|(from, to)| (to.clone(), from.clone()))
+                    .collect();
+                move_files(&back)
+            }
+            Self::Trashed(path) => restore_from_trash(path),
+            Self::Ignored { dir, date } => unignore(dir, date),
+            Self::Unignored { dir, date, reason } => ignore(dir, date, reason.as_deref()),
+        }
+    }
+
+    /// Make the change, or make it again after it was undone
+    pub(super) fn redo(&self) -> anyhow::Result<()> {
+        match self {
+            Self::Moved(moves) => move_files(moves),
+            Self::Trashed(path) => trash::delete(path)
+                .with_context(|| format!("Couldn't move `{}` to the trash", path.display())),
+            Self::Ignored { dir, date } => ignore(dir, date, None),
+            Self::Unignored { dir, date, .. } => unignore(dir, date),
+        }
+    }
+}
+
+/// Changes that can be undone, and changes that were undone and can be redone.
+#[derive(Debug, Default)]
+pub struct UndoStack {
+    done: Vec<Change>,
+    undone: Vec<Change>,
+}
+
+impl UndoStack {
+    /// Remember a change that was just made.
+    /// Whatever was undone can no longer be redone, since files may have moved since.
+    pub fn push(&mut self, change: Change) {
+        self.undone.clear();
+        self.done.push(change);
+        if self.done.len() > MAX_UNDO {
+            self.done.remove(0);
+        }
+    }
+
+    /// Undo the latest change, returning it, or `None` if there's nothing to undo.
+    /// A change that can't be undone is kept, so it can be tried again once whatever
+    /// stopped it is fixed.
+    pub fn undo(&mut self) -> anyhow::Result<Option<Change>> {
+        let change = match self.done.pop() {
+            Some(change) => change,
+            None => return Ok(None),
+        };
+        if let Err(e) = change.undo() {
+            self.done.push(change);
+            return Err(e);
+        }
+        self.undone.push(change.clone());
+
+        Ok(Some(change))
+    }
+
+    /// Redo the latest change that was undone, returning it, or `None` if there's nothing to redo.
+    /// A change that can't be redone is kept, like one that can't be undone.
+    pub fn redo(&mut self) -> anyhow::Result<Option<Change>> {
+        let change = match self.undone.pop() {
+            Some(change) => change,
+            None => return Ok(None),
+        };
+        if let Err(e) = change.redo() {
+            self.undone.push(change);
+            return Err(e);
+        }
+        self.done.push(change.clone());
+
+        Ok(Some(change))
+    }
+}
+
+/// Move files, from the first path of each pair to the second, in order.
+/// If one can't be moved, the ones moved before it are moved back, so either every file is
+/// moved or none are.
+fn move_files(moves: &[(PathBuf, PathBuf)]) -> anyhow::Result<()> {
+    for (i, (from, to)) in moves.iter().enumerate() {
+        if let Err(e) = move_file(from, to) {
+            for (from, to) in moves[..i].iter().rev() {
+                if let Err(back) = move_file(to, from) {
+                    return Err(e.context(format!(
+                        "`{}` couldn't be moved back to `{}` either: {:#}",
+                        to.display(),
+                        from.display(),
+                        back
+                    )));
+                }
+            }
+            return Err(e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Move a file, without ever replacing an existing file
+fn move_file(from: &Path, to: &Path) -> anyhow::Result<()> {
+    if to.exists() {
+        bail!(
+            "Couldn't move `{}` to `{}`, because it already exists.",
+            from.display(),
+            to.display()
+        );
+    }
+
+    // renaming fails between file systems, so fall back to copying
+    fs::rename(from, to)
+        .or_else(|e| match is_cross_device(&e) {
+            true => fs::copy(from, to).and_then(|_| fs::remove_file(from)),
+            false => Err(e),
+        })
+        .with_context(|| format!("Couldn't move `{}` to `{}`", from.display(), to.display()))
+}
+
+/// Check if a file couldn't be renamed because it would move to another file system.
+/// `io::ErrorKind::CrossesDevices` isn't stable in the minimum supported Rust version,
+/// so the operating system's error code is checked instead.
+fn is_cross_device(e: &io::Error) -> bool {
+    // `EXDEV` on Unix, and `ERROR_NOT_SAME_DEVICE` on Windows
+    let code = match cfg!(windows) {
+        true => 17,
+        false => 18,
+    };
+    e.raw_os_error() == Some(code)
+}
+
+/// Ignore the statement from `date` in the ignore file in `dir`, and why
+fn ignore(dir: &Path, date: &NaiveDate, reason: Option<&str>) -> anyhow::Result<()> {
+    extend_ignorefile(dir, &[*date], reason, &[])
+        .with_context(|| format!("Couldn't ignore the statement from {}", date))?;
+    Ok(())
+}
+
+/// Stop ignoring the statement from `date` in the ignore file in `dir`
+fn unignore(dir: &Path, date: &NaiveDate) -> anyhow::Result<()> {
+    remove_from_ignorefile(dir, &[*date])
+        .with_context(|| format!("Couldn't stop ignoring the statement from {}", date))?;
+    Ok(())
+}
+
+/// Put the file most recently moved to the trash from `path` back where it was
+#[cfg(any(
+    target_os = "windows",
+    all(
+        unix,
+        not(target_os = "macos"),
+        not(target_os = "ios"),
+        not(target_os = "android")
+    )
+))]
+fn restore_from_trash(path: &Path) -> anyhow::Result<()> {
+    use trash::os_limited::{list, restore_all};
+
+    let item = list()
+        .context("Couldn't list the files in the trash")?
+        .into_iter()
+        .filter(|item| item.original_path() == path)
+        .max_by_key(|item| item.time_deleted);
+    match item {
+        Some(item) => restore_all([item])
+            .with_context(|| format!("Couldn't restore `{}` from the trash", path.display())),
+        None => bail!("`{}` is no longer in the trash.", path.display()),
+    }
+}
+
+/// Files can't be restored from the trash on this operating system
+#[cfg(not(any(
+    target_os = "windows",
+    all(
+        unix,
+        not(target_os = "macos"),
+        not(target_os = "ios"),
+        not(target_os = "android")
+    )
+)))]
+fn restore_from_trash(path: &Path) -> anyhow::Result<()> {
+    bail!(
+        "Files can't be restored from the trash on this system, so restore `{}` from it yourself.",
+        path.display()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quill_statement::IgnoredStatements;
+    use tempfile::tempdir;
+
+    /// Write an empty file at each path
+    fn touch(paths: &[&Path]) {
+        for path in paths {
+            fs::write(path, "").unwrap();
+        }
+    }
+
+    #[test]
+    fn undo_and_redo_moves() {
+        let dir = tempdir().unwrap();
+        let (a, b) = (dir.path().join("a.pdf"), dir.path().join("b.pdf"));
+        touch(&[&b]);
+
+        let mut stack = UndoStack::default();
+        stack.push(Change::Moved(vec![(a.clone(), b.clone())]));
+
+        assert!(stack.undo().unwrap().is_some());
+        assert!(a.exists() && !b.exists());
+        assert!(stack.undo().unwrap().is_none());
+
+        assert!(stack.redo().unwrap().is_some());
+        assert!(!a.exists() && b.exists());
+        assert!(stack.redo().unwrap().is_none());
+    }
+
+    #[test]
+    fn failed_undo_is_kept() {
+        let dir = tempdir().unwrap();
+        let (a, b) = (dir.path().join("a.pdf"), dir.path().join("b.pdf"));
+        // something new is where the file was moved from, so it can't be moved back
+        touch(&[&a, &b]);
+
+        let mut stack = UndoStack::default();
+        stack.push(Change::Moved(vec![(a.clone(), b.clone())]));
+        assert!(stack.undo().is_err());
+        assert!(a.exists() && b.exists());
+
+        // once it's out of the way, the change can be undone
+        fs::remove_file(&a).unwrap();
+        assert!(stack.undo().unwrap().is_some());
+        assert!(a.exists() && !b.exists());
+
+        // and the same goes for redoing it
+        touch(&[&b]);
+        assert!(stack.redo().is_err());
+        fs::remove_file(&b).unwrap();
+        assert!(stack.redo().unwrap().is_some());
+        assert!(!a.exists() && b.exists());
+    }
+
+    #[test]
+    fn failed_moves_are_moved_back() {
+        let dir = tempdir().unwrap();
+        let path = |name: &str| dir.path().join(name);
+        touch(&[&path("b.pdf"), &path("d.pdf"), &path("f.pdf")]);
+
+        let mut stack = UndoStack::default();
+        stack.push(Change::Moved(vec![
+            (path("a.pdf"), path("b.pdf")),
+            (path("c.pdf"), path("d.pdf")),
+            (path("e.pdf"), path("f.pdf")),
+        ]));
+        // the second file can't be moved back, after the third already has been
+        touch(&[&path("c.pdf")]);
+        assert!(stack.undo().is_err());
+
+        // so the third is where it was before trying
+        assert!(!path("e.pdf").exists() && path("f.pdf").exists());
+        assert!(!path("a.pdf").exists() && path("b.pdf").exists());
+    }
+
+    #[test]
+    fn missing_file_isnt_moved() {
+        let dir = tempdir().unwrap();
+        let (a, b) = (dir.path().join("a.pdf"), dir.path().join("b.pdf"));
+
+        assert!(move_file(&a, &b).is_err());
+        assert!(!a.exists() && !b.exists());
+    }
+
+    #[test]
+    fn undo_and_redo_ignore() {
+        let dir = tempdir().unwrap();
+        let date = NaiveDate::from_ymd_opt(2021, 11, 1).unwrap();
+        let is_ignored = || IgnoredStatements::from(dir.path()).contains(&date);
+
+        let change = Change::Ignored {
+            dir: dir.path().to_path_buf(),
+            date,
+        };
+        change.redo().unwrap();
+        assert!(is_ignored());
+
+        let mut stack = UndoStack::default();
+        stack.push(change);
+        stack.undo().unwrap();
+        assert!(!is_ignored());
+        stack.redo().unwrap();
+        assert!(is_ignored());
+    }
+
+    #[test]
+    fn undo_unignore_keeps_reason() {
+        let dir = tempdir().unwrap();
+        let date = NaiveDate::from_ymd_opt(2021, 11, 1).unwrap();
+        fs::write(
+            dir.path().join(".quillignore.toml"),
+            "dates = [{ date = 2021-11-01, reason = \"lost in the mail\" }]\n",
+        )
+        .unwrap();
+        let ignored = || IgnoredStatements::from(dir.path());
+
+        let change = Change::Unignored {
+            dir: dir.path().to_path_buf(),
+            date,
+            reason: ignored().reason(&date).map(String::from),
+        };
+        change.redo().unwrap();
+        assert!(!ignored().contains(&date));
+
+        let mut stack = UndoStack::default();
+        stack.push(change);
+        stack.undo().unwrap();
+        assert!(ignored().contains(&date));
+        assert_eq!(Some("lost in the mail"), ignored().reason(&date));
+    }
+}
@@ -0,0 +1,73 @@
+//! Benchmarks for generating the dates that statements are expected on.
+//!
+//! Run them with `cargo bench -p quill_statement`.
+
+use chrono::NaiveDate;
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use kronos::{Grain, Grains, NthOf, Shim};
+use quill_statement::{ExpectedDates, IgnoredStatements, Pairings, Statement};
+
+/// The day the dates are generated until, fixed so that runs can be compared
+fn today() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2023, 6, 15).unwrap()
+}
+
+/// The first statement of an account that's 15 years old
+fn first() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2008, 6, 13).unwrap()
+}
+
+fn weekly() -> Shim<'static> {
+    Shim::new(NthOf(6, Grains(Grain::Day), Grains(Grain::Week)))
+}
+
+fn monthly() -> Shim<'static> {
+    Shim::new(NthOf(13, Grains(Grain::Day), Grains(Grain::Month)))
+}
+
+/// Generating every date at once, like when an account is first scanned
+fn generate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generate");
+    for (name, period) in [("weekly", weekly()), ("monthly", monthly())] {
+        group.bench_function(name, |b| {
+            b.iter(|| ExpectedDates::until(&first(), &period, &today()))
+        });
+    }
+    group.finish();
+}
+
+/// Extending dates that were already generated by a day, like when the statements are
+/// scanned again the next day
+fn extend(c: &mut Criterion) {
+    let mut group = c.benchmark_group("extend");
+    let yesterday = today().pred_opt().unwrap();
+    for (name, period) in [("weekly", weekly()), ("monthly", monthly())] {
+        let cached = ExpectedDates::until(&first(), &period, &yesterday);
+        group.bench_function(name, |b| {
+            b.iter_batched(
+                || cached.clone(),
+                |mut dates| {
+                    dates.extend_until(&period, &today());
+                    dates
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+/// Pairing every weekly date with a statement
+fn pair(c: &mut Criterion) {
+    let period = weekly();
+    let dates = ExpectedDates::until(&first(), &period, &today()).into_dates();
+    let stmts: Vec<Statement> = dates.iter().map(Statement::from).collect();
+    let ignored = IgnoredStatements::empty();
+
+    c.bench_function("pair/weekly", |b| {
+        b.iter(|| Pairings::new(&dates, &stmts, &ignored).count())
+    });
+}
+
+criterion_group!(benches, generate, extend, pair);
+criterion_main!(benches);
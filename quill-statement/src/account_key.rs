@@ -0,0 +1,115 @@
+//! The key that identifies an account in the configuration file.
+
+use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::fmt::{self, Display};
+use std::ops::Deref;
+
+/// The key of an account, as in `[Accounts.<key>]` in the configuration file.
+/// Each of an account's named schedules has its own key, `<key>/<schedule>`.
+/// It dereferences to a `&str`, and collections keyed by `AccountKey` can be looked up
+/// with a plain `&str`.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(transparent)]
+pub struct AccountKey(String);
+
+impl AccountKey {
+    /// Create a new account key
+    pub fn new(key: &str) -> Self {
+        Self(key.to_string())
+    }
+
+    /// Return the key of one of the account's named schedules
+    pub fn schedule(&self, name: &str) -> Self {
+        Self(format!("{}/{}", self.0, name))
+    }
+
+    /// Return the key as a string slice
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for AccountKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for AccountKey {
+    fn from(key: &str) -> Self {
+        Self::new(key)
+    }
+}
+
+impl From<String> for AccountKey {
+    fn from(key: String) -> Self {
+        Self(key)
+    }
+}
+
+impl Deref for AccountKey {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for AccountKey {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for AccountKey {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq<str> for AccountKey {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for AccountKey {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn schedule_key() {
+        let key = AccountKey::new("Visa");
+
+        assert_eq!("Visa/transactions", key.schedule("transactions").as_str());
+    }
+
+    #[test]
+    fn lookup_by_str() {
+        let mut map = HashMap::new();
+        map.insert(AccountKey::new("Visa"), 1);
+
+        assert_eq!(Some(&1), map.get("Visa"));
+        assert_eq!(None, map.get("Chequing"));
+    }
+
+    #[test]
+    fn sorted_like_strings() {
+        let mut keys = vec![
+            AccountKey::new("b"),
+            AccountKey::new("a/x"),
+            AccountKey::new("a"),
+        ];
+        keys.sort();
+
+        assert_eq!(keys, vec!["a", "a/x", "b"]);
+    }
+}
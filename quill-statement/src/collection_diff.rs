@@ -0,0 +1,200 @@
+//! Changes between two collections of statements.
+
+use crate::{ObservedStatement, StatementCollection, StatementStatus};
+use chrono::NaiveDate;
+use std::collections::HashMap;
+
+/// A statement that has changed, along with the key of the account it belongs to.
+pub type StatementChange = (String, ObservedStatement);
+
+/// What changed between a previous and a current `StatementCollection`.
+///
+/// Statements are matched between the two collections by their account key and date.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CollectionDiff {
+    newly_available: Vec<StatementChange>,
+    newly_missing: Vec<StatementChange>,
+    removed: Vec<StatementChange>,
+}
+
+impl CollectionDiff {
+    /// Compare the `current` collection of statements against a `previous` one.
+    pub fn new(previous: &StatementCollection, current: &StatementCollection) -> Self {
+        let mut diff = Self::default();
+
+        // index the previous statements by account and date for fast lookup
+        let prev_index: HashMap<(&str, &NaiveDate), &ObservedStatement> = previous
+            .iter()
+            .flat_map(|(key, stmts)| {
+                stmts
+                    .iter()
+                    .map(move |obs| ((key, obs.statement().date()), obs))
+            })
+            .collect();
+
+        for (key, stmts) in current.iter() {
+            for obs in stmts {
+                let prev = prev_index.get(&(key, obs.statement().date()));
+                let prev_status = prev.map(|prev| prev.status());
+
+                match (prev_status, obs.status()) {
+                    (Some(StatementStatus::Available), StatementStatus::Available) => {}
+                    (_, StatementStatus::Available) => {
+                        diff.newly_available.push((key.to_string(), obs.clone()))
+                    }
+                    // keep the previous statement, since it knows where the file was
+                    (Some(StatementStatus::Available), _) => {
+                        if let Some(&prev) = prev {
+                            diff.removed.push((key.to_string(), prev.clone()))
+                        }
+                    }
+                    (Some(StatementStatus::Missing), StatementStatus::Missing) => {}
+                    (_, StatementStatus::Missing) => {
+                        diff.newly_missing.push((key.to_string(), obs.clone()))
+                    }
+                    (_, _) => {}
+                }
+            }
+        }
+
+        // statements that were available but are no longer expected at all
+        for (key, stmts) in previous.iter() {
+            let cur_dates: Vec<&NaiveDate> = match current.get(key) {
+                Some(v) => v.iter().map(|obs| obs.statement().date()).collect(),
+                None => vec![],
+            };
+
+            for obs in stmts {
+                if (obs.status() == StatementStatus::Available)
+                    && !cur_dates.contains(&obs.statement().date())
+                {
+                    diff.removed.push((key.to_string(), obs.clone()));
+                }
+            }
+        }
+
+        // order the changes by account, then chronologically
+        for changes in [
+            &mut diff.newly_available,
+            &mut diff.newly_missing,
+            &mut diff.removed,
+        ] {
+            changes.sort_by(|a, b| {
+                (a.0.as_str(), a.1.statement().date()).cmp(&(b.0.as_str(), b.1.statement().date()))
+            });
+        }
+
+        diff
+    }
+
+    /// Statements that are available now, but weren't before
+    pub fn newly_available(&self) -> &[StatementChange] {
+        &self.newly_available
+    }
+
+    /// Statements that are missing now, but weren't expected before
+    pub fn newly_missing(&self) -> &[StatementChange] {
+        &self.newly_missing
+    }
+
+    /// Statements that were available before, but aren't now.
+    /// These are given as they were previously observed.
+    pub fn removed(&self) -> &[StatementChange] {
+        &self.removed
+    }
+
+    /// Check if nothing changed between the two collections
+    pub fn is_empty(&self) -> bool {
+        self.newly_available.is_empty() && self.newly_missing.is_empty() && self.removed.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Statement;
+
+    fn observed(year: i32, month: u32, day: u32, status: StatementStatus) -> ObservedStatement {
+        let stmt = Statement::from(&NaiveDate::from_ymd_opt(year, month, day).unwrap());
+        ObservedStatement::new(&stmt, status)
+    }
+
+    fn collection(key: &str, stmts: Vec<ObservedStatement>) -> StatementCollection {
+        let mut sc = StatementCollection::new();
+        sc.insert(key, stmts);
+        sc
+    }
+
+    #[test]
+    fn identical_collections() {
+        let sc = collection(
+            "acct",
+            vec![
+                observed(2021, 1, 1, StatementStatus::Available),
+                observed(2021, 2, 1, StatementStatus::Missing),
+            ],
+        );
+
+        assert!(CollectionDiff::new(&sc, &sc).is_empty());
+    }
+
+    #[test]
+    fn newly_available_and_missing() {
+        let prev = collection("acct", vec![observed(2021, 1, 1, StatementStatus::Missing)]);
+        let cur = collection(
+            "acct",
+            vec![
+                observed(2021, 1, 1, StatementStatus::Available),
+                observed(2021, 2, 1, StatementStatus::Missing),
+            ],
+        );
+
+        let diff = CollectionDiff::new(&prev, &cur);
+
+        assert_eq!(
+            vec![(
+                "acct".to_string(),
+                observed(2021, 1, 1, StatementStatus::Available)
+            )],
+            diff.newly_available()
+        );
+        assert_eq!(
+            vec![(
+                "acct".to_string(),
+                observed(2021, 2, 1, StatementStatus::Missing)
+            )],
+            diff.newly_missing()
+        );
+        assert!(diff.removed().is_empty());
+    }
+
+    #[test]
+    fn removed_statements() {
+        let prev = collection(
+            "acct",
+            vec![
+                observed(2021, 1, 1, StatementStatus::Available),
+                observed(2021, 2, 1, StatementStatus::Available),
+            ],
+        );
+        let cur = collection("acct", vec![observed(2021, 2, 1, StatementStatus::Missing)]);
+
+        let diff = CollectionDiff::new(&prev, &cur);
+
+        assert_eq!(
+            vec![
+                (
+                    "acct".to_string(),
+                    observed(2021, 1, 1, StatementStatus::Available)
+                ),
+                (
+                    "acct".to_string(),
+                    observed(2021, 2, 1, StatementStatus::Available)
+                ),
+            ],
+            diff.removed()
+        );
+        assert!(diff.newly_missing().is_empty());
+        assert!(diff.newly_available().is_empty());
+    }
+}
@@ -0,0 +1,225 @@
+//! Count how many of the expected statements are available, and how long the missing ones
+//! have been missing, to report on how complete an account's records are.
+
+use super::{DateDiff, ObservedStatement, StatementStatus};
+use chrono::{Datelike, NaiveDate};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+/// How many statements are available, out of the statements that are expected.
+/// Ignored, unexpected, and pending statements aren't expected yet, so they aren't counted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Completeness {
+    available: usize,
+    expected: usize,
+}
+
+impl Completeness {
+    /// Count the available and expected statements
+    pub fn tally<'a>(stmts: impl IntoIterator<Item = &'a ObservedStatement>) -> Self {
+        let mut tally = Self::default();
+        for obs in stmts {
+            tally.add(obs.status());
+        }
+
+        tally
+    }
+
+    /// Count a statement with the given status
+    fn add(&mut self, status: StatementStatus) {
+        match status {
+            StatementStatus::Available => {
+                self.available += 1;
+                self.expected += 1;
+            }
+            StatementStatus::Missing | StatementStatus::Lost => self.expected += 1,
+            StatementStatus::Ignored | StatementStatus::Unexpected | StatementStatus::Pending => {}
+        }
+    }
+
+    /// How many of the expected statements are available
+    pub fn available(&self) -> usize {
+        self.available
+    }
+
+    /// How many statements are expected
+    pub fn expected(&self) -> usize {
+        self.expected
+    }
+
+    /// How many of the expected statements are missing
+    pub fn missing(&self) -> usize {
+        self.expected - self.available
+    }
+
+    /// The fraction of the expected statements that are available, from 0 to 1,
+    /// if any are expected
+    pub fn fraction(&self) -> Option<f64> {
+        match self.expected {
+            0 => None,
+            _ => Some(self.available as f64 / self.expected as f64),
+        }
+    }
+}
+
+/// Count the available and expected statements in each year, oldest year first.
+/// Years without any expected statements are left out.
+pub fn completeness_by_year<'a>(
+    stmts: impl IntoIterator<Item = &'a ObservedStatement>,
+) -> Vec<(i32, Completeness)> {
+    let mut years: BTreeMap<i32, Completeness> = BTreeMap::new();
+    for obs in stmts {
+        years
+            .entry(obs.statement().date().year())
+            .or_default()
+            .add(obs.status());
+    }
+
+    years
+        .into_iter()
+        .filter(|(_, tally)| tally.expected() > 0)
+        .collect()
+}
+
+/// How long ago a missing statement was due, in broad ranges
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MissingAge {
+    UnderOneMonth,
+    OneToThreeMonths,
+    ThreeToTwelveMonths,
+    OverOneYear,
+}
+
+impl MissingAge {
+    /// Every range, from the most recent to the oldest
+    pub const ALL: [Self; 4] = [
+        Self::UnderOneMonth,
+        Self::OneToThreeMonths,
+        Self::ThreeToTwelveMonths,
+        Self::OverOneYear,
+    ];
+
+    /// The range a statement due on `date` falls into, as of `today`
+    pub fn of(date: &NaiveDate, today: &NaiveDate) -> Self {
+        match DateDiff::between(date, today).months() {
+            m if m < 1 => Self::UnderOneMonth,
+            m if m < 3 => Self::OneToThreeMonths,
+            m if m < 12 => Self::ThreeToTwelveMonths,
+            _ => Self::OverOneYear,
+        }
+    }
+}
+
+/// Ranges are written like "1-3 months"
+impl Display for MissingAge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let range = match self {
+            Self::UnderOneMonth => "< 1 month",
+            Self::OneToThreeMonths => "1-3 months",
+            Self::ThreeToTwelveMonths => "3-12 months",
+            Self::OverOneYear => "> 1 year",
+        };
+
+        write!(f, "{}", range)
+    }
+}
+
+/// Count the missing statements in each range of how long ago they were due, as of `today`.
+/// Every range is included, from the most recent to the oldest, even if it has no statements.
+pub fn missing_by_age<'a>(
+    stmts: impl IntoIterator<Item = &'a ObservedStatement>,
+    today: &NaiveDate,
+) -> Vec<(MissingAge, usize)> {
+    let mut counts: BTreeMap<MissingAge, usize> =
+        MissingAge::ALL.iter().map(|age| (*age, 0)).collect();
+    for obs in stmts {
+        if obs.status().is_missing() {
+            *counts
+                .entry(MissingAge::of(obs.statement().date(), today))
+                .or_default() += 1;
+        }
+    }
+
+    counts.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn observed(d: NaiveDate, status: StatementStatus) -> ObservedStatement {
+        ObservedStatement::new(&crate::Statement::from(&d), status)
+    }
+
+    #[test]
+    fn tally_expected_statements() {
+        let stmts = vec![
+            observed(date(2021, 1, 1), StatementStatus::Available),
+            observed(date(2021, 2, 1), StatementStatus::Missing),
+            observed(date(2021, 3, 1), StatementStatus::Ignored),
+            observed(date(2021, 4, 1), StatementStatus::Available),
+            observed(date(2021, 5, 1), StatementStatus::Pending),
+        ];
+        let tally = Completeness::tally(&stmts);
+
+        assert_eq!(2, tally.available());
+        assert_eq!(3, tally.expected());
+        assert_eq!(1, tally.missing());
+        assert_eq!(Some(2.0 / 3.0), tally.fraction());
+        assert_eq!(None, Completeness::tally(&[]).fraction());
+    }
+
+    #[test]
+    fn by_year() {
+        let stmts = vec![
+            observed(date(2020, 12, 1), StatementStatus::Missing),
+            observed(date(2021, 1, 1), StatementStatus::Ignored),
+            observed(date(2022, 1, 1), StatementStatus::Available),
+            observed(date(2022, 2, 1), StatementStatus::Missing),
+            observed(date(2022, 3, 1), StatementStatus::Available),
+        ];
+
+        let years: Vec<(i32, usize, usize)> = completeness_by_year(&stmts)
+            .into_iter()
+            .map(|(year, tally)| (year, tally.available(), tally.expected()))
+            .collect();
+
+        // nothing was expected in 2021
+        assert_eq!(vec![(2020, 0, 1), (2022, 2, 3)], years);
+    }
+
+    #[test]
+    fn ages_of_missing_statements() {
+        let today = date(2023, 6, 15);
+        let stmts = vec![
+            observed(date(2023, 6, 1), StatementStatus::Missing),
+            observed(date(2023, 5, 15), StatementStatus::Missing),
+            observed(date(2023, 3, 16), StatementStatus::Missing),
+            observed(date(2023, 3, 15), StatementStatus::Missing),
+            observed(date(2022, 6, 15), StatementStatus::Missing),
+            observed(date(2020, 1, 1), StatementStatus::Missing),
+            observed(date(2020, 2, 1), StatementStatus::Available),
+        ];
+
+        assert_eq!(
+            vec![
+                (MissingAge::UnderOneMonth, 1),
+                (MissingAge::OneToThreeMonths, 2),
+                (MissingAge::ThreeToTwelveMonths, 1),
+                (MissingAge::OverOneYear, 2),
+            ],
+            missing_by_age(&stmts, &today)
+        );
+        assert_eq!(
+            vec![0, 0, 0, 0],
+            missing_by_age(&[], &today)
+                .into_iter()
+                .map(|(_, n)| n)
+                .collect::<Vec<usize>>()
+        );
+    }
+}
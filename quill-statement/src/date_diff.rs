@@ -0,0 +1,172 @@
+//! The time between two dates, in the units people talk about it in.
+
+use chrono::{Datelike, NaiveDate};
+use std::fmt::Display;
+
+/// A unit of time that a difference between dates can be counted in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TimeUnit {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl TimeUnit {
+    /// Name the unit, for `n` of them
+    fn name(&self, n: u64) -> &'static str {
+        match (self, n) {
+            (Self::Day, 1) => "day",
+            (Self::Day, _) => "days",
+            (Self::Week, 1) => "week",
+            (Self::Week, _) => "weeks",
+            (Self::Month, 1) => "month",
+            (Self::Month, _) => "months",
+            (Self::Year, 1) => "year",
+            (Self::Year, _) => "years",
+        }
+    }
+}
+
+/// The time from one date to another, which is negative if the other date is earlier
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DateDiff {
+    from: NaiveDate,
+    to: NaiveDate,
+}
+
+impl DateDiff {
+    /// The time from `from` until `to`
+    pub fn between(from: &NaiveDate, to: &NaiveDate) -> Self {
+        Self {
+            from: *from,
+            to: *to,
+        }
+    }
+
+    /// The number of days from one date to the other
+    pub fn days(&self) -> i64 {
+        (self.to - self.from).num_days()
+    }
+
+    /// The number of whole calendar months from one date to the other
+    pub fn months(&self) -> i64 {
+        let (earlier, later) = match self.from <= self.to {
+            true => (self.from, self.to),
+            false => (self.to, self.from),
+        };
+        let mut months = i64::from(later.year() - earlier.year()) * 12 + i64::from(later.month())
+            - i64::from(earlier.month());
+        // a month isn't over until its day of the month comes around again
+        if later.day() < earlier.day() {
+            months -= 1;
+        }
+
+        match self.from <= self.to {
+            true => months,
+            false => -months,
+        }
+    }
+
+    /// Whether the other date is before the first one
+    pub fn is_past(&self) -> bool {
+        self.to < self.from
+    }
+
+    /// The time between the dates in the largest unit that at least one whole one fits in,
+    /// rounded down, like `(3, TimeUnit::Week)` for 23 days
+    pub fn largest_unit(&self) -> (u64, TimeUnit) {
+        let days = self.days().unsigned_abs();
+        let months = self.months().unsigned_abs();
+
+        if months >= 12 {
+            (months / 12, TimeUnit::Year)
+        } else if months >= 1 {
+            (months, TimeUnit::Month)
+        } else if days >= 7 {
+            (days / 7, TimeUnit::Week)
+        } else {
+            (days, TimeUnit::Day)
+        }
+    }
+}
+
+/// Differences are written like "3 weeks ago", "today", or "in 4 days"
+impl Display for DateDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.days(), self.largest_unit()) {
+            (0, _) => write!(f, "today"),
+            (-1, _) => write!(f, "yesterday"),
+            (1, _) => write!(f, "tomorrow"),
+            (days, (n, unit)) if days < 0 => write!(f, "{} {} ago", n, unit.name(n)),
+            (_, (n, unit)) => write!(f, "in {} {}", n, unit.name(n)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn describe(from: NaiveDate, to: NaiveDate) -> String {
+        DateDiff::between(&from, &to).to_string()
+    }
+
+    #[test]
+    fn nearby_days() {
+        let today = date(2023, 3, 15);
+
+        assert_eq!("today", describe(today, today));
+        assert_eq!("yesterday", describe(today, date(2023, 3, 14)));
+        assert_eq!("tomorrow", describe(today, date(2023, 3, 16)));
+        assert_eq!("6 days ago", describe(today, date(2023, 3, 9)));
+        assert_eq!("in 4 days", describe(today, date(2023, 3, 19)));
+    }
+
+    #[test]
+    fn weeks_are_rounded_down() {
+        let today = date(2023, 3, 15);
+
+        assert_eq!("1 week ago", describe(today, date(2023, 3, 8)));
+        assert_eq!("1 week ago", describe(today, date(2023, 3, 2)));
+        assert_eq!("in 3 weeks", describe(today, date(2023, 4, 7)));
+    }
+
+    #[test]
+    fn months_follow_the_calendar() {
+        let today = date(2023, 3, 15);
+
+        assert_eq!(1, DateDiff::between(&today, &date(2023, 4, 15)).months());
+        assert_eq!(0, DateDiff::between(&today, &date(2023, 4, 14)).months());
+        assert_eq!(-2, DateDiff::between(&today, &date(2023, 1, 1)).months());
+        assert_eq!("in 1 month", describe(today, date(2023, 4, 15)));
+        // February never reaches the 31st, so a month hasn't passed since January 31st
+        assert_eq!(
+            "4 weeks ago",
+            describe(date(2023, 2, 28), date(2023, 1, 31))
+        );
+        assert_eq!("11 months ago", describe(today, date(2022, 3, 16)));
+    }
+
+    #[test]
+    fn years() {
+        let today = date(2023, 3, 15);
+
+        assert_eq!("1 year ago", describe(today, date(2022, 3, 15)));
+        assert_eq!("2 years ago", describe(today, date(2020, 12, 1)));
+        assert_eq!("in 5 years", describe(today, date(2028, 6, 1)));
+    }
+
+    #[test]
+    fn past_dates() {
+        let today = date(2023, 3, 15);
+
+        assert!(DateDiff::between(&today, &date(2023, 3, 14)).is_past());
+        assert!(!DateDiff::between(&today, &today).is_past());
+        assert_eq!(-1, DateDiff::between(&today, &date(2023, 3, 14)).days());
+    }
+}
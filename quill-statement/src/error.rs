@@ -13,6 +13,16 @@ pub enum IgnoreFileError {
     InvalidIgnorefile(PathBuf),
     #[error("Ignorefile string could not be parsed:\n{0}.")]
     InvalidIgnorefileString(String),
+    #[error("Ignorefile `{0}` could not be written.")]
+    Unwritable(PathBuf),
+}
+
+#[derive(Debug, Error)]
+pub enum RenameError {
+    #[error("Cannot rename `{0}` to `{1}`, because `{1}` already exists.")]
+    TargetExists(PathBuf, PathBuf),
+    #[error("Error renaming `{0}` to `{1}`.")]
+    Io(PathBuf, PathBuf, #[source] std::io::Error),
 }
 
 #[derive(Debug, Error, PartialEq)]
@@ -0,0 +1,142 @@
+//! Runs of consecutive missing statements, so they can be summarized instead of listed.
+
+use super::{ObservedStatement, StatementStatus};
+use chrono::{Datelike, NaiveDate};
+use std::fmt::Display;
+
+/// A run of consecutive missing statements
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Gap {
+    start: NaiveDate,
+    end: NaiveDate,
+    count: usize,
+}
+
+impl Gap {
+    /// The date of the first missing statement
+    pub fn start(&self) -> &NaiveDate {
+        &self.start
+    }
+
+    /// The date of the last missing statement
+    pub fn end(&self) -> &NaiveDate {
+        &self.end
+    }
+
+    /// How many statements are missing
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+/// Gaps are written with the months they cover, like "Mar–Aug 2020: 6 missing"
+impl Display for Gap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let start = self.start.format("%b %Y").to_string();
+        let end = self.end.format("%b %Y").to_string();
+
+        if start == end {
+            write!(f, "{}", start)?;
+        } else if self.start.year() == self.end.year() {
+            write!(f, "{}\u{2013}{}", self.start.format("%b"), end)?;
+        } else {
+            write!(f, "{}\u{2013}{}", start, end)?;
+        }
+
+        write!(f, ": {} missing", self.count)
+    }
+}
+
+/// Split statements into runs of consecutive missing statements, oldest first.
+/// Only an available statement ends a run, so ignored and unexpected statements are skipped.
+pub(crate) fn missing_runs<'a>(stmts: &[&'a ObservedStatement]) -> Vec<Vec<&'a ObservedStatement>> {
+    let mut stmts: Vec<&ObservedStatement> = stmts
+        .iter()
+        .copied()
+        .filter(|obs| {
+            matches!(
+                obs.status(),
+                StatementStatus::Available | StatementStatus::Missing | StatementStatus::Lost
+            )
+        })
+        .collect();
+    stmts.sort_by_key(|obs| obs.statement().date());
+
+    stmts
+        .split(|obs| obs.status() == StatementStatus::Available)
+        .filter(|run| !run.is_empty())
+        .map(<[&ObservedStatement]>::to_vec)
+        .collect()
+}
+
+/// Find every run of consecutive missing statements, oldest first
+pub fn find_gaps(stmts: &[ObservedStatement]) -> Vec<Gap> {
+    let stmts: Vec<&ObservedStatement> = stmts.iter().collect();
+
+    missing_runs(&stmts)
+        .into_iter()
+        .map(|run| Gap {
+            start: *run[0].statement().date(),
+            end: *run[run.len() - 1].statement().date(),
+            count: run.len(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Statement;
+
+    fn obs(year: i32, month: u32, status: StatementStatus) -> ObservedStatement {
+        let date = NaiveDate::from_ymd_opt(year, month, 1).unwrap();
+        ObservedStatement::new(&Statement::from(&date), status)
+    }
+
+    #[test]
+    fn gaps_are_split_by_available_statements() {
+        use StatementStatus::*;
+        let stmts = vec![
+            obs(2020, 1, Available),
+            obs(2020, 2, Missing),
+            obs(2020, 3, Ignored),
+            obs(2020, 4, Missing),
+            obs(2020, 5, Available),
+            obs(2020, 6, Missing),
+        ];
+
+        let observed: Vec<(u32, u32, usize)> = find_gaps(&stmts)
+            .iter()
+            .map(|g| (g.start().month(), g.end().month(), g.count()))
+            .collect();
+
+        assert_eq!(vec![(2, 4, 2), (6, 6, 1)], observed);
+    }
+
+    #[test]
+    fn no_gaps_without_missing_statements() {
+        let stmts = vec![obs(2020, 1, StatementStatus::Available)];
+
+        assert!(find_gaps(&stmts).is_empty());
+    }
+
+    #[test]
+    fn display() {
+        use StatementStatus::Missing;
+
+        let same_year: Vec<ObservedStatement> = (3..=8).map(|m| obs(2020, m, Missing)).collect();
+        assert_eq!(
+            "Mar\u{2013}Aug 2020: 6 missing",
+            find_gaps(&same_year)[0].to_string()
+        );
+
+        let across_years = vec![obs(2019, 11, Missing), obs(2020, 2, Missing)];
+        assert_eq!(
+            "Nov 2019\u{2013}Feb 2020: 2 missing",
+            find_gaps(&across_years)[0].to_string()
+        );
+
+        let one_month = vec![obs(2020, 3, Missing)];
+        assert_eq!("Mar 2020: 1 missing", find_gaps(&one_month)[0].to_string());
+    }
+}
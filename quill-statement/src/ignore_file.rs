@@ -1,10 +1,12 @@
 //! Read and parse the ignore files written by the user.
 
 use crate::IgnoreFileError;
-use quill_utils::parse_toml_file;
+use chrono::NaiveDate;
+use quill_utils::{parse_toml_file, EditError, TomlEditor};
 use serde::Deserialize;
 use std::path::{Path, PathBuf};
-use toml::value::Datetime;
+use std::str::FromStr;
+use toml::value::{Datetime, Table, Value};
 
 const IGNOREFILE: &str = ".quillignore.toml";
 
@@ -13,20 +15,32 @@ const IGNOREFILE: &str = ".quillignore.toml";
 /// In practice, it should be immediately transformed into an `IgnoredStatements`.
 #[derive(Debug, Deserialize, PartialEq)]
 pub(crate) struct IgnoreFile {
-    dates: Option<Vec<Datetime>>,
+    /// Ignored dates, written as either TOML dates, strings, or
+    /// `{ date = ..., reason = "..." }` tables
+    dates: Option<Vec<Value>>,
+    /// Inclusive `[start, end]` date ranges, written as either TOML dates or strings
+    ranges: Option<Vec<[Value; 2]>>,
+    /// Glob patterns for the names of files that are never statements
+    files: Option<Vec<String>>,
 }
 
 #[allow(dead_code)]
 impl IgnoreFile {
     /// Create a new empty IgnoreFile that doesn't have the dates anywhere
     pub fn missing() -> Self {
-        IgnoreFile { dates: None }
+        IgnoreFile {
+            dates: None,
+            ranges: None,
+            files: None,
+        }
     }
 
     /// Create a new IgnoreFile from an empty array
     pub fn empty() -> Self {
         IgnoreFile {
             dates: Some(vec![]),
+            ranges: None,
+            files: None,
         }
     }
 
@@ -37,14 +51,194 @@ impl IgnoreFile {
         IgnoreFile::try_from(path).unwrap_or_else(|_| Self::empty())
     }
 
-    pub fn dates(&self) -> &Option<Vec<Datetime>> {
+    pub fn dates(&self) -> &Option<Vec<Value>> {
         &self.dates
     }
+
+    pub fn ranges(&self) -> &Option<Vec<[Value; 2]>> {
+        &self.ranges
+    }
+
+    pub fn files(&self) -> &Option<Vec<String>> {
+        &self.files
+    }
+
+    /// Add date ranges to the IgnoreFile
+    pub fn with_ranges(mut self, ranges: Vec<[Value; 2]>) -> Self {
+        self.ranges = Some(ranges);
+        self
+    }
+
+    /// Add file name patterns to the IgnoreFile
+    pub fn with_files(mut self, files: Vec<String>) -> Self {
+        self.files = Some(files);
+        self
+    }
+
+    /// Add dates to the IgnoreFile, along with why they're ignored,
+    /// skipping any that are already listed
+    pub fn add_dates(&mut self, new: &[NaiveDate], reason: Option<&str>) {
+        let dates = self.dates.get_or_insert_with(Vec::new);
+        for d in new {
+            if dates
+                .iter()
+                .any(|entry| entry_date(entry).as_ref() == Some(d))
+            {
+                continue;
+            }
+
+            let date = Value::Datetime(to_datetime(d));
+            dates.push(match reason {
+                Some(r) => {
+                    let mut entry = Table::new();
+                    entry.insert("date".to_string(), date);
+                    entry.insert("reason".to_string(), Value::String(r.to_string()));
+                    Value::Table(entry)
+                }
+                None => date,
+            });
+        }
+    }
+
+    /// Add an inclusive date range to the IgnoreFile
+    pub fn add_range(&mut self, start: &NaiveDate, end: &NaiveDate) {
+        self.ranges.get_or_insert_with(Vec::new).push([
+            Value::Datetime(to_datetime(start)),
+            Value::Datetime(to_datetime(end)),
+        ]);
+    }
+
+    /// Save the IgnoreFile to `path`, adding whatever isn't in the file there yet.
+    /// Everything already in the file is kept as it was, along with its comments,
+    /// and new dates and ranges are written one on each line.
+    /// The file is written next to `path` first and then moved into place,
+    /// so `path` is never left partially written.
+    pub fn save(&self, path: &Path) -> Result<(), IgnoreFileError> {
+        let unwritable = || IgnoreFileError::Unwritable(path.to_path_buf());
+        let mut editor = match TomlEditor::open_or_new(path) {
+            Ok(editor) => editor,
+            Err(EditError::Invalid(..)) => {
+                return Err(IgnoreFileError::InvalidIgnorefile(path.to_path_buf()))
+            }
+            Err(_) => return Err(unwritable()),
+        };
+        let saved = IgnoreFile::try_from(editor.document().to_string().as_str())
+            .unwrap_or_else(|_| Self::missing());
+
+        let inline_values = |values: &Option<Vec<Value>>| -> Vec<String> {
+            values.iter().flatten().map(inline_value).collect()
+        };
+        let inline_ranges = |ranges: &Option<Vec<[Value; 2]>>| -> Vec<String> {
+            ranges
+                .iter()
+                .flatten()
+                .map(|range| inline_value(&Value::Array(range.to_vec())))
+                .collect()
+        };
+        let inline_files = |files: &Option<Vec<String>>| -> Vec<String> {
+            files
+                .iter()
+                .flatten()
+                .map(|f| Value::String(f.clone()).to_string())
+                .collect()
+        };
+
+        for (key, old, new) in [
+            (
+                "dates",
+                inline_values(&saved.dates),
+                inline_values(&self.dates),
+            ),
+            (
+                "ranges",
+                inline_ranges(&saved.ranges),
+                inline_ranges(&self.ranges),
+            ),
+            (
+                "files",
+                inline_files(&saved.files),
+                inline_files(&self.files),
+            ),
+        ] {
+            for entry in new.iter().filter(|entry| !old.contains(entry)) {
+                let value: toml_edit::Value = entry.parse().map_err(|_| unwritable())?;
+                editor
+                    .push_to_array(&[key], value)
+                    .map_err(|_| unwritable())?;
+            }
+        }
+
+        editor.save().map_err(|_| unwritable())
+    }
+}
+
+/// Format a value the way it is written inside a TOML array
+fn inline_value(v: &Value) -> String {
+    match v {
+        Value::Table(t) => {
+            let fields: Vec<String> = t
+                .iter()
+                .map(|(k, v)| format!("{} = {}", inline_key(k), inline_value(v)))
+                .collect();
+            format!("{{ {} }}", fields.join(", "))
+        }
+        Value::Array(a) => {
+            let values: Vec<String> = a.iter().map(inline_value).collect();
+            format!("[{}]", values.join(", "))
+        }
+        _ => v.to_string(),
+    }
+}
+
+/// Quote a table key, unless it can be written bare
+fn inline_key(k: &str) -> String {
+    let bare = !k.is_empty()
+        && k.chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    match bare {
+        true => k.to_string(),
+        false => Value::String(k.to_string()).to_string(),
+    }
+}
+
+/// Convert a TOML date or date string into a date
+pub(crate) fn value_to_date(v: &Value) -> Option<NaiveDate> {
+    match v {
+        Value::Datetime(d) => NaiveDate::from_str(&d.to_string()).ok(),
+        Value::String(s) => NaiveDate::from_str(s).ok(),
+        _ => None,
+    }
+}
+
+/// Return the date of an entry in the `dates` array of an ignore file
+pub(crate) fn entry_date(entry: &Value) -> Option<NaiveDate> {
+    match entry {
+        Value::Table(t) => t.get("date").and_then(value_to_date),
+        _ => value_to_date(entry),
+    }
+}
+
+/// Return why an entry in the `dates` array of an ignore file is ignored, if it says
+pub(crate) fn entry_reason(entry: &Value) -> Option<&str> {
+    match entry {
+        Value::Table(t) => t.get("reason").and_then(Value::as_str),
+        _ => None,
+    }
+}
+
+/// Convert a date into the TOML date it is written as
+fn to_datetime(date: &NaiveDate) -> Datetime {
+    // a formatted `NaiveDate` is always a valid TOML date
+    Datetime::from_str(&date.format("%Y-%m-%d").to_string()).unwrap()
 }
 
 impl From<Vec<Datetime>> for IgnoreFile {
     fn from(v: Vec<Datetime>) -> Self {
-        Self { dates: Some(v) }
+        Self {
+            dates: Some(v.into_iter().map(Value::Datetime).collect()),
+            ranges: None,
+            files: None,
+        }
     }
 }
 
@@ -84,10 +278,70 @@ pub fn ignorefile_path_from_dir(dir: &Path) -> PathBuf {
     dir.join(IGNOREFILE)
 }
 
+/// Add dates, along with why they're ignored, and inclusive date ranges to the
+/// ignore file in `dir`, creating the file if it doesn't exist yet.
+/// Dates that are already listed aren't added again.
+/// Returns the path of the ignore file.
+pub fn extend_ignorefile(
+    dir: &Path,
+    dates: &[NaiveDate],
+    reason: Option<&str>,
+    ranges: &[(NaiveDate, NaiveDate)],
+) -> Result<PathBuf, IgnoreFileError> {
+    let path = ignorefile_path_from_dir(dir);
+    let mut ignore = match IgnoreFile::try_from(path.as_path()) {
+        Ok(i) => i,
+        Err(IgnoreFileError::NotFound(_)) => IgnoreFile::missing(),
+        Err(e) => return Err(e),
+    };
+
+    ignore.add_dates(dates, reason);
+    for (start, end) in ranges {
+        ignore.add_range(start, end);
+    }
+    ignore.save(&path)?;
+
+    Ok(path)
+}
+
+/// Stop ignoring dates in the ignore file in `dir`, removing them from its `dates`.
+/// Dates that are also inside an ignored range are still ignored.
+/// Returns the path of the ignore file.
+pub fn remove_from_ignorefile(dir: &Path, dates: &[NaiveDate]) -> Result<PathBuf, IgnoreFileError> {
+    let path = ignorefile_path_from_dir(dir);
+    let mut editor = match TomlEditor::open(&path) {
+        Ok(editor) => editor,
+        Err(EditError::Unreadable(_, e)) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(IgnoreFileError::NotFound(path))
+        }
+        Err(_) => return Err(IgnoreFileError::InvalidIgnorefile(path)),
+    };
+    let saved = IgnoreFile::try_from(editor.document().to_string().as_str())?;
+
+    let indices: Vec<usize> = saved
+        .dates
+        .iter()
+        .flatten()
+        .enumerate()
+        .filter(|(_, entry)| entry_date(entry).map_or(false, |d| dates.contains(&d)))
+        .map(|(i, _)| i)
+        .collect();
+    if !indices.is_empty() {
+        let unwritable = || IgnoreFileError::Unwritable(path.clone());
+        editor
+            .remove_from_array(&["dates"], &indices)
+            .map_err(|_| unwritable())?;
+        editor.save().map_err(|_| unwritable())?;
+    }
+
+    Ok(path)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::str::FromStr;
+    use std::fs;
+    use tempfile::tempdir;
 
     #[test]
     fn it_works() {
@@ -97,7 +351,11 @@ mod tests {
     #[test]
     fn check_missing() {
         let observed = IgnoreFile::missing();
-        let expected = IgnoreFile { dates: None };
+        let expected = IgnoreFile {
+            dates: None,
+            ranges: None,
+            files: None,
+        };
 
         assert_eq!(expected, observed);
     }
@@ -142,6 +400,240 @@ mod tests {
         check_try_from_path(ignorefile, Ok(expected));
     }
 
+    #[test]
+    fn some_ranges() {
+        let ignorefile = Path::new("tests/some_ranges.toml");
+        let expected = IgnoreFile::from(vec![Datetime::from_str("2021-11-01").unwrap()])
+            .with_ranges(vec![
+                [
+                    Value::Datetime(Datetime::from_str("2019-01-01").unwrap()),
+                    Value::Datetime(Datetime::from_str("2019-12-31").unwrap()),
+                ],
+                [
+                    Value::String("2020-01-01".to_string()),
+                    Value::String("2020-06-30".to_string()),
+                ],
+            ]);
+
+        check_try_from_path(ignorefile, Ok(expected));
+    }
+
+    #[test]
+    fn some_files() {
+        let ignorefile = Path::new("tests/some_files.toml");
+        let expected = IgnoreFile::from(vec![Datetime::from_str("2021-11-01").unwrap()])
+            .with_files(vec![
+                "*promo*.pdf".to_string(),
+                "2020-12-15.pdf".to_string(),
+            ]);
+
+        check_try_from_path(ignorefile, Ok(expected));
+    }
+
+    #[test]
+    fn add_dates_skips_duplicates() {
+        let mut ignore = IgnoreFile::from(vec![Datetime::from_str("2021-11-01").unwrap()]);
+        ignore.add_dates(
+            &[
+                NaiveDate::from_ymd_opt(2021, 11, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2021, 12, 1).unwrap(),
+            ],
+            None,
+        );
+        let expected = IgnoreFile::from(vec![
+            Datetime::from_str("2021-11-01").unwrap(),
+            Datetime::from_str("2021-12-01").unwrap(),
+        ]);
+
+        assert_eq!(expected, ignore);
+    }
+
+    fn reason_entry(date: &str, reason: &str) -> Value {
+        let mut entry = Table::new();
+        entry.insert(
+            "date".to_string(),
+            Value::Datetime(Datetime::from_str(date).unwrap()),
+        );
+        entry.insert("reason".to_string(), Value::String(reason.to_string()));
+        Value::Table(entry)
+    }
+
+    #[test]
+    fn some_reasons() {
+        let ignorefile = Path::new("tests/reasons.toml");
+        let expected = IgnoreFile {
+            dates: Some(vec![
+                Value::Datetime(Datetime::from_str("2021-11-01").unwrap()),
+                reason_entry("2021-12-01", "account frozen"),
+            ]),
+            ranges: None,
+            files: None,
+        };
+
+        check_try_from_path(ignorefile, Ok(expected));
+    }
+
+    #[test]
+    fn add_dates_with_reason() {
+        let mut ignore = IgnoreFile::missing();
+        ignore.add_dates(
+            &[NaiveDate::from_ymd_opt(2021, 12, 1).unwrap()],
+            Some("account frozen"),
+        );
+        let expected = IgnoreFile {
+            dates: Some(vec![reason_entry("2021-12-01", "account frozen")]),
+            ranges: None,
+            files: None,
+        };
+
+        assert_eq!(expected, ignore);
+    }
+
+    #[test]
+    fn entry_date_and_reason() {
+        let entry = reason_entry("2021-12-01", "account frozen");
+
+        assert_eq!(NaiveDate::from_ymd_opt(2021, 12, 1), entry_date(&entry));
+        assert_eq!(Some("account frozen"), entry_reason(&entry));
+        assert_eq!(None, entry_reason(&Value::String("2021-12-01".to_string())));
+    }
+
+    #[test]
+    fn add_range() {
+        let mut ignore = IgnoreFile::missing();
+        ignore.add_range(
+            &NaiveDate::from_ymd_opt(2020, 1, 1).unwrap(),
+            &NaiveDate::from_ymd_opt(2020, 12, 31).unwrap(),
+        );
+        let expected = IgnoreFile {
+            dates: None,
+            ranges: Some(vec![[
+                Value::Datetime(Datetime::from_str("2020-01-01").unwrap()),
+                Value::Datetime(Datetime::from_str("2020-12-31").unwrap()),
+            ]]),
+            files: None,
+        };
+
+        assert_eq!(expected, ignore);
+    }
+
+    #[test]
+    fn save_round_trip() {
+        let dir = tempdir().unwrap();
+        let path = ignorefile_path_from_dir(dir.path());
+        let mut ignore = IgnoreFile::from(vec![Datetime::from_str("2021-11-01").unwrap()]);
+        ignore.add_dates(
+            &[NaiveDate::from_ymd_opt(2021, 12, 1).unwrap()],
+            Some("said \"no statement\" on the phone"),
+        );
+        ignore.add_range(
+            &NaiveDate::from_ymd_opt(2020, 1, 1).unwrap(),
+            &NaiveDate::from_ymd_opt(2020, 12, 31).unwrap(),
+        );
+        let ignore = ignore.with_files(vec!["*promo*.pdf".to_string()]);
+
+        ignore.save(&path).unwrap();
+        assert_eq!(Ok(ignore), IgnoreFile::try_from(path.as_path()));
+        assert!(!path.with_file_name(".quillignore.toml.tmp").exists());
+    }
+
+    #[test]
+    fn save_keeps_comments() {
+        let dir = tempdir().unwrap();
+        let path = ignorefile_path_from_dir(dir.path());
+        fs::write(
+            &path,
+            "# statements I never received\n\ndates = [\n    2021-11-01, # lost in the mail\n]\n",
+        )
+        .unwrap();
+
+        let mut ignore = IgnoreFile::try_from(path.as_path()).unwrap();
+        ignore.add_dates(&[NaiveDate::from_ymd_opt(2021, 12, 1).unwrap()], None);
+        ignore.save(&path).unwrap();
+
+        let saved = fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            "# statements I never received\n\ndates = [\n    2021-11-01, # lost in the mail\n    2021-12-01,\n]\n",
+            saved
+        );
+        assert_eq!(Ok(ignore), IgnoreFile::try_from(saved.as_str()));
+    }
+
+    #[test]
+    fn save_to_missing_directory() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("does-not-exist").join(IGNOREFILE);
+
+        assert_eq!(
+            Err(IgnoreFileError::Unwritable(path.clone())),
+            IgnoreFile::empty().save(&path)
+        );
+    }
+
+    #[test]
+    fn extend_keeps_existing() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            ignorefile_path_from_dir(dir.path()),
+            "dates = [2021-11-01]\n",
+        )
+        .unwrap();
+
+        let path = extend_ignorefile(
+            dir.path(),
+            &[
+                NaiveDate::from_ymd_opt(2021, 11, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2021, 12, 1).unwrap(),
+            ],
+            None,
+            &[],
+        )
+        .unwrap();
+        let expected = IgnoreFile::from(vec![
+            Datetime::from_str("2021-11-01").unwrap(),
+            Datetime::from_str("2021-12-01").unwrap(),
+        ]);
+
+        assert_eq!(Ok(expected), IgnoreFile::try_from(path.as_path()));
+    }
+
+    #[test]
+    fn remove_dates() {
+        let dir = tempdir().unwrap();
+        let path = ignorefile_path_from_dir(dir.path());
+        fs::write(
+            &path,
+            "# closed for renovations\ndates = [\n    2021-10-01,\n    { date = 2021-11-01, reason = \"lost\" },\n    2021-12-01,\n]\nranges = [[2022-01-01, 2022-03-01]]\n",
+        )
+        .unwrap();
+
+        remove_from_ignorefile(
+            dir.path(),
+            &[
+                NaiveDate::from_ymd_opt(2021, 11, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2022, 2, 1).unwrap(),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            "# closed for renovations\ndates = [\n    2021-10-01,\n    2021-12-01,\n]\nranges = [[2022-01-01, 2022-03-01]]\n",
+            fs::read_to_string(&path).unwrap()
+        );
+    }
+
+    #[test]
+    fn remove_dates_without_ignorefile() {
+        let dir = tempdir().unwrap();
+
+        assert_eq!(
+            Err(IgnoreFileError::NotFound(ignorefile_path_from_dir(
+                dir.path()
+            ))),
+            remove_from_ignorefile(dir.path(), &[NaiveDate::from_ymd_opt(2021, 11, 1).unwrap()])
+        );
+    }
+
     #[test]
     #[should_panic]
     fn error_dates() {
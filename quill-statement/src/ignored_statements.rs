@@ -1,53 +1,182 @@
 //! A collection of ignored statements.
 
-use crate::ignore_file::{ignorefile_path_from_dir, IgnoreFile};
+use crate::ignore_file::{
+    entry_date, entry_reason, ignorefile_path_from_dir, value_to_date, IgnoreFile,
+};
 use chrono::NaiveDate;
+use glob::Pattern;
 use serde::Deserialize;
+use std::collections::BTreeMap;
 use std::path::Path;
 use std::slice::Iter;
-use std::str::FromStr;
+use tracing::warn;
 
 /// Control which account statements are ignored.
-/// Essentially a sorted `Vec<NaiveDate>`.
+/// Essentially a sorted `Vec<NaiveDate>`, plus any inclusive date ranges,
+/// the reasons some dates are ignored, and patterns for files that are never statements.
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 pub struct IgnoredStatements {
     dates: Vec<NaiveDate>,
+    #[serde(default)]
+    ranges: Vec<(NaiveDate, NaiveDate)>,
+    #[serde(default)]
+    reasons: BTreeMap<NaiveDate, String>,
+    #[serde(skip)]
+    files: Vec<Pattern>,
+    #[serde(skip)]
+    invalid_ranges: Vec<String>,
 }
 
 impl IgnoredStatements {
     /// Construct an empty `IgnoredStatements` object.
     pub fn empty() -> Self {
-        Self { dates: vec![] }
+        Self {
+            dates: vec![],
+            ranges: vec![],
+            reasons: BTreeMap::new(),
+            files: vec![],
+            invalid_ranges: vec![],
+        }
+    }
+
+    /// Construct an `IgnoredStatements` object from individual dates and inclusive date ranges.
+    pub fn new(mut dates: Vec<NaiveDate>, ranges: Vec<(NaiveDate, NaiveDate)>) -> Self {
+        dates.sort();
+
+        // make sure each range goes forward in time, regardless of how it was written
+        let mut ranges: Vec<(NaiveDate, NaiveDate)> = ranges
+            .into_iter()
+            .map(|(a, b)| if a <= b { (a, b) } else { (b, a) })
+            .collect();
+        ranges.sort();
+
+        Self {
+            dates,
+            ranges,
+            reasons: BTreeMap::new(),
+            files: vec![],
+            invalid_ranges: vec![],
+        }
+    }
+
+    /// Record why some of the dates are ignored
+    pub fn with_reasons(mut self, reasons: BTreeMap<NaiveDate, String>) -> Self {
+        self.reasons = reasons;
+        self
+    }
+
+    /// Return why a date is ignored, if the ignore file says
+    pub fn reason(&self, date: &NaiveDate) -> Option<&str> {
+        self.reasons.get(date).map(String::as_str)
+    }
+
+    /// Ignore files whose names match any of these glob patterns.
+    /// Patterns that aren't valid globs are skipped.
+    pub fn with_files(mut self, patterns: &[String]) -> Self {
+        self.files = patterns
+            .iter()
+            .filter_map(|p| match Pattern::new(p) {
+                Ok(pattern) => Some(pattern),
+                Err(e) => {
+                    warn!(pattern = %p, "skipping invalid ignore pattern: {}", e);
+                    None
+                }
+            })
+            .collect();
+        self
+    }
+
+    /// Return the ranges in the ignore file that were skipped because either end isn't a
+    /// valid date, as they were written
+    pub fn invalid_ranges(&self) -> &[String] {
+        &self.invalid_ranges
+    }
+
+    /// Return the patterns for files that are never statements, as they were written
+    pub fn file_patterns(&self) -> Vec<&str> {
+        self.files.iter().map(Pattern::as_str).collect()
+    }
+
+    /// Check if a file's name matches any of the ignored file patterns
+    pub fn ignores_file(&self, path: &Path) -> bool {
+        match path.file_name().and_then(|name| name.to_str()) {
+            Some(name) => self.files.iter().any(|p| p.matches(name)),
+            None => false,
+        }
     }
 
     /// Return an iterator over the statements
-    pub fn iter(&self) -> Iter<NaiveDate> {
+    pub fn iter(&self) -> Iter<'_, NaiveDate> {
         self.dates.iter()
     }
+
+    /// Return an iterator over the ignored date ranges
+    pub fn ranges(&self) -> Iter<'_, (NaiveDate, NaiveDate)> {
+        self.ranges.iter()
+    }
+
+    /// Check if a date falls within any of the ignored date ranges
+    pub fn in_ranges(&self, date: &NaiveDate) -> bool {
+        self.ranges
+            .iter()
+            .any(|(start, end)| (start <= date) && (date <= end))
+    }
+
+    /// Check if a date is ignored, either individually or as part of a range
+    pub fn contains(&self, date: &NaiveDate) -> bool {
+        self.dates.binary_search(date).is_ok() || self.in_ranges(date)
+    }
 }
 
 impl From<Vec<NaiveDate>> for IgnoredStatements {
     fn from(v: Vec<NaiveDate>) -> Self {
-        Self { dates: v }
+        Self {
+            dates: v,
+            ranges: vec![],
+            reasons: BTreeMap::new(),
+            files: vec![],
+            invalid_ranges: vec![],
+        }
     }
 }
 
 impl From<&IgnoreFile> for IgnoredStatements {
     fn from(ignore: &IgnoreFile) -> Self {
-        match ignore.dates() {
-            Some(v) => {
-                let mut dates: Vec<NaiveDate> = v
-                    .iter()
-                    .filter_map(|d| NaiveDate::from_str(&d.to_string()).ok())
-                    .collect();
-
-                // ensure the list is sorted so iteration over the Vec is the same as moving forward in time
-                dates.sort();
+        // skip any dates that aren't valid
+        let dates: Vec<NaiveDate> = match ignore.dates() {
+            Some(v) => v.iter().filter_map(entry_date).collect(),
+            None => vec![],
+        };
+        let reasons: BTreeMap<NaiveDate, String> = match ignore.dates() {
+            Some(v) => v
+                .iter()
+                .filter_map(|entry| Some((entry_date(entry)?, entry_reason(entry)?.to_string())))
+                .collect(),
+            None => BTreeMap::new(),
+        };
 
-                Self::from(dates)
+        // skip any ranges where either end isn't a valid date, but remember them so
+        // that they can be reported
+        let mut ranges: Vec<(NaiveDate, NaiveDate)> = vec![];
+        let mut invalid_ranges: Vec<String> = vec![];
+        for [start, end] in ignore.ranges().as_deref().unwrap_or_default() {
+            match (value_to_date(start), value_to_date(end)) {
+                (Some(start), Some(end)) => ranges.push((start, end)),
+                _ => {
+                    let range = format!("[{}, {}]", start, end);
+                    warn!(range = %range, "skipping ignored range that isn't between two dates");
+                    invalid_ranges.push(range);
+                }
             }
-            None => Self::empty(),
         }
+
+        // ensure the lists are sorted so iteration is the same as moving forward in time
+        let mut ignored = Self::new(dates, ranges)
+            .with_reasons(reasons)
+            .with_files(ignore.files().as_deref().unwrap_or_default());
+        ignored.invalid_ranges = invalid_ranges;
+
+        ignored
     }
 }
 
@@ -75,7 +204,7 @@ mod tests {
     use std::str::FromStr;
 
     use super::*;
-    use toml::value::Datetime;
+    use toml::value::{Datetime, Value};
 
     #[test]
     fn it_works() {
@@ -102,6 +231,10 @@ mod tests {
         let input = single_stmt.clone();
         let expected = IgnoredStatements {
             dates: single_stmt.clone(),
+            ranges: vec![],
+            reasons: BTreeMap::new(),
+            files: vec![],
+            invalid_ranges: vec![],
         };
 
         check_from_vec_naivedate(input, expected);
@@ -116,6 +249,10 @@ mod tests {
         let input = double_stmt.clone();
         let expected = IgnoredStatements {
             dates: double_stmt.clone(),
+            ranges: vec![],
+            reasons: BTreeMap::new(),
+            files: vec![],
+            invalid_ranges: vec![],
         };
 
         check_from_vec_naivedate(input, expected);
@@ -189,8 +326,101 @@ mod tests {
                 NaiveDate::from_ymd_opt(2021, 5, 25).unwrap(),
                 NaiveDate::from_ymd_opt(2021, 10, 22).unwrap(),
             ],
+            ranges: vec![],
+            reasons: BTreeMap::new(),
+            files: vec![],
+            invalid_ranges: vec![],
         };
 
         check_new(&ignore, expected);
     }
+
+    #[test]
+    fn new_ranges() {
+        let ignore = IgnoreFile::empty().with_ranges(vec![
+            [
+                Value::String("2020-12-31".to_string()),
+                Value::String("2020-01-01".to_string()),
+            ],
+            [
+                Value::Datetime(Datetime::from_str("2019-01-01").unwrap()),
+                Value::Datetime(Datetime::from_str("2019-06-30").unwrap()),
+            ],
+            [
+                Value::String("not a date".to_string()),
+                Value::String("2019-06-30".to_string()),
+            ],
+        ]);
+
+        let mut expected = IgnoredStatements::new(
+            vec![],
+            vec![
+                (
+                    NaiveDate::from_ymd_opt(2019, 1, 1).unwrap(),
+                    NaiveDate::from_ymd_opt(2019, 6, 30).unwrap(),
+                ),
+                (
+                    NaiveDate::from_ymd_opt(2020, 1, 1).unwrap(),
+                    NaiveDate::from_ymd_opt(2020, 12, 31).unwrap(),
+                ),
+            ],
+        );
+        // the range that isn't between two dates is skipped, but kept to be reported
+        expected.invalid_ranges = vec![r#"["not a date", "2019-06-30"]"#.to_string()];
+
+        check_new(&ignore, expected);
+    }
+
+    #[test]
+    fn reasons_from_path() {
+        let ignored = IgnoredStatements::from(Path::new("tests/reasons.toml"));
+
+        assert!(ignored.contains(&NaiveDate::from_ymd_opt(2021, 11, 1).unwrap()));
+        assert!(ignored.contains(&NaiveDate::from_ymd_opt(2021, 12, 1).unwrap()));
+        assert_eq!(
+            None,
+            ignored.reason(&NaiveDate::from_ymd_opt(2021, 11, 1).unwrap())
+        );
+        assert_eq!(
+            Some("account frozen"),
+            ignored.reason(&NaiveDate::from_ymd_opt(2021, 12, 1).unwrap())
+        );
+    }
+
+    #[test]
+    fn files_from_path() {
+        let ignored = IgnoredStatements::from(Path::new("tests/some_files.toml"));
+
+        assert!(ignored.ignores_file(Path::new("2021-spring-promo-offer.pdf")));
+        assert!(ignored.ignores_file(Path::new("statements/2020-12-15.pdf")));
+        assert!(!ignored.ignores_file(Path::new("2020-12-15.csv")));
+        assert!(!ignored.ignores_file(Path::new("2021-01-15.pdf")));
+    }
+
+    #[test]
+    fn invalid_file_patterns_skipped() {
+        let ignored =
+            IgnoredStatements::empty().with_files(&["[promo".to_string(), "*.tmp".to_string()]);
+
+        assert!(ignored.ignores_file(Path::new("download.tmp")));
+        assert!(!ignored.ignores_file(Path::new("[promo")));
+    }
+
+    #[test]
+    fn contains_dates_and_ranges() {
+        let ignored = IgnoredStatements::new(
+            vec![NaiveDate::from_ymd_opt(2021, 5, 1).unwrap()],
+            vec![(
+                NaiveDate::from_ymd_opt(2020, 1, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2020, 12, 31).unwrap(),
+            )],
+        );
+
+        assert!(ignored.contains(&NaiveDate::from_ymd_opt(2021, 5, 1).unwrap()));
+        assert!(ignored.contains(&NaiveDate::from_ymd_opt(2020, 1, 1).unwrap()));
+        assert!(ignored.contains(&NaiveDate::from_ymd_opt(2020, 7, 15).unwrap()));
+        assert!(ignored.contains(&NaiveDate::from_ymd_opt(2020, 12, 31).unwrap()));
+        assert!(!ignored.contains(&NaiveDate::from_ymd_opt(2021, 1, 1).unwrap()));
+        assert!(!ignored.contains(&NaiveDate::from_ymd_opt(2019, 12, 31).unwrap()));
+    }
 }
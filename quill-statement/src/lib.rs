@@ -1,21 +1,40 @@
 //! Parse, read, and keep track of account statements.
 
+mod account_key;
+mod collection_diff;
+mod completeness;
+mod date_diff;
 mod error;
+mod gap;
 mod ignore_file;
 mod ignored_statements;
 mod observed_statement;
 mod ops;
 mod statement_collection;
+mod statement_rename;
 mod statement_status;
 mod statement_struct;
+mod timeline;
+mod transition;
 
-pub use error::{IgnoreFileError, PairingError};
+pub use account_key::AccountKey;
+pub use collection_diff::{CollectionDiff, StatementChange};
+pub use completeness::{completeness_by_year, missing_by_age, Completeness, MissingAge};
+pub use date_diff::{DateDiff, TimeUnit};
+pub use error::{IgnoreFileError, PairingError, RenameError};
+pub use gap::{find_gaps, Gap};
+pub use ignore_file::{extend_ignorefile, remove_from_ignorefile};
 pub use ignored_statements::IgnoredStatements;
 pub use observed_statement::ObservedStatement;
 pub use ops::{
-    expected_statement_dates, next_date_from_given, next_date_from_today, next_weekday_date,
-    pair_dates_statements, prev_date_from_given, prev_date_from_today,
+    describe_period, expected_statement_dates, join_words, next_date_from_given,
+    next_date_from_today, next_weekday_date, ordinal, pair_dates_statements,
+    pair_dates_statements_with_grace, prev_date_from_given, prev_date_from_today, upcoming_dates,
+    ExpectedDates, Pairings,
 };
-pub use statement_collection::StatementCollection;
+pub use statement_collection::{Iter, StatementCollection};
+pub use statement_rename::{plan_renames, StatementRename};
 pub use statement_status::StatementStatus;
 pub use statement_struct::Statement;
+pub use timeline::{bucket_by_month, YearTimeline};
+pub use transition::{statement_transitions, StatementTransition, Transition};
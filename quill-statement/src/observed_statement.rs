@@ -2,8 +2,10 @@
 //! This includes what date it's supposed to correspond to, the statement file as given or expected, and its status.
 
 use super::{Statement, StatementStatus};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct ObservedStatement {
     stmt: Statement,
     status: StatementStatus,
@@ -24,12 +26,44 @@ impl ObservedStatement {
     pub fn status(&self) -> StatementStatus {
         self.status
     }
+
+    /// How many days past its expected date a missing statement is, as of `today`.
+    /// Statements that aren't missing aren't overdue.
+    pub fn days_overdue(&self, today: &NaiveDate) -> Option<i64> {
+        match self.status {
+            StatementStatus::Missing | StatementStatus::Lost => {
+                Some((*today - *self.stmt.date()).num_days().max(0))
+            }
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
     #[test]
     fn it_works() {
         assert_eq!(2 + 2, 4);
     }
+
+    #[test]
+    fn days_overdue() {
+        let stmt = Statement::from(&date(2021, 1, 15));
+        let today = date(2021, 3, 1);
+
+        let missing = ObservedStatement::new(&stmt, StatementStatus::Missing);
+        assert_eq!(Some(45), missing.days_overdue(&today));
+        assert_eq!(Some(0), missing.days_overdue(&date(2021, 1, 15)));
+        // statements aren't overdue before they're expected
+        assert_eq!(Some(0), missing.days_overdue(&date(2021, 1, 1)));
+
+        let available = ObservedStatement::new(&stmt, StatementStatus::Available);
+        assert_eq!(None, available.days_overdue(&today));
+    }
 }
@@ -0,0 +1,116 @@
+//! Describing statement periods in words.
+
+use kronos::Grain;
+
+/// Describe a statement period in words, like "last day of every 3rd month".
+///
+/// Each of `nths` picks the `n`-th `grain` of each period, counting back from the end if negative.
+/// Only every `every`-th `of` is a period.
+pub fn describe_period(nths: &[i64], grain: Grain, every: usize, of: Grain) -> String {
+    let nths: Vec<String> = nths.iter().map(|n| nth_name(*n)).collect();
+    let period = match every {
+        1 => format!("every {}", grain_name(of)),
+        n => format!("every {} {}", ordinal(n as u64), grain_name(of)),
+    };
+
+    format!("{} {} of {}", join_words(&nths), grain_name(grain), period)
+}
+
+/// Name the `n`-th item, counting back from the end if `n` is negative
+fn nth_name(n: i64) -> String {
+    match n {
+        -1 => "last".to_string(),
+        n if n < 0 => format!("{}-last", ordinal(n.unsigned_abs())),
+        n => ordinal(n.unsigned_abs()),
+    }
+}
+
+/// Write a number as an ordinal, like "1st" or "12th"
+pub fn ordinal(n: u64) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+
+    format!("{}{}", n, suffix)
+}
+
+/// Join words into a list, like "1st, 10th and 20th"
+pub fn join_words(words: &[String]) -> String {
+    match words {
+        [] => String::new(),
+        [word] => word.clone(),
+        [rest @ .., last] => format!("{} and {}", rest.join(", "), last),
+    }
+}
+
+/// Name a unit of time
+fn grain_name(grain: Grain) -> &'static str {
+    match grain {
+        Grain::Second => "second",
+        Grain::Minute => "minute",
+        Grain::Hour => "hour",
+        Grain::Day => "day",
+        Grain::Week => "week",
+        Grain::Month => "month",
+        Grain::Quarter => "quarter",
+        Grain::Half => "half-year",
+        Grain::Year => "year",
+        Grain::Lustrum => "lustrum",
+        Grain::Decade => "decade",
+        Grain::Century => "century",
+        Grain::Millenium => "millennium",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ordinals() {
+        let observed: Vec<String> = [1, 2, 3, 4, 11, 12, 13, 21, 22, 101, 111]
+            .iter()
+            .map(|n| ordinal(*n))
+            .collect();
+        let expected = vec![
+            "1st", "2nd", "3rd", "4th", "11th", "12th", "13th", "21st", "22nd", "101st", "111th",
+        ];
+
+        assert_eq!(expected, observed);
+    }
+
+    #[test]
+    fn first_day_of_every_month() {
+        let observed = describe_period(&[1], Grain::Day, 1, Grain::Month);
+
+        assert_eq!("1st day of every month", observed);
+    }
+
+    #[test]
+    fn counts_back_from_the_end() {
+        assert_eq!(
+            "last day of every 3rd month",
+            describe_period(&[-1], Grain::Day, 3, Grain::Month)
+        );
+        assert_eq!(
+            "2nd-last week of every year",
+            describe_period(&[-2], Grain::Week, 1, Grain::Year)
+        );
+    }
+
+    #[test]
+    fn lists_several_days() {
+        assert_eq!(
+            "1st and 15th day of every month",
+            describe_period(&[1, 15], Grain::Day, 1, Grain::Month)
+        );
+        assert_eq!(
+            "1st, 10th and 20th day of every 2nd half-year",
+            describe_period(&[1, 10, 20], Grain::Day, 2, Grain::Half)
+        );
+    }
+}
@@ -0,0 +1,176 @@
+//! Generating the dates that statements are expected on.
+
+use super::next_date::next_date_from_given;
+use chrono::NaiveDate;
+use kronos::Shim;
+
+/// The dates that an account's statements are expected on, from its first statement
+/// until a given day.
+/// They can be extended to a later day without generating the earlier dates again.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExpectedDates {
+    first: NaiveDate,
+    through: NaiveDate,
+    dates: Vec<NaiveDate>,
+}
+
+impl ExpectedDates {
+    /// List the dates from `first` until `today`, inclusive, that follow `period`
+    pub fn until(first: &NaiveDate, period: &Shim, today: &NaiveDate) -> Self {
+        let mut expected = Self {
+            first: *first,
+            through: *first,
+            dates: Vec::new(),
+        };
+        if first <= today {
+            expected.dates.push(*first);
+            expected.extend_until(period, today);
+        } else {
+            expected.through = *today;
+        }
+
+        expected
+    }
+
+    /// Add the dates after the last one that was generated, until `today`, inclusive.
+    /// If `today` is earlier than the last time, the dates after it are dropped instead.
+    /// `period` must be the same one the dates were first generated with.
+    pub fn extend_until(&mut self, period: &Shim, today: &NaiveDate) {
+        if *today < self.through {
+            self.dates.retain(|d| d <= today);
+            self.through = *today;
+            return;
+        }
+        self.through = *today;
+
+        let last = match self.dates.last() {
+            Some(d) => *d,
+            // the first statement is always expected once its day comes
+            None if self.first <= *today => {
+                self.dates.push(self.first);
+                self.first
+            }
+            None => return,
+        };
+
+        // each date is found from the one before it, since stepped periods like every 3 months
+        // count from the date they start from
+        let mut next = next_date_from_given(&last, period);
+        while next <= *today {
+            self.dates.push(next);
+            next = next_date_from_given(&next, period);
+        }
+    }
+
+    /// The date of the first statement
+    pub fn first(&self) -> &NaiveDate {
+        &self.first
+    }
+
+    /// The day the dates were generated until
+    pub fn through(&self) -> &NaiveDate {
+        &self.through
+    }
+
+    /// The expected dates, earliest first
+    pub fn dates(&self) -> &[NaiveDate] {
+        &self.dates
+    }
+
+    /// Take the expected dates, earliest first
+    pub fn into_dates(self) -> Vec<NaiveDate> {
+        self.dates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kronos::{step_by, Grain, Grains, LastOf, NthOf};
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    /// Generate the dates one at a time, starting a new sequence for each date
+    fn one_at_a_time(first: &NaiveDate, period: &Shim, today: &NaiveDate) -> Vec<NaiveDate> {
+        let mut dates = Vec::new();
+        if first <= today {
+            dates.push(*first);
+        }
+        let mut next = next_date_from_given(first, period);
+        while next <= *today {
+            dates.push(next);
+            next = next_date_from_given(&next, period);
+        }
+
+        dates
+    }
+
+    /// Periods like the ones accounts have, which are all made of days
+    fn periods() -> Vec<Shim<'static>> {
+        vec![
+            Shim::new(step_by(Grains(Grain::Day), 1)),
+            Shim::new(NthOf(6, Grains(Grain::Day), Grains(Grain::Week))),
+            Shim::new(NthOf(
+                3,
+                Grains(Grain::Day),
+                step_by(Grains(Grain::Week), 2),
+            )),
+            Shim::new(NthOf(15, Grains(Grain::Day), Grains(Grain::Month))),
+            Shim::new(LastOf(
+                1,
+                Grains(Grain::Day),
+                step_by(Grains(Grain::Month), 3),
+            )),
+            Shim::new(NthOf(1, Grains(Grain::Day), Grains(Grain::Year))),
+        ]
+    }
+
+    #[test]
+    fn same_as_one_at_a_time() {
+        let first = date(2019, 3, 2);
+        let today = date(2021, 7, 18);
+
+        for period in periods() {
+            assert_eq!(
+                one_at_a_time(&first, &period, &today),
+                ExpectedDates::until(&first, &period, &today).into_dates()
+            );
+        }
+    }
+
+    #[test]
+    fn extended_same_as_generated_at_once() {
+        let first = date(2019, 3, 2);
+        let today = date(2021, 7, 18);
+
+        for period in periods() {
+            let mut extended = ExpectedDates::until(&first, &period, &date(2019, 1, 1));
+            for day in [date(2019, 3, 2), date(2020, 2, 29), today] {
+                extended.extend_until(&period, &day);
+            }
+
+            assert_eq!(ExpectedDates::until(&first, &period, &today), extended);
+        }
+    }
+
+    #[test]
+    fn earlier_day_drops_dates() {
+        let period = Shim::new(NthOf(15, Grains(Grain::Day), Grains(Grain::Month)));
+        let first = date(2021, 1, 15);
+        let mut expected = ExpectedDates::until(&first, &period, &date(2021, 6, 1));
+        expected.extend_until(&period, &date(2021, 3, 1));
+
+        assert_eq!(&[date(2021, 1, 15), date(2021, 2, 15)], expected.dates());
+        assert_eq!(&date(2021, 3, 1), expected.through());
+    }
+
+    #[test]
+    fn nothing_before_first() {
+        let period = Shim::new(NthOf(1, Grains(Grain::Day), Grains(Grain::Week)));
+        let expected = ExpectedDates::until(&date(2021, 1, 15), &period, &date(2021, 1, 14));
+
+        assert!(expected.dates().is_empty());
+    }
+}
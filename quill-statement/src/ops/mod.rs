@@ -1,9 +1,17 @@
 //! Multiple operations for working with `Statements`.
 
+pub mod describe;
+pub mod expected_dates;
 pub mod next_date;
 pub mod pairing;
 pub mod prev_date;
 
-pub use next_date::{next_date_from_given, next_date_from_today, next_weekday_date};
-pub use pairing::{expected_statement_dates, pair_dates_statements};
+pub use describe::{describe_period, join_words, ordinal};
+pub use expected_dates::ExpectedDates;
+pub use next_date::{
+    next_date_from_given, next_date_from_today, next_weekday_date, upcoming_dates,
+};
+pub use pairing::{
+    expected_statement_dates, pair_dates_statements, pair_dates_statements_with_grace, Pairings,
+};
 pub use prev_date::{prev_date_from_given, prev_date_from_today};
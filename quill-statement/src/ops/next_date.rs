@@ -2,6 +2,7 @@
 
 use chrono::{Datelike, Duration, Local, NaiveDate, Weekday};
 use kronos::{Grain, Grains, Shim, TimeSequence};
+use std::iter;
 
 /// Calculate the next weekday from a given date
 pub fn next_weekday_date(d: NaiveDate) -> NaiveDate {
@@ -42,10 +43,20 @@ pub fn next_date_from_today(period: &Shim) -> NaiveDate {
     next_date_from_given(&today, period)
 }
 
+/// Iterate over every periodic date after a given date, in chronological order.
+pub fn upcoming_dates<'a, 'b>(
+    from: &NaiveDate,
+    period: &'b Shim<'a>,
+) -> impl Iterator<Item = NaiveDate> + 'b {
+    iter::successors(Some(next_date_from_given(from, period)), move |d| {
+        Some(next_date_from_given(d, period))
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use kronos::step_by;
+    use kronos::{step_by, NthOf};
 
     #[test]
     fn it_works() {
@@ -111,4 +122,21 @@ mod tests {
         check_next_date_from_given(monday, &next_day_shim, tuesday);
         check_next_date_from_given(tuesday, &next_day_shim, next_wednesday);
     }
+
+    #[test]
+    fn upcoming_monthly() {
+        // the 15th of every month
+        let monthly_shim = Shim::new(NthOf(15, Grains(Grain::Day), Grains(Grain::Month)));
+        let from = NaiveDate::from_ymd_opt(2100, 1, 14).unwrap();
+
+        let expected = vec![
+            NaiveDate::from_ymd_opt(2100, 1, 15).unwrap(),
+            // the 15th of February and March are on weekdays
+            NaiveDate::from_ymd_opt(2100, 2, 15).unwrap(),
+            NaiveDate::from_ymd_opt(2100, 3, 15).unwrap(),
+        ];
+        let observed: Vec<NaiveDate> = upcoming_dates(&from, &monthly_shim).take(3).collect();
+
+        assert_eq!(expected, observed);
+    }
 }
@@ -1,14 +1,21 @@
 //! Functions to pair dates with statements.
 
 use crate::{
-    next_date_from_given, IgnoredStatements, ObservedStatement, PairingError, Statement,
-    StatementStatus,
+    ExpectedDates, IgnoredStatements, ObservedStatement, PairingError, Statement, StatementStatus,
 };
 use chrono::{Duration, Local, NaiveDate};
 use kronos::Shim;
+use std::collections::VecDeque;
 use std::slice::Iter;
+use tracing::{debug, debug_span, trace};
+
+/// How far a statement's date can be from an expected date and still be paired with it
+fn proximity_limit() -> Duration {
+    Duration::weeks(1)
+}
 
 /// A helper struct to navigate through the pairing operations
+#[derive(Clone, Debug)]
 struct PairingIter<'a> {
     date_iter: Iter<'a, NaiveDate>,
     this_date: Option<&'a NaiveDate>,
@@ -16,14 +23,16 @@ struct PairingIter<'a> {
     this_date_paired: bool,
     last_date_paired: bool,
     stmt_iter: Iter<'a, Statement>,
+    stmt_idx: usize,
+    stmts_paired: Vec<bool>,
     this_stmt: Option<&'a Statement>,
     last_stmt: Option<&'a Statement>,
     this_stmt_paired: bool,
     last_stmt_paired: bool,
+    ignored: &'a IgnoredStatements,
     ignore_iter: Iter<'a, NaiveDate>,
     this_ig: Option<&'a NaiveDate>,
     last_ig: Option<&'a NaiveDate>,
-    pairs: Vec<ObservedStatement>,
 }
 
 impl<'a> PairingIter<'a> {
@@ -49,14 +58,16 @@ impl<'a> PairingIter<'a> {
             this_date_paired: false,
             last_date_paired: false,
             stmt_iter,
+            stmt_idx: 0,
+            stmts_paired: vec![false; stmts.len()],
             this_stmt,
             last_stmt: None,
             this_stmt_paired: false,
             last_stmt_paired: false,
+            ignored,
             ignore_iter,
             this_ig,
             last_ig: None,
-            pairs: vec![],
         }
     }
 
@@ -87,9 +98,25 @@ impl<'a> PairingIter<'a> {
         self.this_ig
     }
 
-    /// Retrieve the pairings of dates and statements
-    fn pairings(&self) -> &Vec<ObservedStatement> {
-        &self.pairs
+    /// Check if the active date falls within an ignored date range
+    fn date_in_ignored_range(&self) -> bool {
+        match self.date() {
+            Some(d) => self.ignored.in_ranges(d),
+            None => false,
+        }
+    }
+
+    /// Check if the active date is after the date that statements become pending, if there is one
+    fn date_is_pending(&self, pending_after: Option<&NaiveDate>) -> bool {
+        match (self.date(), pending_after) {
+            (Some(d), Some(after)) => d > after,
+            _ => false,
+        }
+    }
+
+    /// Check if the statement at a position in the sorted statements was paired with a date
+    fn is_paired(&self, stmt_idx: usize) -> bool {
+        self.stmts_paired.get(stmt_idx).copied().unwrap_or(false)
     }
 
     /// Move to the next date
@@ -102,6 +129,7 @@ impl<'a> PairingIter<'a> {
 
     /// Move to the next statement
     fn next_statement(&mut self) {
+        self.stmt_idx += 1;
         self.last_stmt = self.this_stmt;
         self.this_stmt = self.stmt_iter.next();
         self.last_stmt_paired = self.this_stmt_paired;
@@ -114,54 +142,108 @@ impl<'a> PairingIter<'a> {
         self.this_ig = self.ignore_iter.next();
     }
 
-    /// Push a new statement and status
-    fn push_statement(&mut self, status: StatementStatus) -> Result<(), PairingError> {
-        let this_stmt = match (self.date(), self.statement()) {
-            (Some(date), Some(stmt)) => Statement::new(stmt.path(), date),
-            (Some(date), None) => Statement::from(date),
-            (None, _) => return Err(PairingError::NoneDateForPairing),
+    /// Pair the active date with the active statement, then move to the next date
+    fn pair_statement(&mut self, date: &NaiveDate, status: StatementStatus) -> ObservedStatement {
+        let this_stmt = match self.statement() {
+            Some(stmt) => Statement::new(stmt.path(), date),
+            None => Statement::from(date),
         };
-        let obs_stmt = ObservedStatement::new(&this_stmt, status);
+        if self.statement().is_some() {
+            self.stmts_paired[self.stmt_idx] = true;
+        }
 
-        self.pairs.push(obs_stmt);
         self.this_date_paired = true;
         self.next_date();
 
-        Ok(())
+        ObservedStatement::new(&this_stmt, status)
     }
 
-    /// Push a the previous statement and given status
-    fn push_previous_statement(&mut self, status: StatementStatus) -> Result<(), PairingError> {
-        let this_stmt = match (self.date(), self.previous_statement()) {
-            (Some(date), Some(stmt)) => Statement::new(stmt.path(), date),
-            (Some(date), None) => Statement::from(date),
-            (None, _) => return Err(PairingError::NoneDateForPairing),
+    /// Pair the active date with the previous statement, then move to the next date
+    fn pair_previous_statement(
+        &mut self,
+        date: &NaiveDate,
+        status: StatementStatus,
+    ) -> ObservedStatement {
+        let this_stmt = match self.previous_statement() {
+            Some(stmt) => Statement::new(stmt.path(), date),
+            None => Statement::from(date),
         };
-        let obs_stmt = ObservedStatement::new(&this_stmt, status);
+        if self.previous_statement().is_some() {
+            self.stmts_paired[self.stmt_idx - 1] = true;
+        }
 
-        self.pairs.push(obs_stmt);
         self.this_date_paired = true;
         self.next_date();
 
-        Ok(())
+        ObservedStatement::new(&this_stmt, status)
+    }
+
+    /// Give the active date a status without a statement, then move to the next date
+    fn pair_date(&mut self, date: &NaiveDate, status: StatementStatus) -> ObservedStatement {
+        let obs_stmt = ObservedStatement::new(&Statement::from(date), status);
+        self.next_date();
+
+        obs_stmt
     }
 
-    /// Push a new statement and status
-    fn push_date(&mut self, status: StatementStatus) -> Result<(), PairingError> {
-        let this_stmt = match self.date() {
-            Some(d) => Statement::from(d),
+    /// Pair the active date with the statement closest to it, or give it a status without one,
+    /// then move to the next date.
+    /// Dates after `pending_after` without a statement are `Pending`, if it's given.
+    fn pair_next_date(
+        &mut self,
+        pending_after: Option<&NaiveDate>,
+    ) -> Result<ObservedStatement, PairingError> {
+        let date = match self.date() {
+            Some(d) => *d,
             None => return Err(PairingError::NoneDateForPairing),
         };
-        let obs_stmt = ObservedStatement::new(&this_stmt, status);
-        self.pairs.push(obs_stmt);
-        self.next_date();
 
-        Ok(())
+        // fast forward the ignores
+        while let Some(ig_date) = self.ignore() {
+            if *ig_date < date {
+                self.next_ignore();
+            } else {
+                break;
+            }
+        }
+
+        // check if the current date should be ignored
+        if (self.ignore() == Some(&date)) || self.date_in_ignored_range() {
+            return Ok(self.pair_date(&date, StatementStatus::Ignored));
+        }
+
+        // fast forward the statements
+        while let Some(stmt) = self.statement() {
+            if *stmt.date() < date {
+                self.next_statement();
+            } else {
+                break;
+            }
+        }
+
+        // check if the previous or current statement should be paired with the current date
+        let obs = if (self.statement_date() == Some(&date))
+            || (self.statement_in_proximity(self.statement()) && self.this_statement_is_closest())
+        {
+            self.pair_statement(&date, StatementStatus::Available)
+        } else if self.statement_in_proximity(self.previous_statement())
+            && !self.this_statement_is_closest()
+        {
+            self.pair_previous_statement(&date, StatementStatus::Available)
+        } else if self.date_is_pending(pending_after) {
+            // statements may not be published until some time after their date
+            self.pair_date(&date, StatementStatus::Pending)
+        } else {
+            // no other options means its missing
+            self.pair_date(&date, StatementStatus::Missing)
+        };
+
+        Ok(obs)
     }
 
     /// Determine if the current statement's date is close enough to the current date
     fn statement_in_proximity(&self, stmt: Option<&Statement>) -> bool {
-        let limit = Duration::weeks(1);
+        let limit = proximity_limit();
 
         if let (Some(d), Some(s)) = (self.date(), stmt) {
             if s.date() > d {
@@ -198,81 +280,180 @@ impl<'a> PairingIter<'a> {
     }
 }
 
-/// Match elements of Dates and Statements together to find closest pairing.
-/// Finds a 1:1 mapping of dates to statements, if possible.
-pub fn pair_dates_statements(
-    dates: &[NaiveDate],
-    stmts: &[Statement],
-    ignored: &IgnoredStatements,
-) -> Result<Vec<ObservedStatement>, PairingError> {
-    // iterators over sorted dates
-    let mut pairs = PairingIter::new(dates, stmts, ignored);
+/// A lazy iterator over the pairings of dates and statements, sorted by date.
+/// Each date is paired with the statement closest to it, if there's one close enough.
+///
+/// Statements that weren't paired with any date, and whose own date is neither
+/// expected nor ignored, are included as `Unexpected`.
+/// Both the dates and statements must be sorted, earliest first.
+#[derive(Clone, Debug)]
+pub struct Pairings<'a> {
+    pairs: PairingIter<'a>,
+    dates: &'a [NaiveDate],
+    stmts: &'a [Statement],
+    ignored: &'a IgnoredStatements,
+    pending_after: Option<&'a NaiveDate>,
+    /// Statements before this position can't be paired with any of the remaining dates
+    settled: usize,
+    /// Dates that were paired but haven't been returned yet, earliest first
+    paired: VecDeque<ObservedStatement>,
+    /// Statements found to be unexpected that haven't been returned yet, earliest first
+    unexpected: VecDeque<&'a Statement>,
+}
 
-    while pairs.date().is_some() {
-        // fast forward the ignores
-        while let (Some(ig_date), Some(date)) = (pairs.ignore(), pairs.date()) {
-            if ig_date < date {
-                pairs.next_ignore();
-            } else {
+impl<'a> Pairings<'a> {
+    /// Pair each date with the closest statement, if there's one close enough
+    pub fn new(
+        dates: &'a [NaiveDate],
+        stmts: &'a [Statement],
+        ignored: &'a IgnoredStatements,
+    ) -> Self {
+        Self {
+            pairs: PairingIter::new(dates, stmts, ignored),
+            dates,
+            stmts,
+            ignored,
+            pending_after: None,
+            settled: 0,
+            paired: VecDeque::new(),
+            unexpected: VecDeque::new(),
+        }
+    }
+
+    /// Allow for statements that haven't been published yet.
+    /// Dates after `pending_after` without a statement are `Pending` instead of `Missing`.
+    pub fn with_grace(mut self, pending_after: &'a NaiveDate) -> Self {
+        self.pending_after = Some(pending_after);
+        self
+    }
+
+    /// Settle the statements that are too far from the remaining dates to be paired with any
+    /// of them, keeping the ones that are unexpected
+    fn settle(&mut self) {
+        let next_date = self.pairs.date().copied();
+        while let Some(stmt) = self.stmts.get(self.settled) {
+            if next_date.map_or(false, |d| d - *stmt.date() <= proximity_limit()) {
                 break;
             }
+
+            let date = stmt.date();
+            let unexpected = !self.pairs.is_paired(self.settled)
+                && !self.dates.contains(date)
+                && !self.ignored.contains(date)
+                && !self.ignored.in_ranges(date);
+            if unexpected {
+                self.unexpected.push_back(stmt);
+            }
+            self.settled += 1;
         }
+    }
 
-        // check if the current date should be ignored
-        if pairs.ignore() == pairs.date() {
-            pairs.push_date(StatementStatus::Ignored)?;
-            continue;
+    /// Take the earliest pairing, if nothing that's still unsettled could come before it.
+    /// A paired date comes before an unexpected statement on the same date.
+    fn take_earliest(&mut self) -> Option<ObservedStatement> {
+        let unexpected_date = self.unexpected.front().map(|stmt| *stmt.date());
+        match (self.paired.front(), unexpected_date) {
+            (Some(obs), Some(date)) if *obs.statement().date() > date => self.take_unexpected(),
+            (Some(obs), _) => {
+                // an unsettled statement from before this date may still turn out to be unexpected
+                let unsettled = self.stmts.get(self.settled);
+                match unsettled.map_or(false, |stmt| stmt.date() < obs.statement().date()) {
+                    true => None,
+                    false => self.paired.pop_front(),
+                }
+            }
+            // any remaining dates are after every settled statement
+            (None, Some(_)) => self.take_unexpected(),
+            (None, None) => None,
         }
+    }
 
-        // fast forward the statements
-        while let (Some(stmt), Some(date)) = (pairs.statement(), pairs.date()) {
-            if stmt.date() < date {
-                pairs.next_statement();
-            } else {
-                break;
+    /// Take the earliest unexpected statement
+    fn take_unexpected(&mut self) -> Option<ObservedStatement> {
+        self.unexpected
+            .pop_front()
+            .map(|stmt| ObservedStatement::new(stmt, StatementStatus::Unexpected))
+    }
+}
+
+impl<'a> Iterator for Pairings<'a> {
+    type Item = ObservedStatement;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.settle();
+            if let Some(obs) = self.take_earliest() {
+                trace_pairing(&obs);
+                return Some(obs);
+            }
+
+            // pair another date, until they've all been paired and every statement is settled
+            match self.pairs.pair_next_date(self.pending_after) {
+                Ok(obs) => self.paired.push_back(obs),
+                Err(_) if self.paired.is_empty() && self.unexpected.is_empty() => return None,
+                Err(_) => {}
             }
         }
+    }
+}
 
-        // check if the previous or current statement should be paired with the current date
-        if (pairs.statement_date() == pairs.date())
-            || (pairs.statement_in_proximity(pairs.statement())
-                && pairs.this_statement_is_closest())
-        {
-            pairs.push_statement(StatementStatus::Available)?;
-        } else if pairs.statement_in_proximity(pairs.previous_statement())
-            && !pairs.this_statement_is_closest()
-        {
-            pairs.push_previous_statement(StatementStatus::Available)?;
-        } else {
-            // no other options means its missing
-            pairs.push_date(StatementStatus::Missing)?;
+/// Log how a date or statement was paired
+fn trace_pairing(obs: &ObservedStatement) {
+    let date = obs.statement().date();
+    let file = obs.statement().path().display();
+    match obs.status() {
+        StatementStatus::Available => trace!(%date, %file, "paired statement"),
+        StatementStatus::Ignored => trace!(%date, "ignored date"),
+        StatementStatus::Missing => trace!(%date, "no statement found for expected date"),
+        StatementStatus::Pending => trace!(%date, "statement expected within grace period"),
+        // statements are only found to be lost after pairing, against a previous scan
+        StatementStatus::Lost => trace!(%date, "statement file has disappeared"),
+        StatementStatus::Unexpected => {
+            debug!(%date, %file, "statement doesn't match any expected date")
         }
     }
+}
 
-    Ok(pairs.pairings().to_vec())
+/// Match elements of Dates and Statements together to find closest pairing.
+/// Finds a 1:1 mapping of dates to statements, if possible.
+///
+/// Statements that weren't paired with any date, and whose own date is neither
+/// expected nor ignored, are included as `Unexpected`.
+/// The results are sorted by date.
+/// Use `Pairings` to go through them without collecting them.
+pub fn pair_dates_statements(
+    dates: &[NaiveDate],
+    stmts: &[Statement],
+    ignored: &IgnoredStatements,
+) -> Result<Vec<ObservedStatement>, PairingError> {
+    let _span = debug_span!("pairing", dates = dates.len(), statements = stmts.len()).entered();
+
+    Ok(Pairings::new(dates, stmts, ignored).collect())
+}
+
+/// Match elements of Dates and Statements together, like `pair_dates_statements`,
+/// allowing for statements that haven't been published yet.
+///
+/// Dates after `pending_after` without a statement are `Pending` instead of `Missing`.
+pub fn pair_dates_statements_with_grace(
+    dates: &[NaiveDate],
+    stmts: &[Statement],
+    ignored: &IgnoredStatements,
+    pending_after: &NaiveDate,
+) -> Result<Vec<ObservedStatement>, PairingError> {
+    let _span = debug_span!("pairing", dates = dates.len(), statements = stmts.len()).entered();
+
+    Ok(Pairings::new(dates, stmts, ignored)
+        .with_grace(pending_after)
+        .collect())
 }
 
 /// List all statement dates given a first date and period
 /// This list is guaranteed to be sorted, earliest first
 pub fn expected_statement_dates<'a>(first: &NaiveDate, period: &Shim<'a>) -> Vec<NaiveDate> {
-    // statement Dates to be returned
-    let mut stmnts = Vec::new();
     let now = Local::now().naive_local().date();
-    // add the first statement date if it is earlier than today
-    if *first <= now {
-        stmnts.push(*first);
-    }
-
-    // iterate through all future statement dates
-    let mut iter_date = next_date_from_given(first, period);
-    while iter_date <= now {
-        stmnts.push(iter_date);
-        // get the next date after the current iterated date
-        iter_date = next_date_from_given(&iter_date, period);
-    }
-    stmnts.sort();
 
-    stmnts
+    ExpectedDates::until(first, period, &now).into_dates()
 }
 
 #[cfg(test)]
@@ -352,7 +533,10 @@ mod tests {
             &[],
             &[blank_statement(2021, 9, 22)],
             &IgnoredStatements::empty(),
-            vec![],
+            vec![ObservedStatement::new(
+                &blank_statement(2021, 9, 22),
+                StatementStatus::Unexpected,
+            )],
         );
     }
 
@@ -372,7 +556,10 @@ mod tests {
             &[],
             &[blank_statement(2021, 9, 22)],
             &IgnoredStatements::from(vec![NaiveDate::from_ymd_opt(2021, 10, 22).unwrap()]),
-            vec![],
+            vec![ObservedStatement::new(
+                &blank_statement(2021, 9, 22),
+                StatementStatus::Unexpected,
+            )],
         );
     }
 
@@ -796,6 +983,35 @@ mod tests {
         check_pair_dates_statements(input_dates, input_stmts, input_ignored, expected);
     }
 
+    /// Every expected date within an ignored range should be ignored,
+    /// even if a statement is available for it.
+    #[test]
+    fn ignored_range() {
+        let input_dates = &[
+            NaiveDate::from_ymd_opt(2021, 9, 22).unwrap(),
+            NaiveDate::from_ymd_opt(2021, 10, 22).unwrap(),
+            NaiveDate::from_ymd_opt(2021, 11, 22).unwrap(),
+            NaiveDate::from_ymd_opt(2021, 12, 22).unwrap(),
+        ];
+        let input_stmts = &[blank_statement(2021, 10, 22)];
+        let input_ignored = &IgnoredStatements::new(
+            vec![],
+            vec![(
+                NaiveDate::from_ymd_opt(2021, 10, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2021, 11, 30).unwrap(),
+            )],
+        );
+
+        let expected = vec![
+            ObservedStatement::new(&blank_statement(2021, 9, 22), StatementStatus::Missing),
+            ObservedStatement::new(&blank_statement(2021, 10, 22), StatementStatus::Ignored),
+            ObservedStatement::new(&blank_statement(2021, 11, 22), StatementStatus::Ignored),
+            ObservedStatement::new(&blank_statement(2021, 12, 22), StatementStatus::Missing),
+        ];
+
+        check_pair_dates_statements(input_dates, input_stmts, input_ignored, expected);
+    }
+
     /// When a statement date doesn't exactly line up with an expected date,
     /// it should still match.
     /// Check that a statement between two dates matches to the closest one in the past.
@@ -832,4 +1048,155 @@ mod tests {
 
         check_pair_dates_statements(input_dates, input_stmts, input_ignored, expected);
     }
+
+    /// Check that a statement far from every date is flagged, in date order
+    #[test]
+    fn unexpected_between_dates() {
+        let input_dates = &[
+            NaiveDate::from_ymd_opt(2021, 9, 22).unwrap(),
+            NaiveDate::from_ymd_opt(2021, 10, 22).unwrap(),
+        ];
+        let input_stmts = &[
+            blank_statement(2021, 9, 22),
+            blank_statement(2021, 10, 5),
+            blank_statement(2021, 10, 22),
+        ];
+        let input_ignored = &IgnoredStatements::empty();
+
+        let expected = vec![
+            ObservedStatement::new(&blank_statement(2021, 9, 22), StatementStatus::Available),
+            ObservedStatement::new(&blank_statement(2021, 10, 5), StatementStatus::Unexpected),
+            ObservedStatement::new(&blank_statement(2021, 10, 22), StatementStatus::Available),
+        ];
+
+        check_pair_dates_statements(input_dates, input_stmts, input_ignored, expected);
+    }
+
+    /// Check that a second copy of an expected statement isn't flagged
+    #[test]
+    fn duplicate_not_unexpected() {
+        let input_dates = &[NaiveDate::from_ymd_opt(2021, 9, 22).unwrap()];
+        let input_stmts = &[blank_statement(2021, 9, 22), blank_statement(2021, 9, 22)];
+        let input_ignored = &IgnoredStatements::empty();
+
+        let expected = vec![ObservedStatement::new(
+            &blank_statement(2021, 9, 22),
+            StatementStatus::Available,
+        )];
+
+        check_pair_dates_statements(input_dates, input_stmts, input_ignored, expected);
+    }
+
+    /// Check that statements within an ignored range aren't flagged
+    #[test]
+    fn ignored_range_not_unexpected() {
+        let input_dates = &[NaiveDate::from_ymd_opt(2021, 9, 22).unwrap()];
+        let input_stmts = &[blank_statement(2021, 9, 1)];
+        let input_ignored = &IgnoredStatements::new(
+            vec![],
+            vec![(
+                NaiveDate::from_ymd_opt(2021, 8, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2021, 9, 5).unwrap(),
+            )],
+        );
+
+        let expected = vec![ObservedStatement::new(
+            &blank_statement(2021, 9, 22),
+            StatementStatus::Missing,
+        )];
+
+        check_pair_dates_statements(input_dates, input_stmts, input_ignored, expected);
+    }
+
+    /// Check that only dates within the grace period are pending
+    #[test]
+    fn pending_within_grace() {
+        let input_dates = &[
+            NaiveDate::from_ymd_opt(2021, 9, 22).unwrap(),
+            NaiveDate::from_ymd_opt(2021, 10, 22).unwrap(),
+            NaiveDate::from_ymd_opt(2021, 11, 22).unwrap(),
+        ];
+        let input_stmts = &[blank_statement(2021, 10, 22)];
+        let pending_after = NaiveDate::from_ymd_opt(2021, 11, 1).unwrap();
+
+        let expected = vec![
+            ObservedStatement::new(&blank_statement(2021, 9, 22), StatementStatus::Missing),
+            ObservedStatement::new(&blank_statement(2021, 10, 22), StatementStatus::Available),
+            ObservedStatement::new(&blank_statement(2021, 11, 22), StatementStatus::Pending),
+        ];
+
+        let observed = pair_dates_statements_with_grace(
+            input_dates,
+            input_stmts,
+            &IgnoredStatements::empty(),
+            &pending_after,
+        )
+        .unwrap();
+        assert_eq!(expected, observed);
+    }
+
+    /// Check that unexpected statements before, between, and after the dates are in date order
+    #[test]
+    fn pairings_interleave_unexpected() {
+        let input_dates = &[
+            NaiveDate::from_ymd_opt(2021, 9, 22).unwrap(),
+            NaiveDate::from_ymd_opt(2021, 10, 22).unwrap(),
+            NaiveDate::from_ymd_opt(2021, 11, 22).unwrap(),
+        ];
+        let input_stmts = &[
+            blank_statement(2021, 6, 1),
+            blank_statement(2021, 9, 22),
+            blank_statement(2021, 10, 5),
+            blank_statement(2021, 10, 8),
+            blank_statement(2021, 11, 20),
+            blank_statement(2022, 2, 1),
+        ];
+        let input_ignored = &IgnoredStatements::empty();
+
+        let expected = vec![
+            ObservedStatement::new(&blank_statement(2021, 6, 1), StatementStatus::Unexpected),
+            ObservedStatement::new(&blank_statement(2021, 9, 22), StatementStatus::Available),
+            ObservedStatement::new(&blank_statement(2021, 10, 5), StatementStatus::Unexpected),
+            ObservedStatement::new(&blank_statement(2021, 10, 8), StatementStatus::Unexpected),
+            ObservedStatement::new(&blank_statement(2021, 10, 22), StatementStatus::Missing),
+            ObservedStatement::new(&blank_statement(2021, 11, 22), StatementStatus::Available),
+            ObservedStatement::new(&blank_statement(2022, 2, 1), StatementStatus::Unexpected),
+        ];
+
+        let observed: Vec<ObservedStatement> =
+            Pairings::new(input_dates, input_stmts, input_ignored).collect();
+        assert_eq!(expected, observed);
+        check_pair_dates_statements(input_dates, input_stmts, input_ignored, expected);
+    }
+
+    /// Check that the pairings can be stopped early
+    #[test]
+    fn pairings_are_lazy() {
+        let input_dates = &[
+            NaiveDate::from_ymd_opt(2021, 9, 22).unwrap(),
+            NaiveDate::from_ymd_opt(2021, 10, 22).unwrap(),
+            NaiveDate::from_ymd_opt(2021, 11, 22).unwrap(),
+        ];
+        let input_stmts = &[blank_statement(2021, 9, 22)];
+        let input_ignored = &IgnoredStatements::empty();
+        let pending_after = NaiveDate::from_ymd_opt(2021, 11, 1).unwrap();
+
+        let mut pairings =
+            Pairings::new(input_dates, input_stmts, input_ignored).with_grace(&pending_after);
+        assert_eq!(
+            Some(ObservedStatement::new(
+                &blank_statement(2021, 9, 22),
+                StatementStatus::Available
+            )),
+            pairings.next()
+        );
+        // only the first date has been paired so far
+        assert_eq!(Some(&input_dates[1]), pairings.pairs.date());
+
+        let rest: Vec<StatementStatus> = pairings.map(|obs| obs.status()).collect();
+        assert_eq!(
+            vec![StatementStatus::Missing, StatementStatus::Pending],
+            rest
+        );
+    }
 }
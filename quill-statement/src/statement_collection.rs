@@ -1,12 +1,23 @@
 //! A collection of all statements for a given account.
 
-use super::ObservedStatement;
-use std::collections::HashMap;
+use super::gap::missing_runs;
+use super::{
+    bucket_by_month, completeness_by_year, find_gaps, missing_by_age, AccountKey, Completeness,
+    Gap, MissingAge, ObservedStatement, StatementStatus, YearTimeline,
+};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::{hash_map, HashMap};
+use std::path::Path;
 
 /// A survey of all account statements that exist and are required
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
 pub struct StatementCollection {
-    inner: HashMap<String, Vec<ObservedStatement>>,
+    inner: HashMap<AccountKey, Vec<ObservedStatement>>,
+
+    /// Dates of the statements expected in the future, for each account
+    #[serde(default)]
+    upcoming: HashMap<AccountKey, Vec<NaiveDate>>,
 }
 
 impl StatementCollection {
@@ -20,16 +31,594 @@ impl StatementCollection {
         self.inner.get(key)
     }
 
+    /// Iterate over the statements for each account, in no particular order
+    pub fn iter(&self) -> Iter<'_> {
+        Iter(self.inner.iter())
+    }
+
+    /// Iterate over every statement of every account, along with the key of its account.
+    /// Accounts are in no particular order, but each account's statements are in the order
+    /// they were inserted.
+    pub fn iter_statements(&self) -> impl Iterator<Item = (&str, &ObservedStatement)> {
+        self.iter()
+            .flat_map(|(k, stmts)| stmts.iter().map(move |obs| (k, obs)))
+    }
+
     /// Insert statements into the collection
     pub fn insert(&mut self, k: &str, v: Vec<ObservedStatement>) -> Option<Vec<ObservedStatement>> {
-        self.inner.insert(k.to_string(), v)
+        self.inner.insert(AccountKey::from(k), v)
+    }
+
+    /// Access the dates of upcoming statements belonging to an account
+    pub fn get_upcoming(&self, key: &str) -> Option<&Vec<NaiveDate>> {
+        self.upcoming.get(key)
+    }
+
+    /// Insert the dates of upcoming statements into the collection.
+    /// The dates should be in chronological order.
+    pub fn insert_upcoming(&mut self, k: &str, v: Vec<NaiveDate>) -> Option<Vec<NaiveDate>> {
+        self.upcoming.insert(AccountKey::from(k), v)
+    }
+
+    /// List the upcoming statements of every account, up to and including the `horizon`.
+    /// The next statement of each account is always included, even if it is after the `horizon`,
+    /// so that every account is represented.
+    /// Statements are sorted by date, then by account key.
+    pub fn upcoming(&self, horizon: &NaiveDate) -> Vec<(&str, &NaiveDate)> {
+        let mut upcoming: Vec<(&str, &NaiveDate)> = self
+            .upcoming
+            .iter()
+            .flat_map(|(k, dates)| {
+                dates
+                    .iter()
+                    .enumerate()
+                    .filter(|&(i, d)| (i == 0) || (d <= horizon))
+                    .map(move |(_, d)| (k.as_str(), d))
+            })
+            .collect();
+
+        upcoming.sort_by_key(|&(k, d)| (d, k));
+
+        upcoming
+    }
+
+    /// List the statements of every account that don't match any expected statement date.
+    /// Statements are sorted by account key, then by date.
+    pub fn unexpected(&self) -> Vec<(&str, &ObservedStatement)> {
+        self.all_with_status(StatementStatus::Unexpected)
+    }
+
+    /// List the missing statements of an account, in the order they were inserted
+    pub fn missing_for(&self, key: &str) -> Vec<&ObservedStatement> {
+        self.get(key).map_or(vec![], |stmts| {
+            stmts
+                .iter()
+                .filter(|obs| obs.status().is_missing())
+                .collect()
+        })
+    }
+
+    /// Count the missing statements of an account
+    pub fn missing_count_for(&self, key: &str) -> usize {
+        self.get(key).map_or(0, |stmts| {
+            stmts.iter().filter(|obs| obs.status().is_missing()).count()
+        })
+    }
+
+    /// Count the missing statements of every account
+    pub fn missing_count(&self) -> usize {
+        self.iter_statements()
+            .filter(|(_, obs)| obs.status().is_missing())
+            .count()
+    }
+
+    /// List the missing statements of every account, including lost ones.
+    /// Statements are sorted by account key, then by date.
+    pub fn all_missing(&self) -> Vec<(&str, &ObservedStatement)> {
+        let mut stmts: Vec<(&str, &ObservedStatement)> = self
+            .iter_statements()
+            .filter(|(_, obs)| obs.status().is_missing())
+            .collect();
+
+        stmts.sort_by_key(|&(k, obs)| (k, obs.statement().date()));
+
+        stmts
+    }
+
+    /// List the statements of every account whose files have disappeared.
+    /// Statements are sorted by account key, then by date.
+    pub fn lost(&self) -> Vec<(&str, &ObservedStatement)> {
+        self.all_with_status(StatementStatus::Lost)
+    }
+
+    /// Mark the statements whose files were found in the `previous` collection, or were
+    /// already lost, but are now missing or pending, as lost.
+    /// Only statements whose files no longer `exist` are lost, so that a file that's still
+    /// there, but isn't matched anymore, like after the account's format changes, is missing.
+    /// Lost statements keep the path their file was last found at.
+    /// Statements are matched between the two collections by their account key and date.
+    /// Returns how many statements were newly lost.
+    pub fn mark_lost(
+        &mut self,
+        previous: &StatementCollection,
+        exists: impl Fn(&Path) -> bool,
+    ) -> usize {
+        let mut newly_lost = 0;
+        for (key, stmts) in self.inner.iter_mut() {
+            let prev_stmts = match previous.get(key) {
+                Some(v) => v,
+                None => continue,
+            };
+
+            for obs in stmts.iter_mut() {
+                if !matches!(
+                    obs.status(),
+                    StatementStatus::Missing | StatementStatus::Pending
+                ) {
+                    continue;
+                }
+                let prev = match prev_stmts
+                    .iter()
+                    .find(|prev| prev.statement().date() == obs.statement().date())
+                {
+                    Some(prev) => prev,
+                    None => continue,
+                };
+                if !matches!(
+                    prev.status(),
+                    StatementStatus::Available | StatementStatus::Lost
+                ) || exists(prev.statement().path())
+                {
+                    continue;
+                }
+                if prev.status() == StatementStatus::Available {
+                    newly_lost += 1;
+                }
+                *obs = ObservedStatement::new(prev.statement(), StatementStatus::Lost);
+            }
+        }
+
+        newly_lost
+    }
+
+    /// Count the statements of every account with each status.
+    /// Statuses without any statements are left out.
+    pub fn counts_by_status(&self) -> HashMap<StatementStatus, usize> {
+        let mut counts = HashMap::new();
+        for (_, obs) in self.iter_statements() {
+            *counts.entry(obs.status()).or_insert(0) += 1;
+        }
+
+        counts
+    }
+
+    /// Find the most recent available statement of an account, if it has any
+    pub fn latest_available(&self, key: &str) -> Option<&ObservedStatement> {
+        self.get(key)?
+            .iter()
+            .filter(|obs| obs.status() == StatementStatus::Available)
+            .max_by_key(|obs| obs.statement().date())
+    }
+
+    /// The fraction of an account's expected statements that are available, from 0 to 1.
+    /// Ignored, unexpected, and pending statements aren't expected yet, so they aren't counted.
+    /// Accounts without any expected statements yet have no completeness.
+    pub fn completeness(&self, key: &str) -> Option<f64> {
+        self.completeness_counts(key).fraction()
+    }
+
+    /// The fraction of every account's expected statements that are available, from 0 to 1
+    pub fn overall_completeness(&self) -> Option<f64> {
+        self.overall_completeness_counts().fraction()
+    }
+
+    /// Count how many of an account's expected statements are available
+    pub fn completeness_counts(&self, key: &str) -> Completeness {
+        self.get(key)
+            .map_or_else(Completeness::default, Completeness::tally)
+    }
+
+    /// Count how many of every account's expected statements are available
+    pub fn overall_completeness_counts(&self) -> Completeness {
+        Completeness::tally(self.iter_statements().map(|(_, obs)| obs))
+    }
+
+    /// Count how many of an account's expected statements are available in each year,
+    /// oldest year first
+    pub fn completeness_by_year(&self, key: &str) -> Vec<(i32, Completeness)> {
+        self.get(key).map_or(vec![], completeness_by_year)
+    }
+
+    /// Count how many of every account's expected statements are available in each year,
+    /// oldest year first
+    pub fn overall_completeness_by_year(&self) -> Vec<(i32, Completeness)> {
+        completeness_by_year(self.iter_statements().map(|(_, obs)| obs))
+    }
+
+    /// Count every account's missing statements by how long ago they were due, as of `today`
+    pub fn missing_by_age(&self, today: &NaiveDate) -> Vec<(MissingAge, usize)> {
+        missing_by_age(self.iter_statements().map(|(_, obs)| obs), today)
+    }
+
+    /// Find the longest run of consecutive missing statements of an account, oldest first.
+    /// Only an available statement ends a run, so ignored and unexpected statements are skipped.
+    /// The earliest run is found if there are several of the same length.
+    pub fn longest_gap(&self, key: &str) -> Vec<&ObservedStatement> {
+        let stmts: Vec<&ObservedStatement> = self.get(key).map_or(vec![], |s| s.iter().collect());
+
+        let mut longest = vec![];
+        for run in missing_runs(&stmts) {
+            if run.len() > longest.len() {
+                longest = run;
+            }
+        }
+
+        longest
+    }
+
+    /// Find every run of consecutive missing statements of an account, oldest first
+    pub fn gaps(&self, key: &str) -> Vec<Gap> {
+        self.get(key).map_or(vec![], |stmts| find_gaps(stmts))
+    }
+
+    /// Bucket an account's statements by year and month, oldest year first
+    pub fn timeline(&self, key: &str) -> Vec<YearTimeline> {
+        self.get(key).map_or(vec![], bucket_by_month)
+    }
+
+    /// Find the statement that has been missing the longest, across every account
+    pub fn most_overdue(&self) -> Option<(&str, &ObservedStatement)> {
+        self.all_missing()
+            .into_iter()
+            .min_by_key(|&(k, obs)| (obs.statement().date(), k))
+    }
+
+    /// Find the next statement expected from any account
+    pub fn next_upcoming(&self) -> Option<(&str, &NaiveDate)> {
+        self.upcoming
+            .iter()
+            .filter_map(|(k, dates)| dates.first().map(|d| (k.as_str(), d)))
+            .min_by_key(|&(k, d)| (d, k))
+    }
+
+    /// List the statements of every account with a given status.
+    /// Statements are sorted by account key, then by date.
+    fn all_with_status(&self, status: StatementStatus) -> Vec<(&str, &ObservedStatement)> {
+        let mut stmts: Vec<(&str, &ObservedStatement)> = self
+            .iter_statements()
+            .filter(|(_, obs)| obs.status() == status)
+            .collect();
+
+        stmts.sort_by_key(|&(k, obs)| (k, obs.statement().date()));
+
+        stmts
+    }
+}
+
+/// An iterator over the statements for each account in a `StatementCollection`
+#[derive(Clone, Debug)]
+pub struct Iter<'a>(hash_map::Iter<'a, AccountKey, Vec<ObservedStatement>>);
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a str, &'a [ObservedStatement]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(k, v)| (k.as_str(), v.as_slice()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a> IntoIterator for &'a StatementCollection {
+    type Item = (&'a str, &'a [ObservedStatement]);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for StatementCollection {
+    type Item = (AccountKey, Vec<ObservedStatement>);
+    type IntoIter = hash_map::IntoIter<AccountKey, Vec<ObservedStatement>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner.into_iter()
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
     #[test]
     fn it_works() {
         assert_eq!(2 + 2, 4);
     }
+
+    #[test]
+    fn upcoming_within_horizon() {
+        let mut sc = StatementCollection::new();
+        sc.insert_upcoming(
+            "monthly",
+            vec![date(2021, 1, 15), date(2021, 2, 15), date(2021, 3, 15)],
+        );
+        sc.insert_upcoming("yearly", vec![date(2021, 12, 1), date(2022, 12, 1)]);
+        sc.insert_upcoming("other", vec![date(2021, 2, 15)]);
+
+        let expected = vec![
+            ("monthly", date(2021, 1, 15)),
+            ("monthly", date(2021, 2, 15)),
+            ("other", date(2021, 2, 15)),
+            // the next yearly statement is included even though it's beyond the horizon
+            ("yearly", date(2021, 12, 1)),
+        ];
+
+        let observed: Vec<(&str, NaiveDate)> = sc
+            .upcoming(&date(2021, 2, 28))
+            .into_iter()
+            .map(|(k, d)| (k, *d))
+            .collect();
+
+        assert_eq!(expected, observed);
+    }
+
+    fn observed(d: NaiveDate, status: StatementStatus) -> ObservedStatement {
+        ObservedStatement::new(&crate::Statement::from(&d), status)
+    }
+
+    #[test]
+    fn missing_statements() {
+        let mut sc = StatementCollection::new();
+        sc.insert(
+            "b",
+            vec![
+                observed(date(2021, 1, 1), StatementStatus::Missing),
+                observed(date(2021, 2, 1), StatementStatus::Available),
+            ],
+        );
+        sc.insert(
+            "a",
+            vec![
+                observed(date(2021, 1, 1), StatementStatus::Available),
+                observed(date(2021, 2, 1), StatementStatus::Missing),
+                observed(date(2021, 3, 1), StatementStatus::Missing),
+            ],
+        );
+
+        let missing_a: Vec<NaiveDate> = sc
+            .missing_for("a")
+            .into_iter()
+            .map(|obs| *obs.statement().date())
+            .collect();
+        assert_eq!(vec![date(2021, 2, 1), date(2021, 3, 1)], missing_a);
+        assert!(sc.missing_for("c").is_empty());
+
+        assert_eq!(2, sc.missing_count_for("a"));
+        assert_eq!(1, sc.missing_count_for("b"));
+        assert_eq!(0, sc.missing_count_for("c"));
+        assert_eq!(3, sc.missing_count());
+
+        let all_missing: Vec<(&str, NaiveDate)> = sc
+            .all_missing()
+            .into_iter()
+            .map(|(k, obs)| (k, *obs.statement().date()))
+            .collect();
+        assert_eq!(
+            vec![
+                ("a", date(2021, 2, 1)),
+                ("a", date(2021, 3, 1)),
+                ("b", date(2021, 1, 1)),
+            ],
+            all_missing
+        );
+    }
+
+    #[test]
+    fn status_counts_and_latest() {
+        let mut sc = StatementCollection::new();
+        sc.insert(
+            "a",
+            vec![
+                observed(date(2021, 1, 1), StatementStatus::Available),
+                observed(date(2021, 2, 1), StatementStatus::Available),
+                observed(date(2021, 3, 1), StatementStatus::Missing),
+                observed(date(2021, 4, 1), StatementStatus::Ignored),
+            ],
+        );
+        sc.insert(
+            "b",
+            vec![observed(date(2021, 1, 1), StatementStatus::Missing)],
+        );
+
+        let counts = sc.counts_by_status();
+        assert_eq!(Some(&2), counts.get(&StatementStatus::Available));
+        assert_eq!(Some(&2), counts.get(&StatementStatus::Missing));
+        assert_eq!(Some(&1), counts.get(&StatementStatus::Ignored));
+        assert_eq!(None, counts.get(&StatementStatus::Unexpected));
+
+        assert_eq!(
+            Some(&date(2021, 2, 1)),
+            sc.latest_available("a").map(|obs| obs.statement().date())
+        );
+        assert_eq!(None, sc.latest_available("b"));
+    }
+
+    #[test]
+    fn completeness_and_gaps() {
+        let mut sc = StatementCollection::new();
+        sc.insert(
+            "a",
+            vec![
+                observed(date(2021, 1, 1), StatementStatus::Missing),
+                observed(date(2021, 2, 1), StatementStatus::Available),
+                observed(date(2021, 3, 1), StatementStatus::Missing),
+                observed(date(2021, 4, 1), StatementStatus::Ignored),
+                observed(date(2021, 5, 1), StatementStatus::Missing),
+                observed(date(2021, 6, 1), StatementStatus::Available),
+            ],
+        );
+        sc.insert(
+            "b",
+            vec![
+                observed(date(2020, 12, 1), StatementStatus::Missing),
+                observed(date(2021, 1, 9), StatementStatus::Unexpected),
+            ],
+        );
+        sc.insert("c", vec![]);
+
+        assert_eq!(Some(0.4), sc.completeness("a"));
+        assert_eq!(Some(0.0), sc.completeness("b"));
+        assert_eq!(None, sc.completeness("c"));
+        assert_eq!(Some(2.0 / 6.0), sc.overall_completeness());
+        assert_eq!(3, sc.completeness_counts("a").missing());
+        assert_eq!(0, sc.completeness_counts("z").expected());
+        let years: Vec<(i32, usize)> = sc
+            .overall_completeness_by_year()
+            .into_iter()
+            .map(|(year, tally)| (year, tally.expected()))
+            .collect();
+        assert_eq!(vec![(2020, 1), (2021, 5)], years);
+
+        // the ignored statement doesn't end the run of missing statements
+        let gap: Vec<NaiveDate> = sc
+            .longest_gap("a")
+            .into_iter()
+            .map(|obs| *obs.statement().date())
+            .collect();
+        assert_eq!(vec![date(2021, 3, 1), date(2021, 5, 1)], gap);
+        assert!(sc.longest_gap("c").is_empty());
+
+        assert_eq!(
+            Some(("b", date(2020, 12, 1))),
+            sc.most_overdue()
+                .map(|(k, obs)| (k, *obs.statement().date()))
+        );
+
+        assert_eq!(None, sc.next_upcoming());
+        sc.insert_upcoming("a", vec![date(2021, 7, 1), date(2021, 8, 1)]);
+        sc.insert_upcoming("b", vec![date(2021, 7, 1)]);
+        assert_eq!(Some(("a", &date(2021, 7, 1))), sc.next_upcoming());
+    }
+
+    #[test]
+    fn iterate_statements() {
+        let mut sc = StatementCollection::new();
+        sc.insert(
+            "a",
+            vec![
+                observed(date(2021, 1, 1), StatementStatus::Available),
+                observed(date(2021, 2, 1), StatementStatus::Missing),
+            ],
+        );
+        sc.insert(
+            "b",
+            vec![observed(date(2021, 1, 1), StatementStatus::Missing)],
+        );
+
+        let mut keys: Vec<&str> = (&sc).into_iter().map(|(k, _)| k).collect();
+        keys.sort_unstable();
+        assert_eq!(vec!["a", "b"], keys);
+
+        let mut stmts: Vec<(&str, NaiveDate)> = sc
+            .iter_statements()
+            .map(|(k, obs)| (k, *obs.statement().date()))
+            .collect();
+        stmts.sort_unstable();
+        assert_eq!(
+            vec![
+                ("a", date(2021, 1, 1)),
+                ("a", date(2021, 2, 1)),
+                ("b", date(2021, 1, 1)),
+            ],
+            stmts
+        );
+
+        let n_owned: usize = sc.into_iter().map(|(_, stmts)| stmts.len()).sum();
+        assert_eq!(3, n_owned);
+    }
+
+    #[test]
+    fn unexpected_statements() {
+        let mut sc = StatementCollection::new();
+        sc.insert(
+            "b",
+            vec![
+                observed(date(2021, 1, 1), StatementStatus::Available),
+                observed(date(2021, 1, 9), StatementStatus::Unexpected),
+            ],
+        );
+        sc.insert(
+            "a",
+            vec![
+                observed(date(2021, 2, 9), StatementStatus::Unexpected),
+                observed(date(2021, 3, 1), StatementStatus::Missing),
+            ],
+        );
+
+        let expected = vec![
+            ("a", observed(date(2021, 2, 9), StatementStatus::Unexpected)),
+            ("b", observed(date(2021, 1, 9), StatementStatus::Unexpected)),
+        ];
+
+        let unexpected: Vec<(&str, ObservedStatement)> = sc
+            .unexpected()
+            .into_iter()
+            .map(|(k, obs)| (k, obs.clone()))
+            .collect();
+
+        assert_eq!(expected, unexpected);
+    }
+
+    #[test]
+    fn lost_statements() {
+        let found = |d: NaiveDate, path: &str, status: StatementStatus| {
+            ObservedStatement::new(&crate::Statement::new(Path::new(path), &d), status)
+        };
+
+        let mut previous = StatementCollection::new();
+        previous.insert(
+            "acct",
+            vec![
+                found(date(2021, 1, 1), "jan.pdf", StatementStatus::Available),
+                found(date(2021, 2, 1), "feb.pdf", StatementStatus::Lost),
+                observed(date(2021, 3, 1), StatementStatus::Missing),
+                found(date(2021, 4, 1), "apr.pdf", StatementStatus::Available),
+                found(date(2021, 5, 1), "may.pdf", StatementStatus::Available),
+                found(date(2021, 6, 1), "jun.pdf", StatementStatus::Lost),
+            ],
+        );
+        let mut sc = StatementCollection::new();
+        sc.insert(
+            "acct",
+            vec![
+                observed(date(2021, 1, 1), StatementStatus::Missing),
+                observed(date(2021, 2, 1), StatementStatus::Missing),
+                observed(date(2021, 3, 1), StatementStatus::Missing),
+                found(date(2021, 4, 1), "apr.pdf", StatementStatus::Available),
+                observed(date(2021, 5, 1), StatementStatus::Missing),
+                observed(date(2021, 6, 1), StatementStatus::Missing),
+            ],
+        );
+
+        // the files from May and June are still there, they just aren't matched anymore
+        let exists = |p: &Path| ["apr.pdf", "may.pdf", "jun.pdf"].contains(&p.to_str().unwrap());
+        // only the statement from January was newly lost
+        assert_eq!(1, sc.mark_lost(&previous, exists));
+
+        let lost: Vec<&NaiveDate> = sc
+            .lost()
+            .into_iter()
+            .map(|(_, obs)| obs.statement().date())
+            .collect();
+        assert_eq!(vec![&date(2021, 1, 1), &date(2021, 2, 1)], lost);
+        // lost statements are still missing
+        assert_eq!(5, sc.missing_count());
+    }
 }
@@ -0,0 +1,181 @@
+//! Rename statement files to follow an account's file name format.
+
+use crate::{RenameError, Statement};
+use chrono::NaiveDate;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Moving a statement file to the name given by a file name format.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StatementRename {
+    from: PathBuf,
+    to: PathBuf,
+    date: NaiveDate,
+}
+
+impl StatementRename {
+    /// Plan to move a statement into `dir`, with a file name following `fmt`
+    pub fn new(stmt: &Statement, dir: &Path, fmt: &str) -> Self {
+        StatementRename {
+            from: stmt.path().to_path_buf(),
+            to: dir.join(stmt.date().format(fmt).to_string()),
+            date: *stmt.date(),
+        }
+    }
+
+    /// The current path of the statement
+    pub fn from(&self) -> &Path {
+        &self.from
+    }
+
+    /// The path the statement will be moved to
+    pub fn to(&self) -> &Path {
+        &self.to
+    }
+
+    /// The date of the statement
+    pub fn date(&self) -> &NaiveDate {
+        &self.date
+    }
+
+    /// Check if the statement already has the right name
+    pub fn is_unchanged(&self) -> bool {
+        self.from == self.to
+    }
+
+    /// Rename the statement file, without ever replacing an existing file
+    pub fn apply(&self) -> Result<(), RenameError> {
+        if self.is_unchanged() {
+            return Ok(());
+        }
+
+        if self.to.exists() {
+            return Err(RenameError::TargetExists(
+                self.from.clone(),
+                self.to.clone(),
+            ));
+        }
+
+        // renaming fails between file systems, so fall back to copying
+        fs::rename(&self.from, &self.to)
+            .or_else(|_| fs::copy(&self.from, &self.to).and_then(|_| fs::remove_file(&self.from)))
+            .map_err(|e| RenameError::Io(self.from.clone(), self.to.clone(), e))
+    }
+}
+
+/// Plan renames for the `files` whose names contain a date following `from_fmt`,
+/// so that they are moved into `dir` with names following `to_fmt`.
+///
+/// Files whose names don't match `from_fmt`, or that already have the right
+/// name, are skipped.
+/// The renames are sorted by date, earliest first.
+pub fn plan_renames<P: AsRef<Path>>(
+    files: &[P],
+    from_fmt: &str,
+    dir: &Path,
+    to_fmt: &str,
+) -> Vec<StatementRename> {
+    let mut renames: Vec<StatementRename> = files
+        .iter()
+        .map(|p| p.as_ref())
+        // only file names that are valid UTF-8 can be parsed for a date
+        .filter(|p| p.file_name().and_then(|f| f.to_str()).is_some())
+        .filter_map(|p| Statement::try_from((p, from_fmt)).ok())
+        .map(|stmt| StatementRename::new(&stmt, dir, to_fmt))
+        .filter(|r| !r.is_unchanged())
+        .collect();
+
+    renames.sort_by(|a, b| (a.date(), a.from()).cmp(&(b.date(), b.from())));
+
+    renames
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn new_rename() {
+        let date = NaiveDate::from_ymd_opt(2021, 3, 15).unwrap();
+        let stmt = Statement::new(Path::new("downloads/eStatement_20210315.pdf"), &date);
+
+        let observed = StatementRename::new(&stmt, Path::new("chequing"), "%Y-%m-%d.pdf");
+
+        assert_eq!(
+            Path::new("downloads/eStatement_20210315.pdf"),
+            observed.from()
+        );
+        assert_eq!(Path::new("chequing/2021-03-15.pdf"), observed.to());
+        assert!(!observed.is_unchanged());
+    }
+
+    #[test]
+    fn plan_skips_unmatched_and_unchanged() {
+        let files = [
+            PathBuf::from("chequing/eStatement_20210415.pdf"),
+            PathBuf::from("chequing/notes.txt"),
+            PathBuf::from("chequing/eStatement_20210315.pdf"),
+        ];
+
+        let observed: Vec<PathBuf> = plan_renames(
+            &files,
+            "eStatement_%Y%m%d.pdf",
+            Path::new("chequing"),
+            "%Y-%m-%d.pdf",
+        )
+        .iter()
+        .map(|r| r.to().to_path_buf())
+        .collect();
+        let expected = vec![
+            PathBuf::from("chequing/2021-03-15.pdf"),
+            PathBuf::from("chequing/2021-04-15.pdf"),
+        ];
+        assert_eq!(expected, observed);
+
+        // renaming to the same format leaves nothing to do
+        assert!(plan_renames(
+            &files,
+            "eStatement_%Y%m%d.pdf",
+            Path::new("chequing"),
+            "eStatement_%Y%m%d.pdf"
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn apply_never_replaces() {
+        let tmp = tempdir().unwrap();
+        let dir = tmp.path();
+        let from = dir.join("eStatement_20210315.pdf");
+        let existing = dir.join("eStatement_20210415.pdf");
+        fs::write(&from, "march").unwrap();
+        fs::write(&existing, "april").unwrap();
+        fs::write(dir.join("2021-04-15.pdf"), "already renamed").unwrap();
+
+        let renames = plan_renames(
+            &[&from, &existing],
+            "eStatement_%Y%m%d.pdf",
+            dir,
+            "%Y-%m-%d.pdf",
+        );
+
+        renames[0].apply().unwrap();
+        assert!(!from.exists());
+        assert_eq!(
+            "march",
+            fs::read_to_string(dir.join("2021-03-15.pdf")).unwrap()
+        );
+
+        assert!(matches!(
+            renames[1].apply(),
+            Err(RenameError::TargetExists(_, _))
+        ));
+        assert_eq!(
+            "already renamed",
+            fs::read_to_string(dir.join("2021-04-15.pdf")).unwrap()
+        );
+    }
+}
@@ -1,10 +1,28 @@
 //! The status of an individual statement.
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum StatementStatus {
     Available,
     Ignored,
     Missing,
+    /// A downloaded statement whose date doesn't match any expected statement,
+    /// which often means the account's statement period is wrong
+    Unexpected,
+    /// An expected statement that hasn't been found yet, but may not have been
+    /// published, since it's within the account's grace period
+    Pending,
+    /// An expected statement whose file was found before, but has since disappeared,
+    /// which often means it was deleted by accident
+    Lost,
+}
+
+impl StatementStatus {
+    /// Whether the statement is expected but hasn't been found, including lost statements
+    pub fn is_missing(self) -> bool {
+        matches!(self, StatementStatus::Missing | StatementStatus::Lost)
+    }
 }
 
 impl From<StatementStatus> for String {
@@ -13,6 +31,9 @@ impl From<StatementStatus> for String {
             StatementStatus::Available => String::from("✔"),
             StatementStatus::Ignored => String::from("-"),
             StatementStatus::Missing => String::from("❌"),
+            StatementStatus::Unexpected => String::from("?"),
+            StatementStatus::Pending => String::from("…"),
+            StatementStatus::Lost => String::from("!"),
         }
     }
 }
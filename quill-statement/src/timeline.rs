@@ -0,0 +1,139 @@
+//! Bucket an account's statements by year and month, to draw a timeline of them.
+
+use super::{ObservedStatement, StatementStatus};
+use chrono::Datelike;
+
+/// The status of an account's statements in each month of a year
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct YearTimeline {
+    year: i32,
+    months: [Option<StatementStatus>; 12],
+}
+
+impl YearTimeline {
+    /// Create a timeline for a year without any statements
+    pub fn new(year: i32) -> Self {
+        Self {
+            year,
+            months: [None; 12],
+        }
+    }
+
+    /// The year of the timeline
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    /// The status of the statements in each month, from January to December.
+    /// Months without any statements have no status.
+    pub fn months(&self) -> &[Option<StatementStatus>; 12] {
+        &self.months
+    }
+
+    /// The status of the statements in a month, from 1 to 12
+    pub fn month(&self, month: u32) -> Option<StatementStatus> {
+        self.months
+            .get(month.checked_sub(1)? as usize)
+            .copied()
+            .flatten()
+    }
+
+    /// Add a statement's status to its month.
+    /// When a month has several statements, the one that needs the most attention is kept.
+    fn add(&mut self, month0: usize, status: StatementStatus) {
+        let slot = &mut self.months[month0];
+        *slot = match *slot {
+            Some(current) if urgency(current) >= urgency(status) => Some(current),
+            _ => Some(status),
+        };
+    }
+}
+
+/// How much a statement's status needs the user's attention, from least to most
+fn urgency(status: StatementStatus) -> u8 {
+    match status {
+        StatementStatus::Ignored => 0,
+        StatementStatus::Available => 1,
+        StatementStatus::Pending => 2,
+        StatementStatus::Unexpected => 3,
+        StatementStatus::Missing => 4,
+        StatementStatus::Lost => 5,
+    }
+}
+
+/// Bucket statements by the year and month of their dates, oldest year first.
+/// Every year between the first and last statements is included, even if it has no statements.
+pub fn bucket_by_month<'a>(
+    stmts: impl IntoIterator<Item = &'a ObservedStatement>,
+) -> Vec<YearTimeline> {
+    let stmts: Vec<&ObservedStatement> = stmts.into_iter().collect();
+    let first = stmts.iter().map(|obs| obs.statement().date().year()).min();
+    let last = stmts.iter().map(|obs| obs.statement().date().year()).max();
+    let (first, last) = match (first, last) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return vec![],
+    };
+
+    let mut years: Vec<YearTimeline> = (first..=last).map(YearTimeline::new).collect();
+    for obs in stmts {
+        let date = obs.statement().date();
+        years[(date.year() - first) as usize].add(date.month0() as usize, obs.status());
+    }
+
+    years
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Statement;
+    use chrono::NaiveDate;
+
+    fn observed(year: i32, month: u32, day: u32, status: StatementStatus) -> ObservedStatement {
+        let date = NaiveDate::from_ymd_opt(year, month, day).unwrap();
+        ObservedStatement::new(&Statement::from(&date), status)
+    }
+
+    #[test]
+    fn empty_timeline() {
+        assert!(bucket_by_month(&[]).is_empty());
+    }
+
+    #[test]
+    fn buckets_by_year_and_month() {
+        let stmts = vec![
+            observed(2021, 11, 1, StatementStatus::Available),
+            observed(2021, 12, 1, StatementStatus::Missing),
+            observed(2019, 3, 1, StatementStatus::Ignored),
+        ];
+        let timeline = bucket_by_month(&stmts);
+
+        // the year without statements is still included
+        let years: Vec<i32> = timeline.iter().map(YearTimeline::year).collect();
+        assert_eq!(vec![2019, 2020, 2021], years);
+
+        assert_eq!(Some(StatementStatus::Ignored), timeline[0].month(3));
+        assert_eq!(None, timeline[0].month(4));
+        assert_eq!(&[None; 12], timeline[1].months());
+        assert_eq!(Some(StatementStatus::Available), timeline[2].month(11));
+        assert_eq!(Some(StatementStatus::Missing), timeline[2].month(12));
+        assert_eq!(None, timeline[2].month(0));
+        assert_eq!(None, timeline[2].month(13));
+    }
+
+    #[test]
+    fn most_urgent_status_in_a_month() {
+        let stmts = vec![
+            observed(2021, 1, 1, StatementStatus::Available),
+            observed(2021, 1, 8, StatementStatus::Missing),
+            observed(2021, 1, 15, StatementStatus::Available),
+            observed(2021, 2, 1, StatementStatus::Ignored),
+            observed(2021, 2, 9, StatementStatus::Unexpected),
+        ];
+        let timeline = bucket_by_month(&stmts);
+
+        assert_eq!(1, timeline.len());
+        assert_eq!(Some(StatementStatus::Missing), timeline[0].month(1));
+        assert_eq!(Some(StatementStatus::Unexpected), timeline[0].month(2));
+    }
+}
@@ -0,0 +1,276 @@
+//! The ways statements change from one scan to the next, for keeping a history of them.
+
+use crate::{ObservedStatement, StatementCollection, StatementStatus};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// How a statement changed between two scans
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Transition {
+    /// Its file was found
+    Appeared { path: PathBuf },
+    /// It became due, but its file hasn't been found
+    Missing,
+    /// It's ignored now
+    Ignored,
+    /// Its file was found somewhere else
+    Renamed { from: PathBuf, to: PathBuf },
+    /// Its file was found before, but isn't anymore
+    Removed { path: PathBuf },
+}
+
+/// A change to one of an account's statements
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct StatementTransition {
+    /// Key of the account the statement belongs to
+    account: String,
+
+    /// Date of the statement
+    date: NaiveDate,
+
+    /// How the statement changed
+    #[serde(flatten)]
+    transition: Transition,
+}
+
+impl StatementTransition {
+    /// Key of the account the statement belongs to
+    pub fn account(&self) -> &str {
+        &self.account
+    }
+
+    /// Date of the statement
+    pub fn date(&self) -> &NaiveDate {
+        &self.date
+    }
+
+    /// How the statement changed
+    pub fn transition(&self) -> &Transition {
+        &self.transition
+    }
+}
+
+/// Whether a statement's file has been found
+fn is_found(status: StatementStatus) -> bool {
+    matches!(
+        status,
+        StatementStatus::Available | StatementStatus::Unexpected
+    )
+}
+
+/// Find how each statement changed from the `previous` collection to the `current` one,
+/// sorted by account key and then date.
+///
+/// Statements are matched between the two collections by their account key and date.
+/// Accounts that aren't in the current collection are left out, so that removing an account
+/// doesn't look like all of its statements were removed.
+pub fn statement_transitions(
+    previous: &StatementCollection,
+    current: &StatementCollection,
+) -> Vec<StatementTransition> {
+    let prev_index: HashMap<(&str, &NaiveDate), &ObservedStatement> = previous
+        .iter()
+        .flat_map(|(key, stmts)| {
+            stmts
+                .iter()
+                .map(move |obs| ((key, obs.statement().date()), obs))
+        })
+        .collect();
+
+    let mut transitions = Vec::new();
+    let mut push = |key: &str, date: &NaiveDate, transition: Transition| {
+        transitions.push(StatementTransition {
+            account: key.to_string(),
+            date: *date,
+            transition,
+        })
+    };
+
+    for (key, stmts) in current.iter() {
+        for obs in stmts {
+            let date = obs.statement().date();
+            let prev = prev_index.get(&(key, date));
+            let prev_found = prev.filter(|prev| is_found(prev.status()));
+
+            match (prev_found, obs.status()) {
+                (Some(prev), status) if is_found(status) => {
+                    if prev.statement().path() != obs.statement().path() {
+                        let from = prev.statement().path().to_path_buf();
+                        let to = obs.statement().path().to_path_buf();
+                        push(key, date, Transition::Renamed { from, to });
+                    }
+                }
+                (None, status) if is_found(status) => {
+                    let path = obs.statement().path().to_path_buf();
+                    push(key, date, Transition::Appeared { path });
+                }
+                (Some(prev), _) => {
+                    let path = prev.statement().path().to_path_buf();
+                    push(key, date, Transition::Removed { path });
+                }
+                (None, StatementStatus::Missing) => {
+                    if prev.map(|prev| prev.status()) != Some(StatementStatus::Missing) {
+                        push(key, date, Transition::Missing);
+                    }
+                }
+                (None, StatementStatus::Ignored) => {
+                    if prev.map(|prev| prev.status()) != Some(StatementStatus::Ignored) {
+                        push(key, date, Transition::Ignored);
+                    }
+                }
+                (None, _) => {}
+            }
+        }
+    }
+
+    // files that were found before on dates that aren't expected or found anymore
+    for (key, stmts) in previous.iter() {
+        let cur_dates: Vec<&NaiveDate> = match current.get(key) {
+            Some(v) => v.iter().map(|obs| obs.statement().date()).collect(),
+            None => continue,
+        };
+
+        for obs in stmts {
+            if is_found(obs.status()) && !cur_dates.contains(&obs.statement().date()) {
+                let path = obs.statement().path().to_path_buf();
+                push(key, obs.statement().date(), Transition::Removed { path });
+            }
+        }
+    }
+
+    transitions.sort_by(|a, b| (&a.account, a.date).cmp(&(&b.account, b.date)));
+
+    transitions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Statement;
+    use std::path::Path;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn observed(d: NaiveDate, status: StatementStatus) -> ObservedStatement {
+        ObservedStatement::new(&Statement::from(&d), status)
+    }
+
+    fn found(d: NaiveDate, path: &str, status: StatementStatus) -> ObservedStatement {
+        ObservedStatement::new(&Statement::new(Path::new(path), &d), status)
+    }
+
+    fn collection(key: &str, stmts: Vec<ObservedStatement>) -> StatementCollection {
+        let mut sc = StatementCollection::new();
+        sc.insert(key, stmts);
+        sc
+    }
+
+    fn events(transitions: &[StatementTransition]) -> Vec<(NaiveDate, Transition)> {
+        transitions
+            .iter()
+            .map(|t| (*t.date(), t.transition().clone()))
+            .collect()
+    }
+
+    #[test]
+    fn unchanged_statements() {
+        let sc = collection(
+            "acct",
+            vec![
+                found(date(2021, 1, 1), "a.pdf", StatementStatus::Available),
+                observed(date(2021, 2, 1), StatementStatus::Missing),
+                observed(date(2021, 3, 1), StatementStatus::Ignored),
+            ],
+        );
+
+        assert!(statement_transitions(&sc, &sc).is_empty());
+    }
+
+    #[test]
+    fn each_kind_of_transition() {
+        let previous = collection(
+            "acct",
+            vec![
+                observed(date(2021, 1, 1), StatementStatus::Missing),
+                found(date(2021, 2, 1), "feb.pdf", StatementStatus::Available),
+                found(date(2021, 3, 1), "mar.pdf", StatementStatus::Available),
+                observed(date(2021, 4, 1), StatementStatus::Missing),
+                observed(date(2021, 5, 1), StatementStatus::Pending),
+            ],
+        );
+        let current = collection(
+            "acct",
+            vec![
+                found(date(2021, 1, 1), "jan.pdf", StatementStatus::Available),
+                found(date(2021, 2, 1), "2021-02.pdf", StatementStatus::Available),
+                observed(date(2021, 3, 1), StatementStatus::Missing),
+                observed(date(2021, 4, 1), StatementStatus::Ignored),
+                observed(date(2021, 5, 1), StatementStatus::Missing),
+            ],
+        );
+
+        assert_eq!(
+            vec![
+                (
+                    date(2021, 1, 1),
+                    Transition::Appeared {
+                        path: PathBuf::from("jan.pdf")
+                    }
+                ),
+                (
+                    date(2021, 2, 1),
+                    Transition::Renamed {
+                        from: PathBuf::from("feb.pdf"),
+                        to: PathBuf::from("2021-02.pdf")
+                    }
+                ),
+                (
+                    date(2021, 3, 1),
+                    Transition::Removed {
+                        path: PathBuf::from("mar.pdf")
+                    }
+                ),
+                (date(2021, 4, 1), Transition::Ignored),
+                (date(2021, 5, 1), Transition::Missing),
+            ],
+            events(&statement_transitions(&previous, &current))
+        );
+    }
+
+    #[test]
+    fn removed_dates_and_accounts() {
+        let mut previous = collection(
+            "acct",
+            vec![found(
+                date(2021, 1, 1),
+                "jan.pdf",
+                StatementStatus::Unexpected,
+            )],
+        );
+        previous.insert(
+            "closed",
+            vec![found(
+                date(2021, 1, 1),
+                "jan.pdf",
+                StatementStatus::Available,
+            )],
+        );
+        let current = collection("acct", vec![]);
+
+        // the removed account's statements aren't reported
+        let transitions = statement_transitions(&previous, &current);
+        assert_eq!(1, transitions.len());
+        assert_eq!("acct", transitions[0].account());
+        assert_eq!(
+            &Transition::Removed {
+                path: PathBuf::from("jan.pdf")
+            },
+            transitions[0].transition()
+        );
+    }
+}
@@ -0,0 +1,215 @@
+//! Locate the directories quill keeps its files in.
+//!
+//! Each directory follows the conventions of the operating system, such as the
+//! XDG base directories on Linux, and some can be overridden with environment variables.
+
+use dirs_next::{cache_dir, config_dir, data_dir, data_local_dir, home_dir};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Name of the directory quill uses within each base directory
+const APP_DIR: &str = "quill";
+
+/// Name of the configuration file within the configuration directory
+const CONFIG_FILE: &str = "config.toml";
+
+/// Environment variable naming the configuration file to use
+pub const CONFIG_ENV: &str = "QUILL_CONFIG";
+
+/// Environment variable naming the profile to use
+pub const PROFILE_ENV: &str = "QUILL_PROFILE";
+
+/// Environment variable naming the directory to keep the cache in
+pub const CACHE_DIR_ENV: &str = "QUILL_CACHE_DIR";
+
+/// The directories quill keeps its configuration, cache, state, and data in.
+/// A directory is `None` if it can't be determined, such as when there's no home directory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProjectDirs {
+    /// Configuration file named by `QUILL_CONFIG`, if any
+    config_file: Option<PathBuf>,
+
+    /// Directory containing the configuration file
+    config: Option<PathBuf>,
+
+    /// Directory for data that only speeds things up, and can be safely deleted
+    cache: Option<PathBuf>,
+
+    /// Directory for state that should persist between runs, but isn't configuration
+    state: Option<PathBuf>,
+
+    /// Directory for data the user may want to keep
+    data: Option<PathBuf>,
+}
+
+/// Find the directories quill keeps its files in, taking environment variable overrides into account.
+/// The directories aren't created until something is written to them.
+pub fn project_dirs() -> ProjectDirs {
+    ProjectDirs::with_overrides(env_path(CONFIG_ENV), env_path(CACHE_DIR_ENV))
+}
+
+/// Read a path from an environment variable, ignoring it if it's empty
+fn env_path(var: &str) -> Option<PathBuf> {
+    env::var_os(var)
+        .filter(|val| !val.is_empty())
+        .map(PathBuf::from)
+}
+
+impl ProjectDirs {
+    /// Find the directories, using the given configuration file and cache directory instead of the defaults
+    fn with_overrides(config_file: Option<PathBuf>, cache: Option<PathBuf>) -> Self {
+        // if $XDG_CONFIG_HOME isn't set, make it the default $HOME/.config
+        let config = config_dir()
+            .or_else(|| home_dir().map(|dir| dir.join(".config")))
+            .map(|dir| dir.join(APP_DIR));
+
+        let cache = cache.or_else(|| cache_dir().map(|dir| dir.join(APP_DIR)));
+
+        // `dirs_next` doesn't know about $XDG_STATE_HOME, so check for it first
+        let state = match env_path("XDG_STATE_HOME") {
+            Some(dir) => Some(dir),
+            // if not set, make it the default $HOME/.local/state on Linux,
+            // and the local application data directory elsewhere
+            None if cfg!(target_os = "linux") => {
+                home_dir().map(|dir| dir.join(".local").join("state"))
+            }
+            None => data_local_dir(),
+        }
+        .map(|dir| dir.join(APP_DIR));
+
+        let data = data_dir().map(|dir| dir.join(APP_DIR));
+
+        Self {
+            config_file,
+            config,
+            cache,
+            state,
+            data,
+        }
+    }
+
+    /// Directory containing the configuration file
+    pub fn config_dir(&self) -> Option<&Path> {
+        self.config.as_deref()
+    }
+
+    /// Directory for data that only speeds things up, and can be safely deleted
+    pub fn cache_dir(&self) -> Option<&Path> {
+        self.cache.as_deref()
+    }
+
+    /// Directory for state that should persist between runs, but isn't configuration
+    pub fn state_dir(&self) -> Option<&Path> {
+        self.state.as_deref()
+    }
+
+    /// Directory for data the user may want to keep
+    pub fn data_dir(&self) -> Option<&Path> {
+        self.data.as_deref()
+    }
+
+    /// Where the configuration file belongs: the file named by `QUILL_CONFIG`,
+    /// or `config.toml` in the configuration directory
+    pub fn config_file(&self) -> Option<PathBuf> {
+        self.config_file
+            .clone()
+            .or_else(|| self.config.as_ref().map(|dir| dir.join(CONFIG_FILE)))
+    }
+
+    /// Where the configuration file of a named profile belongs: `<profile>.toml` in the configuration directory
+    pub fn profile_config_file(&self, profile: &str) -> Option<PathBuf> {
+        self.config
+            .as_ref()
+            .map(|dir| dir.join(format!("{}.toml", profile)))
+    }
+
+    /// The configuration file to load when none is given.
+    /// This is the file named by `QUILL_CONFIG`, then the one in the configuration
+    /// directory if it exists, and `config.toml` in the current directory otherwise.
+    pub fn default_config_file(&self) -> PathBuf {
+        match (&self.config_file, self.config_file()) {
+            (Some(path), _) => path.clone(),
+            (None, Some(path)) if path.exists() => path,
+            _ => PathBuf::from(CONFIG_FILE),
+        }
+    }
+}
+
+/// Create the directory a file will be written to, and any of its parents, if they don't exist
+pub fn create_parent_dir(path: &Path) -> io::Result<()> {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => fs::create_dir_all(dir),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_dirs() {
+        let dirs = ProjectDirs::with_overrides(None, None);
+
+        // every directory is specific to quill
+        for dir in [
+            dirs.config_dir(),
+            dirs.cache_dir(),
+            dirs.state_dir(),
+            dirs.data_dir(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            assert!(dir.ends_with(APP_DIR));
+        }
+        assert_eq!(
+            dirs.config_file(),
+            dirs.config_dir().map(|dir| dir.join(CONFIG_FILE))
+        );
+    }
+
+    #[test]
+    fn profile_config_files() {
+        let dirs = ProjectDirs::with_overrides(None, None);
+
+        assert_eq!(
+            dirs.profile_config_file("business"),
+            dirs.config_dir().map(|dir| dir.join("business.toml"))
+        );
+        // profiles ignore the overridden configuration file
+        let config_file = PathBuf::from("/tmp/quill-test/accounts.toml");
+        let overridden = ProjectDirs::with_overrides(Some(config_file), None);
+        assert_eq!(
+            overridden.profile_config_file("business"),
+            dirs.profile_config_file("business")
+        );
+    }
+
+    #[test]
+    fn overridden_dirs() {
+        let config_file = PathBuf::from("/tmp/quill-test/accounts.toml");
+        let cache = PathBuf::from("/tmp/quill-test/cache");
+        let dirs = ProjectDirs::with_overrides(Some(config_file.clone()), Some(cache.clone()));
+
+        assert_eq!(dirs.config_file(), Some(config_file.clone()));
+        // an overridden configuration file is used even if it doesn't exist yet
+        assert_eq!(dirs.default_config_file(), config_file);
+        assert_eq!(dirs.cache_dir(), Some(cache.as_path()));
+    }
+
+    #[test]
+    fn create_parent_dirs() {
+        let root = tempfile::tempdir().unwrap();
+        let file = root.path().join("state").join("snapshot.json");
+
+        create_parent_dir(&file).unwrap();
+        assert!(root.path().join("state").is_dir());
+        // creating it again is fine
+        create_parent_dir(&file).unwrap();
+        // as is a file without a directory
+        create_parent_dir(Path::new("config.toml")).unwrap();
+    }
+}
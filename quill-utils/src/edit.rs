@@ -0,0 +1,494 @@
+//! Edit TOML files written by the user without losing their comments or formatting.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use toml_edit::{Array, Document, Item, Table, TableLike, Value};
+
+/// Indentation of the values in a multi-line array that doesn't have any yet
+const ARRAY_INDENT: &str = "    ";
+
+#[derive(Debug, Error)]
+pub enum EditError {
+    #[error("Could not read `{}`", .0.display())]
+    Unreadable(PathBuf, #[source] io::Error),
+    #[error("Could not parse `{}`: {1}", .0.display())]
+    Invalid(PathBuf, String),
+    #[error("`{0}` is not a table")]
+    NotATable(String),
+    #[error("`{0}` is not an array")]
+    NotAnArray(String),
+    #[error("`{0}` already exists")]
+    AlreadyExists(String),
+    #[error("Could not write `{}`", .0.display())]
+    Unwritable(PathBuf, #[source] io::Error),
+}
+
+/// A TOML file being edited.
+///
+/// Only the values that are changed are written differently when it's saved.
+/// The comments, order, and formatting of everything else are kept as they were.
+#[derive(Debug)]
+pub struct TomlEditor {
+    path: PathBuf,
+    doc: Document,
+}
+
+impl TomlEditor {
+    /// Start editing the TOML file at `path`
+    pub fn open(path: &Path) -> Result<Self, EditError> {
+        let toml_str =
+            fs::read_to_string(path).map_err(|e| EditError::Unreadable(path.to_path_buf(), e))?;
+        let doc = toml_str.parse().map_err(|e: toml_edit::TomlError| {
+            EditError::Invalid(path.to_path_buf(), e.to_string())
+        })?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            doc,
+        })
+    }
+
+    /// Start editing the TOML file at `path`, or an empty one if it doesn't exist yet
+    pub fn open_or_new(path: &Path) -> Result<Self, EditError> {
+        match Self::open(path) {
+            Err(EditError::Unreadable(_, e)) if e.kind() == io::ErrorKind::NotFound => {
+                Ok(Self::new(path))
+            }
+            opened => opened,
+        }
+    }
+
+    /// Start a new, empty TOML file at `path`, which replaces any file already there once it's saved
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            doc: Document::new(),
+        }
+    }
+
+    /// The path of the file being edited
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The file as it would be saved
+    pub fn document(&self) -> &Document {
+        &self.doc
+    }
+
+    /// The item at the end of the path of `keys`, if there is one
+    pub fn get(&self, keys: &[&str]) -> Option<&Item> {
+        keys.iter()
+            .try_fold(self.doc.as_item(), |item, key| item.get(key))
+    }
+
+    /// Set the value at the end of the path of `keys`, adding any tables along the way.
+    /// Comments before and after an existing value are kept.
+    pub fn set_value(&mut self, keys: &[&str], value: impl Into<Value>) -> Result<(), EditError> {
+        let (key, parents) = split_last(keys);
+        let table = self.table_mut(parents)?;
+        let mut value = value.into();
+
+        match table.get_mut(key) {
+            Some(Item::Value(old)) => {
+                *value.decor_mut() = old.decor().clone();
+                *old = value;
+            }
+            Some(_) => return Err(EditError::AlreadyExists(keys.join("."))),
+            None => {
+                table.insert(key, Item::Value(value));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Add a new table at the end of the path of `keys`, after the other tables beside it.
+    /// It's indented like the last of them, and separated from it by a blank line.
+    pub fn insert_table(&mut self, keys: &[&str], mut new: Table) -> Result<(), EditError> {
+        let (key, parents) = split_last(keys);
+        let is_empty = self.doc.to_string().trim().is_empty();
+        let table = self.table_mut(parents)?;
+        if table.contains_key(key) {
+            return Err(EditError::AlreadyExists(keys.join(".")));
+        }
+
+        let last_sibling = table
+            .iter()
+            .filter_map(|(_, item)| item.as_table())
+            .filter(|t| !t.is_implicit())
+            .last();
+        let header_indent = last_sibling
+            .and_then(|t| t.decor().prefix())
+            .map_or("", |prefix| {
+                indentation(prefix.as_str().unwrap_or_default())
+            })
+            .to_string();
+        let key_indent = last_sibling
+            .and_then(|t| t.iter().next().map(|(k, _)| t.key_decor(k)))
+            .flatten()
+            .and_then(|decor| decor.prefix())
+            .map_or("", |prefix| {
+                indentation(prefix.as_str().unwrap_or_default())
+            })
+            .to_string();
+
+        let blank_line = match is_empty {
+            true => "",
+            false => "\n",
+        };
+        new.decor_mut()
+            .set_prefix(format!("{}{}", blank_line, header_indent));
+        let new_keys: Vec<String> = new.iter().map(|(k, _)| k.to_string()).collect();
+        for k in new_keys {
+            if let Some(decor) = new.key_decor_mut(&k) {
+                decor.set_prefix(key_indent.clone());
+            }
+        }
+        table.insert(key, Item::Table(new));
+
+        Ok(())
+    }
+
+    /// Add a value to the end of the array at the end of the path of `keys`,
+    /// adding the array if it doesn't exist yet.
+    /// Arrays written over several lines keep one value on each line.
+    pub fn push_to_array(
+        &mut self,
+        keys: &[&str],
+        value: impl Into<Value>,
+    ) -> Result<(), EditError> {
+        let (key, parents) = split_last(keys);
+        let table = self.table_mut(parents)?;
+        let array = match table
+            .entry(key)
+            .or_insert(Item::Value(Value::Array(Array::new())))
+            .as_array_mut()
+        {
+            Some(array) => array,
+            None => return Err(EditError::NotAnArray(keys.join("."))),
+        };
+
+        let mut value = value.into();
+        let trailing = array.trailing().as_str().unwrap_or_default().to_string();
+        let multi_line = array.is_empty()
+            || trailing.contains('\n')
+            || array.iter().any(|v| {
+                v.decor()
+                    .prefix()
+                    .and_then(|p| p.as_str())
+                    .map_or(false, |p| p.contains('\n'))
+            });
+
+        match multi_line {
+            true => {
+                let indent = array
+                    .iter()
+                    .last()
+                    .and_then(|v| v.decor().prefix())
+                    .and_then(|p| p.as_str())
+                    .map_or(ARRAY_INDENT, indentation)
+                    .to_string();
+                // any comment after the last value stays on its line, before the new one
+                let before = match trailing.rfind('\n') {
+                    Some(i) => &trailing[..=i],
+                    None => "\n",
+                };
+                value
+                    .decor_mut()
+                    .set_prefix(format!("{}{}", before, indent));
+                value.decor_mut().set_suffix("");
+                array.push_formatted(value);
+                array.set_trailing_comma(true);
+                array.set_trailing("\n");
+            }
+            false => array.push(value),
+        }
+
+        Ok(())
+    }
+
+    /// Remove the values at `indices` from the array at the end of the path of `keys`.
+    /// Anything after a removed value on its line, like a comment, is removed along with it.
+    pub fn remove_from_array(&mut self, keys: &[&str], indices: &[usize]) -> Result<(), EditError> {
+        let (key, parents) = split_last(keys);
+        let array = match self
+            .table_mut(parents)?
+            .get_mut(key)
+            .and_then(Item::as_array_mut)
+        {
+            Some(array) => array,
+            None => return Err(EditError::NotAnArray(keys.join("."))),
+        };
+
+        let mut indices = indices.to_vec();
+        indices.sort_unstable();
+        indices.dedup();
+        indices.retain(|&i| i < array.len());
+        for &i in indices.iter().rev() {
+            let removed = array.remove(i);
+            let prefix = removed
+                .decor()
+                .prefix()
+                .and_then(|p| p.as_str())
+                .unwrap_or_default();
+            let before = match prefix.rfind('\n') {
+                Some(j) => &prefix[..=j],
+                None => prefix,
+            };
+
+            // the rest of the removed value's line is at the start of whatever follows it
+            match array.get_mut(i) {
+                Some(next) => {
+                    let prefix = next
+                        .decor()
+                        .prefix()
+                        .and_then(|p| p.as_str())
+                        .unwrap_or_default();
+                    let after = prefix.find('\n').map_or("", |j| &prefix[j + 1..]);
+                    let prefix = format!("{}{}", before, after);
+                    next.decor_mut().set_prefix(prefix);
+                }
+                None => {
+                    let trailing = array.trailing().as_str().unwrap_or_default();
+                    if let Some(j) = trailing.find('\n') {
+                        let trailing = trailing[j..].to_string();
+                        array.set_trailing(trailing);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write the file, leaving it as it was if it can't be written in full.
+    /// The file is written next to its path first and then moved into place,
+    /// so it's never left partially written.
+    pub fn save(&self) -> Result<(), EditError> {
+        let mut tmp = self.path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+
+        let saved =
+            fs::write(&tmp, self.doc.to_string()).and_then(|_| fs::rename(&tmp, &self.path));
+        if saved.is_err() {
+            // don't leave a half-written file behind
+            let _ = fs::remove_file(&tmp);
+        }
+
+        saved.map_err(|e| EditError::Unwritable(self.path.clone(), e))
+    }
+
+    /// The table at the end of the path of `keys`, adding any that don't exist yet
+    fn table_mut(&mut self, keys: &[&str]) -> Result<&mut dyn TableLike, EditError> {
+        let mut table: &mut dyn TableLike = self.doc.as_table_mut();
+        for (i, key) in keys.iter().enumerate() {
+            let mut implicit = Table::new();
+            implicit.set_implicit(true);
+            table = table
+                .entry(key)
+                .or_insert(Item::Table(implicit))
+                .as_table_like_mut()
+                .ok_or_else(|| EditError::NotATable(keys[..=i].join(".")))?;
+        }
+
+        Ok(table)
+    }
+}
+
+/// Split the last key from the keys of the tables it's in
+fn split_last<'a>(keys: &'a [&'a str]) -> (&'a str, &'a [&'a str]) {
+    match keys.split_last() {
+        Some((key, parents)) => (key, parents),
+        None => ("", &[]),
+    }
+}
+
+/// The spaces and tabs at the start of the last line of a prefix
+fn indentation(prefix: &str) -> &str {
+    let line = match prefix.rfind('\n') {
+        Some(i) => &prefix[i + 1..],
+        None => prefix,
+    };
+
+    match line.trim_start_matches([' ', '\t']).is_empty() {
+        true => line,
+        false => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::{tempdir, TempDir};
+    use toml_edit::value;
+
+    /// Open an editor for a file with these contents, in a directory that's removed once it's dropped
+    fn editor(name: &str, toml_str: &str) -> (TempDir, TomlEditor) {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(format!("{}.toml", name));
+        fs::write(&path, toml_str).unwrap();
+
+        (dir, TomlEditor::open(&path).unwrap())
+    }
+
+    fn saved(editor: &TomlEditor) -> String {
+        editor.save().unwrap();
+        fs::read_to_string(editor.path()).unwrap()
+    }
+
+    #[test]
+    fn set_value_keeps_comments() {
+        let (_dir, mut editor) = editor(
+            "set",
+            "# accounts\n[Accounts.a]\nname = \"Alpha\"  # everyday\ndir = \"~/a\"\n",
+        );
+        editor
+            .set_value(&["Accounts", "a", "name"], "Alfa")
+            .unwrap();
+        editor
+            .set_value(&["Accounts", "a", "group"], "Bank")
+            .unwrap();
+
+        assert_eq!(
+            "# accounts\n[Accounts.a]\nname = \"Alfa\"  # everyday\ndir = \"~/a\"\ngroup = \"Bank\"\n",
+            saved(&editor)
+        );
+        assert_eq!(
+            Some("Alfa"),
+            editor
+                .get(&["Accounts", "a", "name"])
+                .and_then(Item::as_str)
+        );
+    }
+
+    #[test]
+    fn set_value_in_a_value() {
+        let (_dir, mut editor) = editor("not-table", "name = \"Alpha\"\n");
+
+        assert!(matches!(
+            editor.set_value(&["name", "first"], "A"),
+            Err(EditError::NotATable(keys)) if keys == "name"
+        ));
+    }
+
+    #[test]
+    fn insert_table_like_siblings() {
+        let (_dir, mut editor) = editor(
+            "insert",
+            "    [Accounts.a]\n        name = \"Alpha\"\n\n[Tui]\ntick_rate = 10\n\n# the end\n",
+        );
+        let mut table = Table::new();
+        table["name"] = value("Beta");
+        table["dir"] = value("~/b");
+        editor.insert_table(&["Accounts", "b"], table).unwrap();
+
+        assert_eq!(
+            "    [Accounts.a]\n        name = \"Alpha\"\n\n    [Accounts.b]\n        name = \"Beta\"\n        dir = \"~/b\"\n\n[Tui]\ntick_rate = 10\n\n# the end\n",
+            saved(&editor)
+        );
+    }
+
+    #[test]
+    fn insert_existing_table() {
+        let (_dir, mut editor) = editor("existing", "[Accounts.a]\nname = \"Alpha\"\n");
+
+        assert!(matches!(
+            editor.insert_table(&["Accounts", "a"], Table::new()),
+            Err(EditError::AlreadyExists(keys)) if keys == "Accounts.a"
+        ));
+    }
+
+    #[test]
+    fn push_to_multi_line_array() {
+        let (_dir, mut editor) = editor(
+            "push",
+            "# ignored\ndates = [\n    2021-11-01, # lost in the mail\n]\n",
+        );
+        editor
+            .push_to_array(&["dates"], "2021-12-01".parse::<Value>().unwrap())
+            .unwrap();
+
+        assert_eq!(
+            "# ignored\ndates = [\n    2021-11-01, # lost in the mail\n    2021-12-01,\n]\n",
+            saved(&editor)
+        );
+    }
+
+    #[test]
+    fn push_to_new_and_inline_arrays() {
+        let (_dir, mut editor) = editor("push-new", "files = [\"*promo*\"]\n");
+        editor.push_to_array(&["files"], "*.txt").unwrap();
+        editor
+            .push_to_array(&["dates"], "2021-12-01".parse::<Value>().unwrap())
+            .unwrap();
+
+        assert_eq!(
+            "files = [\"*promo*\", \"*.txt\"]\ndates = [\n    2021-12-01,\n]\n",
+            saved(&editor)
+        );
+    }
+
+    #[test]
+    fn remove_from_multi_line_array() {
+        let (_dir, mut editor) = editor(
+            "remove",
+            "dates = [\n    2021-10-01, # moved\n    # lost in the mail\n    2021-11-01,\n    2021-12-01, # closed\n]\n",
+        );
+        editor.remove_from_array(&["dates"], &[0, 2]).unwrap();
+
+        assert_eq!(
+            "dates = [\n    # lost in the mail\n    2021-11-01,\n]\n",
+            saved(&editor)
+        );
+    }
+
+    #[test]
+    fn remove_from_inline_array() {
+        let (_dir, mut editor) = editor("remove-inline", "files = [\"a\", \"b\", \"c\"]\n");
+        editor.remove_from_array(&["files"], &[0, 5]).unwrap();
+        editor.remove_from_array(&["files"], &[1]).unwrap();
+
+        assert_eq!("files = [\"b\"]\n", saved(&editor));
+        assert!(matches!(
+            editor.remove_from_array(&["dates"], &[0]),
+            Err(EditError::NotAnArray(keys)) if keys == "dates"
+        ));
+    }
+
+    #[test]
+    fn open_or_new_missing() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("does-not-exist.toml");
+
+        assert!(matches!(
+            TomlEditor::open(&path),
+            Err(EditError::Unreadable(..))
+        ));
+        assert_eq!(
+            "",
+            TomlEditor::open_or_new(&path)
+                .unwrap()
+                .document()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn new_replaces_existing() {
+        let (dir, _) = editor("replaced", "# old\n[Accounts.a]\nname = \"Alpha\"\n");
+        let mut editor = TomlEditor::new(&dir.path().join("replaced.toml"));
+        editor.insert_table(&["Accounts"], Table::new()).unwrap();
+        let mut table = Table::new();
+        table["name"] = value("Beta");
+        editor.insert_table(&["Accounts", "b"], table).unwrap();
+
+        assert_eq!(
+            "[Accounts]\n\n[Accounts.b]\nname = \"Beta\"\n",
+            saved(&editor)
+        );
+    }
+}
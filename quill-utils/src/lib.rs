@@ -5,10 +5,20 @@ use std::fs::File;
 use std::io::{self, Read};
 use std::path::{Path, PathBuf};
 
+mod dirs;
+mod edit;
+mod path;
+
+pub use crate::dirs::{
+    create_parent_dir, project_dirs, ProjectDirs, CACHE_DIR_ENV, CONFIG_ENV, PROFILE_ENV,
+};
+pub use crate::edit::{EditError, TomlEditor};
+pub use crate::path::{expand_path, interpolate_vars, ExpandError};
+
 /// Parse a TOML file into a map of values.
 pub fn parse_toml_file(path: &Path) -> io::Result<String> {
     // open the file for parsing
-    let mut file = File::open(&path)?;
+    let mut file = File::open(path)?;
 
     // read file contents into a string
     let mut toml_str = String::new();
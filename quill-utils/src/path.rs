@@ -0,0 +1,273 @@
+//! Expand the paths written in configuration files, so the same file works on every operating system.
+
+use dirs_next::home_dir;
+use std::env;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::expand_tilde;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ExpandError {
+    #[error("Environment variable `{0}` is not set")]
+    UnsetVariable(String),
+    #[error("Could not find the home directory of user `{0}`")]
+    UnknownUser(String),
+    #[error("Could not find the home directory")]
+    NoHomeDirectory,
+}
+
+/// Expand a path written in a configuration file.
+///
+/// - Environment variables are replaced with their values, whether they're
+///   written like `$HOME`, `${STATEMENT_ROOT}`, or `%USERPROFILE%`.
+///   `HOME` and `USERPROFILE` are always the home directory, even if they aren't set.
+/// - `~` is replaced with the home directory, and `~user` with the home directory of `user`.
+/// - Outside of Windows, `\` separators are replaced with `/`.
+pub fn expand_path<P: AsRef<Path>>(path: P) -> Result<PathBuf, ExpandError> {
+    let path = path.as_ref().to_string_lossy();
+    let path = expand_vars(&normalize_separators(&path))?;
+
+    let user_path = match path.strip_prefix('~') {
+        Some(p) => p,
+        None => return Ok(PathBuf::from(path)),
+    };
+    let (user, rest) = match user_path.find(is_separator) {
+        Some(i) => (&user_path[..i], &user_path[i + 1..]),
+        None => (user_path, ""),
+    };
+
+    if user.is_empty() {
+        return expand_tilde(&path).ok_or(ExpandError::NoHomeDirectory);
+    }
+
+    match user_home(user) {
+        Some(home) if rest.is_empty() => Ok(home),
+        Some(home) => Ok(home.join(rest)),
+        None => Err(ExpandError::UnknownUser(user.to_string())),
+    }
+}
+
+/// Replace every environment variable written like `${VAR}` in a configuration value.
+///
+/// Unlike [`expand_path`], variables written like `$VAR` or `%VAR%` are left alone,
+/// so values like statement file name formats (`%Y-%m-%d`) aren't changed.
+pub fn interpolate_vars(value: &str) -> Result<String, ExpandError> {
+    let mut interpolated = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(i) = rest.find("${") {
+        interpolated.push_str(&rest[..i]);
+        rest = &rest[i..];
+
+        match variable_at(rest) {
+            Some((name, len)) => {
+                interpolated.push_str(&var_value(name)?);
+                rest = &rest[len..];
+            }
+            // `${` without a valid name is part of the value
+            None => {
+                interpolated.push_str(&rest[..2]);
+                rest = &rest[2..];
+            }
+        }
+    }
+    interpolated.push_str(rest);
+
+    Ok(interpolated)
+}
+
+/// Check if a character separates the components of a path
+fn is_separator(c: char) -> bool {
+    c == '/' || (cfg!(windows) && c == '\\')
+}
+
+/// Replace `\` separators with `/`, outside of Windows
+fn normalize_separators(path: &str) -> String {
+    if cfg!(windows) {
+        path.to_string()
+    } else {
+        path.replace('\\', "/")
+    }
+}
+
+/// Replace every environment variable in a path with its value
+fn expand_vars(path: &str) -> Result<String, ExpandError> {
+    let mut expanded = String::with_capacity(path.len());
+    let mut rest = path;
+
+    while let Some(i) = rest.find(['$', '%']) {
+        expanded.push_str(&rest[..i]);
+        rest = &rest[i..];
+
+        match variable_at(rest) {
+            Some((name, len)) => {
+                expanded.push_str(&var_value(name)?);
+                rest = &rest[len..];
+            }
+            // a lone `$` or `%` is part of the path
+            None => {
+                expanded.push_str(&rest[..1]);
+                rest = &rest[1..];
+            }
+        }
+    }
+    expanded.push_str(rest);
+
+    Ok(expanded)
+}
+
+/// Find the name of the variable referenced at the start of a string,
+/// and how long the reference is, if there is one
+fn variable_at(s: &str) -> Option<(&str, usize)> {
+    let is_name = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let valid = |name: &str| !name.is_empty() && name.chars().all(is_name);
+
+    if let Some(rest) = s.strip_prefix("${") {
+        let name = &rest[..rest.find('}')?];
+        valid(name).then(|| (name, name.len() + 3))
+    } else if let Some(rest) = s.strip_prefix('$') {
+        let name = &rest[..rest.find(|c| !is_name(c)).unwrap_or(rest.len())];
+        valid(name).then(|| (name, name.len() + 1))
+    } else if let Some(rest) = s.strip_prefix('%') {
+        let name = &rest[..rest.find('%')?];
+        valid(name).then(|| (name, name.len() + 2))
+    } else {
+        None
+    }
+}
+
+/// The value of an environment variable.
+/// The home directory variables of every operating system are understood everywhere.
+fn var_value(name: &str) -> Result<String, ExpandError> {
+    match env::var(name) {
+        Ok(val) => Ok(val),
+        Err(_) if name == "HOME" || name == "USERPROFILE" => home_dir()
+            .map(|dir| dir.to_string_lossy().into_owned())
+            .ok_or(ExpandError::NoHomeDirectory),
+        Err(_) => Err(ExpandError::UnsetVariable(name.to_string())),
+    }
+}
+
+/// Find the home directory of another user
+fn user_home(user: &str) -> Option<PathBuf> {
+    if let Some(home) = passwd_home(user) {
+        return Some(home);
+    }
+
+    // otherwise, assume every user's home directory is in the same place,
+    // like `/home/<user>` or `C:\Users\<user>`
+    let dir = home_dir()?.parent()?.join(user);
+    dir.is_dir().then_some(dir)
+}
+
+/// Look up a user's home directory in `/etc/passwd`
+#[cfg(unix)]
+fn passwd_home(user: &str) -> Option<PathBuf> {
+    let passwd = std::fs::read_to_string("/etc/passwd").ok()?;
+
+    passwd.lines().find_map(|line| {
+        // each line is `name:password:uid:gid:comment:home:shell`
+        let fields: Vec<&str> = line.split(':').collect();
+        match fields.as_slice() {
+            [name, _, _, _, _, home, ..] if *name == user => Some(PathBuf::from(home)),
+            _ => None,
+        }
+    })
+}
+
+#[cfg(not(unix))]
+fn passwd_home(_user: &str) -> Option<PathBuf> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn check_expand_path(input: &str, expected: Result<PathBuf, ExpandError>) {
+        assert_eq!(expected, expand_path(input));
+    }
+
+    #[test]
+    fn test_expand_plain_path() {
+        check_expand_path("/tmp/statements", Ok(PathBuf::from("/tmp/statements")));
+        // lone `$` and `%` characters are part of the path
+        check_expand_path("/tmp/100%/$", Ok(PathBuf::from("/tmp/100%/$")));
+    }
+
+    #[test]
+    fn test_expand_vars() {
+        env::set_var("QUILL_UTILS_TEST_ROOT", "/mnt/statements");
+
+        for input in [
+            "$QUILL_UTILS_TEST_ROOT/visa",
+            "${QUILL_UTILS_TEST_ROOT}/visa",
+            "%QUILL_UTILS_TEST_ROOT%/visa",
+        ] {
+            check_expand_path(input, Ok(PathBuf::from("/mnt/statements/visa")));
+        }
+        check_expand_path(
+            "${QUILL_UTILS_TEST_ROOT}visa",
+            Ok(PathBuf::from("/mnt/statementsvisa")),
+        );
+        check_expand_path(
+            "$QUILL_UTILS_TEST_UNSET/visa",
+            Err(ExpandError::UnsetVariable(
+                "QUILL_UTILS_TEST_UNSET".to_string(),
+            )),
+        );
+    }
+
+    #[test]
+    fn test_expand_home_vars() {
+        // `%USERPROFILE%` works even where only `$HOME` is set, and vice versa
+        let expected = home_dir().unwrap().join("Documents");
+
+        check_expand_path("%USERPROFILE%/Documents", Ok(expected.clone()));
+        check_expand_path("$HOME/Documents", Ok(expected.clone()));
+        check_expand_path("~/Documents", Ok(expected));
+    }
+
+    #[test]
+    fn test_interpolate_vars() {
+        env::set_var("QUILL_UTILS_TEST_BANK", "Tangerine");
+
+        assert_eq!(
+            Ok("Tangerine Visa".to_string()),
+            interpolate_vars("${QUILL_UTILS_TEST_BANK} Visa")
+        );
+        // only `${VAR}` is interpolated
+        assert_eq!(
+            Ok("$QUILL_UTILS_TEST_BANK %Y-%m-%d ${} ${".to_string()),
+            interpolate_vars("$QUILL_UTILS_TEST_BANK %Y-%m-%d ${} ${")
+        );
+        assert_eq!(
+            Err(ExpandError::UnsetVariable(
+                "QUILL_UTILS_TEST_UNSET".to_string(),
+            )),
+            interpolate_vars("${QUILL_UTILS_TEST_UNSET}")
+        );
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_normalize_separators() {
+        let expected = home_dir().unwrap().join("Documents").join("visa");
+
+        check_expand_path("~\\Documents\\visa", Ok(expected));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_expand_user() {
+        check_expand_path("~root/statements", Ok(PathBuf::from("/root/statements")));
+        check_expand_path(
+            "~quill-utils-test-nobody/statements",
+            Err(ExpandError::UnknownUser(
+                "quill-utils-test-nobody".to_string(),
+            )),
+        );
+    }
+}